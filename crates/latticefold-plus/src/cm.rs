@@ -6,8 +6,7 @@ use ark_std::{
 use latticefold::{
     transcript::Transcript,
     utils::sumcheck::{
-        utils::{build_eq_x_r, eq_eval},
-        MLSumcheck, Proof, SumCheckError,
+        utils::eq_eval, virtual_polynomial::VirtualPolynomialInfo, MLSumcheck, Proof, SumCheckError,
     },
 };
 use stark_rings::{unit_monomial, CoeffRing, OverField, PolyRing, Ring, Zq};
@@ -49,6 +48,50 @@ pub struct ComX<R> {
     pub vo: Vec<Vec<(R, R)>>,
 }
 
+/// The terms of the [`Cm`] sumcheck polynomial, batched with the powers of a random combinator.
+///
+/// Both range over the extensions of `[tau, m_tau, f, h]` and `M_i * [tau, m_tau, f, h]` of every
+/// instance, followed by the extensions of `t(0)` and `t(1)`.
+struct CmSumcheckTerms<R> {
+    /// The terms multiplied by `eq(r, x)`: every extension but `t(0)` and `t(1)`.
+    eq: VirtualPolynomialInfo<R>,
+    /// The terms without eq factor: the products of `tau` with `t(0)` and `t(1)`.
+    eq_free: VirtualPolynomialInfo<R>,
+}
+
+impl<R: OverField> CmSumcheckTerms<R> {
+    fn new(nvars: usize, L: usize, Mlen: usize, rc: R) -> Self {
+        let width = 4 + 4 * Mlen;
+        let t_idx = L * width;
+        let num_mles = t_idx + 2;
+
+        let mut eq = VirtualPolynomialInfo::new(nvars, num_mles);
+        let mut rcp = R::ONE;
+        for idx in 0..t_idx {
+            eq.add_product(rcp, [idx]).unwrap();
+            rcp *= rc;
+        }
+
+        let mut eq_free = VirtualPolynomialInfo::new(nvars, num_mles);
+        for l in 0..L {
+            let l_idx = l * width;
+            eq_free.add_product(rcp, [l_idx, t_idx]).unwrap(); // t(0)
+            eq_free.add_product(rcp * rc, [l_idx, t_idx + 1]).unwrap(); // t(1)
+        }
+
+        Self { eq, eq_free }
+    }
+
+    /// The degree of the polynomial, eq factor included.
+    fn degree(&self) -> usize {
+        usize::max(self.eq.degree() + 1, self.eq_free.degree())
+    }
+
+    fn evaluate(&self, eq: R, vals: &[R]) -> R {
+        eq * self.eq.evaluate(vals).unwrap() + self.eq_free.evaluate(vals).unwrap()
+    }
+}
+
 impl<R: CoeffRing> Cm<R>
 where
     R::BaseRing: Zq,
@@ -214,15 +257,12 @@ where
         let L = self.rg.instances.len();
 
         let mut mles = Vec::with_capacity(
-            1 // eq
-            + L * (
+            L * (
                 4  // [tau, m_tau, f, h]
-                + 4 * M.len() // M * [tau, ...]
-            )
-            + 2, // t(z)
+                + 4 * M.len()
+                // M * [tau, ...]
+            ) + 2, // t(z)
         );
-        let eq = build_eq_x_r(&r).unwrap();
-        mles.push(eq);
 
         for (i, inst) in self.rg.instances.iter().enumerate() {
             let rtau = inst.tau.iter().map(|z| R::from(*z)).collect::<Vec<_>>();
@@ -261,52 +301,20 @@ where
 
         let Mlen = M.len();
 
-        // Pre-compute random-combinator powers
-        let mut rcps = vec![];
-        let mut rcp = R::BaseRing::ONE;
-        for _ in 0..L {
-            // [tau, m_tau, f, h]
-            for _ in 0..4 {
-                rcps.push(rcp);
-                rcp *= rc;
-            }
-            for _ in 0..Mlen {
-                // M_i * [tau, m_tau, f, h]
-                for _ in 0..4 {
-                    rcps.push(rcp);
-                    rcp *= rc;
-                }
-            }
-        }
-        rcps.push(rcp); // t(0)
-        rcp *= rc;
-        rcps.push(rcp); // t(1)
-
-        let comb_fn = |vals: &[R]| -> R {
-            (0..L)
-                .map(|l| {
-                    let l_idx = 1 + l * (4 + 4 * Mlen);
-                    vals[0] * ( // eq
-                    vals[l_idx] * rcps[l_idx - 1]  // tau
-                    + vals[l_idx + 1] * rcps[l_idx] // m_tau
-                    + vals[l_idx + 2] * rcps[l_idx + 1] // f
-                    + vals[l_idx + 3] * rcps[l_idx + 2] // h
-                    + (0..Mlen).map(|i| {
-                        let idx = l_idx + 4 + i * 4;
-                        vals[idx] * rcps[idx - 1] // M_i * tau
-                        + vals[idx + 1] * rcps[idx] // M_i * m_tau
-                        + vals[idx + 2] * rcps[idx + 1] // M_i * f
-                        + vals[idx + 3] * rcps[idx + 2] // M_i * h
-                     }).sum::<R>()
-                )
-            + (vals[l_idx] * vals[vals.len()-2]) * rcps[vals.len() - 3] // t(0)
-            + (vals[l_idx] * vals[vals.len()-1]) * rcps[vals.len() - 2] // t(1)
-                })
-                .sum::<R>()
+        let terms = CmSumcheckTerms::new(nvars, L, Mlen, R::from(rc));
+        let comb_fn = |vals: &[R], out: &mut [R]| {
+            out[0] = terms.eq.evaluate(vals).unwrap();
+            out[1] = terms.eq_free.evaluate(vals).unwrap();
         };
 
-        let (sumcheck_proof, prover_state) =
-            MLSumcheck::prove_as_subprotocol(transcript, mles.clone(), nvars, 2, comb_fn);
+        let (sumcheck_proof, prover_state) = MLSumcheck::prove_with_eqs_and_eq_free(
+            transcript,
+            &mles.iter().collect::<Vec<_>>(),
+            nvars,
+            terms.degree() - 1, // without the eq factor
+            &[r],
+            comb_fn,
+        );
         let ro = prover_state
             .randomness
             .into_iter()
@@ -316,7 +324,7 @@ where
         let evals = (0..L)
             .map(|l| {
                 let mut e = Vec::with_capacity(1 + Mlen);
-                let l_idx = 1 + l * (4 + 4 * Mlen);
+                let l_idx = l * (4 + 4 * Mlen);
                 e.push([
                     mles[l_idx].evaluate(&ro).unwrap(),
                     mles[l_idx + 1].evaluate(&ro).unwrap(),
@@ -346,6 +354,24 @@ impl<R: CoeffRing> CmProof<R>
 where
     R::BaseRing: Zq,
 {
+    /// Returns the proof with its sumcheck proofs in the compressed format.
+    pub fn compress(&self) -> Self {
+        let L = self.evals.0.len();
+        let Mlen = self.evals.0[0].0.len() - 1;
+        let degree = CmSumcheckTerms::new(self.dcom.out.nvars, L, Mlen, R::ONE).degree();
+        Self {
+            dcom: Dcom {
+                out: self.dcom.out.compress(),
+                ..self.dcom.clone()
+            },
+            sumcheck_proofs: (
+                self.sumcheck_proofs.0.compress(degree),
+                self.sumcheck_proofs.1.compress(degree),
+            ),
+            ..self.clone()
+        }
+    }
+
     pub fn verify(
         &self,
         M: &[SparseMatrix<R>],
@@ -440,6 +466,7 @@ where
         let mut verify_sumcheck =
             |sumcheck_proof: &Proof<R>, evals: &[InstanceEvals<R>]| -> Result<Vec<R>, ()> {
                 let rc: R = transcript.get_challenge().into();
+                let terms = CmSumcheckTerms::new(nvars, L, M.len(), rc);
 
                 let z_idx = L * (4 + 4 * M.len());
 
@@ -472,7 +499,7 @@ where
                 let subclaim = MLSumcheck::verify_as_subprotocol(
                     transcript,
                     nvars,
-                    2,
+                    terms.degree(),
                     claimed_sum,
                     sumcheck_proof,
                 )
@@ -497,31 +524,12 @@ where
 
                 let eq = eq_eval(&r, &ro).unwrap();
 
-                let eval = evals
+                let vals = evals
                     .iter()
-                    .enumerate()
-                    .map(|(l, el)| {
-                        let el = &el.0;
-                        let l_idx = l * (4 + 4 * M.len());
-                        eq * (el[0][0] * rc.pow([l_idx as u64])
-                            + el[0][1] * rc.pow([l_idx as u64 + 1])
-                            + el[0][2] * rc.pow([l_idx as u64 + 2])
-                            + el[0][3] * rc.pow([l_idx as u64 + 3])
-                            + (0..M.len())
-                                .map(|i| {
-                                    // M_i
-                                    let M_evals = el[i + 1];
-                                    let idx = l_idx + 4 + i * 4;
-                                    M_evals[0] * rc.pow([idx as u64])
-                                        + M_evals[1] * rc.pow([idx as u64 + 1])
-                                        + M_evals[2] * rc.pow([idx as u64 + 2])
-                                        + M_evals[3] * rc.pow([idx as u64 + 3])
-                                })
-                                .sum::<R>())
-                            + (t0_ro * el[0][0]) * rc.pow([z_idx as u64])
-                            + (t1_ro * el[0][0]) * rc.pow([z_idx as u64 + 1])
-                    })
-                    .sum::<R>();
+                    .flat_map(|el| el.0.iter().flatten().copied())
+                    .chain([t0_ro, t1_ro])
+                    .collect::<Vec<R>>();
+                let eval = terms.evaluate(eq, &vals);
 
                 assert_eq!(expected_eval, eval);
 
@@ -662,5 +670,8 @@ mod tests {
 
         let mut ts = PoseidonTranscript::empty::<PC>();
         proof.verify(&M, &mut ts).unwrap();
+
+        let mut ts = PoseidonTranscript::empty::<PC>();
+        proof.compress().verify(&M, &mut ts).unwrap();
     }
 }
@@ -18,6 +18,11 @@ pub trait Linearize<R: OverField> {
 
 pub trait LinearizedVerify<R: OverField> {
     fn verify(&self, transcript: &mut impl Transcript<R>) -> bool;
+
+    /// Returns the proof with its sumcheck proofs in the compressed format.
+    fn compress(&self) -> Self
+    where
+        Self: Sized;
 }
 
 #[derive(Clone, Debug)]
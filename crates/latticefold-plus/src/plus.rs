@@ -39,6 +39,26 @@ pub struct PlusProof<R: OverField, P: LinearizedVerify<R>> {
     pub dproof: DecompProof<R>,
 }
 
+impl<R, P> PlusProof<R, P>
+where
+    R::BaseRing: Zq,
+    R: CoeffRing,
+    P: LinearizedVerify<R>,
+{
+    /// Returns the proof with its sumcheck proofs in the compressed format.
+    ///
+    /// The compressed proof is smaller by one ring element per sumcheck round and verifies
+    /// exactly when the original proof does.
+    pub fn compress(&self) -> Self {
+        Self {
+            linb2x: self.linb2x.clone(),
+            lproof: self.lproof.iter().map(LinearizedVerify::compress).collect(),
+            cmproof: self.cmproof.compress(),
+            dproof: self.dproof.clone(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PlusParameters {
     pub lin: LinParameters,
@@ -211,8 +231,12 @@ mod tests {
         let proof = prover.prove(&[cr1cs0, cr1cs1]);
 
         let transcript = PoseidonTranscript::empty::<PC>();
-        let mut verifier = PlusVerifier::init(A, M, pparams, transcript);
+        let mut verifier = PlusVerifier::init(A.clone(), M.clone(), pparams.clone(), transcript);
         verifier.verify(&proof);
+
+        let transcript = PoseidonTranscript::empty::<PC>();
+        let mut verifier = PlusVerifier::init(A, M, pparams, transcript);
+        verifier.verify(&proof.compress());
     }
 
     #[test]
@@ -3,8 +3,7 @@ use latticefold::{
     arith::r1cs::R1CS,
     transcript::Transcript,
     utils::sumcheck::{
-        utils::{build_eq_x_r, eq_eval},
-        MLSumcheck, Proof,
+        utils::eq_eval, virtual_polynomial::VirtualPolynomialInfo, MLSumcheck, Proof,
     },
 };
 use stark_rings::{
@@ -59,6 +58,15 @@ impl<R: Decompose + Ring> ComR1CS<R> {
     }
 }
 
+/// The R1CS constraint `A f * B f - C f`, over the extensions of `(A f, B f, C f)` and without
+/// its eq factor.
+fn r1cs_constraint<R: OverField>(nvars: usize) -> VirtualPolynomialInfo<R> {
+    let mut constraint = VirtualPolynomialInfo::new(nvars, 3);
+    constraint.add_product(R::ONE, [0, 1]).unwrap();
+    constraint.add_product(-R::ONE, [2]).unwrap();
+    constraint
+}
+
 impl<R: Ring> ComR1CSX<R> {
     pub fn matrices(&self) -> Vec<SparseMatrix<R>> {
         vec![
@@ -81,18 +89,22 @@ impl<R: OverField> Linearize<R> for ComR1CS<R> {
             .into_iter()
             .map(|x| x.into())
             .collect();
-        let eq = build_eq_x_r(&r).unwrap();
         let mle_f = DenseMultilinearExtension::from_evaluations_vec(nvars, self.f.clone());
         let mle_ga = DenseMultilinearExtension::from_evaluations_vec(nvars, ga);
         let mle_gb = DenseMultilinearExtension::from_evaluations_vec(nvars, gb);
         let mle_gc = DenseMultilinearExtension::from_evaluations_vec(nvars, gc);
 
-        let mles = vec![eq, mle_ga.clone(), mle_gb.clone(), mle_gc.clone()];
-
-        let comb_fn = |vals: &[R]| -> R { vals[0] * (vals[1] * vals[2] - vals[3]) };
+        let constraint = r1cs_constraint::<R>(nvars);
+        let comb_fn = |vals: &[R], out: &mut [R]| out[0] = constraint.evaluate(vals).unwrap();
 
-        let (sumcheck_proof, prover_state) =
-            MLSumcheck::prove_as_subprotocol(transcript, mles, nvars, 3, comb_fn);
+        let (sumcheck_proof, prover_state) = MLSumcheck::prove_with_eqs(
+            transcript,
+            &[&mle_ga, &mle_gb, &mle_gc],
+            nvars,
+            constraint.degree(),
+            &[r],
+            comb_fn,
+        );
         let ro = prover_state
             .randomness
             .into_iter()
@@ -133,6 +145,13 @@ impl<R: OverField> Linearize<R> for ComR1CS<R> {
     }
 }
 
+impl<R: OverField> ComR1CSProof<R> {
+    /// The degree of the linearization sumcheck polynomial, eq factor included.
+    fn sumcheck_degree(&self) -> usize {
+        r1cs_constraint::<R>(self.nvars).degree() + 1
+    }
+}
+
 impl<R: OverField> LinearizedVerify<R> for ComR1CSProof<R> {
     fn verify(&self, transcript: &mut impl Transcript<R>) -> bool {
         let r: Vec<R> = transcript
@@ -143,7 +162,7 @@ impl<R: OverField> LinearizedVerify<R> for ComR1CSProof<R> {
         let subclaim = MLSumcheck::verify_as_subprotocol(
             transcript,
             self.nvars,
-            3,
+            self.sumcheck_degree(),
             R::zero(),
             &self.sumcheck_proof,
         )
@@ -156,10 +175,21 @@ impl<R: OverField> LinearizedVerify<R> for ComR1CSProof<R> {
 
         let e = eq_eval(&r, &ro).unwrap();
 
-        assert_eq!(e * (self.va * self.vb - self.vc), s);
+        let constraint = r1cs_constraint::<R>(self.nvars);
+        assert_eq!(
+            e * constraint.evaluate(&[self.va, self.vb, self.vc]).unwrap(),
+            s
+        );
 
         true
     }
+
+    fn compress(&self) -> Self {
+        Self {
+            sumcheck_proof: self.sumcheck_proof.compress(self.sumcheck_degree()),
+            ..self.clone()
+        }
+    }
 }
 
 fn absorb_evaluations<R: OverField>(evals: &[R; 4], transcript: &mut impl Transcript<R>) {
@@ -228,6 +258,9 @@ mod tests {
         let (_linb, lproof) = cr1cs.linearize(&mut ts);
 
         let mut ts = PoseidonTranscript::empty::<PC>();
-        lproof.verify(&mut ts);
+        assert!(lproof.verify(&mut ts));
+
+        let mut ts = PoseidonTranscript::empty::<PC>();
+        assert!(lproof.compress().verify(&mut ts));
     }
 }
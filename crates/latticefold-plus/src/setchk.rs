@@ -2,8 +2,7 @@ use ark_std::log2;
 use latticefold::{
     transcript::Transcript,
     utils::sumcheck::{
-        utils::{build_eq_x_r, eq_eval},
-        MLSumcheck, Proof, SumCheckError,
+        utils::eq_eval, virtual_polynomial::VirtualPolynomialInfo, MLSumcheck, Proof, SumCheckError,
     },
 };
 use stark_rings::{OverField, PolyRing, Ring};
@@ -43,6 +42,15 @@ pub enum SetCheckError<R: Ring> {
     ExpectedEvaluation(R, R),
 }
 
+/// The set check term `ev(m_j)^2 - ev(m_j^2)` of one column, over the extensions of
+/// `(ev(m_j), ev(m_j^2))` and without its eq factor.
+fn set_check_term<R: OverField>(nvars: usize) -> VirtualPolynomialInfo<R> {
+    let mut term = VirtualPolynomialInfo::new(nvars, 2);
+    term.add_product(R::ONE, [0, 0]).unwrap();
+    term.add_product(-R::ONE, [1]).unwrap();
+    term
+}
+
 fn ev<R: PolyRing>(r: &R, x: R::BaseRing) -> R::BaseRing {
     r.coeffs()
         .iter()
@@ -84,7 +92,8 @@ impl<R: OverField> In<R> {
         let MTs = Ms.iter().map(|M| M.transpose()).collect::<Vec<_>>();
         let tnvars = log2(Ms[0].nrows.next_power_of_two()) as usize;
 
-        let mut mles = Vec::with_capacity((Ms.len() + ms.len()) * (ncols * 2 + 1));
+        let mut mles = Vec::with_capacity((Ms.len() + ms.len()) * ncols * 2);
+        let mut eq_points = Vec::with_capacity(Ms.len() + ms.len());
         let mut alphas = Vec::with_capacity(Ms.len());
 
         // matrix sets
@@ -115,8 +124,7 @@ impl<R: OverField> In<R> {
                 mles.push(mle_m_prime_j);
             }
 
-            let eq = build_eq_x_r(&c).unwrap();
-            mles.push(eq);
+            eq_points.push(c);
 
             let alpha = transcript.get_challenge();
             alphas.push(alpha);
@@ -142,8 +150,7 @@ impl<R: OverField> In<R> {
             mles.push(mle_m_j);
             mles.push(mle_m_prime_j);
 
-            let eq = build_eq_x_r(&c).unwrap();
-            mles.push(eq);
+            eq_points.push(c);
 
             let alpha = transcript.get_challenge();
             alphas.push(alpha);
@@ -152,41 +159,42 @@ impl<R: OverField> In<R> {
         // random linear combinator, for batching
         let rc: Option<R::BaseRing> = (Ms.len() > 1).then(|| transcript.get_challenge());
 
-        let comb_fn = |vals: &[R]| -> R {
-            let mut lc = R::zero();
+        // The terms of the sets, without their eq factors
+        let term = set_check_term::<R>(tnvars);
+        let comb_fn = |vals: &[R], out: &mut [R]| {
             for (i, alpha) in alphas.iter().enumerate().take(Ms.len()) {
-                // 2 * ncols for (m_j, m_prime_j), +1 for eq
-                let s = i * (2 * ncols + 1);
+                // 2 * ncols for (m_j, m_prime_j)
+                let s = i * 2 * ncols;
                 let mut res = R::zero();
                 for j in 0..ncols {
-                    res += (vals[s + j * 2] * vals[s + j * 2] - vals[s + j * 2 + 1])
+                    res += term.evaluate(&vals[s + j * 2..s + j * 2 + 2]).unwrap()
                         * alpha.pow([j as u64])
                 }
-                res *= vals[s + 2 * ncols]; // eq
-                lc += if let Some(rc) = &rc {
-                    res * rc.pow([i as u64])
-                } else {
-                    return res;
+                out[i] = match &rc {
+                    Some(rc) => res * rc.pow([i as u64]),
+                    None => res,
                 };
             }
             for i in 0..ms.len() {
-                let s_base = Ms.len() * (2 * ncols + 1);
-                let s = s_base + i * 3;
-                let mut res = R::zero();
+                let s_base = Ms.len() * 2 * ncols;
+                let s = s_base + i * 2;
                 let alpha_idx = Ms.len() + i;
-                res += (vals[s] * vals[s] - vals[s + 1]) * alphas[alpha_idx];
-                res *= vals[s + 2]; // eq
-                lc += if let Some(rc) = &rc {
-                    res * rc.pow([alpha_idx as u64])
-                } else {
-                    return res;
+                let res = term.evaluate(&vals[s..s + 2]).unwrap() * alphas[alpha_idx];
+                out[alpha_idx] = match &rc {
+                    Some(rc) => res * rc.pow([alpha_idx as u64]),
+                    None => res,
                 };
             }
-            lc
         };
 
-        let (sumcheck_proof, prover_state) =
-            MLSumcheck::prove_as_subprotocol(transcript, mles, self.nvars, 3, comb_fn);
+        let (sumcheck_proof, prover_state) = MLSumcheck::prove_with_eqs(
+            transcript,
+            &mles.iter().collect::<Vec<_>>(),
+            self.nvars,
+            term.degree(),
+            &eq_points,
+            comb_fn,
+        );
 
         let r = prover_state.randomness.clone();
         let r_poly = prover_state
@@ -263,6 +271,19 @@ impl<R: OverField> In<R> {
 }
 
 impl<R: OverField> Out<R> {
+    /// The degree of the set check sumcheck polynomial, eq factor included.
+    fn sumcheck_degree(&self) -> usize {
+        set_check_term::<R>(self.nvars).degree() + 1
+    }
+
+    /// Returns the output with its sumcheck proof in the compressed format.
+    pub fn compress(&self) -> Self {
+        Self {
+            sumcheck_proof: self.sumcheck_proof.compress(self.sumcheck_degree()),
+            ..self.clone()
+        }
+    }
+
     pub fn verify(&self, transcript: &mut impl Transcript<R>) -> Result<(), SetCheckError<R>> {
         let nclaims = self.e[0].len() + self.b.len();
 
@@ -284,7 +305,7 @@ impl<R: OverField> Out<R> {
         let subclaim = MLSumcheck::verify_as_subprotocol(
             transcript,
             self.nvars,
-            3,
+            self.sumcheck_degree(),
             R::zero(),
             &self.sumcheck_proof,
         )?;
@@ -297,6 +318,7 @@ impl<R: OverField> Out<R> {
         absorb_evaluations(&self.e, &self.b, transcript);
 
         use ark_std::One;
+        let term = set_check_term::<R>(self.nvars);
         let mut ver = R::zero();
         for (i, e) in self.e[0].iter().enumerate() {
             let c = &cba[i].0;
@@ -309,7 +331,7 @@ impl<R: OverField> Out<R> {
                 .map(|(j, e_j)| {
                     let ev1 = R::from(ev(e_j, *beta));
                     let ev2 = R::from(ev(e_j, *beta * beta));
-                    (ev1 * ev1 - ev2) * alpha.pow([j as u64])
+                    term.evaluate(&[ev1, ev2]).unwrap() * alpha.pow([j as u64])
                 })
                 .sum::<R>();
             ver += eq * e_sum * rc.as_ref().unwrap_or(&R::BaseRing::one()).pow([i as u64]);
@@ -323,7 +345,7 @@ impl<R: OverField> Out<R> {
             let b_claim = {
                 let ev1 = R::from(ev(b, *beta));
                 let ev2 = R::from(ev(b, *beta * *beta));
-                ev1 * ev1 - ev2
+                term.evaluate(&[ev1, ev2]).unwrap()
             };
             ver += eq
                 * *alpha
@@ -379,6 +401,9 @@ mod tests {
 
         let mut ts = PoseidonTranscript::empty::<PC>();
         out.verify(&mut ts).unwrap();
+
+        let mut ts = PoseidonTranscript::empty::<PC>();
+        out.compress().verify(&mut ts).unwrap();
     }
 
     #[test]
@@ -467,6 +492,50 @@ mod tests {
         out.verify(&mut ts).unwrap();
     }
 
+    #[test]
+    fn test_set_check_single_matrix_mix() {
+        let n = 4;
+        let M = SparseMatrix::<R>::identity(n);
+        let m0 = vec![R::one(); n];
+        let m1 = vec![unit_monomial(2); n];
+
+        let scin = In {
+            sets: vec![
+                MonomialSet::Matrix(M),
+                MonomialSet::Vector(m0),
+                MonomialSet::Vector(m1),
+            ],
+            nvars: log2(n) as usize,
+        };
+
+        let mut ts = PoseidonTranscript::empty::<PC>();
+        let out = scin.set_check(&[], &mut ts);
+
+        let mut ts = PoseidonTranscript::empty::<PC>();
+        out.verify(&mut ts).unwrap();
+    }
+
+    #[test]
+    fn test_set_check_single_matrix_mix_bad() {
+        let n = 4;
+        let M = SparseMatrix::<R>::identity(n);
+        let mut m0 = vec![R::one(); n];
+        let mut onepx = R::one();
+        onepx.coeffs_mut()[1] = 1u128.into();
+        m0[0] = onepx;
+
+        let scin = In {
+            sets: vec![MonomialSet::Matrix(M), MonomialSet::Vector(m0)],
+            nvars: log2(n) as usize,
+        };
+
+        let mut ts = PoseidonTranscript::empty::<PC>();
+        let out = scin.set_check(&[], &mut ts);
+
+        let mut ts = PoseidonTranscript::empty::<PC>();
+        assert!(out.verify(&mut ts).is_err());
+    }
+
     #[test]
     fn test_set_check_mix_bad() {
         let n = 4;
@@ -0,0 +1,162 @@
+use ark_std::{time::Duration, One};
+use criterion::{
+    criterion_group, criterion_main, AxisScale, BatchSize::SmallInput, BenchmarkId, Criterion,
+    PlotConfiguration,
+};
+use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+use env::ENV;
+use latticefold::{
+    transcript::poseidon::PoseidonTranscript,
+    utils::sumcheck::{
+        small_value::SmallMle,
+        utils::{rand_poly, rand_poly_comb_fn},
+        virtual_polynomial::{VirtualPolynomial, VirtualPolynomialInfo},
+        MLSumcheck,
+    },
+};
+use rand::Rng;
+
+mod env;
+
+type R = GoldilocksRingNTT;
+type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+const NUM_MLES: usize = 3;
+const SMALL_ROUNDS: usize = 4;
+
+/// The range check `f^3 - f` of every digit and the product of the digits, over random
+/// digits in `{-1, 0, 1}` as in the folding sumcheck with `b_small = 2`.
+fn small_poly(nvars: usize) -> (VirtualPolynomialInfo<R>, Vec<SmallMle>) {
+    let mut rng = ark_std::test_rng();
+    let mles: Vec<SmallMle> = (0..NUM_MLES)
+        .map(|_| {
+            let evals = (0..1 << nvars).map(|_| rng.gen_range(-1..=1)).collect();
+            SmallMle::new(nvars, evals).unwrap()
+        })
+        .collect();
+
+    let mut info = VirtualPolynomialInfo::new(nvars, NUM_MLES);
+    for i in 0..NUM_MLES {
+        info.add_product(R::one(), [i, i, i]).unwrap();
+        info.add_product(-R::one(), [i]).unwrap();
+    }
+    info.add_product(R::one(), 0..NUM_MLES).unwrap();
+
+    (info, mles)
+}
+
+fn virtual_poly(info: &VirtualPolynomialInfo<R>, mles: &[SmallMle]) -> VirtualPolynomial<R> {
+    let mut poly = VirtualPolynomial::new(info.nvars());
+    for mle in mles {
+        poly.add_mle(mle.to_mle()).unwrap();
+    }
+    for (coeff, indices) in info.products() {
+        poly.add_product(*coeff, indices.iter().copied()).unwrap();
+    }
+
+    poly
+}
+
+fn sumcheck_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sumcheck small values Goldilocks");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Linear));
+
+    for nvars in [10, 12, 14] {
+        let (info, mles) = small_poly(nvars);
+
+        group.bench_with_input(
+            BenchmarkId::new("Ring rounds", nvars),
+            &(&info, &mles),
+            |b, (info, mles)| {
+                b.iter_batched(
+                    || virtual_poly(info, mles),
+                    |poly| MLSumcheck::prove_virtual(&mut T::default(), poly),
+                    SmallInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("Small value rounds", nvars),
+            &(&info, &mles),
+            |b, (info, mles)| {
+                b.iter_batched(
+                    || mles.to_vec(),
+                    |mles| {
+                        MLSumcheck::prove_small_values(&mut T::default(), info, mles, SMALL_ROUNDS)
+                            .unwrap()
+                    },
+                    SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn univariate_skip_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sumcheck univariate skip Goldilocks");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Linear));
+
+    for nvars in [10, 12, 14] {
+        let ((mles, degree), products, _) =
+            rand_poly::<R>(nvars, (2, 4), 3, &mut ark_std::test_rng()).unwrap();
+        let comb_fn = |vals: &[R]| rand_poly_comb_fn(vals, &products);
+
+        group.bench_with_input(BenchmarkId::new("No skip", nvars), &mles, |b, mles| {
+            b.iter_batched(
+                || mles.clone(),
+                |mles| {
+                    MLSumcheck::prove_as_subprotocol(
+                        &mut T::default(),
+                        mles,
+                        nvars,
+                        degree,
+                        comb_fn,
+                    )
+                },
+                SmallInput,
+            )
+        });
+
+        for skip in [2, 3] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("Skip {skip}"), nvars),
+                &mles,
+                |b, mles| {
+                    b.iter_batched(
+                        || mles.clone(),
+                        |mles| {
+                            MLSumcheck::prove_with_skip(
+                                &mut T::default(),
+                                mles,
+                                nvars,
+                                degree,
+                                skip,
+                                comb_fn,
+                            )
+                        },
+                        SmallInput,
+                    )
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+pub fn benchmarks_main(c: &mut Criterion) {
+    if ENV.GoldilocksRingNTT && ENV.prover {
+        sumcheck_benchmarks(c);
+        univariate_skip_benchmarks(c);
+    }
+}
+
+criterion_group!(
+    name=benches;
+    config = Criterion::default().sample_size(10).measurement_time(Duration::from_secs_f32(ENV.duration)).warm_up_time(Duration::from_secs_f32(ENV.warmup));
+    targets = benchmarks_main
+);
+criterion_main!(benches);
@@ -0,0 +1,75 @@
+//! Peak heap usage of the sumcheck provers.
+//!
+//! Proves the sum of `f_0 * f_1 * f_0` over random extensions, which the caller keeps for later
+//! evaluations as the protocols do, and reports the peak heap usage above the extensions.
+//! `PROVER` selects `owned` (`prove_as_subprotocol` on clones) or `borrowed` (`prove_borrowed`),
+//! and `NVARS` the number of variables.
+//!
+//! ```bash
+//! PROVER=owned cargo run --release --example sumcheck_memory --features dhat-heap
+//! PROVER=borrowed cargo run --release --example sumcheck_memory --features dhat-heap
+//! ```
+
+#[cfg(feature = "dhat-heap")]
+#[global_allocator]
+static ALLOC: dhat::Alloc = dhat::Alloc;
+
+use std::{env, time::Instant};
+
+use ark_std::UniformRand;
+use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+use latticefold::{transcript::poseidon::PoseidonTranscript, utils::sumcheck::MLSumcheck};
+use stark_rings_poly::mle::DenseMultilinearExtension;
+
+type R = GoldilocksRingNTT;
+type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+fn main() {
+    let nvars: usize = env::var("NVARS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(16);
+    let prover = env::var("PROVER").unwrap_or_else(|_| "borrowed".to_string());
+
+    #[cfg(feature = "dhat-heap")]
+    let _profiler = dhat::Profiler::new_heap();
+
+    let mut rng = ark_std::test_rng();
+    let mles: Vec<DenseMultilinearExtension<R>> = (0..2)
+        .map(|_| {
+            let evals = (0..1 << nvars).map(|_| R::rand(&mut rng)).collect();
+            DenseMultilinearExtension::from_evaluations_vec(nvars, evals)
+        })
+        .collect();
+    let comb_fn = |vals: &[R]| vals[0] * vals[1] * vals[2];
+
+    #[cfg(feature = "dhat-heap")]
+    let before = dhat::HeapStats::get().curr_bytes;
+
+    println!("Proving over {nvars} variables with the {prover} prover...");
+    let start = Instant::now();
+    let mut transcript = T::default();
+    match prover.as_str() {
+        "owned" => {
+            let owned = vec![mles[0].clone(), mles[1].clone(), mles[0].clone()];
+            MLSumcheck::prove_as_subprotocol(&mut transcript, owned, nvars, 3, comb_fn);
+        }
+        "borrowed" => {
+            let refs = [&mles[0], &mles[1], &mles[0]];
+            MLSumcheck::prove_borrowed(&mut transcript, &refs, nvars, 3, comb_fn);
+        }
+        _ => panic!("PROVER must be `owned` or `borrowed`"),
+    }
+    println!("Proof generated in {:?}", start.elapsed());
+
+    #[cfg(feature = "dhat-heap")]
+    {
+        let peak = dhat::HeapStats::get().max_bytes.saturating_sub(before);
+        println!(
+            "Peak heap above the extensions: {}",
+            humansize::format_size(peak, humansize::BINARY)
+        );
+    }
+    #[cfg(not(feature = "dhat-heap"))]
+    println!("Run with `--features dhat-heap` to measure the peak heap usage");
+}
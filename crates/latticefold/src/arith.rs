@@ -4,9 +4,12 @@
 
 use core::mem;
 
+use air::Air;
 use ark_ff::Field;
 use ark_std::log2;
 use cyclotomic_rings::rings::SuitableRing;
+use num_bigint::BigUint;
+use plonkish::{PlonkishLayout, PlonkishTable};
 use r1cs::R1CS;
 use stark_rings::{
     balanced_decomposition::{GadgetDecompose, GadgetRecompose},
@@ -23,12 +26,18 @@ use self::{
 use crate::{
     ark_base::*,
     commitment::{AjtaiCommitmentScheme, Commitment, CommitmentError},
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::{DecompositionParamData, DecompositionParams},
+    utils::norms,
 };
 
+pub mod air;
 pub mod ccs;
+pub mod diagnostics;
 pub mod error;
+pub mod optimize;
+pub mod plonkish;
 pub mod r1cs;
+pub mod reduction;
 pub mod utils;
 
 /// A trait for defining the behaviour of an arithmetic constraint system.
@@ -148,15 +157,66 @@ impl<R: Ring> CCS<R> {
         ccs
     }
 
-    ///Constructs a [`R1CS`] instance from a [`CCS`] instance.
-    // TODO check that this is actually valid R1CS and make this return a result
-    pub fn to_r1cs(self) -> R1CS<R> {
-        R1CS::<R> {
-            l: self.l,
-            A: self.M[0].clone(),
-            B: self.M[1].clone(),
-            C: self.M[2].clone(),
+    /// Constructs a [`CCS`] instance from a [`PlonkishTable`].
+    ///
+    /// Also returns the [`PlonkishLayout`] used to build the `z` vector from the table columns.
+    pub fn from_plonkish(table: &PlonkishTable<R>) -> Result<(Self, PlonkishLayout), Error> {
+        table.compile()
+    }
+
+    /// Constructs a [`CCS`] instance from a [`PlonkishTable`]. The CCS instance matrices rows are
+    /// then padded to max { `(n - l - 1) * L`, `m` } next power of 2.
+    pub fn from_plonkish_padded(
+        table: &PlonkishTable<R>,
+        L: usize,
+    ) -> Result<(Self, PlonkishLayout), Error> {
+        let (mut ccs, layout) = Self::from_plonkish(table)?;
+        let len = usize::max((ccs.n - ccs.l - 1) * L, ccs.m).next_power_of_two();
+        ccs.pad_rows_to(len);
+        Ok((ccs, layout))
+    }
+
+    /// Constructs a [`CCS`] instance from an [`Air`] unrolled over its trace rows.
+    pub fn from_air(air: &Air<R>) -> Result<Self, Error> {
+        air.to_ccs()
+    }
+
+    /// Constructs a [`CCS`] instance from an [`Air`] unrolled over its trace rows. The CCS
+    /// instance matrices rows are then padded to max { `(n - l - 1) * L`, `m` } next power of 2.
+    pub fn from_air_padded(air: &Air<R>, L: usize) -> Result<Self, Error> {
+        let mut ccs = Self::from_air(air)?;
+        let len = usize::max((ccs.n - ccs.l - 1) * L, ccs.m).next_power_of_two();
+        ccs.pad_rows_to(len);
+        Ok(ccs)
+    }
+
+    /// Checks that `S` only references existing matrices, that `c` and `S` have the same
+    /// length and that no matrix has an entry outside of the first `n` columns.
+    fn check_structure(&self) -> Result<(), Error> {
+        if self.c.len() != self.S.len() {
+            return Err(Error::LengthsNotEqual(
+                "c".to_string(),
+                "S".to_string(),
+                self.c.len(),
+                self.S.len(),
+            ));
+        }
+
+        if let Some(&j) = self.S.iter().flatten().find(|&&j| j >= self.M.len()) {
+            return Err(Error::MatrixIndexOutOfBounds(j, self.M.len()));
+        }
+
+        if let Some(col) = self
+            .M
+            .iter()
+            .flat_map(|M_j| M_j.coeffs.iter().take(self.m).flatten())
+            .map(|(_, col)| *col)
+            .find(|&col| col >= self.n)
+        {
+            return Err(Error::ColumnOutOfBounds("z".to_string(), col, self.n));
         }
+
+        Ok(())
     }
 
     fn pad_rows_to(&mut self, size: usize) {
@@ -228,11 +288,17 @@ impl<NTT: SuitableRing> Witness<NTT> {
     /// The main operations that need to be done are decomposing the ccs witness.
     /// We can then construct [`f_hat`](crate::arith::Witness::get_fhat).
     pub fn from_w_ccs<P: DecompositionParams>(w_ccs: Vec<NTT>) -> Self {
+        Self::from_w_ccs_with_params(&P::DATA, w_ccs)
+    }
+
+    /// Create a [`Witness`] from a ccs witness, with decomposition parameters chosen at runtime.
+    pub fn from_w_ccs_with_params(params: &DecompositionParamData, w_ccs: Vec<NTT>) -> Self {
         // iNTT
         let w_coeff: Vec<NTT::CoefficientRepresentation> = ICRT::elementwise_icrt(w_ccs.clone());
 
         // decompose radix-B
-        let f_coeff: Vec<NTT::CoefficientRepresentation> = w_coeff.gadget_decompose(P::B, P::L);
+        let f_coeff: Vec<NTT::CoefficientRepresentation> =
+            w_coeff.gadget_decompose(params.b(), params.l());
 
         // NTT(coef_repr_decomposed)
         let f: Vec<NTT> = CRT::elementwise_crt(f_coeff.clone());
@@ -296,13 +362,18 @@ impl<NTT: SuitableRing> Witness<NTT> {
         fhat
     }
 
+    #[cfg(test)]
     pub(crate) fn from_f<P: DecompositionParams>(f: Vec<NTT>) -> Self {
+        Self::from_f_with_params(&P::DATA, f)
+    }
+
+    pub(crate) fn from_f_with_params(params: &DecompositionParamData, f: Vec<NTT>) -> Self {
         let f_coeff: Vec<NTT::CoefficientRepresentation> = ICRT::elementwise_icrt(f.clone());
         let f_hat: Vec<DenseMultilinearExtension<NTT>> = Self::get_fhat(&f_coeff);
         // Reconstruct the original CCS witness from the Ajtai witness
         // Ajtai witness has bound B
         // WE multiply by the base B gadget matrix to reconstruct w_ccs
-        let w_ccs = f.gadget_recompose(P::B, P::L);
+        let w_ccs = f.gadget_recompose(params.b(), params.l());
 
         Self {
             f,
@@ -314,7 +385,7 @@ impl<NTT: SuitableRing> Witness<NTT> {
 
     #[allow(dead_code)]
     fn from_f_slice<P: DecompositionParams>(f: &[NTT]) -> Self {
-        Self::from_f::<P>(f.into())
+        Self::from_f_with_params(&P::DATA, f.into())
     }
 
     /// Reconstruct the original CCS witness from the Ajtai witness
@@ -323,11 +394,20 @@ impl<NTT: SuitableRing> Witness<NTT> {
     /// We can multiply by the base B gadget matrix to reconstruct w_ccs.
     pub fn from_f_coeff<P: DecompositionParams>(
         f_coeff: Vec<NTT::CoefficientRepresentation>,
+    ) -> Self {
+        Self::from_f_coeff_with_params(&P::DATA, f_coeff)
+    }
+
+    /// Reconstruct the original CCS witness from the Ajtai witness, with decomposition parameters
+    /// chosen at runtime.
+    pub fn from_f_coeff_with_params(
+        params: &DecompositionParamData,
+        f_coeff: Vec<NTT::CoefficientRepresentation>,
     ) -> Self {
         let f: Vec<NTT> = CRT::elementwise_crt(f_coeff.clone());
         let f_hat: Vec<DenseMultilinearExtension<NTT>> = Self::get_fhat(&f_coeff);
 
-        let w_ccs = f.gadget_recompose(P::B, P::L);
+        let w_ccs = f.gadget_recompose(params.b(), params.l());
 
         Self {
             f,
@@ -348,7 +428,16 @@ impl<NTT: SuitableRing> Witness<NTT> {
         rng: &mut Rng,
         w_ccs_len: usize,
     ) -> Self {
-        Self::from_w_ccs::<P>((0..w_ccs_len).map(|_| NTT::rand(rng)).collect())
+        Self::rand_with_params(&P::DATA, rng, w_ccs_len)
+    }
+
+    /// Generates a random witness, with decomposition parameters chosen at runtime.
+    pub fn rand_with_params<Rng: rand::Rng + ?Sized>(
+        params: &DecompositionParamData,
+        rng: &mut Rng,
+        w_ccs_len: usize,
+    ) -> Self {
+        Self::from_w_ccs_with_params(params, (0..w_ccs_len).map(|_| NTT::rand(rng)).collect())
     }
 
     /// Produces a commitment from a witness
@@ -368,21 +457,19 @@ impl<NTT: SuitableRing> Witness<NTT> {
         mem::take(&mut self.f_hat)
     }
 
-    #[allow(dead_code)]
-    fn within_bound(&self, b: u128) -> bool {
-        // TODO consider using signed representatives instead
-
-        let coeffs_repr: Vec<NTT::CoefficientRepresentation> =
-            ICRT::elementwise_icrt(self.f.clone());
-
-        // linf_norm should be used in CyclotomicGeneral not in specific ring
-        let b = <<NTT as PolyRing>::BaseRing as Field>::BasePrimeField::from(b);
-        let all_under_bound = coeffs_repr.iter().all(|ele| {
-            let coeffs = ele.coeffs();
-            coeffs.iter().all(|x| x < &b)
-        });
+    /// Returns the infinity norm of `f_coeff`.
+    ///
+    /// The coefficients are taken in the balanced representation, i.e. as integers in
+    /// $(-q/2, q/2\]$, so that the norm of $-x$ is the same as the norm of $x$.
+    pub fn linf_norm(&self) -> BigUint {
+        norms::linf_norm(&self.f_coeff)
+    }
 
-        all_under_bound
+    /// Checks that the infinity norm of `f_coeff` is strictly less than `b`.
+    ///
+    /// See [`Witness::linf_norm`].
+    pub fn within_bound(&self, b: u128) -> bool {
+        self.linf_norm() < BigUint::from(b)
     }
 }
 
@@ -546,4 +633,24 @@ pub mod tests {
         assert!(recreated_witness.check_data::<StarkDP>());
         assert_eq!(recreated_witness, random_witness);
     }
+
+    #[test]
+    fn test_linf_norm() {
+        let w_ccs = vec![
+            GoldilocksRingNTT::from(5u64),
+            -GoldilocksRingNTT::from(7u64),
+            GoldilocksRingNTT::from(3u64),
+        ];
+        let witness = Witness::from_w_ccs::<GoldilocksDP>(w_ccs);
+
+        assert_eq!(witness.linf_norm(), BigUint::from(7u32));
+        assert!(witness.within_bound(8));
+        assert!(!witness.within_bound(7));
+
+        let mut rng = ark_std::test_rng();
+        let random_witness =
+            Witness::<GoldilocksRingNTT>::rand::<_, GoldilocksDP>(&mut rng, WIT_LEN);
+
+        assert!(random_witness.within_bound(GoldilocksDP::B));
+    }
 }
@@ -0,0 +1,534 @@
+//! Provides an AIR front end that unrolls STARK-style transition constraints into CCS.
+//!
+//! An AIR describes a computation as an execution trace of `width` columns and `T` rows,
+//! transition constraints over two consecutive rows and boundary constraints pinning
+//! trace cells to public values.
+//!
+//! The trace is laid out row by row as the CCS witness, and the boundary values form the
+//! CCS statement, so the `z` vector is `boundary values || 1 || trace[0] || ... || trace[T - 1]`.
+//!
+//! See [section 5](https://eprint.iacr.org/2023/552.pdf#page=14) of the CCS paper.
+
+use ark_std::{collections::BTreeMap, log2};
+use stark_rings::Ring;
+use stark_rings_linalg::SparseMatrix;
+
+use super::{error::CSError as Error, CCS};
+use crate::ark_base::*;
+
+/// A variable of a transition constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AirVariable {
+    /// The given column of the current row
+    Cur(usize),
+    /// The given column of the next row
+    Next(usize),
+}
+
+impl AirVariable {
+    fn column(&self) -> usize {
+        match self {
+            Self::Cur(col) | Self::Next(col) => *col,
+        }
+    }
+
+    fn row(&self, step: usize) -> usize {
+        match self {
+            Self::Cur(_) => step,
+            Self::Next(_) => step + 1,
+        }
+    }
+}
+
+/// A transition constraint, i.e. the polynomial $\sum_i c_i \cdot \prod_{v \in V_i} v$
+/// over the variables of two consecutive rows which must vanish on every transition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionConstraint<R: Ring> {
+    /// The terms of the constraint polynomial
+    /// For each element, `.0` is the coefficient, `.1` is the list of multiplied variables
+    pub terms: Vec<(R, Vec<AirVariable>)>,
+}
+
+impl<R: Ring> Default for TransitionConstraint<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Ring> TransitionConstraint<R> {
+    /// Create a new constraint with no terms
+    pub fn new() -> Self {
+        Self { terms: Vec::new() }
+    }
+
+    /// Add a term to the constraint polynomial
+    pub fn add_term(mut self, coeff: impl Into<R>, vars: &[AirVariable]) -> Self {
+        self.terms.push((coeff.into(), vars.to_vec()));
+        self
+    }
+
+    /// The total degree of the constraint polynomial
+    pub fn degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|(_, vars)| vars.len().max(1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Evaluate the constraint on two consecutive rows
+    pub fn evaluate(&self, cur: &[R], next: &[R]) -> R {
+        self.terms.iter().fold(R::zero(), |sum, (coeff, vars)| {
+            sum + vars.iter().fold(*coeff, |acc, var| match var {
+                AirVariable::Cur(col) => acc * cur[*col],
+                AirVariable::Next(col) => acc * next[*col],
+            })
+        })
+    }
+}
+
+/// A boundary constraint, requiring `trace[row][column]` to equal a public value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundaryConstraint {
+    /// The row of the constrained cell
+    pub row: usize,
+    /// The column of the constrained cell
+    pub column: usize,
+}
+
+/// An Algebraic Intermediate Representation of a computation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Air<R: Ring> {
+    /// Number of columns of the trace
+    pub width: usize,
+    /// Number of rows of the trace
+    pub num_rows: usize,
+    /// Constraints enforced on every pair of consecutive rows
+    pub transitions: Vec<TransitionConstraint<R>>,
+    /// Cells of the trace exposed as public values
+    pub boundaries: Vec<BoundaryConstraint>,
+}
+
+impl<R: Ring> Air<R> {
+    /// Create a new AIR over a trace of `num_rows` rows of `width` columns, without constraints
+    pub fn new(width: usize, num_rows: usize) -> Self {
+        Self {
+            width,
+            num_rows,
+            transitions: Vec::new(),
+            boundaries: Vec::new(),
+        }
+    }
+
+    /// Add a transition constraint
+    pub fn add_transition(&mut self, constraint: TransitionConstraint<R>) -> &mut Self {
+        self.transitions.push(constraint);
+        self
+    }
+
+    /// Add a boundary constraint
+    pub fn add_boundary(&mut self, row: usize, column: usize) -> &mut Self {
+        self.boundaries.push(BoundaryConstraint { row, column });
+        self
+    }
+
+    /// Returns the CCS statement of a trace, i.e. its boundary values.
+    pub fn statement(&self, trace: &[Vec<R>]) -> Result<Vec<R>, Error> {
+        self.check_columns()?;
+        self.check_trace(trace)?;
+
+        Ok(self
+            .boundaries
+            .iter()
+            .map(|b| trace[b.row][b.column])
+            .collect())
+    }
+
+    /// Returns the CCS witness of a trace, i.e. the trace laid out row by row.
+    pub fn witness(&self, trace: &[Vec<R>]) -> Result<Vec<R>, Error> {
+        self.check_trace(trace)?;
+
+        Ok(trace.iter().flatten().copied().collect())
+    }
+
+    /// Check that every constraint of the AIR is satisfied by the trace.
+    pub fn check_trace_relation(&self, trace: &[Vec<R>]) -> Result<(), Error> {
+        self.check_trace(trace)?;
+
+        for (step, rows) in trace.windows(2).enumerate() {
+            for (i, constraint) in self.transitions.iter().enumerate() {
+                if !constraint.evaluate(&rows[0], &rows[1]).is_zero() {
+                    return Err(Error::NotSatisfied(i * (trace.len() - 1) + step));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_trace(&self, trace: &[Vec<R>]) -> Result<(), Error> {
+        if trace.len() != self.num_rows {
+            return Err(Error::LengthsNotEqual(
+                "trace".to_string(),
+                "num_rows".to_string(),
+                trace.len(),
+                self.num_rows,
+            ));
+        }
+
+        if let Some(row) = trace.iter().find(|row| row.len() != self.width) {
+            return Err(Error::LengthsNotEqual(
+                "trace row".to_string(),
+                "width".to_string(),
+                row.len(),
+                self.width,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_columns(&self) -> Result<(), Error> {
+        let transition_columns = self
+            .transitions
+            .iter()
+            .flat_map(|constraint| constraint.terms.iter())
+            .flat_map(|(_, vars)| vars.iter().map(|var| var.column()));
+        let boundary_columns = self.boundaries.iter().map(|b| b.column);
+
+        if let Some(col) = transition_columns
+            .chain(boundary_columns)
+            .find(|&col| col >= self.width)
+        {
+            return Err(Error::ColumnOutOfBounds(
+                "trace".to_string(),
+                col,
+                self.width,
+            ));
+        }
+
+        if let Some(b) = self.boundaries.iter().find(|b| b.row >= self.num_rows) {
+            return Err(Error::RowOutOfBounds(b.row, self.num_rows));
+        }
+
+        Ok(())
+    }
+
+    /// Unrolls the AIR over its `T = num_rows` rows into a [`CCS`].
+    ///
+    /// Transition constraint `i` applied to rows `j` and `j + 1` becomes the CCS row
+    /// `i * (T - 1) + j`. For every constraint, each variable it uses gets a shifted-selection
+    /// matrix picking the corresponding cell of the trace on the rows of this constraint,
+    /// and each term becomes a multiset of these matrices. Repeated variables are repeated in
+    /// the multiset, so a term like $x^3$ costs a single matrix.
+    ///
+    /// The boundary constraints are appended as linear rows `trace[row][column] - x_k = 0`.
+    pub fn to_ccs(&self) -> Result<CCS<R>, Error> {
+        let T = self.num_rows;
+        if T < 2 {
+            return Err(Error::RowOutOfBounds(1, T));
+        }
+        self.check_columns()?;
+
+        let steps = T - 1;
+        let l = self.boundaries.len();
+        let one = l;
+        let n = l + 1 + T * self.width;
+        let m = self.transitions.len() * steps + l;
+        let trace_index = |row: usize, col: usize| l + 1 + row * self.width + col;
+
+        let empty_matrix = || SparseMatrix {
+            nrows: m,
+            ncols: n,
+            coeffs: vec![vec![]; m],
+        };
+
+        let mut M: Vec<SparseMatrix<R>> = Vec::new();
+        let mut S: Vec<Vec<usize>> = Vec::new();
+        let mut c: Vec<R> = Vec::new();
+
+        for (i, constraint) in self.transitions.iter().enumerate() {
+            let rows = i * steps..(i + 1) * steps;
+            // Selection matrices of this constraint, `None` standing for the constant term
+            let mut selections: BTreeMap<Option<AirVariable>, usize> = BTreeMap::new();
+
+            for (coeff, vars) in &constraint.terms {
+                let keys: Vec<Option<AirVariable>> = if vars.is_empty() {
+                    vec![None]
+                } else {
+                    vars.iter().copied().map(Some).collect()
+                };
+
+                let S_i = keys
+                    .into_iter()
+                    .map(|key| {
+                        *selections.entry(key).or_insert_with(|| {
+                            let mut selection = empty_matrix();
+                            for (step, row) in rows.clone().enumerate() {
+                                let col = match key {
+                                    Some(var) => trace_index(var.row(step), var.column()),
+                                    None => one,
+                                };
+                                selection.coeffs[row].push((R::one(), col));
+                            }
+                            M.push(selection);
+                            M.len() - 1
+                        })
+                    })
+                    .collect();
+
+                S.push(S_i);
+                c.push(*coeff);
+            }
+        }
+
+        if !self.boundaries.is_empty() {
+            let mut boundary = empty_matrix();
+            for (k, b) in self.boundaries.iter().enumerate() {
+                let row = self.transitions.len() * steps + k;
+                boundary.coeffs[row].push((R::one(), trace_index(b.row, b.column)));
+                boundary.coeffs[row].push((-R::one(), k));
+            }
+
+            M.push(boundary);
+            S.push(vec![M.len() - 1]);
+            c.push(R::one());
+        }
+
+        Ok(CCS {
+            m,
+            n,
+            l,
+            t: M.len(),
+            q: S.len(),
+            d: S.iter().map(|S_i| S_i.len()).max().unwrap_or(0),
+            s: log2(m) as usize,
+            s_prime: log2(n) as usize,
+            M,
+            S,
+            c,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::One;
+    use ark_std::test_rng;
+    use cyclotomic_rings::rings::{
+        BabyBearRingNTT, GoldilocksChallengeSet, GoldilocksRingNTT, SuitableRing,
+    };
+
+    use super::{AirVariable::*, *};
+    use crate::{
+        arith::{Arith, Witness, CCCS},
+        commitment::AjtaiCommitmentScheme,
+        decomposition_parameters::{test_params::GoldilocksDP, DecompositionParams},
+        nifs::linearization::{
+            LFLinearizationProver, LFLinearizationVerifier, LinearizationProver,
+            LinearizationVerifier,
+        },
+        transcript::poseidon::PoseidonTranscript,
+    };
+
+    // Fibonacci: next(a) = b, next(b) = a + b, with a_0 = b_0 = 1 and the last b public.
+    fn get_fibonacci_air<R: Ring>(T: usize) -> Air<R> {
+        let mut air = Air::new(2, T);
+        air.add_transition(
+            TransitionConstraint::new()
+                .add_term(R::one(), &[Next(0)])
+                .add_term(-R::one(), &[Cur(1)]),
+        )
+        .add_transition(
+            TransitionConstraint::new()
+                .add_term(R::one(), &[Next(1)])
+                .add_term(-R::one(), &[Cur(0)])
+                .add_term(-R::one(), &[Cur(1)]),
+        )
+        .add_boundary(0, 0)
+        .add_boundary(0, 1)
+        .add_boundary(T - 1, 1);
+        air
+    }
+
+    fn get_fibonacci_trace<R: Ring>(T: usize) -> Vec<Vec<R>> {
+        let mut trace = vec![vec![R::one(), R::one()]];
+        for i in 1..T {
+            let (a, b) = (trace[i - 1][0], trace[i - 1][1]);
+            trace.push(vec![b, a + b]);
+        }
+        trace
+    }
+
+    // Cubic recurrence: next(x) = x^3 + y, next(y) = y + 1
+    fn get_cubic_air<R: Ring>(T: usize) -> Air<R> {
+        let mut air = Air::new(2, T);
+        air.add_transition(
+            TransitionConstraint::new()
+                .add_term(R::one(), &[Next(0)])
+                .add_term(-R::one(), &[Cur(0), Cur(0), Cur(0)])
+                .add_term(-R::one(), &[Cur(1)]),
+        )
+        .add_transition(
+            TransitionConstraint::new()
+                .add_term(R::one(), &[Next(1)])
+                .add_term(-R::one(), &[Cur(1)])
+                .add_term(-R::one(), &[]),
+        )
+        .add_boundary(0, 0);
+        air
+    }
+
+    fn get_cubic_trace<R: Ring>(T: usize) -> Vec<Vec<R>> {
+        let mut trace = vec![vec![R::from(2u64), R::zero()]];
+        for i in 1..T {
+            let (x, y) = (trace[i - 1][0], trace[i - 1][1]);
+            trace.push(vec![x * x * x + y, y + R::one()]);
+        }
+        trace
+    }
+
+    fn get_z<R: Ring>(air: &Air<R>, trace: &[Vec<R>]) -> Vec<R> {
+        let mut z = air.statement(trace).unwrap();
+        z.push(R::one());
+        z.extend(air.witness(trace).unwrap());
+        z
+    }
+
+    fn test_air_ccs_relation<R: SuitableRing>() {
+        const T: usize = 8;
+
+        let air = get_fibonacci_air::<R>(T);
+        let trace = get_fibonacci_trace::<R>(T);
+        air.check_trace_relation(&trace).unwrap();
+
+        let ccs = air.to_ccs().unwrap();
+        assert_eq!(ccs.m, 2 * (T - 1) + 3);
+        assert_eq!(ccs.l, 3);
+        assert_eq!(ccs.d, 1);
+        ccs.check_relation(&get_z(&air, &trace)).unwrap();
+
+        let air = get_cubic_air::<R>(T);
+        let trace = get_cubic_trace::<R>(T);
+        let ccs = air.to_ccs().unwrap();
+        assert_eq!(ccs.d, 3);
+        // Cur(0) is reused by the cubic term, so the first constraint only has three matrices.
+        assert_eq!(ccs.S[1], vec![1, 1, 1]);
+        ccs.check_relation(&get_z(&air, &trace)).unwrap();
+    }
+
+    #[test]
+    fn test_air_ccs_relation_goldilocks() {
+        test_air_ccs_relation::<GoldilocksRingNTT>();
+    }
+
+    #[test]
+    fn test_air_ccs_relation_babybear() {
+        test_air_ccs_relation::<BabyBearRingNTT>();
+    }
+
+    #[test]
+    fn test_air_ccs_bad_trace() {
+        type RqNTT = GoldilocksRingNTT;
+        const T: usize = 8;
+
+        let air = get_fibonacci_air::<RqNTT>(T);
+        let ccs = air.to_ccs().unwrap();
+
+        // Wrong transition on step 3
+        let mut trace = get_fibonacci_trace::<RqNTT>(T);
+        trace[4][0] += RqNTT::one();
+        assert!(matches!(
+            ccs.check_relation(&get_z(&air, &trace)),
+            Err(Error::NotSatisfied(3))
+        ));
+        assert!(matches!(
+            air.check_trace_relation(&trace),
+            Err(Error::NotSatisfied(3))
+        ));
+
+        // Wrong boundary value
+        let trace = get_fibonacci_trace::<RqNTT>(T);
+        let mut z = get_z(&air, &trace);
+        z[2] += RqNTT::one();
+        assert!(matches!(
+            ccs.check_relation(&z),
+            Err(Error::NotSatisfied(i)) if i == 2 * (T - 1) + 2
+        ));
+    }
+
+    #[test]
+    fn test_air_invalid() {
+        let mut air = get_fibonacci_air::<GoldilocksRingNTT>(8);
+        air.num_rows = 4;
+        assert!(matches!(air.to_ccs(), Err(Error::RowOutOfBounds(7, 4))));
+        air.num_rows = 1;
+        assert!(matches!(air.to_ccs(), Err(Error::RowOutOfBounds(1, 1))));
+
+        let mut air = get_fibonacci_air::<GoldilocksRingNTT>(8);
+        air.add_transition(TransitionConstraint::new().add_term(1u64, &[Cur(2)]));
+        assert!(matches!(
+            air.to_ccs(),
+            Err(Error::ColumnOutOfBounds(_, 2, 2))
+        ));
+    }
+
+    #[test]
+    fn test_air_invalid_trace() {
+        type RqNTT = GoldilocksRingNTT;
+        const T: usize = 8;
+
+        let air = get_fibonacci_air::<RqNTT>(T);
+        let trace = get_fibonacci_trace::<RqNTT>(T - 1);
+        assert!(matches!(
+            air.statement(&trace),
+            Err(Error::LengthsNotEqual(_, _, 7, 8))
+        ));
+        assert!(matches!(
+            air.witness(&trace),
+            Err(Error::LengthsNotEqual(_, _, 7, 8))
+        ));
+        assert!(air.check_trace_relation(&trace).is_err());
+
+        let mut air = get_fibonacci_air::<RqNTT>(T);
+        air.add_boundary(3, 2);
+        let trace = get_fibonacci_trace::<RqNTT>(T);
+        assert!(matches!(
+            air.statement(&trace),
+            Err(Error::ColumnOutOfBounds(_, 2, 2))
+        ));
+    }
+
+    #[test]
+    fn test_air_ccs_linearization() {
+        type RqNTT = GoldilocksRingNTT;
+        type CS = GoldilocksChallengeSet;
+        type DP = GoldilocksDP;
+        type T = PoseidonTranscript<RqNTT, CS>;
+        const ROWS: usize = 4;
+
+        let air = get_cubic_air::<RqNTT>(ROWS);
+        let trace = get_cubic_trace::<RqNTT>(ROWS);
+        let ccs = CCS::from_air_padded(&air, DP::L).unwrap();
+
+        let x_ccs = air.statement(&trace).unwrap();
+        let wit = Witness::from_w_ccs::<DP>(air.witness(&trace).unwrap());
+        let scheme = AjtaiCommitmentScheme::rand(4, wit.f.len(), &mut test_rng());
+        let cm_i = CCCS {
+            cm: wit.commit::<DP>(&scheme).unwrap(),
+            x_ccs,
+        };
+
+        let mut prover_transcript = T::default();
+        let (lcccs, proof) =
+            LFLinearizationProver::<_, T>::prove(&cm_i, &wit, &mut prover_transcript, &ccs)
+                .unwrap();
+
+        let mut verifier_transcript = T::default();
+        let verified =
+            LFLinearizationVerifier::<_, T>::verify(&cm_i, &proof, &mut verifier_transcript, &ccs)
+                .unwrap();
+
+        assert_eq!(lcccs, verified);
+    }
+}
@@ -0,0 +1,459 @@
+//! Provides constraint failure diagnostics for CCS and R1CS instances.
+//!
+//! [`Arith::check_relation`](super::Arith::check_relation) stops at the first failing row.
+//! [`CCS::diagnose`] instead lists every failing row together with the values of its
+//! multiset products, so a bad witness can be traced back to the variables and to the
+//! gadget that produced it.
+
+use ark_std::{collections::BTreeMap, fmt::Display, format, ops::Range};
+use stark_rings::Ring;
+use stark_rings_linalg::SparseMatrix;
+
+use super::{error::CSError as Error, r1cs::R1CS, utils::mat_vec_mul, CCS};
+use crate::ark_base::*;
+
+/// Human-readable names for the variables of `z` and for ranges of constraint rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VariableMap {
+    names: BTreeMap<usize, String>,
+    row_ranges: Vec<(String, Range<usize>)>,
+}
+
+impl VariableMap {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the variable at index `index` of `z`.
+    pub fn name_variable(&mut self, index: usize, name: impl Into<String>) -> &mut Self {
+        self.names.insert(index, name.into());
+        self
+    }
+
+    /// Names a range of constraint rows, e.g. the rows produced by a gadget.
+    pub fn name_rows(&mut self, name: impl Into<String>, rows: Range<usize>) -> &mut Self {
+        self.row_ranges.push((name.into(), rows));
+        self
+    }
+
+    /// Returns the name of the variable at index `index` of `z`, if any.
+    pub fn variable_name(&self, index: usize) -> Option<&str> {
+        self.names.get(&index).map(String::as_str)
+    }
+
+    /// Returns the name of the first named range containing `row`, if any.
+    pub fn rows_name(&self, row: usize) -> Option<&str> {
+        self.row_ranges
+            .iter()
+            .find(|(_, rows)| rows.contains(&row))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the named row ranges, in insertion order.
+    pub fn row_ranges(&self) -> &[(String, Range<usize>)] {
+        &self.row_ranges
+    }
+}
+
+/// The value of $(M_j \cdot z)_r$ for a matrix of a multiset at a failing row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FactorDiagnostic<R: Ring> {
+    /// Index of the matrix
+    pub matrix: usize,
+    /// Value of the row of the matrix applied to `z`
+    pub value: R,
+    /// Indices of the variables of `z` with a non-zero coefficient on the row of the matrix
+    pub variables: Vec<usize>,
+}
+
+/// The contribution of a multiset to a failing row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermDiagnostic<R: Ring> {
+    /// Index of the multiset
+    pub multiset: usize,
+    /// Coefficient of the multiset
+    pub coeff: R,
+    /// Factors of the Hadamard product
+    pub factors: Vec<FactorDiagnostic<R>>,
+    /// The coefficient times the product of the factors
+    pub product: R,
+}
+
+/// A row of the constraint system which does not evaluate to zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowDiagnostic<R: Ring> {
+    /// Index of the row
+    pub row: usize,
+    /// Value of the row, i.e. the sum of the term products
+    pub value: R,
+    /// Contribution of every multiset
+    pub terms: Vec<TermDiagnostic<R>>,
+}
+
+/// Failure statistics over a range of rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowRangeStats {
+    /// Name of the range
+    pub name: String,
+    /// The rows of the range
+    pub rows: Range<usize>,
+    /// Number of failing rows in the range
+    pub failing: usize,
+    /// First failing row of the range, if any
+    pub first_failing: Option<usize>,
+}
+
+/// The result of checking every row of a constraint system against a `z` vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostics<R: Ring> {
+    /// Number of rows checked
+    pub num_rows: usize,
+    /// The failing rows, in increasing order
+    pub failing: Vec<RowDiagnostic<R>>,
+}
+
+impl<R: Ring> Diagnostics<R> {
+    /// Returns `true` if no row fails.
+    pub fn is_satisfied(&self) -> bool {
+        self.failing.is_empty()
+    }
+
+    /// Returns the indices of the failing rows.
+    pub fn failing_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.failing.iter().map(|row| row.row)
+    }
+
+    fn stats(&self, name: String, rows: Range<usize>) -> RowRangeStats {
+        // The failing rows are sorted, so the ones of the range are contiguous
+        let start = self.failing.partition_point(|row| row.row < rows.start);
+        let end = self.failing.partition_point(|row| row.row < rows.end);
+        let failing = end.saturating_sub(start);
+
+        RowRangeStats {
+            name,
+            failing,
+            first_failing: (failing > 0).then(|| self.failing[start].row),
+            rows,
+        }
+    }
+
+    /// Returns the failure statistics of every row range named in `map`.
+    pub fn range_stats(&self, map: &VariableMap) -> Vec<RowRangeStats> {
+        map.row_ranges()
+            .iter()
+            .map(|(name, rows)| self.stats(name.clone(), rows.clone()))
+            .collect()
+    }
+
+    /// Splits the rows in buckets of `bucket_size` rows and returns the failure statistics
+    /// of the buckets containing at least one failing row.
+    pub fn bucket_stats(&self, bucket_size: usize) -> Vec<RowRangeStats> {
+        let bucket_size = bucket_size.max(1);
+        let mut buckets: Vec<RowRangeStats> = Vec::new();
+        for row in self.failing_rows() {
+            let start = row / bucket_size * bucket_size;
+            match buckets.last_mut() {
+                Some(bucket) if bucket.rows.start == start => bucket.failing += 1,
+                _ => {
+                    let end = usize::min(start + bucket_size, self.num_rows);
+                    buckets.push(RowRangeStats {
+                        name: format!("rows {start}..{end}"),
+                        rows: start..end,
+                        failing: 1,
+                        first_failing: Some(row),
+                    });
+                }
+            }
+        }
+
+        buckets
+    }
+}
+
+impl<R: Ring + Display> Diagnostics<R> {
+    /// Returns a human-readable report of the failing rows.
+    ///
+    /// Variables and rows are named after `map` when available.
+    pub fn report(&self, map: Option<&VariableMap>) -> String {
+        let variable = |index: usize| {
+            map.and_then(|map| map.variable_name(index))
+                .map_or_else(|| format!("z[{index}]"), String::from)
+        };
+
+        let mut report = format!("{} of {} rows failing\n", self.failing.len(), self.num_rows);
+
+        for row in &self.failing {
+            match map.and_then(|map| map.rows_name(row.row)) {
+                Some(name) => report += &format!("row {} ({name}): {}\n", row.row, row.value),
+                None => report += &format!("row {}: {}\n", row.row, row.value),
+            }
+
+            for term in &row.terms {
+                report += &format!(
+                    "  multiset {}: {} * ... = {}\n",
+                    term.multiset, term.coeff, term.product
+                );
+                for factor in &term.factors {
+                    let variables: Vec<String> =
+                        factor.variables.iter().map(|&i| variable(i)).collect();
+                    report += &format!(
+                        "    M_{} z = {} from [{}]\n",
+                        factor.matrix,
+                        factor.value,
+                        variables.join(", ")
+                    );
+                }
+            }
+        }
+
+        report
+    }
+}
+
+impl<R: Ring> CCS<R> {
+    /// Checks every row of the [`CCS`] against `z` and describes the failing ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the instance is malformed or if `z` does not have the right length.
+    pub fn diagnose(&self, z: &[R]) -> Result<Diagnostics<R>, Error> {
+        self.check_structure()?;
+
+        let M: Vec<&SparseMatrix<R>> = self.M.iter().collect();
+        diagnose_rows(&M, &self.S, &self.c, self.m, z)
+    }
+}
+
+impl<R: Ring> R1CS<R> {
+    /// Checks every row of the [`R1CS`] against `z` and describes the failing ones.
+    ///
+    /// The R1CS is viewed as a CCS with matrices `A`, `B` and `C` numbered `0`, `1` and `2`,
+    /// and multisets $A \circ B$ and $-C$ numbered `0` and `1`.
+    pub fn diagnose(&self, z: &[R]) -> Result<Diagnostics<R>, Error> {
+        diagnose_rows(
+            &[&self.A, &self.B, &self.C],
+            &[vec![0, 1], vec![2]],
+            &[R::one(), -R::one()],
+            self.A.nrows(),
+            z,
+        )
+    }
+}
+
+/// Checks the `m` rows of $\sum_i c_i \cdot \bigcirc_{j \in S_i} M_j \cdot z$ and describes the
+/// failing ones.
+fn diagnose_rows<R: Ring>(
+    M: &[&SparseMatrix<R>],
+    S: &[Vec<usize>],
+    c: &[R],
+    m: usize,
+    z: &[R],
+) -> Result<Diagnostics<R>, Error> {
+    let Mz: Vec<Vec<R>> = M
+        .iter()
+        .map(|M_j| {
+            let mut M_j_z = mat_vec_mul(M_j, z)?;
+            M_j_z.resize(m, R::zero());
+            Ok(M_j_z)
+        })
+        .collect::<Result<_, Error>>()?;
+
+    let row_value = |r: usize| {
+        S.iter().zip(c).fold(R::zero(), |acc, (S_i, c_i)| {
+            acc + S_i.iter().fold(*c_i, |product, &j| product * Mz[j][r])
+        })
+    };
+
+    let failing = (0..m)
+        .filter_map(|r| {
+            let value = row_value(r);
+            (!value.is_zero()).then(|| RowDiagnostic {
+                row: r,
+                value,
+                terms: diagnose_terms(M, S, c, &Mz, r),
+            })
+        })
+        .collect();
+
+    Ok(Diagnostics {
+        num_rows: m,
+        failing,
+    })
+}
+
+fn diagnose_terms<R: Ring>(
+    M: &[&SparseMatrix<R>],
+    S: &[Vec<usize>],
+    c: &[R],
+    Mz: &[Vec<R>],
+    r: usize,
+) -> Vec<TermDiagnostic<R>> {
+    S.iter()
+        .zip(c)
+        .enumerate()
+        .map(|(i, (S_i, c_i))| {
+            let factors: Vec<FactorDiagnostic<R>> = S_i
+                .iter()
+                .map(|&j| FactorDiagnostic {
+                    matrix: j,
+                    value: Mz[j][r],
+                    variables: M[j]
+                        .coeffs
+                        .get(r)
+                        .map(|row| {
+                            row.iter()
+                                .filter(|(v, _)| !v.is_zero())
+                                .map(|(_, col)| *col)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                })
+                .collect();
+
+            TermDiagnostic {
+                multiset: i,
+                coeff: *c_i,
+                product: factors.iter().fold(*c_i, |acc, f| acc * f.value),
+                factors,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::One;
+    use cyclotomic_rings::rings::GoldilocksRingNTT;
+
+    use super::*;
+    use crate::arith::r1cs::{get_test_r1cs, get_test_z};
+
+    type R = GoldilocksRingNTT;
+
+    fn get_test_map() -> VariableMap {
+        let mut map = VariableMap::new();
+        map.name_variable(0, "x")
+            .name_variable(1, "one")
+            .name_variable(2, "y")
+            .name_variable(3, "x^2")
+            .name_variable(4, "x^3")
+            .name_variable(5, "x^3 + x")
+            .name_rows("square", 0..1)
+            .name_rows("cube", 1..2)
+            .name_rows("output", 2..4);
+        map
+    }
+
+    #[test]
+    fn test_diagnose_satisfied() {
+        let r1cs = get_test_r1cs::<R>();
+        let diagnostics = r1cs.diagnose(&get_test_z(3)).unwrap();
+
+        assert!(diagnostics.is_satisfied());
+        assert_eq!(diagnostics.num_rows, 4);
+        assert!(diagnostics.bucket_stats(2).is_empty());
+    }
+
+    #[test]
+    fn test_diagnose_r1cs() {
+        let r1cs = get_test_r1cs::<R>();
+        let mut z = get_test_z::<R>(3);
+        // Wrong x^2, used by the first two rows
+        z[3] = R::from(10u64);
+
+        let diagnostics = r1cs.diagnose(&z).unwrap();
+        assert_eq!(diagnostics.failing_rows().collect::<Vec<_>>(), vec![0, 1]);
+
+        // x * x - x^2 = 9 - 10
+        let row = &diagnostics.failing[0];
+        assert_eq!(row.value, -R::one());
+        assert_eq!(row.terms.len(), 2);
+        assert_eq!(row.terms[0].product, R::from(9u64));
+        assert_eq!(row.terms[0].factors[0].variables, vec![0]);
+        assert_eq!(row.terms[1].factors[0].matrix, 2);
+        assert_eq!(row.terms[1].factors[0].value, R::from(10u64));
+        assert_eq!(row.terms[1].factors[0].variables, vec![3]);
+
+        let map = get_test_map();
+        let stats = diagnostics.range_stats(&map);
+        assert_eq!(
+            stats.iter().map(|s| s.failing).collect::<Vec<_>>(),
+            vec![1, 1, 0]
+        );
+        assert_eq!(stats[1].first_failing, Some(1));
+        assert_eq!(stats[2].first_failing, None);
+
+        let buckets = diagnostics.bucket_stats(2);
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].rows, 0..2);
+        assert_eq!(buckets[0].failing, 2);
+
+        let report = diagnostics.report(Some(&map));
+        assert!(report.starts_with("2 of 4 rows failing"));
+        assert!(report.contains("row 1 (cube)"));
+        assert!(report.contains("[x^2]"));
+        assert!(diagnostics.report(None).contains("[z[3]]"));
+    }
+
+    #[test]
+    fn test_stats_of_many_rows() {
+        let failing = [3, 4, 9, 10, 11, 30]
+            .into_iter()
+            .map(|row| RowDiagnostic {
+                row,
+                value: R::one(),
+                terms: vec![],
+            })
+            .collect();
+        let diagnostics = Diagnostics {
+            num_rows: 32,
+            failing,
+        };
+
+        let mut map = VariableMap::new();
+        map.name_rows("low", 0..10)
+            .name_rows("overlap", 4..11)
+            .name_rows("empty", 12..30)
+            .name_rows("tail", 30..32);
+        let stats = diagnostics.range_stats(&map);
+        assert_eq!(
+            stats.iter().map(|s| s.failing).collect::<Vec<_>>(),
+            vec![3, 3, 0, 1]
+        );
+        assert_eq!(
+            stats.iter().map(|s| s.first_failing).collect::<Vec<_>>(),
+            vec![Some(3), Some(4), None, Some(30)]
+        );
+
+        let buckets = diagnostics.bucket_stats(8);
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|b| (b.rows.clone(), b.failing, b.first_failing))
+                .collect::<Vec<_>>(),
+            vec![
+                (0..8, 2, Some(3)),
+                (8..16, 3, Some(9)),
+                (24..32, 1, Some(30))
+            ]
+        );
+        assert_eq!(buckets[1].name, "rows 8..16");
+    }
+
+    #[test]
+    fn test_diagnose_ccs_matches_check_relation() {
+        use crate::arith::{tests::get_test_ccs, Arith};
+
+        let ccs = get_test_ccs::<R>(4, 2);
+        let mut z = get_test_z::<R>(5);
+        z[5] += R::one();
+
+        let diagnostics = ccs.diagnose(&z).unwrap();
+        let Err(Error::NotSatisfied(first)) = ccs.check_relation(&z) else {
+            panic!("the tampered witness should not satisfy the CCS");
+        };
+        assert_eq!(diagnostics.failing_rows().next(), Some(first));
+        assert_eq!(diagnostics.num_rows, ccs.m);
+    }
+}
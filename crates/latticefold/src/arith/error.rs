@@ -26,4 +26,20 @@ pub enum CSError {
     /// This error occurs when performing operations on vectors of differing lengths.
     #[error("vectors {0} and {1} have different lengths: {0} and {1}")]
     LengthsNotEqual(String, String, usize, usize),
+
+    /// A Plonkish table or an AIR references a column that does not exist.
+    #[error("{0} column {1} out of bounds: there are {2} such columns")]
+    ColumnOutOfBounds(String, usize, usize),
+
+    /// A multiset of a CCS references a matrix that does not exist.
+    #[error("matrix {0} out of bounds: there are {1} matrices")]
+    MatrixIndexOutOfBounds(usize, usize),
+
+    /// An AIR references a trace row that does not exist.
+    #[error("row {0} out of bounds: the trace has {1} rows")]
+    RowOutOfBounds(usize, usize),
+
+    /// A copy constraint of a Plonkish table cannot be compiled or is violated by an assignment.
+    #[error("invalid copy constraint: {0}")]
+    InvalidCopyConstraint(String),
 }
@@ -0,0 +1,523 @@
+//! Provides optimisation passes shrinking a CCS instance without changing its relation.
+//!
+//! The prover pays for every matrix when computing the $M_j \cdot z$ MLEs and for every
+//! `theta`/`eta` evaluation in folding, so removing duplicated or useless matrices, multisets
+//! and columns directly reduces proving time and proof size.
+
+use ark_std::{collections::BTreeMap, log2, mem};
+use stark_rings::Ring;
+use stark_rings_linalg::SparseMatrix;
+
+use super::{error::CSError as Error, CCS};
+use crate::ark_base::*;
+
+/// A pass of the [`CCS`] optimisation pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizationPass {
+    /// Sorts the entries of every matrix row by column, merging repeated columns and removing
+    /// zero coefficients.
+    NormalizeMatrices,
+    /// Replaces identical matrices by a single one, rewriting `S`.
+    DedupMatrices,
+    /// Merges identical multisets by summing their coefficients, and removes the multisets
+    /// which vanish, i.e. with a zero coefficient or using an all-zero matrix.
+    MergeMultisets,
+    /// Removes the matrices not used by any multiset.
+    DropUnusedMatrices,
+    /// Removes the witness columns not used by any matrix.
+    /// The witness must then be mapped with the returned [`WitnessRemap`].
+    ///
+    /// This changes `n` and the witness length, which LatticeFold pads to a multiple of its
+    /// decomposition parameters and checks in `sanity_check`. Do not use it on instances padded
+    /// for LatticeFold, such as the ones built with [`CCS::from_r1cs_padded`].
+    PruneZeroColumns,
+    /// Removes the rows which are zero in every matrix.
+    ///
+    /// If `m` was a power of two, the rows are padded back to the next power of two, but any
+    /// larger padding is lost. Do not use it on instances padded for LatticeFold, such as the
+    /// ones built with [`CCS::from_r1cs_padded`].
+    PruneZeroRows,
+}
+
+/// The passes run by [`CCS::optimize`].
+///
+/// They keep `m` and `n`, so the optimised instance keeps the padding of the original one.
+pub const DEFAULT_PASSES: [OptimizationPass; 4] = [
+    OptimizationPass::NormalizeMatrices,
+    OptimizationPass::DedupMatrices,
+    OptimizationPass::MergeMultisets,
+    OptimizationPass::DropUnusedMatrices,
+];
+
+/// Maps the witness of an original [`CCS`] to the witness of its optimised version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessRemap {
+    /// Length of the public input
+    pub l: usize,
+    /// Length of the original witness
+    pub w_len: usize,
+    /// Indices of the original witness entries kept, in order
+    pub kept: Vec<usize>,
+}
+
+impl WitnessRemap {
+    fn identity(l: usize, w_len: usize) -> Self {
+        Self {
+            l,
+            w_len,
+            kept: (0..w_len).collect(),
+        }
+    }
+
+    /// Returns `true` if no witness column has been eliminated.
+    pub fn is_identity(&self) -> bool {
+        self.kept.len() == self.w_len
+    }
+
+    /// Maps an original witness to the optimised one.
+    pub fn map_witness<R: Ring>(&self, w_ccs: &[R]) -> Result<Vec<R>, Error> {
+        if w_ccs.len() != self.w_len {
+            return Err(Error::LengthsNotEqual(
+                "w_ccs".to_string(),
+                "remap".to_string(),
+                w_ccs.len(),
+                self.w_len,
+            ));
+        }
+
+        Ok(self.kept.iter().map(|&k| w_ccs[k]).collect())
+    }
+
+    /// Maps an original `z` vector to the optimised one.
+    pub fn map_z<R: Ring>(&self, z: &[R]) -> Result<Vec<R>, Error> {
+        if z.len() != self.l + 1 + self.w_len {
+            return Err(Error::LengthsNotEqual(
+                "z".to_string(),
+                "remap".to_string(),
+                z.len(),
+                self.l + 1 + self.w_len,
+            ));
+        }
+
+        let mut mapped = z[..self.l + 1].to_vec();
+        mapped.extend(self.map_witness(&z[self.l + 1..])?);
+        Ok(mapped)
+    }
+}
+
+fn is_zero_matrix<R: Ring>(M: &SparseMatrix<R>) -> bool {
+    M.coeffs.iter().flatten().all(|(v, _)| v.is_zero())
+}
+
+impl<R: Ring> CCS<R> {
+    /// Runs the [`DEFAULT_PASSES`] on the [`CCS`].
+    ///
+    /// Returns the optimised instance and the [`WitnessRemap`] to apply to its witnesses.
+    pub fn optimize(self) -> Result<(Self, WitnessRemap), Error> {
+        self.optimize_with(&DEFAULT_PASSES)
+    }
+
+    /// Runs the given passes on the [`CCS`], in order.
+    ///
+    /// Returns the optimised instance and the [`WitnessRemap`] to apply to its witnesses.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `S` references a matrix which does not exist, if `c` and `S` have
+    /// different lengths or if a matrix has an entry outside of the first `n` columns.
+    pub fn optimize_with(
+        mut self,
+        passes: &[OptimizationPass],
+    ) -> Result<(Self, WitnessRemap), Error> {
+        self.check_structure()?;
+
+        let mut remap = WitnessRemap::identity(self.l, self.n - self.l - 1);
+        for pass in passes {
+            match pass {
+                OptimizationPass::NormalizeMatrices => self.normalize_matrices(),
+                OptimizationPass::DedupMatrices => self.dedup_matrices(),
+                OptimizationPass::MergeMultisets => self.merge_multisets(),
+                OptimizationPass::DropUnusedMatrices => self.drop_unused_matrices(),
+                OptimizationPass::PruneZeroColumns => self.prune_zero_columns(&mut remap),
+                OptimizationPass::PruneZeroRows => self.prune_zero_rows(),
+            }
+        }
+
+        self.t = self.M.len();
+        self.q = self.S.len();
+        self.d = self.S.iter().map(|S_i| S_i.len()).max().unwrap_or(0);
+        self.s = log2(self.m) as usize;
+        self.s_prime = log2(self.n) as usize;
+
+        Ok((self, remap))
+    }
+
+    fn normalize_matrices(&mut self) {
+        for M_j in self.M.iter_mut() {
+            for row in M_j.coeffs.iter_mut() {
+                let mut entries: BTreeMap<usize, R> = BTreeMap::new();
+                for (v, col) in row.iter() {
+                    *entries.entry(*col).or_insert_with(R::zero) += *v;
+                }
+
+                *row = entries
+                    .into_iter()
+                    .filter(|(_, v)| !v.is_zero())
+                    .map(|(col, v)| (v, col))
+                    .collect();
+            }
+        }
+    }
+
+    fn dedup_matrices(&mut self) {
+        let mut unique: Vec<SparseMatrix<R>> = Vec::with_capacity(self.M.len());
+        let index: Vec<usize> = mem::take(&mut self.M)
+            .into_iter()
+            .map(|M_j| match unique.iter().position(|U| *U == M_j) {
+                Some(k) => k,
+                None => {
+                    unique.push(M_j);
+                    unique.len() - 1
+                }
+            })
+            .collect();
+
+        self.M = unique;
+        self.S.iter_mut().flatten().for_each(|j| *j = index[*j]);
+    }
+
+    fn merge_multisets(&mut self) {
+        let mut merged: Vec<(Vec<usize>, R)> = Vec::with_capacity(self.S.len());
+        for (mut S_i, c_i) in mem::take(&mut self.S)
+            .into_iter()
+            .zip(mem::take(&mut self.c))
+        {
+            // The Hadamard product is commutative
+            S_i.sort_unstable();
+            match merged.iter_mut().find(|(S, _)| *S == S_i) {
+                Some((_, c)) => *c += c_i,
+                None => merged.push((S_i, c_i)),
+            }
+        }
+
+        merged.retain(|(S_i, c_i)| {
+            !c_i.is_zero() && !S_i.iter().any(|&j| is_zero_matrix(&self.M[j]))
+        });
+        (self.S, self.c) = merged.into_iter().unzip();
+    }
+
+    fn drop_unused_matrices(&mut self) {
+        let mut used = vec![false; self.M.len()];
+        self.S.iter().flatten().for_each(|&j| used[j] = true);
+
+        let mut index = vec![0; self.M.len()];
+        let mut kept = 0;
+        for (j, &is_used) in used.iter().enumerate() {
+            index[j] = kept;
+            kept += is_used as usize;
+        }
+
+        self.M = mem::take(&mut self.M)
+            .into_iter()
+            .zip(used)
+            .filter_map(|(M_j, is_used)| is_used.then_some(M_j))
+            .collect();
+        self.S.iter_mut().flatten().for_each(|j| *j = index[*j]);
+    }
+
+    fn prune_zero_columns(&mut self, remap: &mut WitnessRemap) {
+        let w_offset = self.l + 1;
+        let mut used = vec![false; self.n - w_offset];
+        self.M
+            .iter()
+            .flat_map(|M_j| M_j.coeffs.iter().flatten())
+            .filter(|(v, col)| *col >= w_offset && !v.is_zero())
+            .for_each(|(_, col)| used[col - w_offset] = true);
+
+        if used.iter().all(|&is_used| is_used) {
+            return;
+        }
+
+        let mut index = vec![None; used.len()];
+        let mut kept = Vec::new();
+        for (k, &is_used) in used.iter().enumerate() {
+            if is_used {
+                index[k] = Some(w_offset + kept.len());
+                kept.push(remap.kept[k]);
+            }
+        }
+
+        let n = w_offset + kept.len();
+        for M_j in self.M.iter_mut() {
+            for row in M_j.coeffs.iter_mut() {
+                row.retain_mut(|(_, col)| {
+                    if *col < w_offset {
+                        return true;
+                    }
+                    match index[*col - w_offset] {
+                        Some(new_col) => {
+                            *col = new_col;
+                            true
+                        }
+                        // The coefficient is zero as the column is unused
+                        None => false,
+                    }
+                });
+            }
+            M_j.ncols = n;
+        }
+
+        self.n = n;
+        remap.kept = kept;
+    }
+
+    fn prune_zero_rows(&mut self) {
+        let kept_rows: Vec<usize> = (0..self.m)
+            .filter(|&r| {
+                self.M.iter().any(|M_j| {
+                    M_j.coeffs
+                        .get(r)
+                        .is_some_and(|row| row.iter().any(|(v, _)| !v.is_zero()))
+                })
+            })
+            .collect();
+
+        let m = if self.m.is_power_of_two() {
+            kept_rows.len().next_power_of_two()
+        } else {
+            kept_rows.len()
+        };
+
+        for M_j in self.M.iter_mut() {
+            let mut coeffs: Vec<Vec<(R, usize)>> = kept_rows
+                .iter()
+                .map(|&r| M_j.coeffs.get(r).cloned().unwrap_or_default())
+                .collect();
+            coeffs.resize(m, vec![]);
+            M_j.coeffs = coeffs;
+            M_j.nrows = m;
+        }
+
+        self.m = m;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::One;
+    use ark_std::{
+        rand::{rngs::StdRng, Rng},
+        test_rng,
+    };
+    use cyclotomic_rings::rings::{BabyBearRingNTT, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::arith::{
+        ccs::{
+            get_test_degree_three_ccs, get_test_degree_three_z,
+            get_test_dummy_degree_three_ccs_non_scalar,
+        },
+        r1cs::{get_test_r1cs, get_test_z},
+        tests::get_test_ccs,
+        utils::{hadamard, mat_vec_mul, vec_add, vec_scalar_mul},
+        Arith,
+    };
+
+    /// Evaluates $\sum_i c_i \cdot \bigcirc_{j \in S_i} M_j \cdot z$ row by row.
+    fn evaluate_rows<R: Ring>(ccs: &CCS<R>, z: &[R]) -> Vec<R> {
+        let mut result = vec![R::zero(); ccs.m];
+        for (S_i, c_i) in ccs.S.iter().zip(&ccs.c) {
+            let mut product = vec![R::one(); ccs.m];
+            for &j in S_i {
+                let mut M_j_z = mat_vec_mul(&ccs.M[j], z).unwrap();
+                M_j_z.resize(ccs.m, R::zero());
+                product = hadamard(&product, &M_j_z).unwrap();
+            }
+            result = vec_add(&result, &vec_scalar_mul(&product, c_i)).unwrap();
+        }
+        result
+    }
+
+    /// Checks that both instances evaluate to the same non-zero rows on random `z` vectors, and
+    /// that they reach the same verdict on satisfying `z` vectors drawn by `sample_satisfying`,
+    /// on these vectors with one entry changed and on random `z` vectors.
+    ///
+    /// Rows removed by [`OptimizationPass::PruneZeroRows`] and padding rows are always zero,
+    /// so comparing the non-zero values in order is enough for every pass.
+    fn assert_equivalent<R: Ring>(
+        original: &CCS<R>,
+        optimized: &CCS<R>,
+        remap: &WitnessRemap,
+        sample_satisfying: impl Fn(&mut StdRng) -> Vec<R>,
+    ) {
+        let mut rng = test_rng();
+        let verdicts = |z: &[R]| {
+            (
+                original.check_relation(z).is_ok(),
+                optimized.check_relation(&remap.map_z(z).unwrap()).is_ok(),
+            )
+        };
+
+        for _ in 0..4 {
+            let z: Vec<R> = (0..original.n).map(|_| R::rand(&mut rng)).collect();
+            let non_zero =
+                |rows: Vec<R>| -> Vec<R> { rows.into_iter().filter(|v| !v.is_zero()).collect() };
+
+            assert_eq!(
+                non_zero(evaluate_rows(original, &z)),
+                non_zero(evaluate_rows(optimized, &remap.map_z(&z).unwrap()))
+            );
+            let (before, after) = verdicts(&z);
+            assert_eq!(before, after);
+
+            let mut z = sample_satisfying(&mut rng);
+            assert_eq!(verdicts(&z), (true, true));
+
+            // Entries of pruned columns do not matter, so the verdict may stay positive
+            let i = rng.gen_range(0..z.len());
+            z[i] += R::from(rng.gen_range(1..64u64));
+            let (before, after) = verdicts(&z);
+            assert_eq!(before, after);
+        }
+
+        assert_eq!(optimized.t, optimized.M.len());
+        assert_eq!(optimized.q, optimized.S.len());
+        assert_eq!(optimized.n, original.l + 1 + remap.kept.len());
+    }
+
+    fn test_dedup_matrices<R: Ring>() {
+        let ccs = get_test_degree_three_ccs::<R>();
+        let (optimized, remap) = ccs.clone().optimize().unwrap();
+
+        // B and C are identical
+        assert_eq!(optimized.t, 3);
+        assert_eq!(optimized.S, vec![vec![0, 1, 1], vec![2]]);
+        assert!(remap.is_identity());
+        assert_equivalent(&ccs, &optimized, &remap, |rng| {
+            get_test_degree_three_z(rng.gen_range(0..64))
+        });
+
+        let z = get_test_degree_three_z::<R>(3);
+        optimized.check_relation(&z).unwrap();
+
+        let witness = vec![R::from(2u64); 5];
+        let ccs = get_test_dummy_degree_three_ccs_non_scalar::<R>(&witness, 1, 8, 3, 1, 5);
+        let (optimized, remap) = ccs.clone().optimize().unwrap();
+
+        // A, B and C are identical
+        assert_eq!(optimized.t, 2);
+        assert_eq!(optimized.S, vec![vec![0, 0, 0], vec![1]]);
+        assert_eq!(optimized.d, 3);
+        // Every entry z_i of a satisfying vector solves z_i^3 = 4 * z_i
+        assert_equivalent(&ccs, &optimized, &remap, |rng| {
+            (0..ccs.n)
+                .map(|_| [R::zero(), R::from(2u64), -R::from(2u64)][rng.gen_range(0..3)])
+                .collect()
+        });
+    }
+
+    #[test]
+    fn test_dedup_matrices_goldilocks() {
+        test_dedup_matrices::<GoldilocksRingNTT>();
+    }
+
+    #[test]
+    fn test_dedup_matrices_babybear() {
+        test_dedup_matrices::<BabyBearRingNTT>();
+    }
+
+    #[test]
+    fn test_merge_multisets() {
+        type R = GoldilocksRingNTT;
+
+        let r1cs = get_test_r1cs::<R>();
+        let mut ccs = CCS::from_r1cs(r1cs, 4);
+        // A * B - C + B * A + C
+        ccs.S = vec![vec![0, 1], vec![2], vec![1, 0], vec![2]];
+        ccs.c = vec![R::one(), -R::one(), R::one(), R::one()];
+        ccs.q = 4;
+
+        let (optimized, remap) = ccs.clone().optimize().unwrap();
+
+        // 2 * A * B, with C no longer used
+        assert_eq!(optimized.S, vec![vec![0, 1]]);
+        assert_eq!(optimized.c, vec![R::from(2u64)]);
+        assert_eq!(optimized.t, 2);
+        assert_eq!(optimized.d, 2);
+        assert_equivalent(&ccs, &optimized, &remap, |rng| {
+            get_test_z(rng.gen_range(0..64))
+        });
+    }
+
+    #[test]
+    fn test_prune_zero_columns() {
+        type R = GoldilocksRingNTT;
+
+        let original = CCS::from_r1cs(get_test_r1cs::<R>(), 4);
+
+        // Insert an unused column at z index 3
+        let mut ccs = original.clone();
+        for M_j in ccs.M.iter_mut() {
+            for (_, col) in M_j.coeffs.iter_mut().flatten() {
+                if *col >= 3 {
+                    *col += 1;
+                }
+            }
+            M_j.ncols += 1;
+        }
+        ccs.n += 1;
+
+        // The default passes keep the column
+        let (optimized, remap) = ccs.clone().optimize().unwrap();
+        assert!(remap.is_identity());
+        assert_eq!(optimized.n, ccs.n);
+
+        let mut passes = DEFAULT_PASSES.to_vec();
+        passes.push(OptimizationPass::PruneZeroColumns);
+        let (optimized, remap) = ccs.clone().optimize_with(&passes).unwrap();
+        assert_eq!(remap.kept, vec![0, 2, 3, 4]);
+        assert_eq!(optimized, original);
+        assert_equivalent(&ccs, &optimized, &remap, |rng| {
+            let mut z = get_test_z(rng.gen_range(0..64));
+            z.insert(3, R::rand(rng));
+            z
+        });
+
+        let z = get_test_z::<R>(3);
+        let mut padded_z = z.clone();
+        padded_z.insert(3, R::from(42u64));
+        assert_eq!(remap.map_z(&padded_z).unwrap(), z);
+        optimized
+            .check_relation(&remap.map_z(&padded_z).unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_prune_zero_rows() {
+        type R = GoldilocksRingNTT;
+
+        let ccs = get_test_ccs::<R>(4, 2);
+        assert_eq!(ccs.m, 8);
+
+        let passes = [OptimizationPass::PruneZeroRows];
+        let (optimized, remap) = ccs.clone().optimize_with(&passes).unwrap();
+        assert_eq!(optimized.m, 4);
+        assert_eq!(optimized.s, 2);
+        assert_equivalent(&ccs, &optimized, &remap, |rng| {
+            get_test_z(rng.gen_range(0..64))
+        });
+        optimized.check_relation(&get_test_z(3)).unwrap();
+    }
+
+    #[test]
+    fn test_optimize_invalid() {
+        type R = GoldilocksRingNTT;
+
+        let mut ccs = CCS::from_r1cs(get_test_r1cs::<R>(), 4);
+        ccs.S[0] = vec![0, 4];
+        assert!(matches!(
+            ccs.optimize(),
+            Err(Error::MatrixIndexOutOfBounds(4, 3))
+        ));
+    }
+}
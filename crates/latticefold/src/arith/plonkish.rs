@@ -0,0 +1,703 @@
+//! Provides a Plonkish front end that compiles custom-gate tables into CCS.
+//!
+//! A Plonkish table consists of fixed selector columns, advice (witness) columns,
+//! instance (public input) columns, a list of custom gates and a list of copy constraints.
+//! Every custom gate is a polynomial over column queries and is enforced on every row of
+//! the table. Selectors are public constants, so they are absorbed into the CCS matrices
+//! and do not contribute to the degree of the resulting CCS.
+//!
+//! See [section 5](https://eprint.iacr.org/2023/552.pdf#page=14) of the CCS paper.
+
+use ark_std::{format, log2};
+use stark_rings::Ring;
+use stark_rings_linalg::SparseMatrix;
+
+use super::{error::CSError as Error, CCS};
+use crate::ark_base::*;
+
+/// A column of a Plonkish table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Column {
+    /// A fixed column holding public constants.
+    Selector(usize),
+    /// A column holding private witness values.
+    Advice(usize),
+    /// A column holding public input/output values.
+    Instance(usize),
+}
+
+/// A query to a column at a row offset relative to the current row.
+///
+/// Rotations wrap around the table, i.e. `Query::next` on the last row refers to the first row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Query {
+    /// The queried column
+    pub column: Column,
+    /// The row offset relative to the current row
+    pub rotation: i32,
+}
+
+impl Query {
+    /// Query `column` at the current row
+    pub fn cur(column: Column) -> Self {
+        Self::rotated(column, 0)
+    }
+
+    /// Query `column` at the next row
+    pub fn next(column: Column) -> Self {
+        Self::rotated(column, 1)
+    }
+
+    /// Query `column` at the row `rotation` rows away from the current row
+    pub fn rotated(column: Column, rotation: i32) -> Self {
+        Self { column, rotation }
+    }
+
+    fn row(&self, row: usize, num_rows: usize) -> usize {
+        (row as i64 + self.rotation as i64).rem_euclid(num_rows as i64) as usize
+    }
+}
+
+/// A cell of a Plonkish table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    /// The column of the cell
+    pub column: Column,
+    /// The row of the cell
+    pub row: usize,
+}
+
+impl Cell {
+    /// Create a new cell
+    pub fn new(column: Column, row: usize) -> Self {
+        Self { column, row }
+    }
+}
+
+/// A custom gate, i.e. the polynomial $\sum_i c_i \cdot \prod_{q \in Q_i} q$
+/// over column queries which must vanish on every row of the table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomGate<R: Ring> {
+    /// The terms of the gate polynomial
+    /// For each element, `.0` is the coefficient, `.1` is the list of multiplied queries
+    pub terms: Vec<(R, Vec<Query>)>,
+}
+
+impl<R: Ring> Default for CustomGate<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Ring> CustomGate<R> {
+    /// Create a new gate with no terms
+    pub fn new() -> Self {
+        Self { terms: Vec::new() }
+    }
+
+    /// Add a term to the gate polynomial
+    pub fn add_term(mut self, coeff: impl Into<R>, queries: &[Query]) -> Self {
+        self.terms.push((coeff.into(), queries.to_vec()));
+        self
+    }
+
+    /// The degree of the gate once selectors are absorbed into constants,
+    /// i.e. the largest number of advice and instance queries in a single term.
+    pub fn degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|(_, queries)| {
+                queries
+                    .iter()
+                    .filter(|q| !matches!(q.column, Column::Selector(_)))
+                    .count()
+                    .max(1)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A Plonkish constraint table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlonkishTable<R: Ring> {
+    /// Number of rows of the table
+    pub num_rows: usize,
+    /// Number of advice columns
+    pub num_advice: usize,
+    /// Number of instance columns
+    pub num_instance: usize,
+    /// The values of the selector columns, indexed as `selectors[column][row]`
+    pub selectors: Vec<Vec<R>>,
+    /// The custom gates enforced on every row
+    pub gates: Vec<CustomGate<R>>,
+    /// Pairs of cells that must hold equal values
+    pub copy_constraints: Vec<(Cell, Cell)>,
+}
+
+/// Maps cells of a [`PlonkishTable`] to positions of the CCS `z` vector.
+///
+/// The `z` vector follows the layout `x || 1 || w`, where `x` is the flattened,
+/// column-major content of the instance columns and `w` contains one variable for
+/// every class of copy-constrained advice cells that is not bound to an instance cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlonkishLayout {
+    num_rows: usize,
+    num_instance: usize,
+    num_advice: usize,
+    advice_to_z: Vec<usize>,
+    witness_len: usize,
+}
+
+impl PlonkishLayout {
+    /// Length of the public input `x_ccs`
+    pub fn x_len(&self) -> usize {
+        self.num_instance * self.num_rows
+    }
+
+    /// Length of the CCS witness `w_ccs`
+    pub fn witness_len(&self) -> usize {
+        self.witness_len
+    }
+
+    /// The position in the `z` vector of the given advice or instance cell
+    pub fn z_index(&self, cell: Cell) -> Option<usize> {
+        match cell.column {
+            Column::Instance(col) if col < self.num_instance && cell.row < self.num_rows => {
+                Some(col * self.num_rows + cell.row)
+            }
+            Column::Advice(col) if col < self.num_advice && cell.row < self.num_rows => {
+                Some(self.advice_to_z[col * self.num_rows + cell.row])
+            }
+            _ => None,
+        }
+    }
+
+    /// Given the instance and advice columns of the table, returns the CCS
+    /// statement `x_ccs` and witness `w_ccs`.
+    pub fn assign<R: Ring>(
+        &self,
+        instance: &[Vec<R>],
+        advice: &[Vec<R>],
+    ) -> Result<(Vec<R>, Vec<R>), Error> {
+        check_columns("instance", instance, self.num_instance, self.num_rows)?;
+        check_columns("advice", advice, self.num_advice, self.num_rows)?;
+
+        let x_ccs: Vec<R> = instance.iter().flatten().copied().collect();
+        let mut w_ccs: Vec<Option<R>> = vec![None; self.witness_len];
+
+        for (col, values) in advice.iter().enumerate() {
+            for (row, &value) in values.iter().enumerate() {
+                let z_i = self.advice_to_z[col * self.num_rows + row];
+                let assigned = if z_i < x_ccs.len() {
+                    Some(x_ccs[z_i])
+                } else {
+                    w_ccs[z_i - x_ccs.len() - 1]
+                };
+
+                match assigned {
+                    Some(assigned) if assigned != value => {
+                        return Err(Error::InvalidCopyConstraint(format!(
+                            "advice cell ({col}, {row}) differs from a copy-constrained cell"
+                        )));
+                    }
+                    Some(_) => {}
+                    None => w_ccs[z_i - x_ccs.len() - 1] = Some(value),
+                }
+            }
+        }
+
+        // Every witness variable corresponds to at least one advice cell.
+        let w_ccs = w_ccs.into_iter().map(|w| w.unwrap_or(R::zero())).collect();
+
+        Ok((x_ccs, w_ccs))
+    }
+
+    /// Given the instance and advice columns of the table, returns the CCS `z` vector.
+    pub fn z_vector<R: Ring>(
+        &self,
+        instance: &[Vec<R>],
+        advice: &[Vec<R>],
+    ) -> Result<Vec<R>, Error> {
+        let (x_ccs, w_ccs) = self.assign(instance, advice)?;
+
+        let mut z = Vec::with_capacity(x_ccs.len() + w_ccs.len() + 1);
+        z.extend(x_ccs);
+        z.push(R::one());
+        z.extend(w_ccs);
+
+        Ok(z)
+    }
+}
+
+impl<R: Ring> PlonkishTable<R> {
+    /// Create a new table without gates and copy constraints
+    pub fn new(
+        num_rows: usize,
+        num_advice: usize,
+        num_instance: usize,
+        selectors: Vec<Vec<R>>,
+    ) -> Self {
+        Self {
+            num_rows,
+            num_advice,
+            num_instance,
+            selectors,
+            gates: Vec::new(),
+            copy_constraints: Vec::new(),
+        }
+    }
+
+    /// Add a custom gate to the table
+    pub fn add_gate(&mut self, gate: CustomGate<R>) -> &mut Self {
+        self.gates.push(gate);
+        self
+    }
+
+    /// Constrain two cells to hold equal values
+    pub fn add_copy_constraint(&mut self, lhs: Cell, rhs: Cell) -> &mut Self {
+        self.copy_constraints.push((lhs, rhs));
+        self
+    }
+
+    /// Computes the position of every advice cell in the CCS `z` vector,
+    /// merging copy-constrained cells into a single variable.
+    pub fn layout(&self) -> Result<PlonkishLayout, Error> {
+        let n = self.num_rows;
+        let x_len = self.num_instance * n;
+        let cell_index = |cell: &Cell| -> Result<usize, Error> {
+            if cell.row >= n {
+                return Err(Error::InvalidCopyConstraint(format!(
+                    "row {} out of bounds: table has {} rows",
+                    cell.row, n
+                )));
+            }
+            match cell.column {
+                Column::Instance(col) => {
+                    check_column_index("instance", col, self.num_instance)?;
+                    Ok(col * n + cell.row)
+                }
+                Column::Advice(col) => {
+                    check_column_index("advice", col, self.num_advice)?;
+                    Ok(x_len + col * n + cell.row)
+                }
+                Column::Selector(_) => Err(Error::InvalidCopyConstraint(
+                    "selector cells cannot be copy-constrained".to_string(),
+                )),
+            }
+        };
+
+        // Union-find over instance cells followed by advice cells
+        let mut parent: Vec<usize> = (0..x_len + self.num_advice * n).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for (lhs, rhs) in &self.copy_constraints {
+            let (a, b) = (cell_index(lhs)?, cell_index(rhs)?);
+            let (a, b) = (find(&mut parent, a), find(&mut parent, b));
+            // Keep the smallest index as the root so that instance cells represent their class.
+            match a.cmp(&b) {
+                ark_std::cmp::Ordering::Less => parent[b] = a,
+                ark_std::cmp::Ordering::Greater => parent[a] = b,
+                ark_std::cmp::Ordering::Equal => {}
+            }
+        }
+
+        // Two distinct instance cells in one class would leave one of them unchecked.
+        for i in 0..x_len {
+            let root = find(&mut parent, i);
+            if root != i {
+                return Err(Error::InvalidCopyConstraint(format!(
+                    "instance cells {root} and {i} are copy-constrained to each other"
+                )));
+            }
+        }
+
+        let mut root_to_z = vec![usize::MAX; parent.len()];
+        let mut witness_len = 0;
+        let advice_to_z = (x_len..parent.len())
+            .map(|i| {
+                let root = find(&mut parent, i);
+                if root < x_len {
+                    root
+                } else {
+                    if root_to_z[root] == usize::MAX {
+                        root_to_z[root] = x_len + 1 + witness_len;
+                        witness_len += 1;
+                    }
+                    root_to_z[root]
+                }
+            })
+            .collect();
+
+        Ok(PlonkishLayout {
+            num_rows: n,
+            num_instance: self.num_instance,
+            num_advice: self.num_advice,
+            advice_to_z,
+            witness_len,
+        })
+    }
+
+    /// The largest selector column queried by a gate, if any
+    fn max_selector_column(&self) -> Option<usize> {
+        self.gates
+            .iter()
+            .flat_map(|gate| &gate.terms)
+            .flat_map(|(_, queries)| queries)
+            .filter_map(|q| match q.column {
+                Column::Selector(col) => Some(col),
+                _ => None,
+            })
+            .max()
+    }
+
+    /// Compiles the table into a [`CCS`] together with the layout of its `z` vector.
+    ///
+    /// Every term of every gate becomes a multiset of the CCS. The selector values of a term
+    /// are multiplied into the rows of its first matrix, so the CCS degree equals the
+    /// largest number of advice and instance queries in a term.
+    pub fn compile(&self) -> Result<(CCS<R>, PlonkishLayout), Error> {
+        if let Some(col) = self.max_selector_column() {
+            check_column_index("selector", col, self.selectors.len())?;
+        }
+        check_column_lengths("selector", &self.selectors, self.num_rows)?;
+
+        let layout = self.layout()?;
+        let n_rows = self.num_rows;
+        let m = self.gates.len() * n_rows;
+        let l = layout.x_len();
+        let ncols = l + 1 + layout.witness_len();
+
+        let mut M: Vec<SparseMatrix<R>> = Vec::new();
+        let mut S: Vec<Vec<usize>> = Vec::new();
+        let mut c: Vec<R> = Vec::new();
+
+        for (g, gate) in self.gates.iter().enumerate() {
+            for (coeff, queries) in &gate.terms {
+                let (selectors, cells): (Vec<&Query>, Vec<&Query>) = queries
+                    .iter()
+                    .partition(|q| matches!(q.column, Column::Selector(_)));
+
+                let term_len = cells.len().max(1);
+                let mut term_M = vec![
+                    SparseMatrix {
+                        nrows: m,
+                        ncols,
+                        coeffs: vec![vec![]; m],
+                    };
+                    term_len
+                ];
+
+                for row in 0..n_rows {
+                    let selector_product = selectors
+                        .iter()
+                        .map(|q| match q.column {
+                            Column::Selector(col) => self.selectors[col][q.row(row, n_rows)],
+                            _ => unreachable!(),
+                        })
+                        .fold(R::one(), |acc, s| acc * s);
+
+                    // This term is switched off on the current row.
+                    if selector_product.is_zero() {
+                        continue;
+                    }
+
+                    let ccs_row = g * n_rows + row;
+                    if cells.is_empty() {
+                        term_M[0].coeffs[ccs_row].push((selector_product, l));
+                        continue;
+                    }
+
+                    for (k, q) in cells.iter().enumerate() {
+                        let cell = Cell::new(q.column, q.row(row, n_rows));
+                        let z_i = layout.z_index(cell).ok_or_else(|| match q.column {
+                            Column::Instance(col) => Error::ColumnOutOfBounds(
+                                "instance".to_string(),
+                                col,
+                                self.num_instance,
+                            ),
+                            Column::Advice(col) => {
+                                Error::ColumnOutOfBounds("advice".to_string(), col, self.num_advice)
+                            }
+                            Column::Selector(col) => Error::ColumnOutOfBounds(
+                                "selector".to_string(),
+                                col,
+                                self.selectors.len(),
+                            ),
+                        })?;
+                        let value = if k == 0 { selector_product } else { R::one() };
+                        term_M[k].coeffs[ccs_row].push((value, z_i));
+                    }
+                }
+
+                S.push((M.len()..M.len() + term_len).collect());
+                c.push(*coeff);
+                M.extend(term_M);
+            }
+        }
+
+        let ccs = CCS {
+            m,
+            n: ncols,
+            l,
+            t: M.len(),
+            q: S.len(),
+            d: S.iter().map(|S_i| S_i.len()).max().unwrap_or(0),
+            s: log2(m) as usize,
+            s_prime: log2(ncols) as usize,
+            M,
+            S,
+            c,
+        };
+
+        Ok((ccs, layout))
+    }
+}
+
+fn check_column_index(kind: &str, col: usize, num_columns: usize) -> Result<(), Error> {
+    if col >= num_columns {
+        return Err(Error::ColumnOutOfBounds(kind.to_string(), col, num_columns));
+    }
+    Ok(())
+}
+
+fn check_columns<R>(
+    kind: &str,
+    columns: &[Vec<R>],
+    num_columns: usize,
+    num_rows: usize,
+) -> Result<(), Error> {
+    if columns.len() != num_columns {
+        return Err(Error::LengthsNotEqual(
+            format!("{kind} columns"),
+            format!("num_{kind}"),
+            columns.len(),
+            num_columns,
+        ));
+    }
+
+    check_column_lengths(kind, columns, num_rows)
+}
+
+fn check_column_lengths<R>(kind: &str, columns: &[Vec<R>], num_rows: usize) -> Result<(), Error> {
+    if let Some(column) = columns.iter().find(|column| column.len() != num_rows) {
+        return Err(Error::LengthsNotEqual(
+            format!("{kind} column"),
+            "num_rows".to_string(),
+            column.len(),
+            num_rows,
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{One, Zero};
+    use cyclotomic_rings::rings::GoldilocksRingNTT;
+
+    use super::*;
+    use crate::arith::{r1cs::to_F_vec, Arith};
+
+    type RqNTT = GoldilocksRingNTT;
+
+    const A: Column = Column::Advice(0);
+    const B: Column = Column::Advice(1);
+    const C: Column = Column::Advice(2);
+    const Q_L: Column = Column::Selector(0);
+    const Q_R: Column = Column::Selector(1);
+    const Q_O: Column = Column::Selector(2);
+    const Q_M: Column = Column::Selector(3);
+    const Q_C: Column = Column::Selector(4);
+
+    fn neg(x: u64) -> RqNTT {
+        -RqNTT::from(x)
+    }
+
+    // The vanilla PLONK gate q_l * a + q_r * b + q_o * c + q_m * a * b + q_c = 0
+    fn plonk_gate() -> CustomGate<RqNTT> {
+        CustomGate::new()
+            .add_term(1u64, &[Query::cur(Q_L), Query::cur(A)])
+            .add_term(1u64, &[Query::cur(Q_R), Query::cur(B)])
+            .add_term(1u64, &[Query::cur(Q_O), Query::cur(C)])
+            .add_term(1u64, &[Query::cur(Q_M), Query::cur(A), Query::cur(B)])
+            .add_term(1u64, &[Query::cur(Q_C)])
+    }
+
+    // Computes y = x^2 + x + 5 for public x and y:
+    // row 0: a * b = c   (x * x = x^2)
+    // row 1: a + b = c   (x^2 + x = t)
+    // row 2: a + 5 = c   (t + 5 = y)
+    // row 3: unused
+    fn get_test_table() -> PlonkishTable<RqNTT> {
+        let zero = RqNTT::zero();
+        let one = RqNTT::one();
+        let selectors = vec![
+            vec![zero, one, one, zero],         // q_l
+            vec![zero, one, zero, zero],        // q_r
+            vec![neg(1), neg(1), neg(1), zero], // q_o
+            vec![one, zero, zero, zero],        // q_m
+            vec![zero, zero, RqNTT::from(5u64), zero],
+        ];
+        let mut table = PlonkishTable::new(4, 3, 1, selectors);
+        table.add_gate(plonk_gate());
+
+        let x = Cell::new(Column::Instance(0), 0);
+        let y = Cell::new(Column::Instance(0), 1);
+        table
+            .add_copy_constraint(x, Cell::new(A, 0))
+            .add_copy_constraint(x, Cell::new(B, 0))
+            .add_copy_constraint(x, Cell::new(B, 1))
+            .add_copy_constraint(Cell::new(C, 0), Cell::new(A, 1))
+            .add_copy_constraint(Cell::new(C, 1), Cell::new(A, 2))
+            .add_copy_constraint(Cell::new(C, 2), y);
+        table
+    }
+
+    fn get_test_assignment(x: usize) -> (Vec<Vec<RqNTT>>, Vec<Vec<RqNTT>>) {
+        let y = x * x + x + 5;
+        let instance = vec![to_F_vec(vec![x, y, 0, 0])];
+        let advice = vec![
+            to_F_vec(vec![x, x * x, x * x + x, 0]),
+            to_F_vec(vec![x, x, 0, 0]),
+            to_F_vec(vec![x * x, x * x + x, y, 0]),
+        ];
+        (instance, advice)
+    }
+
+    #[test]
+    fn test_plonkish_ccs_relation() {
+        let table = get_test_table();
+        let (ccs, layout) = table.compile().unwrap();
+
+        assert_eq!(ccs.d, 2);
+        assert_eq!(ccs.q, 5);
+        assert_eq!(ccs.l, 4);
+
+        let (instance, advice) = get_test_assignment(3);
+        let z = layout.z_vector(&instance, &advice).unwrap();
+        assert_eq!(z.len(), ccs.n);
+        ccs.check_relation(&z).unwrap();
+    }
+
+    #[test]
+    fn test_plonkish_copy_constraints_merge_cells() {
+        let table = get_test_table();
+        let layout = table.layout().unwrap();
+
+        // x is shared by a_0, b_0 and b_1, and the output is bound to y.
+        assert_eq!(layout.z_index(Cell::new(A, 0)), Some(0));
+        assert_eq!(layout.z_index(Cell::new(B, 1)), Some(0));
+        assert_eq!(layout.z_index(Cell::new(C, 2)), Some(1));
+        assert_eq!(
+            layout.z_index(Cell::new(C, 0)),
+            layout.z_index(Cell::new(A, 1))
+        );
+        // 12 advice cells, 4 bound to instances and 2 merged with other advice cells
+        assert_eq!(layout.witness_len(), 6);
+    }
+
+    #[test]
+    fn test_plonkish_bad_witness() {
+        let table = get_test_table();
+        let (ccs, layout) = table.compile().unwrap();
+
+        let (instance, mut advice) = get_test_assignment(3);
+        // Breaks the copy constraint between c_0 and a_1
+        advice[0][1] += RqNTT::one();
+        assert!(layout.z_vector(&instance, &advice).is_err());
+
+        let (mut instance, advice) = get_test_assignment(3);
+        instance[0][1] += RqNTT::one();
+        assert!(layout.z_vector(&instance, &advice).is_err());
+
+        // Breaks the gate on row 1 without touching copy-constrained cells
+        let (instance, advice) = get_test_assignment(3);
+        let mut z = layout.z_vector(&instance, &advice).unwrap();
+        let t = layout.z_index(Cell::new(C, 1)).unwrap();
+        z[t] += RqNTT::one();
+        assert!(ccs.check_relation(&z).is_err());
+    }
+
+    #[test]
+    fn test_plonkish_high_degree_gate() {
+        // q_on * (a^3 - next(a)) + q_c = 0, i.e. a_{i+1} = a_i^3 + q_c on every enabled row
+        let q_on = Column::Selector(0);
+        let q_c = Column::Selector(1);
+        let gate = CustomGate::new()
+            .add_term(
+                1u64,
+                &[
+                    Query::cur(q_on),
+                    Query::cur(A),
+                    Query::cur(A),
+                    Query::cur(A),
+                ],
+            )
+            .add_term(neg(1), &[Query::cur(q_on), Query::next(A)])
+            .add_term(1u64, &[Query::cur(q_c)]);
+        assert_eq!(gate.degree(), 3);
+
+        let selectors = vec![to_F_vec(vec![1, 1, 1, 0]), to_F_vec(vec![1, 2, 3, 0])];
+        let mut table = PlonkishTable::new(4, 1, 0, selectors);
+        table.add_gate(gate);
+        let (ccs, layout) = table.compile().unwrap();
+        assert_eq!(ccs.d, 3);
+        assert_eq!(ccs.l, 0);
+
+        let mut a = vec![RqNTT::from(2u64)];
+        for i in 0..3 {
+            a.push(a[i] * a[i] * a[i] + RqNTT::from(i as u64 + 1));
+        }
+        let mut advice = vec![a];
+
+        let z = layout.z_vector(&[], &advice).unwrap();
+        ccs.check_relation(&z).unwrap();
+
+        advice[0][3] += RqNTT::one();
+        let z = layout.z_vector(&[], &advice).unwrap();
+        assert!(matches!(
+            ccs.check_relation(&z),
+            Err(Error::NotSatisfied(2))
+        ));
+    }
+
+    #[test]
+    fn test_plonkish_invalid_tables() {
+        let mut table = get_test_table();
+        table.add_copy_constraint(
+            Cell::new(Column::Instance(0), 2),
+            Cell::new(Column::Instance(0), 3),
+        );
+        assert!(matches!(
+            table.compile(),
+            Err(Error::InvalidCopyConstraint(_))
+        ));
+
+        let mut table = get_test_table();
+        table.add_gate(CustomGate::new().add_term(1u64, &[Query::cur(Column::Advice(7))]));
+        assert!(matches!(
+            table.compile(),
+            Err(Error::ColumnOutOfBounds(_, 7, 3))
+        ));
+
+        // The gates query 5 selector columns
+        let mut table = get_test_table();
+        table.selectors.pop();
+        assert!(matches!(
+            table.compile(),
+            Err(Error::ColumnOutOfBounds(kind, 4, 4)) if kind == "selector"
+        ));
+
+        let mut table = get_test_table();
+        table.selectors[2].pop();
+        assert!(matches!(table.compile(), Err(Error::LengthsNotEqual(..))));
+    }
+}
@@ -0,0 +1,337 @@
+//! Provides degree reduction of a CCS instance down to R1CS.
+//!
+//! Every multiset product of degree greater than two is split into a chain of auxiliary
+//! variables, each holding the product of two linear combinations of `z`. In every row, one
+//! degree two product is kept in $A \circ B$, while the other ones get their own auxiliary
+//! variable and join the linear terms in $C$.
+//!
+//! The auxiliary variables are appended to the witness, so the `z` vector of the resulting
+//! R1CS is `x_ccs || 1 || w_ccs || aux`. [`R1CSWitnessMap`] computes `aux` from `z`.
+
+use ark_std::collections::BTreeMap;
+use stark_rings::Ring;
+use stark_rings_linalg::SparseMatrix;
+
+use super::{error::CSError as Error, r1cs::R1CS, CCS};
+use crate::ark_base::*;
+
+type LinearCombination<R> = Vec<(R, usize)>;
+
+/// Maps a satisfying `z` vector of a [`CCS`] to a satisfying `z` vector of its
+/// degree-reduced [`R1CS`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct R1CSWitnessMap<R: Ring> {
+    /// Length of the `z` vector of the original CCS
+    pub n: usize,
+    /// Auxiliary variables, in order.
+    /// Each variable is the product of two linear combinations over the extended `z` vector.
+    pub aux: Vec<(LinearCombination<R>, LinearCombination<R>)>,
+}
+
+impl<R: Ring> R1CSWitnessMap<R> {
+    /// Number of auxiliary variables introduced by the reduction
+    pub fn num_aux(&self) -> usize {
+        self.aux.len()
+    }
+
+    /// Extends a CCS `z` vector with the auxiliary variables of the reduction.
+    pub fn extend_z(&self, z: &[R]) -> Result<Vec<R>, Error> {
+        if z.len() != self.n {
+            return Err(Error::LengthsNotEqual(
+                "z".to_string(),
+                "n".to_string(),
+                z.len(),
+                self.n,
+            ));
+        }
+
+        let mut z = z.to_vec();
+        z.reserve(self.aux.len());
+        for (a, b) in &self.aux {
+            let value = evaluate(a, &z) * evaluate(b, &z);
+            z.push(value);
+        }
+
+        Ok(z)
+    }
+
+    /// Extends a CCS witness with the auxiliary variables of the reduction.
+    pub fn extend_witness(&self, x_ccs: &[R], w_ccs: &[R]) -> Result<Vec<R>, Error> {
+        let mut z: Vec<R> = Vec::with_capacity(x_ccs.len() + 1 + w_ccs.len());
+        z.extend_from_slice(x_ccs);
+        z.push(R::one());
+        z.extend_from_slice(w_ccs);
+
+        let mut z = self.extend_z(&z)?;
+        Ok(z.split_off(x_ccs.len() + 1))
+    }
+}
+
+fn evaluate<R: Ring>(lc: &[(R, usize)], z: &[R]) -> R {
+    lc.iter()
+        .fold(R::zero(), |acc, (coeff, col)| acc + *coeff * z[*col])
+}
+
+fn scale<R: Ring>(lc: LinearCombination<R>, coeff: R) -> LinearCombination<R> {
+    lc.into_iter().map(|(v, col)| (coeff * v, col)).collect()
+}
+
+fn accumulate<R: Ring>(acc: &mut BTreeMap<usize, R>, coeff: R, lc: &[(R, usize)]) {
+    for (v, col) in lc {
+        *acc.entry(*col).or_insert_with(R::zero) += coeff * *v;
+    }
+}
+
+fn collect<R: Ring>(acc: BTreeMap<usize, R>, coeff: R) -> LinearCombination<R> {
+    acc.into_iter()
+        .filter(|(_, v)| !v.is_zero())
+        .map(|(col, v)| (coeff * v, col))
+        .collect()
+}
+
+impl<R: Ring> CCS<R> {
+    /// Reduces the [`CCS`] to an [`R1CS`] instance.
+    ///
+    /// Returns the R1CS together with the [`R1CSWitnessMap`] extending satisfying CCS
+    /// assignments with the auxiliary variables. The first `m` constraints of the R1CS are the
+    /// rows of the CCS, the remaining ones define the auxiliary variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `S` references a matrix which does not exist, if `c` and `S` have
+    /// different lengths or if a matrix has an entry outside of the first `n` columns.
+    pub fn to_r1cs(&self) -> Result<(R1CS<R>, R1CSWitnessMap<R>), Error> {
+        self.check_structure()?;
+
+        let one = self.l;
+        let mut map = R1CSWitnessMap {
+            n: self.n,
+            aux: Vec::new(),
+        };
+        let mut new_aux = |a: LinearCombination<R>, b: LinearCombination<R>| {
+            map.aux.push((a, b));
+            vec![(R::one(), self.n + map.aux.len() - 1)]
+        };
+
+        let mut rows: Vec<(
+            LinearCombination<R>,
+            LinearCombination<R>,
+            LinearCombination<R>,
+        )> = Vec::with_capacity(self.m);
+
+        for r in 0..self.m {
+            let mut linear: BTreeMap<usize, R> = BTreeMap::new();
+            let mut quadratic: Option<(LinearCombination<R>, LinearCombination<R>)> = None;
+
+            for (S_i, c_i) in self.S.iter().zip(&self.c) {
+                if c_i.is_zero() {
+                    continue;
+                }
+
+                let mut factors: Vec<LinearCombination<R>> = S_i
+                    .iter()
+                    .map(|&j| self.M[j].coeffs.get(r).cloned().unwrap_or_default())
+                    .collect();
+
+                // The whole product vanishes on this row
+                if factors.iter().any(|f| f.is_empty()) {
+                    continue;
+                }
+
+                match factors.len() {
+                    0 => accumulate(&mut linear, *c_i, &[(R::one(), one)]),
+                    1 => accumulate(&mut linear, *c_i, &factors[0]),
+                    _ => {
+                        let last = factors.pop().expect("at least two factors");
+                        let mut factors = factors.into_iter();
+                        let first = factors.next().expect("at least two factors");
+                        let acc = factors.fold(first, |acc, f| new_aux(acc, f));
+
+                        if quadratic.is_none() {
+                            quadratic = Some((scale(acc, *c_i), last));
+                        } else {
+                            let product = new_aux(acc, last);
+                            accumulate(&mut linear, *c_i, &product);
+                        }
+                    }
+                }
+            }
+
+            rows.push(match quadratic {
+                Some((a, b)) => (a, b, collect(linear, -R::one())),
+                None if linear.is_empty() => (vec![], vec![], vec![]),
+                None => (collect(linear, R::one()), vec![(R::one(), one)], vec![]),
+            });
+        }
+
+        let aux_rows = map
+            .aux
+            .iter()
+            .enumerate()
+            .map(|(k, (a, b))| (a.clone(), b.clone(), vec![(R::one(), self.n + k)]));
+        rows.extend(aux_rows);
+
+        let nrows = rows.len();
+        let ncols = self.n + map.aux.len();
+        let matrix = |coeffs: Vec<LinearCombination<R>>| SparseMatrix {
+            nrows,
+            ncols,
+            coeffs,
+        };
+
+        let mut A = Vec::with_capacity(nrows);
+        let mut B = Vec::with_capacity(nrows);
+        let mut C = Vec::with_capacity(nrows);
+        for (a, b, c) in rows {
+            A.push(a);
+            B.push(b);
+            C.push(c);
+        }
+
+        Ok((
+            R1CS {
+                l: self.l,
+                A: matrix(A),
+                B: matrix(B),
+                C: matrix(C),
+            },
+            map,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::One;
+    use cyclotomic_rings::rings::{BabyBearRingNTT, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::arith::{
+        air::{Air, AirVariable::*, TransitionConstraint},
+        r1cs::{get_test_r1cs, get_test_z},
+        Arith,
+    };
+
+    #[test]
+    fn test_r1cs_round_trip() {
+        type R = GoldilocksRingNTT;
+
+        let r1cs = get_test_r1cs::<R>();
+        let ccs = CCS::from_r1cs(r1cs.clone(), r1cs.A.nrows());
+        let (reduced, map) = ccs.to_r1cs().unwrap();
+
+        assert_eq!(map.num_aux(), 0);
+        assert_eq!(reduced, r1cs);
+
+        let z = get_test_z::<R>(3);
+        assert_eq!(map.extend_z(&z).unwrap(), z);
+        reduced.check_relation(&z).unwrap();
+    }
+
+    fn test_reduce_high_degree<R: Ring>() {
+        const T: usize = 6;
+
+        // next(x) = 3 * x^3 + x * y + y, next(y) = y + 1
+        let mut air = Air::new(2, T);
+        air.add_transition(
+            TransitionConstraint::new()
+                .add_term(R::one(), &[Next(0)])
+                .add_term(-R::from(3u64), &[Cur(0), Cur(0), Cur(0)])
+                .add_term(-R::one(), &[Cur(0), Cur(1)])
+                .add_term(-R::one(), &[Cur(1)]),
+        )
+        .add_transition(
+            TransitionConstraint::new()
+                .add_term(R::one(), &[Next(1)])
+                .add_term(-R::one(), &[Cur(1)])
+                .add_term(-R::one(), &[]),
+        )
+        .add_boundary(0, 0)
+        .add_boundary(T - 1, 1);
+
+        let mut trace = vec![vec![R::from(2u64), R::zero()]];
+        for i in 1..T {
+            let (x, y) = (trace[i - 1][0], trace[i - 1][1]);
+            trace.push(vec![R::from(3u64) * x * x * x + x * y + y, y + R::one()]);
+        }
+
+        let ccs = CCS::from_air(&air).unwrap();
+        let (r1cs, map) = ccs.to_r1cs().unwrap();
+
+        // One auxiliary variable for x^2 and one for x * y on each transition
+        assert_eq!(map.num_aux(), 2 * (T - 1));
+        assert_eq!(r1cs.A.nrows(), ccs.m + map.num_aux());
+
+        let x_ccs = air.statement(&trace).unwrap();
+        let w_ccs = air.witness(&trace).unwrap();
+        let w = map.extend_witness(&x_ccs, &w_ccs).unwrap();
+        assert_eq!(w.len(), w_ccs.len() + map.num_aux());
+
+        let mut z = x_ccs.clone();
+        z.push(R::one());
+        z.extend(w);
+        r1cs.check_relation(&z).unwrap();
+
+        // A wrong transition is caught on the corresponding CCS row
+        let mut bad_trace = trace.clone();
+        bad_trace[3][0] += R::one();
+        let bad_z = map.extend_z(&ccs_z(&air, &bad_trace)).unwrap();
+        assert!(matches!(
+            r1cs.check_relation(&bad_z),
+            Err(Error::NotSatisfied(2))
+        ));
+
+        // Tampering with an auxiliary variable breaks the row using it, here x * y on the last step
+        let mut bad_z = z.clone();
+        let last = bad_z.len() - 1;
+        bad_z[last] += R::one();
+        assert!(matches!(
+            r1cs.check_relation(&bad_z),
+            Err(Error::NotSatisfied(i)) if i == T - 2
+        ));
+    }
+
+    fn ccs_z<R: Ring>(air: &Air<R>, trace: &[Vec<R>]) -> Vec<R> {
+        let mut z = air.statement(trace).unwrap();
+        z.push(R::one());
+        z.extend(air.witness(trace).unwrap());
+        z
+    }
+
+    #[test]
+    fn test_reduce_high_degree_goldilocks() {
+        test_reduce_high_degree::<GoldilocksRingNTT>();
+    }
+
+    #[test]
+    fn test_reduce_high_degree_babybear() {
+        test_reduce_high_degree::<BabyBearRingNTT>();
+    }
+
+    #[test]
+    fn test_reduce_invalid() {
+        type R = GoldilocksRingNTT;
+
+        let r1cs = get_test_r1cs::<R>();
+        let mut ccs = CCS::from_r1cs(r1cs, 4);
+        ccs.S[1] = vec![3];
+        assert!(matches!(
+            ccs.to_r1cs(),
+            Err(Error::MatrixIndexOutOfBounds(3, 3))
+        ));
+
+        ccs.S[1] = vec![2];
+        ccs.c.push(R::one());
+        assert!(matches!(
+            ccs.to_r1cs(),
+            Err(Error::LengthsNotEqual(_, _, 3, 2))
+        ));
+
+        ccs.c.pop();
+        ccs.n -= 1;
+        assert!(matches!(
+            ccs.to_r1cs(),
+            Err(Error::ColumnOutOfBounds(_, 5, 5))
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+//! Searches LatticeFold parameters for a ring and a witness length.
+//!
+//! ```text
+//! cargo run --bin latticefold-params -- --ring goldilocks --wit-len 1024 --security 128
+//! ```
+
+use std::{env, process, str::FromStr};
+
+use cyclotomic_rings::{
+    challenge_set::LatticefoldChallengeSet,
+    rings::{
+        BabyBearChallengeSet, BabyBearRingNTT, FrogChallengeSet, FrogRingNTT,
+        GoldilocksChallengeSet, GoldilocksRingNTT, StarkChallengeSet, StarkRingNTT, SuitableRing,
+    },
+};
+use latticefold::utils::param_selection::{
+    challenge_expansion, select_params, CostObjective, ParamQuery,
+};
+
+const USAGE: &str = "\
+Usage: latticefold-params [OPTIONS]
+
+Options:
+  --ring <goldilocks|babybear|stark|frog>            Ring to use [default: goldilocks]
+  --wit-len <N>                                      Witness length [default: 1024]
+  --security <BITS>                                  Target security [default: 128]
+  --objective <proof-size|prover-time|commitment-width>
+                                                     Cost to minimize [default: proof-size]
+  --challenge-expansion <C>                          Expansion factor of the challenge set
+                                                     [default: from the challenge set of the ring]
+  --max-kappa <N>                                    Largest kappa to consider [default: 64]
+  --max-b-small <N>                                  Largest b to consider [default: 64]
+  --top <N>                                          Number of results [default: 10]";
+
+fn main() {
+    if let Err(err) = run(env::args().skip(1).collect()) {
+        eprintln!("error: {err}\n\n{USAGE}");
+        process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut ring = "goldilocks".to_string();
+    let mut query = ParamQuery::new(1024, 128.0, 0);
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            println!("{USAGE}");
+            return Ok(());
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--ring" => ring = value.to_lowercase(),
+            "--wit-len" => query.wit_len = parse(&flag, &value)?,
+            "--security" => query.security_bits = parse(&flag, &value)?,
+            "--challenge-expansion" => query.challenge_expansion = parse(&flag, &value)?,
+            "--max-kappa" => query.max_kappa = parse(&flag, &value)?,
+            "--max-b-small" => query.max_b_small = parse(&flag, &value)?,
+            "--top" => query.max_results = parse(&flag, &value)?,
+            "--objective" => {
+                query.objective = match value.as_str() {
+                    "proof-size" => CostObjective::ProofSize,
+                    "prover-time" => CostObjective::ProverTime,
+                    "commitment-width" => CostObjective::CommitmentWidth,
+                    _ => return Err(format!("unknown objective: {value}")),
+                }
+            }
+            _ => return Err(format!("unknown option: {flag}")),
+        }
+    }
+
+    match ring.as_str() {
+        "goldilocks" => search::<GoldilocksRingNTT, GoldilocksChallengeSet>(query),
+        "babybear" => search::<BabyBearRingNTT, BabyBearChallengeSet>(query),
+        "stark" => search::<StarkRingNTT, StarkChallengeSet>(query),
+        "frog" => search::<FrogRingNTT, FrogChallengeSet>(query),
+        _ => Err(format!("unknown ring: {ring}")),
+    }
+}
+
+fn parse<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {flag}: {value}"))
+}
+
+fn search<R: SuitableRing, CS: LatticefoldChallengeSet<R>>(
+    mut query: ParamQuery,
+) -> Result<(), String> {
+    if query.challenge_expansion == 0 {
+        query.challenge_expansion = challenge_expansion::<R, CS>();
+    }
+
+    let candidates = select_params::<R>(&query).map_err(|err| err.to_string())?;
+
+    println!(
+        "{:>4} {:>24} {:>4} {:>6} {:>4} {:>6} {:>10} {:>8} {:>12}",
+        "#", "B", "L", "b", "k", "kappa", "security", "margin", "cost"
+    );
+    for (i, c) in candidates.iter().enumerate() {
+        println!(
+            "{:>4} {:>24} {:>4} {:>6} {:>4} {:>6} {:>10.1} {:>8.2} {:>12}",
+            i + 1,
+            c.params.b(),
+            c.params.l(),
+            c.params.b_small(),
+            c.params.k(),
+            c.kappa,
+            c.security_bits,
+            c.norm_growth_margin,
+            c.cost
+        );
+    }
+
+    Ok(())
+}
@@ -3,11 +3,21 @@
 use thiserror::Error;
 
 mod commitment_scheme;
+mod hiding;
 mod homomorphic_commitment;
+#[cfg(feature = "std")]
+mod key_file;
 #[macro_use]
 mod operations;
+mod opening;
+mod streaming;
 pub use commitment_scheme::*;
+pub use hiding::*;
 pub use homomorphic_commitment::*;
+#[cfg(feature = "std")]
+pub use key_file::*;
+pub use opening::*;
+pub use streaming::*;
 
 /// Errors that can occur in commitment operations.
 #[derive(Debug, Error)]
@@ -23,4 +33,25 @@ pub enum CommitmentError {
     /// An Ajtai matrix should have size commitment_length x witness_length.
     #[error("Ajtai matrix has dimensions: {0}x{1}, expected: {2}x{3}")]
     WrongAjtaiMatrixDimensions(usize, usize, usize, usize),
+    /// A witness position is outside of the Ajtai matrix.
+    #[error("Witness index {0} is out of bounds, the witness length is {1}")]
+    IndexOutOfBounds(usize, usize),
+    /// The opening does not satisfy the norm bound.
+    #[error("The opening does not satisfy the norm bound {0:?}")]
+    NormBoundExceeded(NormBound),
+    /// The opening does not match the commitment.
+    #[error("The opening does not match the commitment")]
+    InvalidOpening,
+    /// The mask of a hiding commitment has the wrong length.
+    #[error("Wrong length of the mask: {0}, expected: {1}")]
+    WrongMaskLength(usize, usize),
+    /// A hiding commitment scheme needs at least one randomness column and a positive mask bound.
+    #[error("Invalid hiding parameters: {0} randomness columns, mask bound {1}")]
+    InvalidHidingParameters(usize, u128),
+    /// A gadget decomposition needs a base of at least 2.
+    #[error("Invalid gadget base: {0}")]
+    InvalidGadgetBase(u128),
+    /// The masks of an evaluation proof would wrap around the modulus.
+    #[error("The masking bounds of the evaluation proof exceed half the modulus")]
+    MaskingBoundTooLarge,
 }
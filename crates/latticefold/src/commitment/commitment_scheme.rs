@@ -8,7 +8,9 @@ use stark_rings_linalg::Matrix;
 
 use super::homomorphic_commitment::Commitment;
 use crate::{
-    ark_base::*, commitment::CommitmentError, decomposition_parameters::DecompositionParams,
+    ark_base::*,
+    commitment::CommitmentError,
+    decomposition_parameters::{DecompositionParamData, DecompositionParams},
 };
 
 /// A concrete instantiation of the Ajtai commitment scheme.
@@ -23,6 +25,10 @@ impl<R> AjtaiCommitmentScheme<R> {
     pub fn new(matrix: Matrix<R>) -> Self {
         Self { matrix }
     }
+
+    pub(crate) fn matrix(&self) -> &Matrix<R> {
+        &self.matrix
+    }
 }
 
 impl<R: Ring> AjtaiCommitmentScheme<R> {
@@ -66,6 +72,45 @@ impl<R: Ring> AjtaiCommitmentScheme<R> {
     pub fn width(&self) -> usize {
         self.matrix.ncols
     }
+
+    /// Updates a commitment to a witness that changed at a few positions.
+    ///
+    /// Each change is given as `(index, old, new)`. Since the commitment is linear, the result
+    /// is `cm + A_index * (new - old)` summed over the changes, at the cost of `kappa`
+    /// multiplications per change.
+    pub fn update(
+        &self,
+        cm: &Commitment<R>,
+        changes: &[(usize, R, R)],
+    ) -> Result<Commitment<R>, CommitmentError> {
+        self.apply_deltas(cm, changes.iter().map(|(i, old, new)| (*i, *new - old)))
+    }
+
+    /// Adds `A_index * delta` to `cm` for each `(index, delta)`.
+    fn apply_deltas(
+        &self,
+        cm: &Commitment<R>,
+        deltas: impl IntoIterator<Item = (usize, R)>,
+    ) -> Result<Commitment<R>, CommitmentError> {
+        if cm.len() != self.kappa() {
+            return Err(CommitmentError::WrongCommitmentLength(
+                cm.len(),
+                self.kappa(),
+            ));
+        }
+
+        let mut res = cm.as_ref().to_vec();
+        for (index, delta) in deltas {
+            if index >= self.width() {
+                return Err(CommitmentError::IndexOutOfBounds(index, self.width()));
+            }
+            res.iter_mut()
+                .zip(self.matrix.vals.iter())
+                .for_each(|(c, row)| *c += row[index] * delta);
+        }
+
+        Ok(Commitment::from_vec_raw(res))
+    }
 }
 
 // SuitableRing helpers
@@ -90,14 +135,23 @@ impl<NTT: SuitableRing> AjtaiCommitmentScheme<NTT> {
     pub fn decompose_and_commit_coeff<P: DecompositionParams>(
         &self,
         f: &[NTT::CoefficientRepresentation],
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        self.decompose_and_commit_coeff_with_params(&P::DATA, f)
+    }
+
+    /// Same as [`Self::decompose_and_commit_coeff`], with decomposition parameters chosen at runtime.
+    pub fn decompose_and_commit_coeff_with_params(
+        &self,
+        params: &DecompositionParamData,
+        f: &[NTT::CoefficientRepresentation],
     ) -> Result<Commitment<NTT>, CommitmentError> {
         let f = f
-            .decompose_to_vec(P::B, P::L)
+            .decompose_to_vec(params.b(), params.l())
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
 
-        self.commit_coeff::<P>(f)
+        self.commit_ntt(&CRT::elementwise_crt(f))
     }
 
     /// Takes an NTT form witness, transforms it into the coefficient form,
@@ -106,20 +160,130 @@ impl<NTT: SuitableRing> AjtaiCommitmentScheme<NTT> {
     pub fn decompose_and_commit_ntt<P: DecompositionParams>(
         &self,
         w: Vec<NTT>,
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        self.decompose_and_commit_ntt_with_params(&P::DATA, w)
+    }
+
+    /// Same as [`Self::decompose_and_commit_ntt`], with decomposition parameters chosen at runtime.
+    pub fn decompose_and_commit_ntt_with_params(
+        &self,
+        params: &DecompositionParamData,
+        w: Vec<NTT>,
     ) -> Result<Commitment<NTT>, CommitmentError> {
         let coeff: Vec<NTT::CoefficientRepresentation> = ICRT::elementwise_icrt(w);
 
-        self.decompose_and_commit_coeff::<P>(&coeff)
+        self.decompose_and_commit_coeff_with_params(params, &coeff)
+    }
+
+    /// Same as [`Self::update`] for a witness committed with [`Self::commit_coeff`].
+    pub fn update_coeff(
+        &self,
+        cm: &Commitment<NTT>,
+        changes: &[(
+            usize,
+            NTT::CoefficientRepresentation,
+            NTT::CoefficientRepresentation,
+        )],
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        let deltas: Vec<NTT> =
+            CRT::elementwise_crt(changes.iter().map(|(_, old, new)| *new - old).collect());
+
+        self.apply_deltas(cm, changes.iter().map(|(i, _, _)| *i).zip(deltas))
+    }
+
+    /// Updates a commitment produced by [`Self::decompose_and_commit_coeff`].
+    ///
+    /// Each change is given as `(index, old, new)` where `index` is a position of the original
+    /// witness. Both `old` and `new` are decomposed, so only the columns of the digits of
+    /// `index` are touched.
+    pub fn update_decomposed_coeff<P: DecompositionParams>(
+        &self,
+        cm: &Commitment<NTT>,
+        changes: &[(
+            usize,
+            NTT::CoefficientRepresentation,
+            NTT::CoefficientRepresentation,
+        )],
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        self.update_decomposed_coeff_with_params(&P::DATA, cm, changes)
+    }
+
+    /// Same as [`Self::update_decomposed_coeff`], with decomposition parameters chosen at runtime.
+    pub fn update_decomposed_coeff_with_params(
+        &self,
+        params: &DecompositionParamData,
+        cm: &Commitment<NTT>,
+        changes: &[(
+            usize,
+            NTT::CoefficientRepresentation,
+            NTT::CoefficientRepresentation,
+        )],
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        let l = params.l();
+        let old: Vec<_> = changes.iter().map(|(_, old, _)| *old).collect();
+        let new: Vec<_> = changes.iter().map(|(_, _, new)| *new).collect();
+
+        let mut columns = Vec::with_capacity(changes.len() * l);
+        let mut delta_digits = Vec::with_capacity(changes.len() * l);
+        for (((index, _, _), old_digits), new_digits) in changes
+            .iter()
+            .zip(old.decompose_to_vec(params.b(), l))
+            .zip(new.decompose_to_vec(params.b(), l))
+        {
+            for (j, (old, new)) in old_digits.into_iter().zip(new_digits).enumerate() {
+                columns.push(index * l + j);
+                delta_digits.push(new - old);
+            }
+        }
+        let deltas: Vec<NTT> = CRT::elementwise_crt(delta_digits);
+
+        self.apply_deltas(cm, columns.into_iter().zip(deltas))
+    }
+
+    /// Updates a commitment produced by [`Self::decompose_and_commit_ntt`].
+    ///
+    /// See [`Self::update_decomposed_coeff`].
+    pub fn update_decomposed_ntt<P: DecompositionParams>(
+        &self,
+        cm: &Commitment<NTT>,
+        changes: &[(usize, NTT, NTT)],
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        self.update_decomposed_ntt_with_params(&P::DATA, cm, changes)
+    }
+
+    /// Same as [`Self::update_decomposed_ntt`], with decomposition parameters chosen at runtime.
+    pub fn update_decomposed_ntt_with_params(
+        &self,
+        params: &DecompositionParamData,
+        cm: &Commitment<NTT>,
+        changes: &[(usize, NTT, NTT)],
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        let old: Vec<NTT::CoefficientRepresentation> =
+            ICRT::elementwise_icrt(changes.iter().map(|(_, old, _)| *old).collect());
+        let new: Vec<NTT::CoefficientRepresentation> =
+            ICRT::elementwise_icrt(changes.iter().map(|(_, _, new)| *new).collect());
+        let changes: Vec<_> = changes
+            .iter()
+            .zip(old.into_iter().zip(new))
+            .map(|((i, _, _), (old, new))| (*i, old, new))
+            .collect();
+
+        self.update_decomposed_coeff_with_params(params, cm, &changes)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use cyclotomic_rings::rings::GoldilocksRingNTT;
-    use stark_rings::OverField;
+    use ark_std::{test_rng, UniformRand};
+    use cyclotomic_rings::rings::{GoldilocksRingNTT, GoldilocksRingPoly};
+    use stark_rings::{cyclotomic_ring::ICRT, OverField};
 
     use super::{AjtaiCommitmentScheme, CommitmentError};
-    use crate::ark_base::*;
+    use crate::{
+        ark_base::*,
+        commitment::SeededAjtaiMatrix,
+        decomposition_parameters::{test_params::GoldilocksDP, DecompositionParams},
+    };
 
     pub(crate) fn generate_ajtai<NTT: OverField>(
         kappa: usize,
@@ -157,4 +321,85 @@ mod tests {
 
         Ok(())
     }
+
+    fn to_coeff_changes(
+        changes: &[(usize, GoldilocksRingNTT, GoldilocksRingNTT)],
+    ) -> Vec<(usize, GoldilocksRingPoly, GoldilocksRingPoly)> {
+        let old: Vec<GoldilocksRingPoly> =
+            ICRT::elementwise_icrt(changes.iter().map(|(_, old, _)| *old).collect());
+        let new: Vec<GoldilocksRingPoly> =
+            ICRT::elementwise_icrt(changes.iter().map(|(_, _, new)| *new).collect());
+
+        changes
+            .iter()
+            .zip(old.into_iter().zip(new))
+            .map(|((i, _, _), (old, new))| (*i, old, new))
+            .collect()
+    }
+
+    #[test]
+    fn test_update() -> Result<(), CommitmentError> {
+        type R = GoldilocksRingNTT;
+        const WITNESS_SIZE: usize = 64;
+
+        let mut rng = test_rng();
+        let scheme = SeededAjtaiMatrix::<R>::new([1; 32], 4, WITNESS_SIZE).materialize();
+        let mut f: Vec<R> = (0..WITNESS_SIZE).map(|_| R::rand(&mut rng)).collect();
+        let cm = scheme.commit_ntt(&f)?;
+
+        let mut changes = Vec::new();
+        for index in [3, 17, 3, 63] {
+            let new = R::rand(&mut rng);
+            changes.push((index, f[index], new));
+            f[index] = new;
+        }
+
+        assert_eq!(scheme.update(&cm, &changes)?, scheme.commit_ntt(&f)?);
+
+        let f_coeff: Vec<GoldilocksRingPoly> = ICRT::elementwise_icrt(f.clone());
+        let cm_coeff = scheme.commit_coeff::<GoldilocksDP>(f_coeff)?;
+        let coeff_changes = to_coeff_changes(&changes);
+        assert_eq!(scheme.update_coeff(&cm, &coeff_changes)?, cm_coeff);
+
+        assert!(matches!(
+            scheme.update(&cm, &[(WITNESS_SIZE, f[0], f[0])]),
+            Err(CommitmentError::IndexOutOfBounds(
+                WITNESS_SIZE,
+                WITNESS_SIZE
+            ))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_decomposed() -> Result<(), CommitmentError> {
+        type R = GoldilocksRingNTT;
+        type DP = GoldilocksDP;
+        const WITNESS_SIZE: usize = 16;
+
+        let mut rng = test_rng();
+        let scheme = SeededAjtaiMatrix::<R>::new([2; 32], 4, WITNESS_SIZE * DP::L).materialize();
+        let mut w: Vec<R> = (0..WITNESS_SIZE).map(|_| R::rand(&mut rng)).collect();
+        let cm = scheme.decompose_and_commit_ntt::<DP>(w.clone())?;
+
+        let mut changes = Vec::new();
+        for index in [0, 5, 15] {
+            let new = R::rand(&mut rng);
+            changes.push((index, w[index], new));
+            w[index] = new;
+        }
+
+        let updated = scheme.update_decomposed_ntt::<DP>(&cm, &changes)?;
+        assert_eq!(updated, scheme.decompose_and_commit_ntt::<DP>(w.clone())?);
+
+        // The same changes in the coefficient form
+        let coeff_changes = to_coeff_changes(&changes);
+        assert_eq!(
+            scheme.update_decomposed_coeff::<DP>(&cm, &coeff_changes)?,
+            updated
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,300 @@
+use ark_ff::{Field, PrimeField, Zero};
+use cyclotomic_rings::rings::SuitableRing;
+use stark_rings::{cyclotomic_ring::CRT, PolyRing, Ring};
+use stark_rings_linalg::Matrix;
+
+use super::{homomorphic_commitment::Commitment, AjtaiCommitmentScheme, NormBound, Opening};
+use crate::{ark_base::*, commitment::CommitmentError, utils::sumcheck::utils::from_i128};
+
+/// A hiding variant of the Ajtai commitment scheme.
+///
+/// The Ajtai matrix has `rand_cols` extra columns, multiplied by a short random mask `r`,
+/// i.e. a commitment to `f` is $A \cdot f + A_r \cdot r$. The coefficients of the mask are
+/// sampled uniformly in $\[-\beta, \beta\]$ for the mask bound $\beta$, so the mask adds at
+/// most $\beta$ to the norm budget of the committed witness.
+///
+/// Commitments stay additively homomorphic: the sum of two commitments opens to the sum of
+/// the witnesses under the sum of the masks.
+#[derive(Clone, Debug)]
+pub struct HidingAjtaiCommitmentScheme<R> {
+    scheme: AjtaiCommitmentScheme<R>,
+    rand_cols: usize,
+    mask_bound: u128,
+}
+
+impl<R: Ring> HidingAjtaiCommitmentScheme<R> {
+    /// Create a new scheme from an Ajtai matrix whose last `rand_cols` columns multiply the mask.
+    pub fn new(
+        matrix: Matrix<R>,
+        rand_cols: usize,
+        mask_bound: u128,
+    ) -> Result<Self, CommitmentError> {
+        if rand_cols == 0 || rand_cols > matrix.ncols || mask_bound == 0 {
+            return Err(CommitmentError::InvalidHidingParameters(
+                rand_cols, mask_bound,
+            ));
+        }
+
+        Ok(Self {
+            scheme: AjtaiCommitmentScheme::new(matrix),
+            rand_cols,
+            mask_bound,
+        })
+    }
+
+    /// Returns a random hiding Ajtai commitment scheme for witnesses of length `n`.
+    pub fn rand<Rng: rand::Rng + ?Sized>(
+        kappa: usize,
+        n: usize,
+        rand_cols: usize,
+        mask_bound: u128,
+        rng: &mut Rng,
+    ) -> Result<Self, CommitmentError> {
+        let matrix: Vec<Vec<R>> = (0..kappa)
+            .map(|_| (0..n + rand_cols).map(|_| R::rand(rng)).collect())
+            .collect();
+
+        Self::new(matrix.into(), rand_cols, mask_bound)
+    }
+
+    /// Ajtai matrix number of rows
+    pub fn kappa(&self) -> usize {
+        self.scheme.kappa()
+    }
+
+    /// The length of the committed witnesses, i.e. the width of the matrix without the
+    /// randomness columns.
+    pub fn width(&self) -> usize {
+        self.scheme.width() - self.rand_cols
+    }
+
+    /// The number of randomness columns, i.e. the length of a mask.
+    pub fn rand_cols(&self) -> usize {
+        self.rand_cols
+    }
+
+    /// The bound on the coefficients of a mask.
+    pub fn mask_bound(&self) -> u128 {
+        self.mask_bound
+    }
+}
+
+impl<NTT: SuitableRing> HidingAjtaiCommitmentScheme<NTT> {
+    /// Samples a mask in the coefficient form, with coefficients uniform in
+    /// $\[-\beta, \beta\]$.
+    pub fn sample_mask<Rng: rand::Rng + ?Sized>(
+        &self,
+        rng: &mut Rng,
+    ) -> Vec<NTT::CoefficientRepresentation> {
+        sample_short::<NTT, _>(self.rand_cols, self.mask_bound, rng)
+    }
+
+    /// Commits to a witness in the NTT form under a freshly sampled mask.
+    ///
+    /// Returns the commitment and the mask, needed to open it.
+    pub fn commit_ntt<Rng: rand::Rng + ?Sized>(
+        &self,
+        f: &[NTT],
+        rng: &mut Rng,
+    ) -> Result<(Commitment<NTT>, Vec<NTT::CoefficientRepresentation>), CommitmentError> {
+        let mask = self.sample_mask(rng);
+        let cm = self.commit_ntt_with_mask(f, &mask)?;
+
+        Ok((cm, mask))
+    }
+
+    /// Commits to a witness in the NTT form under the given mask.
+    pub fn commit_ntt_with_mask(
+        &self,
+        f: &[NTT],
+        mask: &[NTT::CoefficientRepresentation],
+    ) -> Result<Commitment<NTT>, CommitmentError> {
+        if f.len() != self.width() {
+            return Err(CommitmentError::WrongWitnessLength(f.len(), self.width()));
+        }
+        if mask.len() != self.rand_cols {
+            return Err(CommitmentError::WrongMaskLength(mask.len(), self.rand_cols));
+        }
+
+        let mut f_and_mask = Vec::with_capacity(self.scheme.width());
+        f_and_mask.extend_from_slice(f);
+        f_and_mask.extend(CRT::elementwise_crt(mask.to_vec()));
+
+        self.scheme.commit_ntt(&f_and_mask)
+    }
+
+    /// Checks that `opening` opens `cm` under `mask`, that `opening` satisfies `norm_bound`
+    /// and that the coefficients of the mask are within the mask bound.
+    pub fn verify_opening(
+        &self,
+        cm: &Commitment<NTT>,
+        opening: &Opening<NTT::CoefficientRepresentation>,
+        mask: &[NTT::CoefficientRepresentation],
+        norm_bound: NormBound,
+    ) -> Result<(), CommitmentError> {
+        if cm.len() != self.kappa() {
+            return Err(CommitmentError::WrongCommitmentLength(
+                cm.len(),
+                self.kappa(),
+            ));
+        }
+        let mask_bound = NormBound::Infinity(self.mask_bound.saturating_add(1));
+        if !mask_bound.check(mask) {
+            return Err(CommitmentError::NormBoundExceeded(mask_bound));
+        }
+        if !norm_bound.check(&opening.f) {
+            return Err(CommitmentError::NormBoundExceeded(norm_bound));
+        }
+        if self.commit_ntt_with_mask(&CRT::elementwise_crt(opening.f.clone()), mask)? != *cm {
+            return Err(CommitmentError::InvalidOpening);
+        }
+
+        Ok(())
+    }
+}
+
+/// Samples `len` ring elements in the coefficient form, with coefficients uniform in
+/// $\[-\beta, \beta\]$ for the bound $\beta$.
+pub(crate) fn sample_short<NTT: SuitableRing, Rng: rand::Rng + ?Sized>(
+    len: usize,
+    bound: u128,
+    rng: &mut Rng,
+) -> Vec<NTT::CoefficientRepresentation> {
+    let bound = bound as i128;
+
+    (0..len)
+        .map(|_| {
+            let mut x = NTT::CoefficientRepresentation::zero();
+            for coeff in x.coeffs_mut().iter_mut() {
+                *coeff = from_i128::<<<NTT as PolyRing>::BaseRing as Field>::BasePrimeField>(
+                    rng.gen_range(-bound..=bound),
+                );
+            }
+            x
+        })
+        .collect()
+}
+
+/// Returns the smallest number of randomness columns for which commitments with `kappa` rows
+/// and mask bound `mask_bound` are statistically hiding with `security_bits` bits of security.
+///
+/// By the leftover hash lemma, it suffices that the mask has
+/// $\kappa \cdot d \cdot \log q + 2\lambda$ bits of min-entropy. The estimate is conservative,
+/// it rounds $\log (2\beta + 1)$ down and $\log q$ up.
+pub fn min_rand_cols<NTT: SuitableRing>(
+    kappa: usize,
+    mask_bound: u128,
+    security_bits: usize,
+) -> usize {
+    let degree = NTT::CoefficientRepresentation::dimension();
+    let log_q = <<NTT as PolyRing>::BaseRing as Field>::BasePrimeField::MODULUS_BIT_SIZE as usize;
+    let entropy_per_col = degree * (2 * mask_bound.max(1) + 1).ilog2() as usize;
+
+    (kappa * degree * log_q + 2 * security_bits).div_ceil(entropy_per_col)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::test_rng;
+    use cyclotomic_rings::rings::GoldilocksRingNTT;
+    use num_bigint::BigUint;
+    use stark_rings::cyclotomic_ring::ICRT;
+
+    use super::*;
+    use crate::utils::norms::linf_norm;
+
+    type R = GoldilocksRingNTT;
+
+    const KAPPA: usize = 4;
+    const WIT_LEN: usize = 32;
+    const MASK_BOUND: u128 = 8;
+
+    fn scheme() -> HidingAjtaiCommitmentScheme<R> {
+        let rand_cols = min_rand_cols::<R>(KAPPA, MASK_BOUND, 128);
+        HidingAjtaiCommitmentScheme::rand(KAPPA, WIT_LEN, rand_cols, MASK_BOUND, &mut test_rng())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_hiding_commitment() {
+        let mut rng = test_rng();
+        let scheme = scheme();
+        let f: Vec<R> = (0..WIT_LEN).map(|i| R::from(i as u64)).collect();
+
+        let (cm_1, mask_1) = scheme.commit_ntt(&f, &mut rng).unwrap();
+        let (cm_2, mask_2) = scheme.commit_ntt(&f, &mut rng).unwrap();
+
+        assert_ne!(cm_1, cm_2);
+        assert_eq!(scheme.commit_ntt_with_mask(&f, &mask_1).unwrap(), cm_1);
+        assert_eq!(scheme.commit_ntt_with_mask(&f, &mask_2).unwrap(), cm_2);
+    }
+
+    #[test]
+    fn test_mask_within_bound() {
+        let scheme = scheme();
+        let mask = scheme.sample_mask(&mut test_rng());
+
+        assert!(linf_norm(&mask) <= BigUint::from(MASK_BOUND));
+    }
+
+    #[test]
+    fn test_verify_opening() {
+        let mut rng = test_rng();
+        let scheme = scheme();
+        let f: Vec<R> = (0..WIT_LEN).map(|i| R::from(i as u64)).collect();
+        let (cm, mask) = scheme.commit_ntt(&f, &mut rng).unwrap();
+        let opening = Opening::new(ICRT::elementwise_icrt(f.clone()));
+
+        scheme
+            .verify_opening(&cm, &opening, &mask, NormBound::Infinity(32))
+            .unwrap();
+        assert!(matches!(
+            scheme.verify_opening(&cm, &opening, &mask, NormBound::Infinity(31)),
+            Err(CommitmentError::NormBoundExceeded(NormBound::Infinity(31)))
+        ));
+
+        // The mask bound is checked before the commitment
+        let mut long_mask = mask.clone();
+        long_mask[0].coeffs_mut()[0] = (MASK_BOUND as u64 + 1).into();
+        assert!(matches!(
+            scheme.verify_opening(&cm, &opening, &long_mask, NormBound::Infinity(32)),
+            Err(CommitmentError::NormBoundExceeded(NormBound::Infinity(b))) if b == MASK_BOUND + 1
+        ));
+
+        let (other_cm, _) = scheme.commit_ntt(&f, &mut rng).unwrap();
+        assert!(matches!(
+            scheme.verify_opening(&other_cm, &opening, &mask, NormBound::Infinity(32)),
+            Err(CommitmentError::InvalidOpening)
+        ));
+    }
+
+    #[test]
+    fn test_hiding_commitment_is_homomorphic() {
+        let mut rng = test_rng();
+        let scheme = scheme();
+        let f_1: Vec<R> = (0..WIT_LEN).map(|i| R::from(i as u64)).collect();
+        let f_2: Vec<R> = (0..WIT_LEN).map(|i| R::from(2 * i as u64 + 1)).collect();
+
+        let (cm_1, mask_1) = scheme.commit_ntt(&f_1, &mut rng).unwrap();
+        let (cm_2, mask_2) = scheme.commit_ntt(&f_2, &mut rng).unwrap();
+
+        let f: Vec<R> = f_1.iter().zip(&f_2).map(|(a, b)| *a + *b).collect();
+        let mask: Vec<_> = mask_1.iter().zip(&mask_2).map(|(a, b)| *a + *b).collect();
+
+        assert_eq!(scheme.commit_ntt_with_mask(&f, &mask).unwrap(), cm_1 + cm_2);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let mut rng = test_rng();
+
+        assert!(matches!(
+            HidingAjtaiCommitmentScheme::<R>::rand(KAPPA, WIT_LEN, 0, MASK_BOUND, &mut rng),
+            Err(CommitmentError::InvalidHidingParameters(0, MASK_BOUND))
+        ));
+        assert!(matches!(
+            scheme().commit_ntt_with_mask(&vec![R::zero(); WIT_LEN], &[]),
+            Err(CommitmentError::WrongMaskLength(0, _))
+        ));
+    }
+}
@@ -0,0 +1,688 @@
+//! On-disk format for Ajtai commitment keys.
+//!
+//! A key file is laid out as follows, integers being little-endian:
+//!
+//! | field          | size                                     |
+//! |----------------|------------------------------------------|
+//! | magic `LFCK`   | 4                                        |
+//! | version        | 2                                        |
+//! | kind           | 1 (0 for a seed, 1 for a full matrix)    |
+//! | ring modulus   | 2 (length) + length                      |
+//! | ring degree    | 8                                        |
+//! | kappa, width   | 8 + 8                                    |
+//! | B, L, b, k     | 16 + 8 + 8 + 8                           |
+//! | body           | 1 + 32 for a seed, the entries otherwise |
+//! | SHA-256        | 32                                       |
+//!
+//! A seed is preceded by the identifier of the PRG expanding it, so that a key file always
+//! describes the same matrix. The only PRG is ChaCha20, with identifier 0, as used by
+//! [`SeededAjtaiMatrix`].
+//!
+//! A full matrix is stored row by row in the NTT form, each entry serialized uncompressed, so
+//! that the entries can be read in place from a memory-mapped file. The hash covers everything
+//! before it.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use ark_ff::{Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use cyclotomic_rings::rings::SuitableRing;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use stark_rings::{PolyRing, Ring};
+use thiserror::Error;
+
+use super::{AjtaiCommitmentScheme, AjtaiMatrix, SeededAjtaiMatrix};
+use crate::decomposition_parameters::DecompositionParamData;
+
+/// The first bytes of a key file.
+pub const KEY_FILE_MAGIC: [u8; 4] = *b"LFCK";
+/// The version of the key file format written by this crate.
+pub const KEY_FILE_VERSION: u16 = 2;
+
+const SEED_KIND: u8 = 0;
+const MATRIX_KIND: u8 = 1;
+/// The identifier of the ChaCha20 PRG of [`SeededAjtaiMatrix`].
+const CHACHA20_PRG: u8 = 0;
+const HASH_LEN: usize = 32;
+
+/// Errors that can arise when reading or writing a key file.
+#[derive(Debug, Error)]
+pub enum KeyFileError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] SerializationError),
+    #[error("not a commitment key file")]
+    BadMagic,
+    #[error("unsupported key file version {0}, expected {KEY_FILE_VERSION}")]
+    UnsupportedVersion(u16),
+    #[error("unknown key kind {0}")]
+    UnknownKind(u8),
+    #[error("unknown PRG {0}")]
+    UnknownPrg(u8),
+    #[error("the key is for another ring")]
+    RingMismatch,
+    #[error("the key header {0:?} does not match the expected {1:?}")]
+    HeaderMismatch(Box<KeyHeader>, Box<KeyHeader>),
+    #[error("the key file is corrupted")]
+    IntegrityCheckFailed,
+}
+
+/// The metadata of a key file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyHeader {
+    /// The modulus of the base prime field of the ring.
+    pub modulus: BigUint,
+    /// The degree of the ring over its base ring.
+    pub degree: usize,
+    /// The number of rows of the Ajtai matrix.
+    pub kappa: usize,
+    /// The number of columns of the Ajtai matrix.
+    pub width: usize,
+    /// The decomposition parameters the key was generated for.
+    pub params: DecompositionParamData,
+}
+
+impl KeyHeader {
+    /// The header of a `kappa x width` key over the ring `NTT`.
+    pub fn new<NTT: SuitableRing>(
+        kappa: usize,
+        width: usize,
+        params: DecompositionParamData,
+    ) -> Self {
+        Self {
+            modulus: <<NTT as PolyRing>::BaseRing as Field>::BasePrimeField::MODULUS.into(),
+            degree: NTT::CoefficientRepresentation::dimension(),
+            kappa,
+            width,
+            params,
+        }
+    }
+
+    fn check_ring<NTT: SuitableRing>(&self) -> Result<(), KeyFileError> {
+        let expected = Self::new::<NTT>(self.kappa, self.width, self.params);
+        if (&self.modulus, self.degree) != (&expected.modulus, expected.degree) {
+            return Err(KeyFileError::RingMismatch);
+        }
+
+        Ok(())
+    }
+
+    fn write(&self, kind: u8, w: &mut impl Write) -> io::Result<()> {
+        let modulus = self.modulus.to_bytes_le();
+
+        w.write_all(&KEY_FILE_MAGIC)?;
+        w.write_all(&KEY_FILE_VERSION.to_le_bytes())?;
+        w.write_all(&[kind])?;
+        w.write_all(&(modulus.len() as u16).to_le_bytes())?;
+        w.write_all(&modulus)?;
+        for x in [self.degree, self.kappa, self.width] {
+            w.write_all(&(x as u64).to_le_bytes())?;
+        }
+        w.write_all(&self.params.b().to_le_bytes())?;
+        for x in [self.params.l(), self.params.b_small(), self.params.k()] {
+            w.write_all(&(x as u64).to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn read(r: &mut impl Read) -> Result<(u8, Self), KeyFileError> {
+        if read_array::<4>(r)? != KEY_FILE_MAGIC {
+            return Err(KeyFileError::BadMagic);
+        }
+        let version = u16::from_le_bytes(read_array(r)?);
+        if version != KEY_FILE_VERSION {
+            return Err(KeyFileError::UnsupportedVersion(version));
+        }
+        let [kind] = read_array(r)?;
+        if kind != SEED_KIND && kind != MATRIX_KIND {
+            return Err(KeyFileError::UnknownKind(kind));
+        }
+
+        let mut modulus = vec![0; u16::from_le_bytes(read_array(r)?) as usize];
+        r.read_exact(&mut modulus)?;
+        let [degree, kappa, width] = [read_usize(r)?, read_usize(r)?, read_usize(r)?];
+        let b = u128::from_le_bytes(read_array(r)?);
+        let [l, b_small, k] = [read_usize(r)?, read_usize(r)?, read_usize(r)?];
+        if kappa == 0 || width == 0 || kappa.checked_mul(width).is_none() {
+            return Err(KeyFileError::IntegrityCheckFailed);
+        }
+
+        Ok((
+            kind,
+            Self {
+                modulus: BigUint::from_bytes_le(&modulus),
+                degree,
+                kappa,
+                width,
+                params: DecompositionParamData::new_unchecked(b, l, b_small, k),
+            },
+        ))
+    }
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_usize(r: &mut impl Read) -> Result<usize, KeyFileError> {
+    usize::try_from(u64::from_le_bytes(read_array(r)?))
+        .map_err(|_| KeyFileError::IntegrityCheckFailed)
+}
+
+fn read_seed(r: &mut impl Read) -> Result<[u8; 32], KeyFileError> {
+    let [prg] = read_array(r)?;
+    if prg != CHACHA20_PRG {
+        return Err(KeyFileError::UnknownPrg(prg));
+    }
+
+    Ok(read_array(r)?)
+}
+
+/// Hashes everything written through it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Hashes everything read through it.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// An Ajtai commitment key that can be stored in a key file.
+///
+/// A seeded key only stores its seed and regenerates the entries on demand, a full key stores
+/// every entry and avoids the cost of sampling them.
+#[derive(Clone, Debug)]
+pub enum CommitmentKey<R> {
+    /// A key derived from a seed.
+    Seeded(SeededAjtaiMatrix<R>),
+    /// A key with all of its entries.
+    Matrix(AjtaiCommitmentScheme<R>),
+}
+
+impl<R: Ring> AjtaiMatrix<R> for CommitmentKey<R> {
+    fn kappa(&self) -> usize {
+        match self {
+            Self::Seeded(matrix) => matrix.kappa(),
+            Self::Matrix(scheme) => AjtaiMatrix::kappa(scheme),
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Self::Seeded(matrix) => matrix.width(),
+            Self::Matrix(scheme) => AjtaiMatrix::width(scheme),
+        }
+    }
+
+    fn mul_add_columns(&self, start: usize, cols: &[R], acc: &mut [R]) {
+        match self {
+            Self::Seeded(matrix) => matrix.mul_add_columns(start, cols, acc),
+            Self::Matrix(scheme) => scheme.mul_add_columns(start, cols, acc),
+        }
+    }
+}
+
+impl<NTT: SuitableRing> CommitmentKey<NTT> {
+    /// Returns the commitment scheme of the key, computing the entries of a seeded key.
+    pub fn into_scheme(self) -> AjtaiCommitmentScheme<NTT> {
+        match self {
+            Self::Seeded(matrix) => matrix.materialize(),
+            Self::Matrix(scheme) => scheme,
+        }
+    }
+
+    /// The header describing this key, generated for the decomposition parameters `params`.
+    pub fn header(&self, params: DecompositionParamData) -> KeyHeader {
+        KeyHeader::new::<NTT>(self.kappa(), self.width(), params)
+    }
+
+    /// Writes the key in the key file format.
+    pub fn write(
+        &self,
+        params: DecompositionParamData,
+        w: &mut impl Write,
+    ) -> Result<(), KeyFileError> {
+        let mut w = HashingWriter {
+            inner: w,
+            hasher: Sha256::new(),
+        };
+
+        match self {
+            Self::Seeded(matrix) => {
+                self.header(params).write(SEED_KIND, &mut w)?;
+                w.write_all(&[CHACHA20_PRG])?;
+                w.write_all(&matrix.seed())?;
+            }
+            Self::Matrix(scheme) => {
+                self.header(params).write(MATRIX_KIND, &mut w)?;
+                for x in scheme.matrix().vals.iter().flatten() {
+                    x.serialize_uncompressed(&mut w)?;
+                }
+            }
+        }
+
+        let hash = w.hasher.finalize();
+        w.inner.write_all(&hash)?;
+
+        Ok(())
+    }
+
+    /// Reads a key in the key file format, checking its integrity.
+    pub fn read(r: &mut impl Read) -> Result<(KeyHeader, Self), KeyFileError> {
+        let mut r = HashingReader {
+            inner: r,
+            hasher: Sha256::new(),
+        };
+
+        let (kind, header) = KeyHeader::read(&mut r)?;
+        header.check_ring::<NTT>()?;
+        let key = if kind == SEED_KIND {
+            Self::Seeded(SeededAjtaiMatrix::new(
+                read_seed(&mut r)?,
+                header.kappa,
+                header.width,
+            ))
+        } else {
+            // The header is not checked against the hash yet, so the matrix only grows with the
+            // entries actually read instead of being allocated from the claimed dimensions.
+            let mut rows = Vec::new();
+            for _ in 0..header.kappa {
+                let mut row = Vec::new();
+                for _ in 0..header.width {
+                    row.push(NTT::deserialize_uncompressed(&mut r)?);
+                }
+                rows.push(row);
+            }
+            Self::Matrix(AjtaiCommitmentScheme::new(rows.into()))
+        };
+
+        let hash = r.hasher.finalize();
+        if read_array::<HASH_LEN>(&mut r.inner)? != hash.as_slice() {
+            return Err(KeyFileError::IntegrityCheckFailed);
+        }
+
+        Ok((header, key))
+    }
+
+    /// Saves the key to the file at `path`.
+    ///
+    /// The key is written to a temporary file next to `path` and then renamed, so that concurrent
+    /// readers never see a partially written key.
+    pub fn save(
+        &self,
+        params: DecompositionParamData,
+        path: impl AsRef<Path>,
+    ) -> Result<(), KeyFileError> {
+        let path = path.as_ref();
+        let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+
+        let mut w = BufWriter::new(File::create(&tmp_path)?);
+        self.write(params, &mut w)?;
+        w.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+
+    /// Loads a key from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<(KeyHeader, Self), KeyFileError> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Loads the key cached at `path`, or creates it with `create` and caches it if there is no
+    /// file at `path`.
+    ///
+    /// Fails if the cached key does not match `kappa`, `width` and `params`.
+    pub fn load_or_create(
+        path: impl AsRef<Path>,
+        kappa: usize,
+        width: usize,
+        params: DecompositionParamData,
+        create: impl FnOnce() -> Self,
+    ) -> Result<Self, KeyFileError> {
+        let path = path.as_ref();
+        let expected = KeyHeader::new::<NTT>(kappa, width, params);
+
+        if path.exists() {
+            let (header, key) = Self::load(path)?;
+            if header != expected {
+                return Err(KeyFileError::HeaderMismatch(
+                    Box::new(header),
+                    Box::new(expected),
+                ));
+            }
+            return Ok(key);
+        }
+
+        let key = create();
+        if key.header(params) != expected {
+            return Err(KeyFileError::HeaderMismatch(
+                Box::new(key.header(params)),
+                Box::new(expected),
+            ));
+        }
+        key.save(params, path)?;
+
+        Ok(key)
+    }
+}
+
+/// A commitment key read in place from a memory-mapped key file.
+///
+/// The entries of a full matrix are deserialized when they are used, so processes mapping the
+/// same file share its pages instead of each holding a copy of the matrix. This trades memory
+/// for time: every call to [`AjtaiMatrix::mul_add_columns`] deserializes all the entries it
+/// reads, so committing to many witnesses is faster with [`MappedCommitmentKey::to_key`]. The
+/// file must not be modified while it is mapped.
+#[cfg(feature = "mmap")]
+pub struct MappedCommitmentKey<R> {
+    header: KeyHeader,
+    key: MappedKey<R>,
+}
+
+#[cfg(feature = "mmap")]
+enum MappedKey<R> {
+    Seeded(SeededAjtaiMatrix<R>),
+    Matrix {
+        mmap: memmap2::Mmap,
+        offset: usize,
+        entry_size: usize,
+    },
+}
+
+#[cfg(feature = "mmap")]
+impl<NTT: SuitableRing> MappedCommitmentKey<NTT> {
+    /// Maps the key file at `path`, checking its header and its integrity.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, KeyFileError> {
+        let file = File::open(path)?;
+        // SAFETY: the key file must not be modified while mapped, as documented on the type.
+        #[allow(unsafe_code)]
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+
+        let (body, hash) = mmap
+            .len()
+            .checked_sub(HASH_LEN)
+            .map(|n| mmap.split_at(n))
+            .ok_or(KeyFileError::IntegrityCheckFailed)?;
+        if Sha256::digest(body).as_slice() != hash {
+            return Err(KeyFileError::IntegrityCheckFailed);
+        }
+
+        let mut r = body;
+        let (kind, header) = KeyHeader::read(&mut r)?;
+        header.check_ring::<NTT>()?;
+        let offset = body.len() - r.len();
+
+        let key = if kind == SEED_KIND {
+            MappedKey::Seeded(SeededAjtaiMatrix::new(
+                read_seed(&mut r)?,
+                header.kappa,
+                header.width,
+            ))
+        } else {
+            let entry_size = NTT::zero().uncompressed_size();
+            let len = header
+                .kappa
+                .checked_mul(header.width)
+                .and_then(|n| n.checked_mul(entry_size));
+            if len != Some(r.len()) {
+                return Err(KeyFileError::IntegrityCheckFailed);
+            }
+            MappedKey::Matrix {
+                mmap,
+                offset,
+                entry_size,
+            }
+        };
+
+        Ok(Self { header, key })
+    }
+
+    /// The header of the key file.
+    pub fn header(&self) -> &KeyHeader {
+        &self.header
+    }
+
+    /// Reads the whole key into memory.
+    pub fn to_key(&self) -> CommitmentKey<NTT> {
+        match &self.key {
+            MappedKey::Seeded(matrix) => CommitmentKey::Seeded(matrix.clone()),
+            MappedKey::Matrix { .. } => {
+                let rows: Vec<Vec<NTT>> = (0..self.header.kappa)
+                    .map(|i| (0..self.header.width).map(|j| self.entry(i, j)).collect())
+                    .collect();
+                CommitmentKey::Matrix(AjtaiCommitmentScheme::new(rows.into()))
+            }
+        }
+    }
+
+    fn entry(&self, row: usize, col: usize) -> NTT {
+        let MappedKey::Matrix {
+            mmap,
+            offset,
+            entry_size,
+        } = &self.key
+        else {
+            unreachable!("only full keys are stored in the file");
+        };
+        let start = offset + (row * self.header.width + col) * entry_size;
+
+        // The file passed the integrity check, so the entries are the ones that were written.
+        NTT::deserialize_uncompressed_unchecked(&mmap[start..start + entry_size])
+            .expect("key entries are well-formed")
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<NTT: SuitableRing> AjtaiMatrix<NTT> for MappedCommitmentKey<NTT> {
+    fn kappa(&self) -> usize {
+        self.header.kappa
+    }
+
+    fn width(&self) -> usize {
+        self.header.width
+    }
+
+    fn mul_add_columns(&self, start: usize, cols: &[NTT], acc: &mut [NTT]) {
+        match &self.key {
+            MappedKey::Seeded(matrix) => matrix.mul_add_columns(start, cols, acc),
+            MappedKey::Matrix { .. } => {
+                for (i, acc_i) in acc.iter_mut().enumerate() {
+                    for (j, x) in cols.iter().enumerate() {
+                        *acc_i += self.entry(i, start + j) * x;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use ark_std::{test_rng, UniformRand};
+    use cyclotomic_rings::rings::{BabyBearRingNTT, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::{
+        commitment::commit_iter,
+        decomposition_parameters::{test_params::GoldilocksDP, DecompositionParams},
+    };
+
+    type R = GoldilocksRingNTT;
+
+    const KAPPA: usize = 2;
+    const WIDTH: usize = 100;
+    const PARAMS: DecompositionParamData = GoldilocksDP::DATA;
+
+    fn keys() -> [CommitmentKey<R>; 2] {
+        [
+            CommitmentKey::Seeded(SeededAjtaiMatrix::new([3; 32], KAPPA, WIDTH)),
+            CommitmentKey::Matrix(AjtaiCommitmentScheme::rand(KAPPA, WIDTH, &mut test_rng())),
+        ]
+    }
+
+    fn witness() -> Vec<R> {
+        let mut rng = test_rng();
+        (0..WIDTH).map(|_| R::rand(&mut rng)).collect()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("latticefold-{}-{name}.key", std::process::id()))
+    }
+
+    #[test]
+    fn test_key_file_roundtrip() {
+        let f = witness();
+
+        for key in keys() {
+            let mut bytes = Vec::new();
+            key.write(PARAMS, &mut bytes).unwrap();
+            let (header, read_key) = CommitmentKey::<R>::read(&mut bytes.as_slice()).unwrap();
+
+            assert_eq!(header, key.header(PARAMS));
+            assert_eq!(
+                commit_iter(&read_key, f.clone(), 10).unwrap(),
+                commit_iter(&key, f.clone(), 10).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_file_corruption() {
+        for key in keys() {
+            let mut bytes = Vec::new();
+            key.write(PARAMS, &mut bytes).unwrap();
+
+            let mut corrupted = bytes.clone();
+            let last = corrupted.len() - HASH_LEN - 1;
+            corrupted[last] ^= 1;
+            assert!(matches!(
+                CommitmentKey::<R>::read(&mut corrupted.as_slice()),
+                Err(KeyFileError::IntegrityCheckFailed)
+            ));
+
+            let mut corrupted = bytes.clone();
+            corrupted[0] = b'X';
+            assert!(matches!(
+                CommitmentKey::<R>::read(&mut corrupted.as_slice()),
+                Err(KeyFileError::BadMagic)
+            ));
+
+            assert!(CommitmentKey::<R>::read(&mut &bytes[..bytes.len() - 1]).is_err());
+            assert!(matches!(
+                CommitmentKey::<BabyBearRingNTT>::read(&mut bytes.as_slice()),
+                Err(KeyFileError::RingMismatch)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_key_file_malformed_header() {
+        let [seeded, full] = keys();
+
+        let mut bytes = Vec::new();
+        seeded.write(PARAMS, &mut bytes).unwrap();
+        let prg = bytes.len() - HASH_LEN - 33;
+        bytes[prg] = 1;
+        assert!(matches!(
+            CommitmentKey::<R>::read(&mut bytes.as_slice()),
+            Err(KeyFileError::UnknownPrg(1))
+        ));
+
+        // A huge kappa fails on the missing entries instead of being allocated upfront
+        let mut bytes = Vec::new();
+        full.write(PARAMS, &mut bytes).unwrap();
+        let kappa = 9 + u16::from_le_bytes([bytes[7], bytes[8]]) as usize + 8;
+        bytes[kappa..kappa + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(CommitmentKey::<R>::read(&mut bytes.as_slice()).is_err());
+
+        bytes[kappa..kappa + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            CommitmentKey::<R>::read(&mut bytes.as_slice()),
+            Err(KeyFileError::IntegrityCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_load_or_create() {
+        let path = temp_path("cache");
+        let _ = fs::remove_file(&path);
+        let [_, key] = keys();
+        let f = witness();
+        let expected = commit_iter(&key, f.clone(), 10).unwrap();
+
+        let created =
+            CommitmentKey::<R>::load_or_create(&path, KAPPA, WIDTH, PARAMS, || key.clone())
+                .unwrap();
+        assert_eq!(commit_iter(&created, f.clone(), 10).unwrap(), expected);
+
+        let loaded = CommitmentKey::<R>::load_or_create(&path, KAPPA, WIDTH, PARAMS, || {
+            panic!("the key is cached")
+        })
+        .unwrap();
+        assert_eq!(commit_iter(&loaded, f, 10).unwrap(), expected);
+
+        assert!(matches!(
+            CommitmentKey::<R>::load_or_create(&path, KAPPA + 1, WIDTH, PARAMS, || key.clone()),
+            Err(KeyFileError::HeaderMismatch(..))
+        ));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_key() {
+        let f = witness();
+
+        for (i, key) in keys().into_iter().enumerate() {
+            let path = temp_path(&format!("mmap-{i}"));
+            key.save(PARAMS, &path).unwrap();
+
+            let mapped = MappedCommitmentKey::<R>::open(&path).unwrap();
+            assert_eq!(mapped.header(), &key.header(PARAMS));
+            let expected = commit_iter(&key, f.clone(), 10).unwrap();
+            assert_eq!(commit_iter(&mapped, f.clone(), 10).unwrap(), expected);
+            assert_eq!(
+                commit_iter(&mapped.to_key(), f.clone(), 10).unwrap(),
+                expected
+            );
+
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use cyclotomic_rings::rings::SuitableRing;
+use num_bigint::BigUint;
+use stark_rings::{
+    cyclotomic_ring::{CRT, ICRT},
+    PolyRing, Ring,
+};
+
+use super::{homomorphic_commitment::Commitment, AjtaiCommitmentScheme};
+use crate::{
+    ark_base::*,
+    commitment::CommitmentError,
+    utils::norms::{l2_norm_squared, linf_norm},
+};
+
+/// A norm bound on the opening of a commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NormBound {
+    /// The infinity norm is strictly less than the bound.
+    Infinity(u128),
+    /// The l2 norm is at most the bound.
+    L2(u128),
+}
+
+impl NormBound {
+    /// Checks that `f` satisfies the bound.
+    pub fn check<R: PolyRing>(&self, f: &[R]) -> bool
+    where
+        R::BaseRing: PrimeField,
+    {
+        match self {
+            Self::Infinity(b) => linf_norm(f) < BigUint::from(*b),
+            Self::L2(b) => l2_norm_squared(f) <= BigUint::from(*b).pow(2),
+        }
+    }
+}
+
+/// An opening of an Ajtai commitment, i.e. the committed witness in the coefficient form.
+///
+/// `R` is the coefficient form of the ring.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Opening<R: Ring> {
+    /// The committed witness.
+    pub f: Vec<R>,
+}
+
+impl<R: Ring> Opening<R> {
+    /// Create an opening from a witness in the coefficient form.
+    pub fn new(f: Vec<R>) -> Self {
+        Self { f }
+    }
+}
+
+impl<NTT: SuitableRing> AjtaiCommitmentScheme<NTT> {
+    /// Returns the opening of the commitment to the NTT form witness `f`.
+    pub fn open(&self, f: &[NTT]) -> Opening<NTT::CoefficientRepresentation> {
+        Opening::new(ICRT::elementwise_icrt(f.to_vec()))
+    }
+
+    /// Checks that `opening` opens `cm` and satisfies `norm_bound`.
+    pub fn verify_opening(
+        &self,
+        cm: &Commitment<NTT>,
+        opening: &Opening<NTT::CoefficientRepresentation>,
+        norm_bound: NormBound,
+    ) -> Result<(), CommitmentError> {
+        if cm.len() != self.kappa() {
+            return Err(CommitmentError::WrongCommitmentLength(
+                cm.len(),
+                self.kappa(),
+            ));
+        }
+        if !norm_bound.check(&opening.f) {
+            return Err(CommitmentError::NormBoundExceeded(norm_bound));
+        }
+        if self.commit_ntt(&CRT::elementwise_crt(opening.f.clone()))? != *cm {
+            return Err(CommitmentError::InvalidOpening);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::{test_rng, UniformRand};
+    use cyclotomic_rings::rings::GoldilocksRingNTT;
+
+    use super::*;
+    use crate::commitment::SeededAjtaiMatrix;
+
+    type R = GoldilocksRingNTT;
+
+    const WITNESS_SIZE: usize = 32;
+
+    fn setup() -> (AjtaiCommitmentScheme<R>, Vec<R>, Commitment<R>) {
+        let scheme = SeededAjtaiMatrix::<R>::new([3; 32], 4, WITNESS_SIZE).materialize();
+        let f: Vec<R> = (0..WITNESS_SIZE).map(|i| R::from(i as u64)).collect();
+        let cm = scheme.commit_ntt(&f).unwrap();
+
+        (scheme, f, cm)
+    }
+
+    #[test]
+    fn test_verify_opening() {
+        let (scheme, f, cm) = setup();
+        let opening = scheme.open(&f);
+
+        // The largest coefficient is 31, the squared l2 norm is 10416
+        scheme
+            .verify_opening(&cm, &opening, NormBound::Infinity(32))
+            .unwrap();
+        scheme
+            .verify_opening(&cm, &opening, NormBound::L2(103))
+            .unwrap();
+
+        assert!(matches!(
+            scheme.verify_opening(&cm, &opening, NormBound::Infinity(31)),
+            Err(CommitmentError::NormBoundExceeded(NormBound::Infinity(31)))
+        ));
+        assert!(matches!(
+            scheme.verify_opening(&cm, &opening, NormBound::L2(102)),
+            Err(CommitmentError::NormBoundExceeded(NormBound::L2(102)))
+        ));
+    }
+
+    #[test]
+    fn test_verify_opening_wrong_witness() {
+        let (scheme, mut f, cm) = setup();
+        f[0] = R::rand(&mut test_rng());
+
+        assert!(matches!(
+            scheme.verify_opening(&cm, &scheme.open(&f), NormBound::Infinity(u128::MAX)),
+            Err(CommitmentError::InvalidOpening)
+        ));
+    }
+
+    #[test]
+    fn test_opening_serialization() {
+        let (scheme, f, _) = setup();
+        let opening = scheme.open(&f);
+
+        let mut bytes = Vec::new();
+        opening.serialize_compressed(&mut bytes).unwrap();
+
+        assert_eq!(
+            Opening::deserialize_compressed(bytes.as_slice()).unwrap(),
+            opening
+        );
+    }
+}
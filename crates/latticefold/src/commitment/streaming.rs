@@ -0,0 +1,307 @@
+use ark_std::{cfg_chunks, marker::PhantomData};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use stark_rings::Ring;
+use stark_rings_linalg::Matrix;
+
+use super::{homomorphic_commitment::Commitment, AjtaiCommitmentScheme};
+use crate::{ark_base::*, commitment::CommitmentError};
+
+/// Number of columns processed by a single task of [`StreamingCommitter::absorb`].
+const COLUMN_BLOCK: usize = 1 << 10;
+
+/// An Ajtai matrix that can be read one block of columns at a time.
+///
+/// This lets [`StreamingCommitter`] commit to a witness without materializing it, and, for
+/// [`SeededAjtaiMatrix`], without materializing the matrix either.
+pub trait AjtaiMatrix<R: Ring>: Sync {
+    /// Number of rows of the matrix.
+    fn kappa(&self) -> usize;
+
+    /// Number of columns of the matrix.
+    fn width(&self) -> usize;
+
+    /// Adds the product of the columns `start..start + cols.len()` by `cols` to `acc`.
+    ///
+    /// `acc` has length [`AjtaiMatrix::kappa`] and the columns are within the matrix.
+    fn mul_add_columns(&self, start: usize, cols: &[R], acc: &mut [R]);
+}
+
+impl<R: Ring> AjtaiMatrix<R> for Matrix<R> {
+    fn kappa(&self) -> usize {
+        self.nrows
+    }
+
+    fn width(&self) -> usize {
+        self.ncols
+    }
+
+    fn mul_add_columns(&self, start: usize, cols: &[R], acc: &mut [R]) {
+        for (acc_i, row) in acc.iter_mut().zip(self.vals.iter()) {
+            for (a, x) in row[start..start + cols.len()].iter().zip(cols) {
+                *acc_i += *a * x;
+            }
+        }
+    }
+}
+
+impl<R: Ring> AjtaiMatrix<R> for AjtaiCommitmentScheme<R> {
+    fn kappa(&self) -> usize {
+        self.matrix().kappa()
+    }
+
+    fn width(&self) -> usize {
+        self.matrix().width()
+    }
+
+    fn mul_add_columns(&self, start: usize, cols: &[R], acc: &mut [R]) {
+        self.matrix().mul_add_columns(start, cols, acc)
+    }
+}
+
+/// An Ajtai matrix derived from a 32-byte seed.
+///
+/// The entries of column `j` are sampled from the stream `j` of a ChaCha20 PRG seeded with the
+/// seed. Columns are generated on demand, so only the seed is kept in memory, and a product with
+/// a range of columns generates exactly these columns.
+#[derive(Clone, Debug)]
+pub struct SeededAjtaiMatrix<R> {
+    seed: [u8; 32],
+    kappa: usize,
+    width: usize,
+    _r: PhantomData<R>,
+}
+
+impl<R: Ring> SeededAjtaiMatrix<R> {
+    /// Create a `kappa x width` matrix derived from `seed`.
+    pub fn new(seed: [u8; 32], kappa: usize, width: usize) -> Self {
+        Self {
+            seed,
+            kappa,
+            width,
+            _r: PhantomData,
+        }
+    }
+
+    /// The seed of the matrix.
+    pub fn seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    /// Computes all the entries of the matrix.
+    pub fn materialize(&self) -> AjtaiCommitmentScheme<R> {
+        let mut rows = vec![Vec::with_capacity(self.width); self.kappa];
+        for j in 0..self.width {
+            let mut rng = self.column_rng(j);
+            rows.iter_mut().for_each(|row| row.push(R::rand(&mut rng)));
+        }
+
+        AjtaiCommitmentScheme::new(rows.into())
+    }
+
+    /// The PRG generating the `j`-th column.
+    fn column_rng(&self, j: usize) -> ChaCha20Rng {
+        let mut rng = ChaCha20Rng::from_seed(self.seed);
+        rng.set_stream(j as u64);
+        rng
+    }
+}
+
+impl<R: Ring> AjtaiMatrix<R> for SeededAjtaiMatrix<R> {
+    fn kappa(&self) -> usize {
+        self.kappa
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn mul_add_columns(&self, start: usize, cols: &[R], acc: &mut [R]) {
+        for (j, x) in (start..).zip(cols) {
+            let mut rng = self.column_rng(j);
+            acc.iter_mut()
+                .for_each(|acc_i| *acc_i += R::rand(&mut rng) * x);
+        }
+    }
+}
+
+/// Computes an Ajtai commitment from a witness given in consecutive chunks.
+///
+/// Only `kappa` partial sums are kept between chunks, so the peak memory is bounded by the
+/// chunk size. Under the `parallel` feature each chunk is processed in parallel over blocks of
+/// columns.
+pub struct StreamingCommitter<'a, R: Ring, M: AjtaiMatrix<R>> {
+    matrix: &'a M,
+    acc: Vec<R>,
+    absorbed: usize,
+}
+
+impl<'a, R: Ring, M: AjtaiMatrix<R>> StreamingCommitter<'a, R, M> {
+    /// Starts a commitment with the Ajtai matrix `matrix`.
+    pub fn new(matrix: &'a M) -> Self {
+        Self {
+            matrix,
+            acc: vec![R::zero(); matrix.kappa()],
+            absorbed: 0,
+        }
+    }
+
+    /// The number of witness entries absorbed so far.
+    pub fn absorbed(&self) -> usize {
+        self.absorbed
+    }
+
+    /// Absorbs the next chunk of the witness.
+    pub fn absorb(&mut self, chunk: &[R]) -> Result<(), CommitmentError> {
+        let width = self.matrix.width();
+        if self.absorbed + chunk.len() > width {
+            return Err(CommitmentError::WrongWitnessLength(
+                self.absorbed + chunk.len(),
+                width,
+            ));
+        }
+
+        let start = self.absorbed;
+        let kappa = self.matrix.kappa();
+        let partials: Vec<Vec<R>> = cfg_chunks!(chunk, COLUMN_BLOCK)
+            .enumerate()
+            .map(|(i, cols)| {
+                let mut partial = vec![R::zero(); kappa];
+                self.matrix
+                    .mul_add_columns(start + i * COLUMN_BLOCK, cols, &mut partial);
+                partial
+            })
+            .collect();
+
+        for partial in partials {
+            self.acc
+                .iter_mut()
+                .zip(partial)
+                .for_each(|(acc_i, x)| *acc_i += x);
+        }
+        self.absorbed += chunk.len();
+
+        Ok(())
+    }
+
+    /// Returns the commitment, once the whole witness has been absorbed.
+    pub fn finalize(self) -> Result<Commitment<R>, CommitmentError> {
+        if self.absorbed != self.matrix.width() {
+            return Err(CommitmentError::WrongWitnessLength(
+                self.absorbed,
+                self.matrix.width(),
+            ));
+        }
+
+        Ok(Commitment::from_vec_raw(self.acc))
+    }
+}
+
+/// Commits to a witness given as an iterator, buffering at most `chunk_size` entries.
+pub fn commit_iter<R: Ring, M: AjtaiMatrix<R>>(
+    matrix: &M,
+    witness: impl IntoIterator<Item = R>,
+    chunk_size: usize,
+) -> Result<Commitment<R>, CommitmentError> {
+    let chunk_size = chunk_size.max(1);
+    let mut committer = StreamingCommitter::new(matrix);
+    let mut chunk = Vec::with_capacity(chunk_size);
+
+    for x in witness {
+        chunk.push(x);
+        if chunk.len() == chunk_size {
+            committer.absorb(&chunk)?;
+            chunk.clear();
+        }
+    }
+    committer.absorb(&chunk)?;
+
+    committer.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::test_rng;
+    use cyclotomic_rings::rings::GoldilocksRingNTT;
+
+    use super::*;
+
+    type R = GoldilocksRingNTT;
+
+    const KAPPA: usize = 4;
+    const WIDTH: usize = 3 * COLUMN_BLOCK + 17;
+
+    fn witness() -> Vec<R> {
+        let mut rng = test_rng();
+        (0..WIDTH).map(|_| R::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_streaming_commit() {
+        let scheme = AjtaiCommitmentScheme::<R>::rand(KAPPA, WIDTH, &mut test_rng());
+        let f = witness();
+        let expected = scheme.commit(&f).unwrap();
+
+        for chunk_size in [1, 100, COLUMN_BLOCK, 2 * COLUMN_BLOCK + 5, WIDTH] {
+            let mut committer = StreamingCommitter::new(&scheme);
+            for chunk in f.chunks(chunk_size) {
+                committer.absorb(chunk).unwrap();
+            }
+            assert_eq!(committer.finalize().unwrap(), expected);
+        }
+
+        assert_eq!(commit_iter(&scheme, f, 1000).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_seeded_matrix() {
+        let matrix = SeededAjtaiMatrix::<R>::new([7; 32], KAPPA, WIDTH);
+        let scheme = matrix.materialize();
+        assert_eq!((scheme.kappa(), scheme.width()), (KAPPA, WIDTH));
+
+        let f = witness();
+        let expected = scheme.commit(&f).unwrap();
+
+        assert_eq!(commit_iter(&matrix, f.clone(), 100).unwrap(), expected);
+        assert_eq!(commit_iter(&matrix, f, COLUMN_BLOCK).unwrap(), expected);
+
+        // A product with a range of columns only depends on these columns
+        let cols = witness();
+        let (start, end) = (COLUMN_BLOCK - 3, COLUMN_BLOCK + 5);
+        let mut acc = vec![R::zero(); KAPPA];
+        matrix.mul_add_columns(start, &cols[start..end], &mut acc);
+        let mut padded = vec![R::zero(); WIDTH];
+        padded[start..end].copy_from_slice(&cols[start..end]);
+        assert_eq!(Commitment::from(acc), scheme.commit(&padded).unwrap());
+
+        assert_ne!(
+            SeededAjtaiMatrix::<R>::new([8; 32], KAPPA, WIDTH)
+                .materialize()
+                .commit(&witness())
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_streaming_commit_wrong_length() {
+        let scheme = AjtaiCommitmentScheme::<R>::rand(KAPPA, WIDTH, &mut test_rng());
+        let f = witness();
+
+        let mut committer = StreamingCommitter::new(&scheme);
+        committer.absorb(&f[..10]).unwrap();
+        assert!(matches!(
+            committer.finalize(),
+            Err(CommitmentError::WrongWitnessLength(10, WIDTH))
+        ));
+
+        let mut committer = StreamingCommitter::new(&scheme);
+        committer.absorb(&f).unwrap();
+        assert!(matches!(
+            committer.absorb(&f[..1]),
+            Err(CommitmentError::WrongWitnessLength(_, WIDTH))
+        ));
+    }
+}
@@ -3,11 +3,20 @@
 //! Decomposition parameters dictate how higher-bound witness are
 //! decomposed into lower-bound witnesses.
 
+use ark_ff::{Field, PrimeField};
 use ark_std::fmt::Display;
+use cyclotomic_rings::rings::SuitableRing;
+use num_bigint::BigUint;
+use stark_rings::PolyRing;
+use thiserror::Error;
 
 /// Decomposition parameters.
 /// Convenient to enforce them compile-time.
 /// Contains both gadget matrix data and Latticefold decomposition step data.
+///
+/// This is a zero-cost wrapper around [`DecompositionParamData`]: every prover and verifier
+/// taking a `P: DecompositionParams` forwards [`DecompositionParams::DATA`] to its
+/// `_with_params` counterpart. The consts are trusted and not validated.
 pub trait DecompositionParams: Clone {
     /// The MSIS bound.
     const B: u128;
@@ -17,23 +26,118 @@ pub trait DecompositionParams: Clone {
     const B_SMALL: usize;
     /// K = log_b B.
     const K: usize;
+    /// The value-level representation of the parameters.
+    const DATA: DecompositionParamData =
+        DecompositionParamData::new_unchecked(Self::B, Self::L, Self::B_SMALL, Self::K);
 }
 
 impl<P: DecompositionParams> From<P> for DecompositionParamData {
     fn from(_: P) -> Self {
-        {
-            Self { b: P::B, l: P::L }
-        }
+        P::DATA
     }
 }
 
-// Nice representation of parameters for printing out in benchmarks.
-#[derive(Clone, Copy)]
+/// Errors that can arise when validating decomposition parameters.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DecompositionParamsError {
+    /// One of the bases is smaller than 2, or `L` or `K` is zero.
+    #[error("degenerate decomposition parameters: B = {0}, L = {1}, b = {2}, k = {3}")]
+    Degenerate(u128, usize, usize, usize),
+    /// `K` is not $\lceil \log_b B \rceil$.
+    #[error("k = {0} is not log_b B for b = {1} and B = {2}")]
+    InvalidK(usize, usize, u128),
+    /// $B^L \le q / 2$, so the gadget decomposition cannot represent every ring element.
+    #[error("B^L = {0}^{1} does not exceed half of the ring modulus")]
+    GadgetTooSmall(u128, usize),
+}
+
+/// Value-level decomposition parameters, chosen at runtime.
+///
+/// Also provides a nice representation of parameters for printing out in benchmarks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DecompositionParamData {
     // The MSIS bound.
     b: u128,
     // The ring modulus should be < B^L.
     l: usize,
+    // The small b from the decomposition step of LF.
+    b_small: usize,
+    // K = log_b B.
+    k: usize,
+}
+
+impl DecompositionParamData {
+    /// Create validated decomposition parameters for the ring `R`.
+    ///
+    /// Checks that $K = \lceil \log_b B \rceil$ and that $B^L > q / 2$, where $q$ is the
+    /// modulus of the base prime field of `R`.
+    pub fn new<R: SuitableRing>(
+        b: u128,
+        l: usize,
+        b_small: usize,
+        k: usize,
+    ) -> Result<Self, DecompositionParamsError> {
+        if b < 2 || b_small < 2 || l == 0 || k == 0 {
+            return Err(DecompositionParamsError::Degenerate(b, l, b_small, k));
+        }
+
+        let (b_big, b_small_big) = (BigUint::from(b), BigUint::from(b_small));
+        if b_small_big.pow(k as u32 - 1) >= b_big || b_small_big.pow(k as u32) < b_big {
+            return Err(DecompositionParamsError::InvalidK(k, b_small, b));
+        }
+
+        let modulus: BigUint =
+            <<<R as PolyRing>::BaseRing as Field>::BasePrimeField as PrimeField>::MODULUS.into();
+        if b_big.pow(l as u32) <= modulus / 2u32 {
+            return Err(DecompositionParamsError::GadgetTooSmall(b, l));
+        }
+
+        Ok(Self::new_unchecked(b, l, b_small, k))
+    }
+
+    /// Create decomposition parameters without validating them.
+    pub const fn new_unchecked(b: u128, l: usize, b_small: usize, k: usize) -> Self {
+        Self { b, l, b_small, k }
+    }
+
+    /// The MSIS bound.
+    pub const fn b(&self) -> u128 {
+        self.b
+    }
+
+    /// The ring modulus should be < B^L.
+    pub const fn l(&self) -> usize {
+        self.l
+    }
+
+    /// The small b from the decomposition step of LF.
+    pub const fn b_small(&self) -> usize {
+        self.b_small
+    }
+
+    /// K = log_b B.
+    pub const fn k(&self) -> usize {
+        self.k
+    }
+}
+
+/// The smallest `l` such that $b^l > q / 2$, i.e. the number of balanced digits in base `b` of
+/// an element of the base prime field of `R`.
+///
+/// `b` must be at least 2.
+pub fn gadget_length<R: SuitableRing>(b: u128) -> usize {
+    let modulus_half: BigUint =
+        BigUint::from(<<R as PolyRing>::BaseRing as Field>::BasePrimeField::MODULUS) / 2u32;
+    debug_assert!(b >= 2, "the gadget base is at least 2");
+    let b = BigUint::from(b);
+
+    let mut power = b.clone();
+    let mut l = 1;
+    while power <= modulus_half {
+        power *= &b;
+        l += 1;
+    }
+    l
 }
 
 impl Display for DecompositionParamData {
@@ -112,3 +216,72 @@ pub mod test_params {
         const K: usize = 10;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cyclotomic_rings::rings::{BabyBearRingNTT, GoldilocksRingNTT, StarkRingNTT};
+
+    use super::{
+        test_params::{BabyBearDP, GoldilocksDP, StarkDP, StarkFoldingDP},
+        *,
+    };
+
+    fn validate<R: SuitableRing, P: DecompositionParams>() -> DecompositionParamData {
+        DecompositionParamData::new::<R>(P::B, P::L, P::B_SMALL, P::K).unwrap()
+    }
+
+    #[test]
+    fn test_const_params_are_valid() {
+        assert_eq!(
+            validate::<GoldilocksRingNTT, GoldilocksDP>(),
+            GoldilocksDP::DATA
+        );
+        assert_eq!(validate::<BabyBearRingNTT, BabyBearDP>(), BabyBearDP::DATA);
+        assert_eq!(validate::<StarkRingNTT, StarkDP>(), StarkDP::DATA);
+        assert_eq!(
+            validate::<StarkRingNTT, StarkFoldingDP>(),
+            StarkFoldingDP::DATA
+        );
+        assert_eq!(
+            DecompositionParamData::from(GoldilocksDP),
+            GoldilocksDP::DATA
+        );
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        type R = GoldilocksRingNTT;
+
+        assert_eq!(
+            DecompositionParamData::new::<R>(1 << 15, 5, 1, 15),
+            Err(DecompositionParamsError::Degenerate(1 << 15, 5, 1, 15))
+        );
+        assert_eq!(
+            DecompositionParamData::new::<R>(1 << 15, 5, 2, 14),
+            Err(DecompositionParamsError::InvalidK(14, 2, 1 << 15))
+        );
+        assert_eq!(
+            DecompositionParamData::new::<R>(1 << 15, 5, 2, 16),
+            Err(DecompositionParamsError::InvalidK(16, 2, 1 << 15))
+        );
+        // 2^60 does not exceed half of the Goldilocks prime
+        assert_eq!(
+            DecompositionParamData::new::<R>(1 << 15, 4, 2, 15),
+            Err(DecompositionParamsError::GadgetTooSmall(1 << 15, 4))
+        );
+        // Non-power bounds round K up
+        assert!(DecompositionParamData::new::<R>(1000, 7, 10, 3).is_ok());
+    }
+
+    #[test]
+    fn test_gadget_length() {
+        type R = GoldilocksRingNTT;
+
+        // 2^60 does not exceed half of the Goldilocks prime, 2^75 does
+        assert_eq!(gadget_length::<R>(1 << 15), 5);
+        assert_eq!(gadget_length::<R>(1 << 16), 4);
+        assert!(
+            DecompositionParamData::new::<R>(1 << 15, gadget_length::<R>(1 << 15), 2, 15).is_ok()
+        );
+    }
+}
@@ -1,7 +1,9 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(clippy::type_complexity)]
 #![allow(non_snake_case)]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+// Memory-mapping a key file is the only unsafe operation, see `MappedCommitmentKey::open`.
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 
 #[macro_use]
 extern crate ark_std;
@@ -12,6 +14,7 @@ pub mod arith;
 pub mod commitment;
 pub mod decomposition_parameters;
 pub mod nifs;
+pub mod pcs;
 pub mod transcript;
 pub mod utils;
 
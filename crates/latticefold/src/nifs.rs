@@ -6,14 +6,25 @@ use ark_ff::{Field, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{marker::PhantomData, vec::Vec};
 use cyclotomic_rings::rings::SuitableRing;
+use num_bigint::BigUint;
+use rand::RngCore;
 use stark_rings::OverField;
 
-use self::{decomposition::*, error::LatticefoldError, folding::*, linearization::*};
+use self::{
+    decomposition::*,
+    error::{FoldingError, LatticefoldError, LinearizationError, ProtocolStep},
+    folding::*,
+    linearization::*,
+};
 use crate::{
     arith::{error::CSError, Witness, CCCS, CCS, LCCCS},
     commitment::AjtaiCommitmentScheme,
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::{DecompositionParamData, DecompositionParams},
+    pcs::PcsRing,
     transcript::{Transcript, TranscriptWithShortChallenges},
+    utils::sumcheck::zk::{
+        MaskClaim, MaskCommitmentScheme, MaskEvalProof, MaskOpening, SumcheckMask,
+    },
 };
 
 pub mod decomposition;
@@ -25,6 +36,9 @@ pub mod linearization;
 mod tests;
 
 /// `NTT` is a cyclotomic ring in the NTT form.
+///
+/// The sumcheck messages and the evaluation claims of the proof depend on the witnesses, so
+/// the proof is not zero-knowledge. See [`MaskedLFProof`] for a proof with masked sumchecks.
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct LFProof<NTT: OverField> {
     pub linearization_proof: LinearizationProof<NTT>,
@@ -33,6 +47,62 @@ pub struct LFProof<NTT: OverField> {
     pub folding_proof: FoldingProof<NTT>,
 }
 
+impl<NTT: SuitableRing> LFProof<NTT> {
+    /// Returns the proof with its sumcheck proofs in the compressed format, see
+    /// [`Proof::compress`](crate::utils::sumcheck::Proof::compress).
+    ///
+    /// The compressed proof is smaller by one ring element per sumcheck round and verifies
+    /// exactly when the original proof does.
+    pub fn compress<P: DecompositionParams>(
+        &self,
+        ccs: &CCS<NTT>,
+    ) -> Result<Self, LatticefoldError<NTT>> {
+        self.compress_with_params(&P::DATA, ccs)
+    }
+
+    /// [`LFProof::compress`] with runtime decomposition parameters.
+    ///
+    /// The degrees of the sumchecks are derived from `ccs` and `params` as the verifiers do.
+    pub fn compress_with_params(
+        &self,
+        params: &DecompositionParamData,
+        ccs: &CCS<NTT>,
+    ) -> Result<Self, LatticefoldError<NTT>> {
+        let mut proof = self.clone();
+        proof.linearization_proof.linearization_sumcheck = self
+            .linearization_proof
+            .linearization_sumcheck
+            .compress(linearization::utils::sumcheck_degree(ccs)?);
+        proof.folding_proof.pointshift_sumcheck_proof = self
+            .folding_proof
+            .pointshift_sumcheck_proof
+            .compress(folding::sumcheck_degree(params, ccs.s)?);
+        Ok(proof)
+    }
+}
+
+/// A proof of [`NIFSProver::prove_masked`], whose linearization and folding sumchecks are
+/// masked.
+///
+/// The masks are committed with a [`MaskCommitmentScheme`] and their evaluations are proved in
+/// zero knowledge, so the masking hides the round messages of the two sumchecks, see
+/// [`crate::utils::sumcheck::zk`]. The proof is still not zero-knowledge: the decomposed and
+/// folded witnesses are committed with the non-hiding [`AjtaiCommitmentScheme`], and the
+/// evaluations `v`, `u`, `theta_s` and `eta_s` are sent in the clear.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MaskedLFProof<NTT: OverField> {
+    /// The proof, holding the masked sumchecks
+    pub proof: LFProof<NTT>,
+    /// The mask of the linearization sumcheck
+    pub linearization_mask: SumcheckMask<NTT>,
+    /// The proof of the evaluation of the linearization mask
+    pub linearization_mask_proof: MaskEvalProof<NTT>,
+    /// The mask of the folding sumcheck
+    pub folding_mask: SumcheckMask<NTT>,
+    /// The proof of the evaluation of the folding mask
+    pub folding_mask_proof: MaskEvalProof<NTT>,
+}
+
 /// `NTT` is a suitable cyclotomic ring.
 /// `P` is the decomposition parameters.
 /// `T` is the FS-transform transcript.
@@ -54,16 +124,169 @@ impl<NTT: SuitableRing, P: DecompositionParams, T: TranscriptWithShortChallenges
         ccs: &CCS<NTT>,
         scheme: &AjtaiCommitmentScheme<NTT>,
     ) -> Result<(LCCCS<NTT>, Witness<NTT>, LFProof<NTT>), LatticefoldError<NTT>> {
-        sanity_check::<NTT, P>(ccs)?;
+        Self::prove_with_params(&P::DATA, acc, w_acc, cm_i, w_i, transcript, ccs, scheme)
+    }
+}
+
+impl<NTT: PcsRing, P: DecompositionParams, T: TranscriptWithShortChallenges<NTT>>
+    NIFSProver<NTT, P, T>
+{
+    /// Same as [`NIFSProver::prove`], with the linearization and folding sumchecks masked, see
+    /// [`MaskedLFProof`].
+    ///
+    /// The masks of the sumchecks are committed with `mask_scheme`, which must hold masking
+    /// polynomials of the degrees of both sumchecks over `ccs.s` variables.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_masked<Rng: rand::Rng + ?Sized>(
+        acc: &LCCCS<NTT>,
+        w_acc: &Witness<NTT>,
+        cm_i: &CCCS<NTT>,
+        w_i: &Witness<NTT>,
+        transcript: &mut (impl TranscriptWithShortChallenges<NTT> + Clone),
+        ccs: &CCS<NTT>,
+        scheme: &AjtaiCommitmentScheme<NTT>,
+        mask_scheme: &MaskCommitmentScheme<NTT>,
+        rng: &mut Rng,
+    ) -> Result<(LCCCS<NTT>, Witness<NTT>, MaskedLFProof<NTT>), LatticefoldError<NTT>> {
+        Self::prove_masked_with_params(
+            &P::DATA,
+            acc,
+            w_acc,
+            cm_i,
+            w_i,
+            transcript,
+            ccs,
+            scheme,
+            mask_scheme,
+            rng,
+        )
+    }
+}
+
+impl<NTT: PcsRing, P, T: TranscriptWithShortChallenges<NTT>> NIFSProver<NTT, P, T> {
+    /// Same as [`NIFSProver::prove_masked`], with decomposition parameters chosen at runtime.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_masked_with_params<Rng: rand::Rng + ?Sized>(
+        params: &DecompositionParamData,
+        acc: &LCCCS<NTT>,
+        w_acc: &Witness<NTT>,
+        cm_i: &CCCS<NTT>,
+        w_i: &Witness<NTT>,
+        transcript: &mut (impl TranscriptWithShortChallenges<NTT> + Clone),
+        ccs: &CCS<NTT>,
+        scheme: &AjtaiCommitmentScheme<NTT>,
+        mask_scheme: &MaskCommitmentScheme<NTT>,
+        rng: &mut Rng,
+    ) -> Result<(LCCCS<NTT>, Witness<NTT>, MaskedLFProof<NTT>), LatticefoldError<NTT>> {
+        let (lcccs, wit, proof, masks) = Self::prove_with_masking(
+            params,
+            acc,
+            w_acc,
+            cm_i,
+            w_i,
+            transcript,
+            ccs,
+            scheme,
+            Some((mask_scheme, &mut *rng)),
+        )?;
+        let [linearization, folding] = masks.expect("the sumchecks are masked");
+        let (linearization_mask, linearization_claim, linearization_opening) = linearization;
+        let (folding_mask, folding_claim, folding_opening) = folding;
+        let linearization_mask_proof = linearization_claim
+            .prove(mask_scheme, &linearization_opening, transcript, rng)
+            .map_err(LinearizationError::from)?;
+        let folding_mask_proof = folding_claim
+            .prove(mask_scheme, &folding_opening, transcript, rng)
+            .map_err(FoldingError::from)?;
+
+        Ok((
+            lcccs,
+            wit,
+            MaskedLFProof {
+                proof,
+                linearization_mask,
+                linearization_mask_proof,
+                folding_mask,
+                folding_mask_proof,
+            },
+        ))
+    }
+}
+
+impl<NTT: SuitableRing, P, T: TranscriptWithShortChallenges<NTT>> NIFSProver<NTT, P, T> {
+    /// Same as [`NIFSProver::prove`], with decomposition parameters chosen at runtime.
+    ///
+    /// `params` is used in place of the parameters of `P`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_params(
+        params: &DecompositionParamData,
+        acc: &LCCCS<NTT>,
+        w_acc: &Witness<NTT>,
+        cm_i: &CCCS<NTT>,
+        w_i: &Witness<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        scheme: &AjtaiCommitmentScheme<NTT>,
+    ) -> Result<(LCCCS<NTT>, Witness<NTT>, LFProof<NTT>), LatticefoldError<NTT>> {
+        let (lcccs, wit, proof, _) = Self::prove_with_masking(
+            params,
+            acc,
+            w_acc,
+            cm_i,
+            w_i,
+            transcript,
+            ccs,
+            scheme,
+            None::<(_, &mut dyn RngCore)>,
+        )?;
+
+        Ok((lcccs, wit, proof))
+    }
+
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn prove_with_masking<Rng: rand::Rng + ?Sized>(
+        params: &DecompositionParamData,
+        acc: &LCCCS<NTT>,
+        w_acc: &Witness<NTT>,
+        cm_i: &CCCS<NTT>,
+        w_i: &Witness<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        scheme: &AjtaiCommitmentScheme<NTT>,
+        mut masking: Option<(&MaskCommitmentScheme<NTT>, &mut Rng)>,
+    ) -> Result<
+        (
+            LCCCS<NTT>,
+            Witness<NTT>,
+            LFProof<NTT>,
+            Option<[(SumcheckMask<NTT>, MaskClaim<NTT>, MaskOpening<NTT>); 2]>,
+        ),
+        LatticefoldError<NTT>,
+    > {
+        sanity_check::<NTT>(params, ccs)?;
+        check_norm(ProtocolStep::Linearization, w_acc, params.b())?;
+        check_norm(ProtocolStep::Linearization, w_i, params.b())?;
 
         absorb_public_input::<NTT>(acc, cm_i, transcript);
 
-        let (linearized_cm_i, linearization_proof) =
-            LFLinearizationProver::<_, T>::prove(cm_i, w_i, transcript, ccs)?;
+        let (linearized_cm_i, linearization_proof, linearization_mask) =
+            LFLinearizationProver::<_, T>::prove_with_masking(
+                cm_i,
+                w_i,
+                transcript,
+                ccs,
+                masking
+                    .as_mut()
+                    .map(|(mask_scheme, rng)| (*mask_scheme, &mut **rng)),
+            )?;
+
         let (mz_mles_l, decomposed_lcccs_l, decomposed_wit_l, decomposition_proof_l) =
-            LFDecompositionProver::<_, T>::prove::<P>(acc, w_acc, transcript, ccs, scheme)?;
+            LFDecompositionProver::<_, T>::prove_with_params(
+                params, acc, w_acc, transcript, ccs, scheme,
+            )?;
         let (mz_mles_r, decomposed_lcccs_r, decomposed_wit_r, decomposition_proof_r) =
-            LFDecompositionProver::<_, T>::prove::<P>(
+            LFDecompositionProver::<_, T>::prove_with_params(
+                params,
                 &linearized_cm_i,
                 w_i,
                 transcript,
@@ -85,9 +308,13 @@ impl<NTT: SuitableRing, P: DecompositionParams, T: TranscriptWithShortChallenges
             mz_mles.append(&mut mz_mles_r);
             (mz_mles, lcccs, wit_s)
         };
+        check_decomposed_norms(params, &wit_s)?;
 
-        let (folded_lcccs, wit, folding_proof) =
-            LFFoldingProver::<_, T>::prove::<P>(&lcccs, wit_s, transcript, ccs, &mz_mles)?;
+        let (folded_lcccs, wit, folding_proof, folding_mask) =
+            LFFoldingProver::<_, T>::prove_with_masking(
+                params, &lcccs, wit_s, transcript, ccs, &mz_mles, masking,
+            )?;
+        check_folded_norm(params, &wit)?;
 
         Ok((
             folded_lcccs,
@@ -98,6 +325,7 @@ impl<NTT: SuitableRing, P: DecompositionParams, T: TranscriptWithShortChallenges
                 decomposition_proof_r,
                 folding_proof,
             },
+            linearization_mask.zip(folding_mask).map(|(l, f)| [l, f]),
         ))
     }
 }
@@ -121,23 +349,107 @@ impl<NTT: SuitableRing, P: DecompositionParams, T: TranscriptWithShortChallenges
         transcript: &mut impl TranscriptWithShortChallenges<NTT>,
         ccs: &CCS<NTT>,
     ) -> Result<LCCCS<NTT>, LatticefoldError<NTT>> {
-        sanity_check::<NTT, P>(ccs)?;
+        Self::verify_with_params(&P::DATA, acc, cm_i, proof, transcript, ccs)
+    }
+}
 
-        absorb_public_input::<NTT>(acc, cm_i, transcript);
+impl<NTT: PcsRing, P: DecompositionParams, T: TranscriptWithShortChallenges<NTT>>
+    NIFSVerifier<NTT, P, T>
+{
+    /// Verifies a proof of [`NIFSProver::prove_masked`], whose masks are committed with
+    /// `mask_scheme`.
+    pub fn verify_masked(
+        acc: &LCCCS<NTT>,
+        cm_i: &CCCS<NTT>,
+        proof: &MaskedLFProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        mask_scheme: &MaskCommitmentScheme<NTT>,
+    ) -> Result<LCCCS<NTT>, LatticefoldError<NTT>> {
+        Self::verify_masked_with_params(&P::DATA, acc, cm_i, proof, transcript, ccs, mask_scheme)
+    }
+}
 
-        let linearized_cm_i = LFLinearizationVerifier::<_, T>::verify(
+impl<NTT: PcsRing, P, T: TranscriptWithShortChallenges<NTT>> NIFSVerifier<NTT, P, T> {
+    /// Same as [`NIFSVerifier::verify_masked`], with decomposition parameters chosen at runtime.
+    pub fn verify_masked_with_params(
+        params: &DecompositionParamData,
+        acc: &LCCCS<NTT>,
+        cm_i: &CCCS<NTT>,
+        proof: &MaskedLFProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        mask_scheme: &MaskCommitmentScheme<NTT>,
+    ) -> Result<LCCCS<NTT>, LatticefoldError<NTT>> {
+        let (lcccs, mask_claims) = Self::verify_with_masking(
+            params,
+            acc,
             cm_i,
-            &proof.linearization_proof,
+            &proof.proof,
+            Some([&proof.linearization_mask, &proof.folding_mask]),
             transcript,
             ccs,
         )?;
-        let decomposed_acc = LFDecompositionVerifier::<_, T>::verify::<P>(
+        let [linearization_claim, folding_claim] = mask_claims.expect("the sumchecks are masked");
+        linearization_claim
+            .verify(mask_scheme, &proof.linearization_mask_proof, transcript)
+            .map_err(LinearizationError::from)?;
+        folding_claim
+            .verify(mask_scheme, &proof.folding_mask_proof, transcript)
+            .map_err(FoldingError::from)?;
+
+        Ok(lcccs)
+    }
+}
+
+impl<NTT: SuitableRing, P, T: TranscriptWithShortChallenges<NTT>> NIFSVerifier<NTT, P, T> {
+    /// Same as [`NIFSVerifier::verify`], with decomposition parameters chosen at runtime.
+    ///
+    /// `params` is used in place of the parameters of `P`.
+    pub fn verify_with_params(
+        params: &DecompositionParamData,
+        acc: &LCCCS<NTT>,
+        cm_i: &CCCS<NTT>,
+        proof: &LFProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+    ) -> Result<LCCCS<NTT>, LatticefoldError<NTT>> {
+        let (lcccs, _) =
+            Self::verify_with_masking(params, acc, cm_i, proof, None, transcript, ccs)?;
+
+        Ok(lcccs)
+    }
+
+    fn verify_with_masking(
+        params: &DecompositionParamData,
+        acc: &LCCCS<NTT>,
+        cm_i: &CCCS<NTT>,
+        proof: &LFProof<NTT>,
+        masks: Option<[&SumcheckMask<NTT>; 2]>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+    ) -> Result<(LCCCS<NTT>, Option<[MaskClaim<NTT>; 2]>), LatticefoldError<NTT>> {
+        sanity_check::<NTT>(params, ccs)?;
+
+        absorb_public_input::<NTT>(acc, cm_i, transcript);
+
+        let (linearized_cm_i, linearization_claim) =
+            LFLinearizationVerifier::<_, T>::verify_with_masking(
+                cm_i,
+                &proof.linearization_proof,
+                masks.map(|[mask, _]| mask),
+                transcript,
+                ccs,
+            )?;
+        let decomposed_acc = LFDecompositionVerifier::<_, T>::verify_with_params(
+            params,
             acc,
             &proof.decomposition_proof_l,
             transcript,
             ccs,
         )?;
-        let decomposed_cm_i = LFDecompositionVerifier::<_, T>::verify::<P>(
+        let decomposed_cm_i = LFDecompositionVerifier::<_, T>::verify_with_params(
+            params,
             &linearized_cm_i,
             &proof.decomposition_proof_r,
             transcript,
@@ -153,25 +465,65 @@ impl<NTT: SuitableRing, P: DecompositionParams, T: TranscriptWithShortChallenges
             decomposed_acc
         };
 
-        Ok(LFFoldingVerifier::<NTT, T>::verify::<P>(
+        let (lcccs, folding_claim) = LFFoldingVerifier::<NTT, T>::verify_with_masking(
+            params,
             &lcccs_s,
             &proof.folding_proof,
+            masks.map(|[_, mask]| mask),
             transcript,
             ccs,
-        )?)
+        )?;
+
+        Ok((
+            lcccs,
+            linearization_claim.zip(folding_claim).map(|(l, f)| [l, f]),
+        ))
     }
 }
 
-fn sanity_check<NTT: SuitableRing, DP: DecompositionParams>(
+fn sanity_check<NTT: SuitableRing>(
+    params: &DecompositionParamData,
     ccs: &CCS<NTT>,
 ) -> Result<(), LatticefoldError<NTT>> {
-    if ccs.m != usize::max((ccs.n - ccs.l - 1) * DP::L, ccs.m).next_power_of_two() {
-        return Err(CSError::InvalidSizeBounds(ccs.m, ccs.n, DP::L).into());
+    if ccs.m != usize::max((ccs.n - ccs.l - 1) * params.l(), ccs.m).next_power_of_two() {
+        return Err(CSError::InvalidSizeBounds(ccs.m, ccs.n, params.l()).into());
     }
 
     Ok(())
 }
 
+/// Checks that the infinity norm of `wit` is below `bound` at the end of `step`.
+fn check_norm<NTT: SuitableRing>(
+    step: ProtocolStep,
+    wit: &Witness<NTT>,
+    bound: u128,
+) -> Result<(), LatticefoldError<NTT>> {
+    let norm = wit.linf_norm();
+    if norm >= BigUint::from(bound) {
+        return Err(LatticefoldError::NormBoundExceeded(step, norm, bound));
+    }
+
+    Ok(())
+}
+
+/// Checks that the witnesses output by the decomposition are below `b`.
+fn check_decomposed_norms<NTT: SuitableRing>(
+    params: &DecompositionParamData,
+    wit_s: &[Witness<NTT>],
+) -> Result<(), LatticefoldError<NTT>> {
+    wit_s
+        .iter()
+        .try_for_each(|wit| check_norm(ProtocolStep::Decomposition, wit, params.b_small() as u128))
+}
+
+/// Checks that the witness output by the folding is below `B`, so that it can be folded again.
+fn check_folded_norm<NTT: SuitableRing>(
+    params: &DecompositionParamData,
+    wit: &Witness<NTT>,
+) -> Result<(), LatticefoldError<NTT>> {
+    check_norm(ProtocolStep::Folding, wit, params.b())
+}
+
 fn absorb_public_input<NTT: SuitableRing>(
     acc: &LCCCS<NTT>,
     cm_i: &CCCS<NTT>,
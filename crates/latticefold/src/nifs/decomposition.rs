@@ -14,7 +14,7 @@ use crate::{
     arith::{error::CSError, utils::mat_vec_mul, Witness, CCS, LCCCS},
     ark_base::*,
     commitment::{AjtaiCommitmentScheme, Commitment, CommitmentError},
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::DecompositionParamData,
     nifs::error::DecompositionError,
     transcript::Transcript,
     utils::mle_helpers::{evaluate_mles, to_mles_err},
@@ -30,7 +30,8 @@ mod utils;
 impl<NTT: SuitableRing, T: Transcript<NTT>> DecompositionProver<NTT, T>
     for LFDecompositionProver<NTT, T>
 {
-    fn prove<P: DecompositionParams>(
+    fn prove_with_params(
+        params: &DecompositionParamData,
         cm_i: &LCCCS<NTT>,
         wit: &Witness<NTT>,
         transcript: &mut impl Transcript<NTT>,
@@ -45,14 +46,14 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> DecompositionProver<NTT, T>
         ),
         DecompositionError,
     > {
-        sanity_check::<NTT, P>(ccs)?;
+        sanity_check::<NTT>(params, ccs)?;
         let log_m = ccs.s;
 
-        let wit_s: Vec<Witness<NTT>> = Self::decompose_witness::<P>(wit);
+        let wit_s: Vec<Witness<NTT>> = Self::decompose_witness(params, wit);
 
-        let x_s = Self::compute_x_s::<P>(cm_i.x_w.clone(), cm_i.h);
+        let x_s = Self::compute_x_s(params, cm_i.x_w.clone(), cm_i.h);
 
-        let y_s: Vec<Commitment<NTT>> = Self::commit_witnesses::<P>(&wit_s, scheme, cm_i)?;
+        let y_s: Vec<Commitment<NTT>> = Self::commit_witnesses(params, &wit_s, scheme, cm_i)?;
 
         let v_s: Vec<Vec<NTT>> = Self::compute_v_s(&wit_s, &cm_i.r)?;
 
@@ -60,7 +61,7 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> DecompositionProver<NTT, T>
 
         let u_s = Self::compute_u_s(&mz_mles, &cm_i.r)?;
 
-        let mut lcccs_s = Vec::with_capacity(P::K);
+        let mut lcccs_s = Vec::with_capacity(params.k());
 
         for (((x, y), u), v) in x_s.iter().zip(&y_s).zip(&u_s).zip(&v_s) {
             transcript.absorb_slice(x);
@@ -91,13 +92,14 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> DecompositionProver<NTT, T>
 impl<NTT: OverField, T: Transcript<NTT>> DecompositionVerifier<NTT, T>
     for LFDecompositionVerifier<NTT, T>
 {
-    fn verify<P: DecompositionParams>(
+    fn verify_with_params(
+        params: &DecompositionParamData,
         cm_i: &LCCCS<NTT>,
         proof: &DecompositionProof<NTT>,
         transcript: &mut impl Transcript<NTT>,
         _ccs: &CCS<NTT>,
     ) -> Result<Vec<LCCCS<NTT>>, DecompositionError> {
-        let mut lcccs_s = Vec::<LCCCS<NTT>>::with_capacity(P::K);
+        let mut lcccs_s = Vec::<LCCCS<NTT>>::with_capacity(params.k());
 
         for (((x, y), u), v) in proof
             .x_s
@@ -125,7 +127,7 @@ impl<NTT: OverField, T: Transcript<NTT>> DecompositionVerifier<NTT, T>
             });
         }
 
-        let b_s: Vec<_> = Self::calculate_b_s::<P>();
+        let b_s: Vec<_> = Self::calculate_b_s(params);
 
         let y = Self::recompose_commitment(&proof.y_s, &b_s)?;
         if y != cm_i.cm {
@@ -156,34 +158,35 @@ impl<NTT: OverField, T: Transcript<NTT>> DecompositionVerifier<NTT, T>
 }
 
 impl<NTT: SuitableRing, T: Transcript<NTT>> LFDecompositionProver<NTT, T> {
-    /// Decomposes a witness `wit` into `P::K` vectors norm `< P::B_SMALL` such that
-    /// $$ \text{wit} = \sum\limits_{i=0}^{\text{P::K} - 1} \text{P::B\\_SMALL}^i \cdot \text{wit}_i.$$
+    /// Decomposes a witness `wit` into `k` vectors norm `< b` such that
+    /// $$ \text{wit} = \sum\limits_{i=0}^{k - 1} b^i \cdot \text{wit}_i.$$
     ///
-    fn decompose_witness<P: DecompositionParams>(wit: &Witness<NTT>) -> Vec<Witness<NTT>> {
-        let f_s = decompose_B_vec_into_k_vec::<NTT, P>(&wit.f_coeff);
+    fn decompose_witness(params: &DecompositionParamData, wit: &Witness<NTT>) -> Vec<Witness<NTT>> {
+        let f_s = decompose_B_vec_into_k_vec::<NTT>(params, &wit.f_coeff);
         cfg_into_iter!(f_s)
-            .map(|f| Witness::from_f_coeff::<P>(f))
+            .map(|f| Witness::from_f_coeff_with_params(params, f))
             .collect()
     }
 
     /// Takes the concatenation `x_w || h`, performs gadget decomposition of it,
-    /// decomposes the resulting `P::B`-short vector into `P::K` `P::B_SMALL`-vectors
-    /// and gadget-composes each of the vectors back to obtain `P::K` vectors in their NTT form.
-    fn compute_x_s<P: DecompositionParams>(mut x_w: Vec<NTT>, h: NTT) -> Vec<Vec<NTT>> {
+    /// decomposes the resulting `B`-short vector into `k` `b`-vectors
+    /// and gadget-composes each of the vectors back to obtain `k` vectors in their NTT form.
+    fn compute_x_s(params: &DecompositionParamData, mut x_w: Vec<NTT>, h: NTT) -> Vec<Vec<NTT>> {
         x_w.push(h);
-        decompose_big_vec_into_k_vec_and_compose_back::<NTT, P>(x_w)
+        decompose_big_vec_into_k_vec_and_compose_back::<NTT>(params, x_w)
     }
 
     /// Ajtai commits to witnesses `wit_s` using Ajtai commitment scheme `scheme`.
-    fn commit_witnesses<P: DecompositionParams>(
+    fn commit_witnesses(
+        params: &DecompositionParamData,
         wit_s: &[Witness<NTT>],
         scheme: &AjtaiCommitmentScheme<NTT>,
         cm_i: &LCCCS<NTT>,
     ) -> Result<Vec<Commitment<NTT>>, CommitmentError> {
-        let b = NTT::from(P::B_SMALL as u128);
+        let b = NTT::from(params.b_small() as u128);
 
         let commitments_k1: Vec<_> = cfg_iter!(wit_s[1..])
-            .map(|wit| wit.commit::<P>(scheme))
+            .map(|wit| scheme.commit_ntt(&wit.f))
             .collect::<Result<_, _>>()?;
 
         let b_sum = commitments_k1
@@ -285,18 +288,19 @@ impl<NTT: OverField, T: Transcript<NTT>> LFDecompositionVerifier<NTT, T> {
             .ok_or(DecompositionError::RecomposedError)
     }
 
-    fn calculate_b_s<P: DecompositionParams>() -> Vec<NTT> {
-        (0..P::K)
-            .map(|i| NTT::from((P::B_SMALL as u128).pow(i as u32)))
+    fn calculate_b_s(params: &DecompositionParamData) -> Vec<NTT> {
+        (0..params.k())
+            .map(|i| NTT::from((params.b_small() as u128).pow(i as u32)))
             .collect()
     }
 }
 
-fn sanity_check<NTT: SuitableRing, DP: DecompositionParams>(
+fn sanity_check<NTT: SuitableRing>(
+    params: &DecompositionParamData,
     ccs: &CCS<NTT>,
 ) -> Result<(), DecompositionError> {
-    if ccs.m != usize::max((ccs.n - ccs.l - 1) * DP::L, ccs.m).next_power_of_two() {
-        return Err(CSError::InvalidSizeBounds(ccs.m, ccs.n, DP::L).into());
+    if ccs.m != usize::max((ccs.n - ccs.l - 1) * params.l(), ccs.m).next_power_of_two() {
+        return Err(CSError::InvalidSizeBounds(ccs.m, ccs.n, params.l()).into());
     }
 
     Ok(())
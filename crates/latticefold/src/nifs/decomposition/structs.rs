@@ -9,7 +9,7 @@ use crate::{
     arith::{Witness, CCS, LCCCS},
     ark_base::*,
     commitment::{AjtaiCommitmentScheme, Commitment},
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::{DecompositionParamData, DecompositionParams},
     nifs::error::DecompositionError,
     transcript::Transcript,
 };
@@ -60,6 +60,26 @@ pub trait DecompositionProver<NTT: SuitableRing, T: Transcript<NTT>> {
             DecompositionProof<NTT>,
         ),
         DecompositionError,
+    > {
+        Self::prove_with_params(&P::DATA, cm_i, wit, transcript, ccs, scheme)
+    }
+
+    /// Same as [`DecompositionProver::prove`], with decomposition parameters chosen at runtime.
+    fn prove_with_params(
+        params: &DecompositionParamData,
+        cm_i: &LCCCS<NTT>,
+        wit: &Witness<NTT>,
+        transcript: &mut impl Transcript<NTT>,
+        ccs: &CCS<NTT>,
+        scheme: &AjtaiCommitmentScheme<NTT>,
+    ) -> Result<
+        (
+            Vec<Vec<DenseMultilinearExtension<NTT>>>,
+            Vec<LCCCS<NTT>>,
+            Vec<Witness<NTT>>,
+            DecompositionProof<NTT>,
+        ),
+        DecompositionError,
     >;
 }
 
@@ -69,6 +89,17 @@ pub trait DecompositionVerifier<NTT: OverField, T: Transcript<NTT>> {
         proof: &DecompositionProof<NTT>,
         transcript: &mut impl Transcript<NTT>,
         ccs: &CCS<NTT>,
+    ) -> Result<Vec<LCCCS<NTT>>, DecompositionError> {
+        Self::verify_with_params(&P::DATA, cm_i, proof, transcript, ccs)
+    }
+
+    /// Same as [`DecompositionVerifier::verify`], with decomposition parameters chosen at runtime.
+    fn verify_with_params(
+        params: &DecompositionParamData,
+        cm_i: &LCCCS<NTT>,
+        proof: &DecompositionProof<NTT>,
+        transcript: &mut impl Transcript<NTT>,
+        ccs: &CCS<NTT>,
     ) -> Result<Vec<LCCCS<NTT>>, DecompositionError>;
 }
 
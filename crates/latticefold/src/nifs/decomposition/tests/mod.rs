@@ -142,11 +142,13 @@ fn test_decompose_witness() {
 
     let (_, _, _, _, wit, _) = generate_decomposition_args::<RqNTT, CS, DP>(KAPPA, N, WIT_LEN);
 
-    let wit_vec =
-        LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness::<DP>(&wit);
+    let wit_vec = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness(
+        &DP::DATA,
+        &wit,
+    );
 
     // Compute expected result
-    let f_s = decompose_B_vec_into_k_vec::<RqNTT, DP>(&wit.f_coeff);
+    let f_s = decompose_B_vec_into_k_vec::<RqNTT>(&DP::DATA, &wit.f_coeff);
     let expected_wit_vec: Vec<Witness<RqNTT>> = cfg_into_iter!(f_s)
         .map(Witness::from_f_coeff::<DP>)
         .collect();
@@ -177,7 +179,8 @@ fn test_compute_x_s() {
     const N: usize = WIT_LEN * DP::L;
 
     let (lcccs, _, _, _, _, _) = generate_decomposition_args::<RqNTT, CS, DP>(KAPPA, N, WIT_LEN);
-    let x_s = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::compute_x_s::<DP>(
+    let x_s = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::compute_x_s(
+        &DP::DATA,
         lcccs.x_w.clone(),
         lcccs.h,
     );
@@ -185,7 +188,7 @@ fn test_compute_x_s() {
     // Compute expected result
     let mut x_w_clone = lcccs.x_w.clone();
     x_w_clone.push(lcccs.h);
-    let expected_x_s = decompose_big_vec_into_k_vec_and_compose_back::<RqNTT, DP>(x_w_clone);
+    let expected_x_s = decompose_big_vec_into_k_vec_and_compose_back::<RqNTT>(&DP::DATA, x_w_clone);
 
     // Validate
     assert!(!x_s.is_empty(), "X_s vector should not be empty");
@@ -212,11 +215,16 @@ fn test_commit_witnesses() {
     let (cm_i, _, _, _, wit, scheme) =
         generate_decomposition_args::<RqNTT, CS, DP>(KAPPA, N, WIT_LEN);
 
-    let wit_vec =
-        LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness::<DP>(&wit);
+    let wit_vec = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness(
+        &DP::DATA,
+        &wit,
+    );
     let y_s: Vec<Commitment<RqNTT>> =
-        LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::commit_witnesses::<DP>(
-            &wit_vec, &scheme, &cm_i,
+        LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::commit_witnesses(
+            &DP::DATA,
+            &wit_vec,
+            &scheme,
+            &cm_i,
         )
         .unwrap();
 
@@ -245,8 +253,10 @@ fn test_compute_v_s() {
     const N: usize = WIT_LEN * DP::L;
 
     let (lcccs, _, _, _, wit, _) = generate_decomposition_args::<RqNTT, CS, DP>(KAPPA, N, WIT_LEN);
-    let wit_vec =
-        LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness::<DP>(&wit);
+    let wit_vec = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness(
+        &DP::DATA,
+        &wit,
+    );
     let v_s =
         LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::compute_v_s(&wit_vec, &lcccs.r)
             .unwrap();
@@ -276,9 +286,12 @@ fn test_compute_u_s() {
 
     let (lcccs, _, _, ccs, wit, _) =
         generate_decomposition_args::<RqNTT, CS, DP>(KAPPA, N, WIT_LEN);
-    let wit_vec =
-        LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness::<DP>(&wit);
-    let x_s = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::compute_x_s::<DP>(
+    let wit_vec = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::decompose_witness(
+        &DP::DATA,
+        &wit,
+    );
+    let x_s = LFDecompositionProver::<_, PoseidonTranscript<RqNTT, CS>>::compute_x_s(
+        &DP::DATA,
         lcccs.x_w.clone(),
         lcccs.h,
     );
@@ -360,7 +373,7 @@ fn test_recompose_commitment() {
     )
     .unwrap();
 
-    let b_s = Verifier::calculate_b_s::<DP>();
+    let b_s = Verifier::calculate_b_s(&DP::DATA);
 
     let should_equal_y0 =
         Verifier::recompose_commitment(&proof.y_s, &b_s).expect("Recomposing proof failed");
@@ -390,7 +403,7 @@ fn test_recompose_u() {
     )
     .unwrap();
 
-    let b_s = Verifier::calculate_b_s::<DP>();
+    let b_s = Verifier::calculate_b_s(&DP::DATA);
 
     let u = Verifier::recompose(&proof.u_s, &b_s).expect("Recomposing proof u failed");
 
@@ -420,7 +433,7 @@ fn test_recompose_v() {
     )
     .unwrap();
 
-    let b_s = Verifier::calculate_b_s::<DP>();
+    let b_s = Verifier::calculate_b_s(&DP::DATA);
 
     let v = Verifier::recompose(&proof.v_s, &b_s).expect("Recomposing proof u failed");
 
@@ -450,7 +463,7 @@ fn test_recompose_xw_and_h() {
     )
     .unwrap();
 
-    let b_s = Verifier::calculate_b_s::<DP>();
+    let b_s = Verifier::calculate_b_s(&DP::DATA);
 
     let mut x_w = Verifier::recompose(&proof.x_s, &b_s).expect("Recomposing proof x_w failed");
     let h = x_w.pop().expect("x_w does not contain h");
@@ -5,14 +5,12 @@ use stark_rings::{
 };
 use stark_rings_linalg::ops::Transpose;
 
-use crate::{ark_base::*, decomposition_parameters::DecompositionParams};
+use crate::{ark_base::*, decomposition_parameters::DecompositionParamData};
 
-/// Decompose a vector of arbitrary norm in its NTT form into DP::K vectors
+/// Decompose a vector of arbitrary norm in its NTT form into `params.k()` vectors
 /// and applies the gadget-B matrix again.
-pub(super) fn decompose_big_vec_into_k_vec_and_compose_back<
-    NTT: SuitableRing,
-    DP: DecompositionParams,
->(
+pub(super) fn decompose_big_vec_into_k_vec_and_compose_back<NTT: SuitableRing>(
+    params: &DecompositionParamData,
     x: Vec<NTT>,
 ) -> Vec<Vec<NTT>> {
     // Allow x to have length m
@@ -20,13 +18,13 @@ pub(super) fn decompose_big_vec_into_k_vec_and_compose_back<
 
     // radix-B
     let decomposed_in_B: Vec<NTT::CoefficientRepresentation> =
-        coeff_repr.gadget_decompose(DP::B, DP::L);
+        coeff_repr.gadget_decompose(params.b(), params.l());
 
     // We now have a m * l length vector
     // Each element from original vector is mapped to l-length chunk
 
     decomposed_in_B
-        .decompose_to_vec(DP::B_SMALL as u128, DP::K)
+        .decompose_to_vec(params.b_small() as u128, params.k())
         // We have a k by (m*l) matrix
         .transpose()
         // We have a (m*l) by k matrix
@@ -34,18 +32,20 @@ pub(super) fn decompose_big_vec_into_k_vec_and_compose_back<
         // We recompose to a m * k matrix
         // Where could recompose basis b horizontally to recreate the original vector
         .map(|vec| {
-            vec.chunks(DP::L)
-                .map(|chunk| recompose(chunk, DP::B).crt())
+            vec.chunks(params.l())
+                .map(|chunk| recompose(chunk, params.b()).crt())
                 .collect()
         })
         .collect()
 }
 
-/// Decompose a vector of norm B in its coefficient form into DP::K small vectors.
-pub(super) fn decompose_B_vec_into_k_vec<NTT: SuitableRing, DP: DecompositionParams>(
+/// Decompose a vector of norm B in its coefficient form into `params.k()` small vectors.
+pub(super) fn decompose_B_vec_into_k_vec<NTT: SuitableRing>(
+    params: &DecompositionParamData,
     x: &[NTT::CoefficientRepresentation],
 ) -> Vec<Vec<NTT::CoefficientRepresentation>> {
-    x.decompose_to_vec(DP::B_SMALL as u128, DP::K).transpose()
+    x.decompose_to_vec(params.b_small() as u128, params.k())
+        .transpose()
 }
 
 #[cfg(test)]
@@ -94,7 +94,7 @@ mod tests {
             .collect();
 
         // Call the function
-        let decomposed = decompose_B_vec_into_k_vec::<RqNTT, DP>(&test_vector);
+        let decomposed = decompose_B_vec_into_k_vec::<RqNTT>(&DP::DATA, &test_vector);
 
         // Check that we get K vectors back from the decomposition
         assert_eq!(
@@ -167,7 +167,7 @@ mod tests {
             .map(|_| draw_ring_below_bound::<RqPoly, { DP::B }>(&mut rng).crt())
             .collect();
         let decomposed_and_composed_back =
-            decompose_big_vec_into_k_vec_and_compose_back::<RqNTT, DP>(test_vector.clone());
+            decompose_big_vec_into_k_vec_and_compose_back::<RqNTT>(&DP::DATA, test_vector.clone());
         let restore_decomposed =
             recompose_from_k_vec_to_big_vec::<RqNTT>(&decomposed_and_composed_back);
 
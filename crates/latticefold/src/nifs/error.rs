@@ -1,3 +1,6 @@
+use core::fmt;
+
+use num_bigint::BigUint;
 use stark_rings::Ring;
 use stark_rings_poly::polynomials::ArithErrors;
 use thiserror::Error;
@@ -19,6 +22,26 @@ pub enum LatticefoldError<R: Ring> {
     FoldingError(#[from] FoldingError<R>),
     #[error("constraint system related error: {0}")]
     ConstraintSystemError(#[from] CSError),
+    #[error("witness norm {1} is not below the bound {2} after {0}")]
+    NormBoundExceeded(ProtocolStep, BigUint, u128),
+}
+
+/// A subprotocol of the folding scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolStep {
+    Linearization,
+    Decomposition,
+    Folding,
+}
+
+impl fmt::Display for ProtocolStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linearization => write!(f, "linearization"),
+            Self::Decomposition => write!(f, "decomposition"),
+            Self::Folding => write!(f, "folding"),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -33,6 +56,8 @@ pub enum LinearizationError<R: Ring> {
     ArithmeticError(#[from] ArithErrors),
     #[error("mle evaluation failed: {0}")]
     EvaluationError(#[from] MleEvaluationError),
+    #[error("sumcheck mask commitment error: {0}")]
+    CommitmentError(#[from] CommitmentError),
 }
 
 #[derive(Debug, Error)]
@@ -63,4 +88,6 @@ pub enum FoldingError<R: Ring> {
     EvaluationError(#[from] MleEvaluationError),
     #[error("sumcheck challenge point were not generate correctly")]
     SumcheckChallengeError,
+    #[error("sumcheck mask commitment error: {0}")]
+    CommitmentError(#[from] CommitmentError),
 }
@@ -8,6 +8,7 @@
 use ark_ff::Zero;
 use ark_std::{cfg_iter, iter::successors, iterable::Iterable};
 use cyclotomic_rings::rings::SuitableRing;
+use rand::RngCore;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use stark_rings_poly::mle::DenseMultilinearExtension;
@@ -18,12 +19,17 @@ use crate::{
     arith::{error::CSError, Witness, CCS, LCCCS},
     ark_base::*,
     commitment::Commitment,
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::DecompositionParamData,
+    pcs::PcsRing,
     transcript::TranscriptWithShortChallenges,
     utils::{
         mle_helpers::evaluate_mles,
         sumcheck::{
-            prover::ProverState, utils::eq_eval, MLSumcheck, SumCheckError::SumCheckFailed,
+            prover::ProverState,
+            utils::eq_eval,
+            zk::{MaskClaim, MaskCommitmentScheme, MaskEvalProof, MaskOpening, SumcheckMask},
+            MLSumcheck,
+            SumCheckError::SumCheckFailed,
         },
     },
 };
@@ -33,20 +39,98 @@ mod tests;
 
 mod utils;
 pub use structs::*;
+pub(crate) use utils::sumcheck_degree;
 
 mod structs;
 
 impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingProver<NTT, T>
     for LFFoldingProver<NTT, T>
 {
-    fn prove<P: DecompositionParams>(
+    fn prove_with_params(
+        params: &DecompositionParamData,
         cm_i_s: &[LCCCS<NTT>],
-        mut w_s: Vec<Witness<NTT>>,
+        w_s: Vec<Witness<NTT>>,
         transcript: &mut impl TranscriptWithShortChallenges<NTT>,
         ccs: &CCS<NTT>,
         mz_mles: &[Vec<DenseMultilinearExtension<NTT>>],
     ) -> Result<(LCCCS<NTT>, Witness<NTT>, FoldingProof<NTT>), FoldingError<NTT>> {
-        sanity_check::<NTT, P>(ccs)?;
+        let (lcccs, w_0, proof, _) = Self::prove_with_masking(
+            params,
+            cm_i_s,
+            w_s,
+            transcript,
+            ccs,
+            mz_mles,
+            None::<(_, &mut dyn RngCore)>,
+        )?;
+
+        Ok((lcccs, w_0, proof))
+    }
+}
+
+impl<NTT: PcsRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingProver<NTT, T> {
+    /// [`FoldingProver::prove_with_params`] with a masked sumcheck, see
+    /// [`MLSumcheck::prove_zk_with_eqs`].
+    ///
+    /// The mask of the sumcheck is committed with `scheme` and its evaluation is proved with
+    /// [`MaskClaim::prove`] after the step. The masking only hides the round messages: the
+    /// evaluations `theta_s` and `eta_s` of the proof are sent in the clear.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn prove_masked_with_params<Rng: rand::Rng + ?Sized>(
+        params: &DecompositionParamData,
+        cm_i_s: &[LCCCS<NTT>],
+        w_s: Vec<Witness<NTT>>,
+        transcript: &mut (impl TranscriptWithShortChallenges<NTT> + Clone),
+        ccs: &CCS<NTT>,
+        mz_mles: &[Vec<DenseMultilinearExtension<NTT>>],
+        scheme: &MaskCommitmentScheme<NTT>,
+        rng: &mut Rng,
+    ) -> Result<
+        (
+            LCCCS<NTT>,
+            Witness<NTT>,
+            FoldingProof<NTT>,
+            SumcheckMask<NTT>,
+            MaskEvalProof<NTT>,
+        ),
+        FoldingError<NTT>,
+    > {
+        let (lcccs, w_0, proof, mask) = Self::prove_with_masking(
+            params,
+            cm_i_s,
+            w_s,
+            transcript,
+            ccs,
+            mz_mles,
+            Some((scheme, &mut *rng)),
+        )?;
+        let (mask, claim, opening) = mask.expect("the sumcheck is masked");
+        let mask_proof = claim.prove(scheme, &opening, transcript, rng)?;
+
+        Ok((lcccs, w_0, proof, mask, mask_proof))
+    }
+}
+
+impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingProver<NTT, T> {
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn prove_with_masking<Rng: rand::Rng + ?Sized>(
+        params: &DecompositionParamData,
+        cm_i_s: &[LCCCS<NTT>],
+        mut w_s: Vec<Witness<NTT>>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        mz_mles: &[Vec<DenseMultilinearExtension<NTT>>],
+        masking: Option<(&MaskCommitmentScheme<NTT>, &mut Rng)>,
+    ) -> Result<
+        (
+            LCCCS<NTT>,
+            Witness<NTT>,
+            FoldingProof<NTT>,
+            Option<(SumcheckMask<NTT>, MaskClaim<NTT>, MaskOpening<NTT>)>,
+        ),
+        FoldingError<NTT>,
+    > {
+        sanity_check::<NTT>(params, ccs)?;
 
         // Free some unneeded vars/memory
         w_s.iter_mut().for_each(|w_i| {
@@ -56,14 +140,14 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingProver<NTT
             w_i.w_ccs.shrink_to_fit();
         });
 
-        if cm_i_s.len() != 2 * P::K {
+        if cm_i_s.len() != 2 * params.k() {
             return Err(FoldingError::IncorrectLength);
         }
 
         let log_m = ccs.s;
 
         // Step 1: Generate alpha, zeta, mu, beta challenges
-        let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<P>(log_m);
+        let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(params, log_m);
 
         // Step 2: Compute g polynomial and sumcheck on it
         // Setup f_hat_mle for later evaluation of thetas
@@ -71,13 +155,14 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingProver<NTT
 
         let ris = Self::get_ris(cm_i_s);
 
-        let prechallenged_Ms_1 =
-            Self::calculate_challenged_mz_mle(&mz_mles[0..P::K], &zeta_s[0..P::K])?;
+        let k = params.k();
+        let prechallenged_Ms_1 = Self::calculate_challenged_mz_mle(&mz_mles[0..k], &zeta_s[0..k])?;
         let prechallenged_Ms_2 =
-            Self::calculate_challenged_mz_mle(&mz_mles[P::K..2 * P::K], &zeta_s[P::K..2 * P::K])?;
-        let (g_mles, g_degree) = create_sumcheck_polynomial::<_, P>(
+            Self::calculate_challenged_mz_mle(&mz_mles[k..2 * k], &zeta_s[k..2 * k])?;
+        let (g_mles, eq_points, g_terms) = create_sumcheck_polynomial(
+            params,
             log_m,
-            f_hat_mles.clone(),
+            &f_hat_mles,
             &alpha_s,
             &prechallenged_Ms_1,
             &prechallenged_Ms_2,
@@ -86,11 +171,51 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingProver<NTT
             &mu_s,
         )?;
 
-        let comb_fn = |vals: &[NTT]| -> NTT { sumcheck_polynomial_comb_fn::<NTT, P>(vals, &mu_s) };
+        let g_degree = sumcheck_polynomial_degree(&g_terms);
+        let comb_fn =
+            |vals: &[NTT], out: &mut [NTT]| sumcheck_polynomial_comb_fn::<NTT>(&g_terms, vals, out);
 
-        // Step 5: Run sum check prover
-        let (sum_check_proof, prover_state) =
-            MLSumcheck::prove_as_subprotocol(transcript, g_mles, log_m, g_degree, comb_fn);
+        // Step 5: Run sum check prover, the first round looks up the range check of the small
+        // evaluations of the f_hat MLEs
+        let first_round =
+            small_value_first_round(params, &g_mles, &f_hat_mles, &eq_points, &mu_s, g_degree);
+        let mles = g_mles
+            .iter()
+            .chain(f_hat_mles.iter().flatten())
+            .collect::<Vec<_>>();
+        let (sum_check_proof, prover_state, mask) = match masking {
+            Some((scheme, rng)) => {
+                let (proof, prover_state, opening) = MLSumcheck::prove_zk_with_eqs_and_first_round(
+                    transcript,
+                    &mles,
+                    log_m,
+                    g_degree,
+                    &eq_points,
+                    first_round,
+                    comb_fn,
+                    scheme,
+                    rng,
+                )?;
+                let claim = MaskClaim::new(&proof.mask, &prover_state);
+                (
+                    proof.sumcheck,
+                    prover_state,
+                    Some((proof.mask, claim, opening)),
+                )
+            }
+            None => {
+                let (proof, prover_state) = MLSumcheck::prove_with_eqs_and_first_round(
+                    transcript,
+                    &mles,
+                    log_m,
+                    g_degree,
+                    &eq_points,
+                    first_round,
+                    comb_fn,
+                );
+                (proof, prover_state, None)
+            }
+        };
 
         let r_0 = Self::get_sumcheck_randomness(prover_state);
 
@@ -105,7 +230,7 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingProver<NTT
         eta_s.iter().for_each(|etas| transcript.absorb_slice(etas));
 
         // Step 5 get rho challenges
-        let (rho_s_coeff, rho_s) = get_rhos::<_, _, P>(transcript);
+        let (rho_s_coeff, rho_s) = get_rhos(params, transcript);
 
         let f_0: Vec<NTT> = Self::compute_f_0(&rho_s, &w_s);
 
@@ -118,7 +243,7 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingProver<NTT
 
         let lcccs = prepare_public_output(r_0, v_0, cm_0, u_0, x_0, h);
 
-        let w_0 = Witness::from_f::<P>(f_0);
+        let w_0 = Witness::from_f_with_params(params, f_0);
 
         let folding_proof = FoldingProof {
             pointshift_sumcheck_proof: sum_check_proof,
@@ -126,36 +251,83 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingProver<NTT
             eta_s,
         };
 
-        Ok((lcccs, w_0, folding_proof))
+        Ok((lcccs, w_0, folding_proof, mask))
     }
 }
 
 impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingVerifier<NTT, T>
     for LFFoldingVerifier<NTT, T>
 {
-    fn verify<P: DecompositionParams>(
+    fn verify_with_params(
+        params: &DecompositionParamData,
         cm_i_s: &[LCCCS<NTT>],
         proof: &FoldingProof<NTT>,
         transcript: &mut impl TranscriptWithShortChallenges<NTT>,
         ccs: &CCS<NTT>,
     ) -> Result<LCCCS<NTT>, FoldingError<NTT>> {
-        sanity_check::<NTT, P>(ccs)?;
+        let (lcccs, _) = Self::verify_with_masking(params, cm_i_s, proof, None, transcript, ccs)?;
+
+        Ok(lcccs)
+    }
+}
+
+impl<NTT: PcsRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingVerifier<NTT, T> {
+    /// Verifies a proof of [`LFFoldingProver::prove_masked_with_params`] with the sumcheck mask
+    /// `mask` and the proof `mask_proof` of its evaluation, committed with `scheme`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_masked_with_params(
+        params: &DecompositionParamData,
+        cm_i_s: &[LCCCS<NTT>],
+        proof: &FoldingProof<NTT>,
+        mask: &SumcheckMask<NTT>,
+        mask_proof: &MaskEvalProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        scheme: &MaskCommitmentScheme<NTT>,
+    ) -> Result<LCCCS<NTT>, FoldingError<NTT>> {
+        let (lcccs, mask_claim) =
+            Self::verify_with_masking(params, cm_i_s, proof, Some(mask), transcript, ccs)?;
+        mask_claim
+            .expect("the sumcheck is masked")
+            .verify(scheme, mask_proof, transcript)?;
+
+        Ok(lcccs)
+    }
+}
+
+impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingVerifier<NTT, T> {
+    pub(crate) fn verify_with_masking(
+        params: &DecompositionParamData,
+        cm_i_s: &[LCCCS<NTT>],
+        proof: &FoldingProof<NTT>,
+        mask: Option<&SumcheckMask<NTT>>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+    ) -> Result<(LCCCS<NTT>, Option<MaskClaim<NTT>>), FoldingError<NTT>> {
+        sanity_check::<NTT>(params, ccs)?;
 
         // Step 1: Generate alpha, zeta, mu, beta challenges and validate input
-        let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<P>(ccs.s);
+        let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(params, ccs.s);
 
         // Calculate claims for sumcheck verification
         let (claim_g1, claim_g3) = Self::calculate_claims(&alpha_s, &zeta_s, cm_i_s);
 
         let nvars = ccs.s;
-        let degree = 2 * P::B_SMALL;
+        let degree = sumcheck_degree(params, nvars)?;
 
         //Step 2: The sumcheck.
-        let (r_0, expected_evaluation) =
-            Self::verify_sumcheck_proof(transcript, nvars, degree, claim_g1 + claim_g3, proof)?;
+        let (r_0, expected_evaluation, mask_claim) = Self::verify_sumcheck_proof(
+            transcript,
+            nvars,
+            degree,
+            claim_g1 + claim_g3,
+            proof,
+            mask,
+        )?;
 
         // Verify evaluation claim
-        Self::verify_evaluation::<P>(
+        Self::verify_evaluation(
+            params,
             &alpha_s,
             &beta_s,
             &mu_s,
@@ -175,7 +347,7 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingVerifier<N
             .eta_s
             .iter()
             .for_each(|etas| transcript.absorb_slice(etas));
-        let (rho_s_coeff, rho_s) = get_rhos::<_, _, P>(transcript);
+        let (rho_s_coeff, rho_s) = get_rhos(params, transcript);
 
         // Step 6
         let (v_0, cm_0, u_0, x_0) = compute_v0_u0_x0_cm_0(
@@ -190,7 +362,10 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> FoldingVerifier<N
         // Step 7: Compute f0 and Witness_0
 
         let h = x_0.last().copied().ok_or(FoldingError::IncorrectLength)?;
-        Ok(prepare_public_output(r_0, v_0, cm_0, u_0, x_0, h))
+        Ok((
+            prepare_public_output(r_0, v_0, cm_0, u_0, x_0, h),
+            mask_claim,
+        ))
     }
 }
 
@@ -270,7 +445,8 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingProver<N
 
 impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingVerifier<NTT, T> {
     #[allow(clippy::too_many_arguments)]
-    fn verify_evaluation<P: DecompositionParams>(
+    fn verify_evaluation(
+        params: &DecompositionParamData,
         alpha_s: &[NTT],
         beta_s: &[NTT],
         mu_s: &[NTT],
@@ -288,7 +464,8 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingVerifier
             .map(|r_i: &Vec<NTT>| eq_eval(r_i, r_0))
             .collect::<Result<Vec<_>, _>>()?;
 
-        let should_equal_s: NTT = compute_sumcheck_claim_expected_value::<NTT, P>(
+        let should_equal_s: NTT = compute_sumcheck_claim_expected_value(
+            params,
             alpha_s,
             mu_s,
             &proof.theta_s,
@@ -348,16 +525,33 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingVerifier
         degree: usize,
         total_claim: NTT,
         proof: &FoldingProof<NTT>,
-    ) -> Result<(Vec<NTT>, NTT), FoldingError<NTT>> {
+        mask: Option<&SumcheckMask<NTT>>,
+    ) -> Result<(Vec<NTT>, NTT, Option<MaskClaim<NTT>>), FoldingError<NTT>> {
         //Step 2: The sumcheck.
         // Verify the sumcheck proof.
-        let sub_claim = MLSumcheck::verify_as_subprotocol(
-            transcript,
-            nvars,
-            degree,
-            total_claim,
-            &proof.pointshift_sumcheck_proof,
-        )?;
+        let (sub_claim, mask_claim) = match mask {
+            Some(mask) => {
+                let (sub_claim, mask_claim) = MLSumcheck::verify_masked(
+                    transcript,
+                    nvars,
+                    degree,
+                    total_claim,
+                    mask,
+                    &proof.pointshift_sumcheck_proof,
+                )?;
+                (sub_claim, Some(mask_claim))
+            }
+            None => (
+                MLSumcheck::verify_as_subprotocol(
+                    transcript,
+                    nvars,
+                    degree,
+                    total_claim,
+                    &proof.pointshift_sumcheck_proof,
+                )?,
+                None,
+            ),
+        };
 
         let r_0 = sub_claim
             .point
@@ -365,15 +559,16 @@ impl<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>> LFFoldingVerifier
             .map(|x| x.into())
             .collect::<Vec<NTT>>();
 
-        Ok((r_0, sub_claim.expected_evaluation))
+        Ok((r_0, sub_claim.expected_evaluation, mask_claim))
     }
 }
 
-fn sanity_check<NTT: SuitableRing, DP: DecompositionParams>(
+fn sanity_check<NTT: SuitableRing>(
+    params: &DecompositionParamData,
     ccs: &CCS<NTT>,
 ) -> Result<(), FoldingError<NTT>> {
-    if ccs.m != usize::max((ccs.n - ccs.l - 1) * DP::L, ccs.m).next_power_of_two() {
-        return Err(CSError::InvalidSizeBounds(ccs.m, ccs.n, DP::L).into());
+    if ccs.m != usize::max((ccs.n - ccs.l - 1) * params.l(), ccs.m).next_power_of_two() {
+        return Err(CSError::InvalidSizeBounds(ccs.m, ccs.n, params.l()).into());
     }
 
     Ok(())
@@ -7,7 +7,7 @@ use stark_rings_poly::mle::DenseMultilinearExtension;
 use crate::{
     arith::{Witness, CCS, LCCCS},
     ark_base::Vec,
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::{DecompositionParamData, DecompositionParams},
     nifs::error::FoldingError,
     transcript::TranscriptWithShortChallenges,
     utils::sumcheck,
@@ -66,6 +66,18 @@ pub trait FoldingProver<NTT: SuitableRing, T: TranscriptWithShortChallenges<NTT>
         transcript: &mut impl TranscriptWithShortChallenges<NTT>,
         ccs: &CCS<NTT>,
         mz_mles: &[Vec<DenseMultilinearExtension<NTT>>],
+    ) -> Result<(LCCCS<NTT>, Witness<NTT>, FoldingProof<NTT>), FoldingError<NTT>> {
+        Self::prove_with_params(&P::DATA, cm_i_s, w_s, transcript, ccs, mz_mles)
+    }
+
+    /// Same as [`FoldingProver::prove`], with decomposition parameters chosen at runtime.
+    fn prove_with_params(
+        params: &DecompositionParamData,
+        cm_i_s: &[LCCCS<NTT>],
+        w_s: Vec<Witness<NTT>>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        mz_mles: &[Vec<DenseMultilinearExtension<NTT>>],
     ) -> Result<(LCCCS<NTT>, Witness<NTT>, FoldingProof<NTT>), FoldingError<NTT>>;
 }
 
@@ -90,6 +102,17 @@ pub trait FoldingVerifier<NTT: SuitableRing, T: TranscriptWithShortChallenges<NT
         proof: &FoldingProof<NTT>,
         transcript: &mut impl TranscriptWithShortChallenges<NTT>,
         ccs: &CCS<NTT>,
+    ) -> Result<LCCCS<NTT>, FoldingError<NTT>> {
+        Self::verify_with_params(&P::DATA, cm_i_s, proof, transcript, ccs)
+    }
+
+    /// Same as [`FoldingVerifier::verify`], with decomposition parameters chosen at runtime.
+    fn verify_with_params(
+        params: &DecompositionParamData,
+        cm_i_s: &[LCCCS<NTT>],
+        proof: &FoldingProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
     ) -> Result<LCCCS<NTT>, FoldingError<NTT>>;
 }
 
@@ -1,6 +1,6 @@
 use ark_ff::{Field, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
-use ark_std::{io::Cursor, test_rng};
+use ark_std::{io::Cursor, iter::successors, test_rng, UniformRand};
 use cyclotomic_rings::{
     challenge_set::LatticefoldChallengeSet,
     rings::{
@@ -9,6 +9,7 @@ use cyclotomic_rings::{
     },
 };
 use num_traits::{One, Zero};
+use rand::Rng;
 use stark_rings::{
     cyclotomic_ring::{
         models::{
@@ -17,7 +18,7 @@ use stark_rings::{
         },
         ICRT,
     },
-    Ring,
+    PolyRing, Ring,
 };
 use stark_rings_poly::mle::DenseMultilinearExtension;
 
@@ -41,7 +42,8 @@ use crate::{
             prepare_public_output,
             utils::{
                 compute_v0_u0_x0_cm_0, create_sumcheck_polynomial, get_rhos,
-                sumcheck_polynomial_comb_fn, SqueezeAlphaBetaZetaMu,
+                small_value_first_round, sumcheck_polynomial_comb_fn, sumcheck_polynomial_degree,
+                sumcheck_polynomial_terms, SqueezeAlphaBetaZetaMu,
             },
             FoldingProver, FoldingVerifier, LFFoldingProver, LFFoldingVerifier,
         },
@@ -251,7 +253,7 @@ fn test_get_sumcheck_randomness() {
 
     let (lccs, mut wit_s, mut transcript, ccs, _, mz_mles) =
         setup_test_environment::<RqNTT, CS, DP>(n, false);
-    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<DP>(ccs.s);
+    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, ccs.s);
     let f_hat_mles =
         LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::setup_f_hat_mles(&mut wit_s);
 
@@ -269,9 +271,10 @@ fn test_get_sumcheck_randomness() {
             &zeta_s[DP::K..2 * DP::K],
         )
         .unwrap();
-    let (g_mles, g_degree) = create_sumcheck_polynomial::<_, DP>(
+    let (g_mles, eq_points, g_terms) = create_sumcheck_polynomial(
+        &DP::DATA,
         ccs.s,
-        f_hat_mles,
+        &f_hat_mles,
         &alpha_s,
         &prechallenged_Ms_1,
         &prechallenged_Ms_2,
@@ -281,12 +284,23 @@ fn test_get_sumcheck_randomness() {
     )
     .unwrap();
 
-    let comb_fn =
-        |vals: &[RqNTT]| -> RqNTT { sumcheck_polynomial_comb_fn::<RqNTT, DP>(vals, &mu_s) };
+    let g_degree = sumcheck_polynomial_degree(&g_terms);
+    let comb_fn = |vals: &[RqNTT], out: &mut [RqNTT]| {
+        sumcheck_polynomial_comb_fn::<RqNTT>(&g_terms, vals, out)
+    };
 
     // Compute sumcheck proof
-    let (_, prover_state) =
-        MLSumcheck::prove_as_subprotocol(&mut transcript, g_mles, ccs.s, g_degree, comb_fn);
+    let (_, prover_state) = MLSumcheck::prove_with_eqs(
+        &mut transcript,
+        &g_mles
+            .iter()
+            .chain(f_hat_mles.iter().flatten())
+            .collect::<Vec<_>>(),
+        ccs.s,
+        g_degree,
+        &eq_points,
+        comb_fn,
+    );
     // Derive randomness
     let r_0 = LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::get_sumcheck_randomness(
         prover_state,
@@ -306,7 +320,7 @@ fn test_get_thetas() {
 
     let (lccs, mut wit_s, mut transcript, ccs, _, mz_mles) =
         setup_test_environment::<RqNTT, CS, DP>(n, false);
-    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<DP>(ccs.s);
+    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, ccs.s);
     let f_hat_mles =
         LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::setup_f_hat_mles(&mut wit_s);
 
@@ -324,9 +338,10 @@ fn test_get_thetas() {
             &zeta_s[DP::K..2 * DP::K],
         )
         .unwrap();
-    let (g_mles, g_degree) = create_sumcheck_polynomial::<_, DP>(
+    let (g_mles, eq_points, g_terms) = create_sumcheck_polynomial(
+        &DP::DATA,
         ccs.s,
-        f_hat_mles.clone(),
+        &f_hat_mles,
         &alpha_s,
         &prechallenged_Ms_1,
         &prechallenged_Ms_2,
@@ -336,11 +351,22 @@ fn test_get_thetas() {
     )
     .unwrap();
 
-    let comb_fn =
-        |vals: &[RqNTT]| -> RqNTT { sumcheck_polynomial_comb_fn::<RqNTT, DP>(vals, &mu_s) };
+    let g_degree = sumcheck_polynomial_degree(&g_terms);
+    let comb_fn = |vals: &[RqNTT], out: &mut [RqNTT]| {
+        sumcheck_polynomial_comb_fn::<RqNTT>(&g_terms, vals, out)
+    };
 
-    let (_, prover_state) =
-        MLSumcheck::prove_as_subprotocol(&mut transcript, g_mles, ccs.s, g_degree, comb_fn);
+    let (_, prover_state) = MLSumcheck::prove_with_eqs(
+        &mut transcript,
+        &g_mles
+            .iter()
+            .chain(f_hat_mles.iter().flatten())
+            .collect::<Vec<_>>(),
+        ccs.s,
+        g_degree,
+        &eq_points,
+        comb_fn,
+    );
     let r_0 = LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::get_sumcheck_randomness(
         prover_state,
     );
@@ -378,7 +404,7 @@ fn test_get_etas() {
 
     let (lccs, mut wit_s, mut transcript, ccs, _, mz_mles) =
         setup_test_environment::<RqNTT, CS, DP>(n, false);
-    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<DP>(ccs.s);
+    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, ccs.s);
     let f_hat_mles =
         LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::setup_f_hat_mles(&mut wit_s);
 
@@ -396,9 +422,10 @@ fn test_get_etas() {
             &zeta_s[DP::K..2 * DP::K],
         )
         .unwrap();
-    let (g_mles, g_degree) = create_sumcheck_polynomial::<_, DP>(
+    let (g_mles, eq_points, g_terms) = create_sumcheck_polynomial(
+        &DP::DATA,
         ccs.s,
-        f_hat_mles,
+        &f_hat_mles,
         &alpha_s,
         &prechallenged_Ms_1,
         &prechallenged_Ms_2,
@@ -408,11 +435,22 @@ fn test_get_etas() {
     )
     .unwrap();
 
-    let comb_fn =
-        |vals: &[RqNTT]| -> RqNTT { sumcheck_polynomial_comb_fn::<RqNTT, DP>(vals, &mu_s) };
+    let g_degree = sumcheck_polynomial_degree(&g_terms);
+    let comb_fn = |vals: &[RqNTT], out: &mut [RqNTT]| {
+        sumcheck_polynomial_comb_fn::<RqNTT>(&g_terms, vals, out)
+    };
 
-    let (_, prover_state) =
-        MLSumcheck::prove_as_subprotocol(&mut transcript, g_mles, ccs.s, g_degree, comb_fn);
+    let (_, prover_state) = MLSumcheck::prove_with_eqs(
+        &mut transcript,
+        &g_mles
+            .iter()
+            .chain(f_hat_mles.iter().flatten())
+            .collect::<Vec<_>>(),
+        ccs.s,
+        g_degree,
+        &eq_points,
+        comb_fn,
+    );
     let r_0 = LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::get_sumcheck_randomness(
         prover_state,
     );
@@ -450,7 +488,7 @@ fn test_get_rhos() {
     let (_, _, mut transcript, _, _, _) = setup_test_environment::<RqNTT, CS, DP>(n, false);
     let mut transcript_clone = transcript.clone();
 
-    let (rho_s_coeff, rho_s) = get_rhos::<_, _, DP>(&mut transcript);
+    let (rho_s_coeff, rho_s) = get_rhos(&DP::DATA, &mut transcript);
 
     // Compute expected result
     transcript_clone.absorb_field_element(&<_>::from_base_prime_field(
@@ -478,7 +516,7 @@ fn test_prepare_public_output() {
 
     let (lccs, mut wit_s, mut transcript, ccs, _, mz_mles) =
         setup_test_environment::<RqNTT, CS, DP>(n, false);
-    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<DP>(ccs.s);
+    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, ccs.s);
     let f_hat_mles =
         LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::setup_f_hat_mles(&mut wit_s);
 
@@ -496,9 +534,10 @@ fn test_prepare_public_output() {
             &zeta_s[DP::K..2 * DP::K],
         )
         .unwrap();
-    let (g_mles, g_degree) = create_sumcheck_polynomial::<_, DP>(
+    let (g_mles, eq_points, g_terms) = create_sumcheck_polynomial(
+        &DP::DATA,
         ccs.s,
-        f_hat_mles.clone(),
+        &f_hat_mles,
         &alpha_s,
         &prechallenged_Ms_1,
         &prechallenged_Ms_2,
@@ -508,11 +547,22 @@ fn test_prepare_public_output() {
     )
     .unwrap();
 
-    let comb_fn =
-        |vals: &[RqNTT]| -> RqNTT { sumcheck_polynomial_comb_fn::<RqNTT, DP>(vals, &mu_s) };
+    let g_degree = sumcheck_polynomial_degree(&g_terms);
+    let comb_fn = |vals: &[RqNTT], out: &mut [RqNTT]| {
+        sumcheck_polynomial_comb_fn::<RqNTT>(&g_terms, vals, out)
+    };
 
-    let (_, prover_state) =
-        MLSumcheck::prove_as_subprotocol(&mut transcript, g_mles, ccs.s, g_degree, comb_fn);
+    let (_, prover_state) = MLSumcheck::prove_with_eqs(
+        &mut transcript,
+        &g_mles
+            .iter()
+            .chain(f_hat_mles.iter().flatten())
+            .collect::<Vec<_>>(),
+        ccs.s,
+        g_degree,
+        &eq_points,
+        comb_fn,
+    );
     let r_0 = LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::get_sumcheck_randomness(
         prover_state,
     );
@@ -528,7 +578,7 @@ fn test_prepare_public_output() {
         .for_each(|thetas| transcript.absorb_slice(thetas));
     eta_s.iter().for_each(|etas| transcript.absorb_slice(etas));
 
-    let (rho_s_coeff, rho_s) = get_rhos::<_, _, DP>(&mut transcript);
+    let (rho_s_coeff, rho_s) = get_rhos(&DP::DATA, &mut transcript);
     let (v_0, cm_0, u_0, x_0) =
         compute_v0_u0_x0_cm_0(&rho_s_coeff, &rho_s, &theta_s, &lccs, &eta_s, &ccs);
     let expected_x_0 = x_0[0..x_0.len() - 1].to_vec();
@@ -561,7 +611,7 @@ fn test_compute_f_0() {
 
     let (lccs, mut wit_s, mut transcript, ccs, _, Mz_mles) =
         setup_test_environment::<RqNTT, CS, DP>(n, false);
-    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<DP>(ccs.s);
+    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, ccs.s);
     let f_hat_mles =
         LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::setup_f_hat_mles(&mut wit_s);
 
@@ -578,9 +628,10 @@ fn test_compute_f_0() {
             &zeta_s[DP::K..2 * DP::K],
         )
         .unwrap();
-    let (g_mles, g_degree) = create_sumcheck_polynomial::<_, DP>(
+    let (g_mles, eq_points, g_terms) = create_sumcheck_polynomial(
+        &DP::DATA,
         ccs.s,
-        f_hat_mles.clone(),
+        &f_hat_mles,
         &alpha_s,
         &prechallenged_Ms_1,
         &prechallenged_Ms_2,
@@ -590,11 +641,22 @@ fn test_compute_f_0() {
     )
     .unwrap();
 
-    let comb_fn =
-        |vals: &[RqNTT]| -> RqNTT { sumcheck_polynomial_comb_fn::<RqNTT, DP>(vals, &mu_s) };
+    let g_degree = sumcheck_polynomial_degree(&g_terms);
+    let comb_fn = |vals: &[RqNTT], out: &mut [RqNTT]| {
+        sumcheck_polynomial_comb_fn::<RqNTT>(&g_terms, vals, out)
+    };
 
-    let (_, prover_state) =
-        MLSumcheck::prove_as_subprotocol(&mut transcript, g_mles, ccs.s, g_degree, comb_fn);
+    let (_, prover_state) = MLSumcheck::prove_with_eqs(
+        &mut transcript,
+        &g_mles
+            .iter()
+            .chain(f_hat_mles.iter().flatten())
+            .collect::<Vec<_>>(),
+        ccs.s,
+        g_degree,
+        &eq_points,
+        comb_fn,
+    );
     let r_0 = LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::get_sumcheck_randomness(
         prover_state,
     );
@@ -609,7 +671,7 @@ fn test_compute_f_0() {
         .for_each(|thetas| transcript.absorb_slice(thetas));
     eta_s.iter().for_each(|etas| transcript.absorb_slice(etas));
 
-    let (_, rho_s) = get_rhos::<_, _, DP>(&mut transcript);
+    let (_, rho_s) = get_rhos(&DP::DATA, &mut transcript);
 
     let f_0: Vec<RqNTT> =
         LFFoldingProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::compute_f_0(&rho_s, &wit_s);
@@ -634,6 +696,131 @@ fn test_compute_f_0() {
     );
 }
 
+#[test]
+fn test_sumcheck_polynomial_terms() {
+    type RqNTT = GoldilocksRqNTT;
+    type CS = GoldilocksChallengeSet;
+    type DP = GoldilocksDP;
+
+    let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
+    let (_, _, _, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, 4);
+    let terms = sumcheck_polynomial_terms(&DP::DATA, 4, &mu_s).unwrap();
+    assert_eq!(sumcheck_polynomial_degree(&terms), 2 * DP::B_SMALL - 1);
+
+    let extension_degree = <RqNTT as SuitableRing>::CoefficientRepresentation::dimension()
+        / <RqNTT as PolyRing>::dimension();
+    let vals: Vec<RqNTT> = (0..2 + 2 * DP::K * extension_degree)
+        .map(|i| RqNTT::from(i as u128 + 3))
+        .collect();
+    let mut out = vec![RqNTT::zero(); 3];
+    sumcheck_polynomial_comb_fn(&terms, &vals, &mut out);
+
+    let range_check: RqNTT = mu_s
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &mu)| {
+            let vals = &vals;
+            successors(Some(mu), move |&power| Some(power * mu))
+                .take(extension_degree)
+                .enumerate()
+                .map(move |(d, mu_power)| {
+                    let f = vals[2 + i * extension_degree + d];
+                    mu_power
+                        * f
+                        * (1..DP::B_SMALL)
+                            .map(|b| f * f - RqNTT::from((b * b) as u128))
+                            .product::<RqNTT>()
+                })
+        })
+        .sum();
+    assert_eq!(out, vec![vals[0], vals[1], range_check]);
+}
+
+#[test]
+fn test_small_value_first_round() {
+    type RqNTT = GoldilocksRqNTT;
+    type CS = GoldilocksChallengeSet;
+    type DP = GoldilocksDP;
+    const LOG_M: usize = 4;
+
+    let mut rng = test_rng();
+    let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
+    let (_, beta_s, _, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, LOG_M);
+    let terms = sumcheck_polynomial_terms(&DP::DATA, LOG_M, &mu_s).unwrap();
+    let degree = sumcheck_polynomial_degree(&terms);
+
+    let extension_degree = <RqNTT as SuitableRing>::CoefficientRepresentation::dimension()
+        / <RqNTT as PolyRing>::dimension();
+    let rand_mle = |rng: &mut _| {
+        DenseMultilinearExtension::from_evaluations_vec(
+            LOG_M,
+            (0..1 << LOG_M).map(|_| RqNTT::rand(rng)).collect(),
+        )
+    };
+    let g_mles = vec![rand_mle(&mut rng), rand_mle(&mut rng)];
+    let f_hat_mles: Vec<Vec<DenseMultilinearExtension<RqNTT>>> = (0..2 * DP::K)
+        .map(|_| {
+            (0..extension_degree)
+                .map(|_| {
+                    let evals = (0..1 << LOG_M)
+                        .map(|_| {
+                            let mut f = RqNTT::zero();
+                            for slot in f.coeffs_mut() {
+                                let digit = rng.gen_range(0..DP::B_SMALL as u128);
+                                *slot = if rng.gen() {
+                                    digit.into()
+                                } else {
+                                    -<RqNTT as PolyRing>::BaseRing::from(digit)
+                                };
+                            }
+                            f
+                        })
+                        .collect();
+                    DenseMultilinearExtension::from_evaluations_vec(LOG_M, evals)
+                })
+                .collect()
+        })
+        .collect();
+    let eq_points: Vec<Vec<RqNTT>> = (0..2)
+        .map(|_| (0..LOG_M).map(|_| RqNTT::rand(&mut rng)).collect())
+        .chain([beta_s])
+        .collect();
+
+    let first_round =
+        small_value_first_round(&DP::DATA, &g_mles, &f_hat_mles, &eq_points, &mu_s, degree);
+    assert!(first_round.is_some());
+
+    let mles: Vec<_> = g_mles.iter().chain(f_hat_mles.iter().flatten()).collect();
+    let comb_fn =
+        |vals: &[RqNTT], out: &mut [RqNTT]| sumcheck_polynomial_comb_fn(&terms, vals, out);
+    let (expected, _) = MLSumcheck::prove_with_eqs(
+        &mut transcript.clone(),
+        &mles,
+        LOG_M,
+        degree,
+        &eq_points,
+        comb_fn,
+    );
+    let (proof, _) = MLSumcheck::prove_with_eqs_and_first_round(
+        &mut transcript,
+        &mles,
+        LOG_M,
+        degree,
+        &eq_points,
+        first_round,
+        comb_fn,
+    );
+    assert_eq!(proof, expected);
+
+    // A slot out of range is left to the sumcheck prover
+    let mut f_hat_mles = f_hat_mles;
+    f_hat_mles[0][0].evaluations[3] = RqNTT::from(DP::B_SMALL as u128);
+    assert!(
+        small_value_first_round(&DP::DATA, &g_mles, &f_hat_mles, &eq_points, &mu_s, degree)
+            .is_none()
+    );
+}
+
 #[test]
 fn test_full_prove() {
     type RqNTT = StarkRqNTT;
@@ -690,7 +877,7 @@ fn test_verify_evaluation() {
         setup_test_environment::<RqNTT, CS, DP>(n, true);
     let proof = proof.unwrap();
 
-    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu::<DP>(ccs.s);
+    let (alpha_s, beta_s, zeta_s, mu_s) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, ccs.s);
 
     let nvars = ccs.s;
     let degree = 2 * DP::B_SMALL;
@@ -700,17 +887,19 @@ fn test_verify_evaluation() {
             &alpha_s, &zeta_s, &lccs_vec,
         );
 
-    let (r_0, expected_evaluation) =
+    let (r_0, expected_evaluation, _) =
         LFFoldingVerifier::<RqNTT, PoseidonTranscript<RqNTT, CS>>::verify_sumcheck_proof(
             &mut transcript,
             nvars,
             degree,
             claim_g1 + claim_g3,
             &proof,
+            None,
         )
         .unwrap();
 
-    let result = LFFoldingVerifier::<RqNTT, PoseidonTranscript<RqNTT, CS>>::verify_evaluation::<DP>(
+    let result = LFFoldingVerifier::<RqNTT, PoseidonTranscript<RqNTT, CS>>::verify_evaluation(
+        &DP::DATA,
         &alpha_s,
         &beta_s,
         &mu_s,
@@ -770,7 +959,7 @@ fn test_verify_sumcheck_proof() {
         setup_test_environment::<RqNTT, CS, DP>(n, true);
     let proof = proof.unwrap();
 
-    let (alpha_s, _, zeta_s, _) = transcript.squeeze_alpha_beta_zeta_mu::<DP>(ccs.s);
+    let (alpha_s, _, zeta_s, _) = transcript.squeeze_alpha_beta_zeta_mu(&DP::DATA, ccs.s);
 
     let nvars = ccs.s;
     let degree = 2 * DP::B_SMALL;
@@ -786,10 +975,11 @@ fn test_verify_sumcheck_proof() {
         degree,
         claim_g1 + claim_g3,
         &proof,
+        None,
     );
 
     match result {
-        Ok((r_0, _)) => {
+        Ok((r_0, _, _)) => {
             assert_eq!(r_0.len(), ccs.s);
             // We can add more assertions here if needed
         }
@@ -2,10 +2,13 @@
 
 use ark_ff::{Field, PrimeField, Zero};
 use ark_std::{
+    cfg_into_iter,
     iter::{self, successors},
     iterable::Iterable,
 };
 use cyclotomic_rings::{rings::SuitableRing, rotation::rot_lin_combination};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use stark_rings::{cyclotomic_ring::CRT, OverField, PolyRing, Ring};
 use stark_rings_poly::mle::DenseMultilinearExtension;
 
@@ -13,10 +16,14 @@ use crate::{
     arith::{CCS, LCCCS},
     ark_base::*,
     commitment::Commitment,
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::DecompositionParamData,
     nifs::error::FoldingError,
     transcript::{Transcript, TranscriptWithShortChallenges},
-    utils::sumcheck::utils::build_eq_x_r,
+    utils::sumcheck::{
+        small_value::{as_small_integer, SmallValueTable},
+        split_eq::SplitEq,
+        virtual_polynomial::VirtualPolynomialInfo,
+    },
 };
 
 /// A trait for squeezing challenges (`alpha`, `beta`, `zeta`, `mu`) from a cryptographic sponge.
@@ -30,11 +37,9 @@ pub(crate) trait SqueezeAlphaBetaZetaMu<NTT: SuitableRing> {
     /// Extracts the cryptographic challenge vectors of provided length
     ///
     /// ### Arguments
+    /// - `params`: The decomposition parameters of the protocol.
     /// - `log_m`: The length of the $\beta$ challenge vector.
     ///
-    /// ### Type Parameters
-    /// - `P`: The decomposition parameters of the protocol.
-    ///
     /// ### Returns
     /// - `(Vec<NTT>, Vec<NTT>, Vec<NTT>, Vec<NTT>)`: A tuple containing four challenge vectors:
     ///   - `alpha`: A challenge vector of length $2 \cdot k$, where $k$ is defined in the decomposition parameters.
@@ -42,22 +47,24 @@ pub(crate) trait SqueezeAlphaBetaZetaMu<NTT: SuitableRing> {
     ///   - `zeta`: A challenge vector of length $2 \cdot k$, where $k$ is defined in the decomposition parameters.
     ///   - `mu`: A challenge vector of length $2 \cdot k$, where $k$ is defined in the decomposition parameters.
     ///
-    fn squeeze_alpha_beta_zeta_mu<P: DecompositionParams>(
+    fn squeeze_alpha_beta_zeta_mu(
         &mut self,
+        params: &DecompositionParamData,
         log_m: usize,
     ) -> (Vec<NTT>, Vec<NTT>, Vec<NTT>, Vec<NTT>);
 }
 
 impl<NTT: SuitableRing, T: Transcript<NTT>> SqueezeAlphaBetaZetaMu<NTT> for T {
-    fn squeeze_alpha_beta_zeta_mu<P: DecompositionParams>(
+    fn squeeze_alpha_beta_zeta_mu(
         &mut self,
+        params: &DecompositionParamData,
         log_m: usize,
     ) -> (Vec<NTT>, Vec<NTT>, Vec<NTT>, Vec<NTT>) {
         self.absorb_field_element(&<NTT::BaseRing as Field>::from_base_prime_field(
             <NTT::BaseRing as Field>::BasePrimeField::from_be_bytes_mod_order(b"alpha_s"),
         ));
         let alpha_s = self
-            .get_challenges(2 * P::K)
+            .get_challenges(2 * params.k())
             .into_iter()
             .map(|x| NTT::from(x))
             .collect::<Vec<_>>();
@@ -66,7 +73,7 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> SqueezeAlphaBetaZetaMu<NTT> for T {
             <NTT::BaseRing as Field>::BasePrimeField::from_be_bytes_mod_order(b"zeta_s"),
         ));
         let zeta_s = self
-            .get_challenges(2 * P::K)
+            .get_challenges(2 * params.k())
             .into_iter()
             .map(|x| NTT::from(x))
             .collect::<Vec<_>>();
@@ -75,7 +82,7 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> SqueezeAlphaBetaZetaMu<NTT> for T {
             <NTT::BaseRing as Field>::BasePrimeField::from_be_bytes_mod_order(b"mu_s"),
         ));
         let mut mu_s = self
-            .get_challenges((2 * P::K) - 1)
+            .get_challenges((2 * params.k()) - 1)
             .into_iter()
             .map(|x| NTT::from(x))
             .collect::<Vec<_>>(); // Note is one challenge less
@@ -103,9 +110,9 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> SqueezeAlphaBetaZetaMu<NTT> for T {
 /// # Type Parameters
 /// - `R`: A ring suitable to be used in the LatticeFold protocol.
 /// - `T`: A type implementing a cryptographic sponge construction.
-/// - `P`: The decomposition parameters of the protocol.
 ///
 /// # Arguments
+/// - `params`: The decomposition parameters of the protocol.
 /// - `transcript`: A mutable reference to the transcript `T` from which we squeeze the challenges.
 ///
 /// # Returns
@@ -113,18 +120,15 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> SqueezeAlphaBetaZetaMu<NTT> for T {
 ///   - The first element is a vector of challenges in coefficient form.
 ///   - The second element is the same vector of challenges in NTT form.
 ///
-pub(super) fn get_rhos<
-    R: SuitableRing,
-    T: TranscriptWithShortChallenges<R>,
-    P: DecompositionParams,
->(
+pub(super) fn get_rhos<R: SuitableRing, T: TranscriptWithShortChallenges<R>>(
+    params: &DecompositionParamData,
     transcript: &mut T,
 ) -> (Vec<R::CoefficientRepresentation>, Vec<R>) {
     transcript.absorb_field_element(&<R::BaseRing as Field>::from_base_prime_field(
         <R::BaseRing as Field>::BasePrimeField::from_be_bytes_mod_order(b"rho_s"),
     ));
 
-    let mut rhos_coeff = transcript.get_small_challenges((2 * P::K) - 1); // Note that we are missing the first element
+    let mut rhos_coeff = transcript.get_small_challenges((2 * params.k()) - 1); // Note that we are missing the first element
     rhos_coeff.push(R::CoefficientRepresentation::ONE);
     let rhos = CRT::elementwise_crt(rhos_coeff.clone());
     (rhos_coeff, rhos)
@@ -186,10 +190,12 @@ pub(super) fn get_rhos<
 ///
 /// # Returns
 ///
-/// - `Result<(Vec<RefCounter<DenseMultilinearExtension<NTT>>>, usize), FoldingError<NTT>>`  
+/// - `Result<(Vec<DenseMultilinearExtension<NTT>>, Vec<Vec<NTT>>, usize), FoldingError<NTT>>`  
 ///   - On success, returns a tuple containing:
-///     - A `Vec<RefCounter<DenseMultilinearExtension<NTT>>>`, the MLEs that make up the polynomial.
-///     - A `usize` of the degree of the final polynomial.
+///     - A `Vec<DenseMultilinearExtension<NTT>>`, the MLEs of $g_1$ and $g_3$ for the first and the second $k$.
+///       Followed by the MLEs of `f_hat_mles`, they make up the polynomial without its eq factors.
+///     - A `Vec<Vec<NTT>>` of the points of the eq factors, $\vec{r}_1$, $\vec{r}\_{k+1}$ and $\vec{\beta}$.
+///     - The terms multiplying the eq factors, see [`sumcheck_polynomial_terms`].
 ///
 /// # Errors
 ///
@@ -197,137 +203,296 @@ pub(super) fn get_rhos<
 ///
 /// $$
 #[allow(clippy::too_many_arguments)]
-pub(super) fn create_sumcheck_polynomial<NTT: OverField, DP: DecompositionParams>(
+pub(super) fn create_sumcheck_polynomial<NTT: SuitableRing>(
+    params: &DecompositionParamData,
     log_m: usize,
-    f_hat_mles: Vec<Vec<DenseMultilinearExtension<NTT>>>,
+    f_hat_mles: &[Vec<DenseMultilinearExtension<NTT>>],
     alpha_s: &[NTT],
     challenged_Ms_1: &DenseMultilinearExtension<NTT>,
     challenged_Ms_2: &DenseMultilinearExtension<NTT>,
     r_s: &[Vec<NTT>],
     beta_s: &[NTT],
     mu_s: &[NTT],
-) -> Result<(Vec<DenseMultilinearExtension<NTT>>, usize), FoldingError<NTT>> {
-    if alpha_s.len() != 2 * DP::K
-        || f_hat_mles.len() != 2 * DP::K
-        || r_s.len() != 2 * DP::K
+) -> Result<
+    (
+        Vec<DenseMultilinearExtension<NTT>>,
+        Vec<Vec<NTT>>,
+        Vec<VirtualPolynomialInfo<NTT>>,
+    ),
+    FoldingError<NTT>,
+> {
+    if alpha_s.len() != 2 * params.k()
+        || f_hat_mles.len() != 2 * params.k()
+        || r_s.len() != 2 * params.k()
         || beta_s.len() != log_m
-        || mu_s.len() != 2 * DP::K
+        || mu_s.len() != 2 * params.k()
     {
         return Err(FoldingError::IncorrectLength);
     }
 
     #[cfg(test)]
     {
-        if r_s[..DP::K].iter().any(|r| r != &r_s[0])
-            || r_s[DP::K..].iter().any(|r| r != &r_s[DP::K])
+        if r_s[..params.k()].iter().any(|r| r != &r_s[0])
+            || r_s[params.k()..].iter().any(|r| r != &r_s[params.k()])
         {
             return Err(FoldingError::SumcheckChallengeError);
         }
     }
 
-    let len = 2 + 2 + // g1 + g3
-        1 + f_hat_mles.len() * f_hat_mles[0].len(); // g2
-    let mut mles = Vec::with_capacity(len);
+    // g1 + g3, the MLEs of g2 are borrowed by the sumcheck prover
+    let mut mles = Vec::with_capacity(2);
 
-    // We assume here that decomposition subprotocol puts the same r challenge point
-    // into all decomposed linearized commitments
-    let r_i_eq = build_eq_x_r(&r_s[0])?;
     prepare_g1_and_3_k_mles_list(
         &mut mles,
-        r_i_eq.clone(),
-        &f_hat_mles[0..DP::K],
-        &alpha_s[0..DP::K],
+        &f_hat_mles[0..params.k()],
+        &alpha_s[0..params.k()],
         challenged_Ms_1,
     );
 
-    let r_i_eq = build_eq_x_r(&r_s[DP::K])?;
     prepare_g1_and_3_k_mles_list(
         &mut mles,
-        r_i_eq,
-        &f_hat_mles[DP::K..2 * DP::K],
-        &alpha_s[DP::K..2 * DP::K],
+        &f_hat_mles[params.k()..2 * params.k()],
+        &alpha_s[params.k()..2 * params.k()],
         challenged_Ms_2,
     );
 
-    // g2
-    let beta_eq_x = build_eq_x_r(beta_s)?;
-    prepare_g2_i_mle_list(&mut mles, beta_eq_x, f_hat_mles);
+    // We assume here that decomposition subprotocol puts the same r challenge point
+    // into all decomposed linearized commitments
+    let eq_points = vec![r_s[0].clone(), r_s[params.k()].clone(), beta_s.to_vec()];
 
-    let degree = 2 * DP::B_SMALL;
+    let terms = sumcheck_polynomial_terms(params, log_m, mu_s)?;
 
-    Ok((mles, degree))
+    Ok((mles, eq_points, terms))
 }
 
-/// Combines evaluations of MLE into evaluation of folding sumcheck polynomial
-///
-/// # Arguments
+/// The terms of the folding sumcheck polynomial multiplying its eq factors, over the MLEs produced
+/// by `create_sumcheck_polynomial` followed by the $\hat{f}$ multilinear extensions.
 ///
-/// - `vals: &[NTT]`:
-///   The evaluations of the multilinear extensions produced by the `create_sumcheck_polynomial` function
-/// - `mu_s: &[NTT]`
-///   The $\mu$ challenges
+/// The first two terms are the MLEs of $g_1 + g_3$ for the first and the second $k$, the third
+/// one is the range check
+/// $$
+/// \sum\_{i=1}^{2k} \sum\_{j=0}^{\tau - 1} \mu_i^{j+1} \cdot \mathrm{mle}\[\hat{f}\_{ij}\] \cdot
+/// \prod\_{b=1}^{b\_{small}-1} \left( \mathrm{mle}\[\hat{f}\_{ij}\]^2 - b^2 \right).
+/// $$
 ///
-///  # Returns
-///  - NTT:
-///    The value of the same evaluation point evaluated by the folding sumcheck polynomial
-pub(crate) fn sumcheck_polynomial_comb_fn<NTT: SuitableRing, P: DecompositionParams>(
-    vals: &[NTT],
+/// The prover combines the MLEs with them and the verifier derives the degree of the sumcheck
+/// from them, so that both agree on it.
+pub(super) fn sumcheck_polynomial_terms<NTT: SuitableRing>(
+    params: &DecompositionParamData,
+    log_m: usize,
     mu_s: &[NTT],
-) -> NTT {
+) -> Result<Vec<VirtualPolynomialInfo<NTT>>, FoldingError<NTT>> {
+    if mu_s.len() != 2 * params.k() {
+        return Err(FoldingError::IncorrectLength);
+    }
+
     let extension_degree = NTT::CoefficientRepresentation::dimension() / <NTT>::dimension();
+    let num_mles = 2 + mu_s.len() * extension_degree;
+
+    let mut g_1_and_3_first = VirtualPolynomialInfo::new(log_m, num_mles);
+    g_1_and_3_first.add_product(NTT::one(), [0])?;
+    let mut g_1_and_3_second = VirtualPolynomialInfo::new(log_m, num_mles);
+    g_1_and_3_second.add_product(NTT::one(), [1])?;
+
+    // The coefficients of x * prod_{b=1}^{b_small-1} (x^2 - b^2), lowest first
+    let mut range_coeffs = vec![NTT::zero(), NTT::one()];
+    for b in 1..params.b_small() {
+        let b_squared = NTT::from(b as u128 * b as u128);
+        let mut next = vec![NTT::zero(); range_coeffs.len() + 2];
+        for (m, &coeff) in range_coeffs.iter().enumerate() {
+            next[m] -= b_squared * coeff;
+            next[m + 2] += coeff;
+        }
+        range_coeffs = next;
+    }
+
+    let mut range_check = VirtualPolynomialInfo::new(log_m, num_mles);
+    for (i, &mu) in mu_s.iter().enumerate() {
+        for (d, mu_power) in successors(Some(mu), |&power| Some(power * mu))
+            .take(extension_degree)
+            .enumerate()
+        {
+            let f_i = 2 + i * extension_degree + d;
+            for (m, &coeff) in range_coeffs.iter().enumerate() {
+                if !coeff.is_zero() {
+                    range_check.add_product(mu_power * coeff, iter::repeat(f_i).take(m))?;
+                }
+            }
+        }
+    }
 
-    // Add eq_r * g1 * g3 for first k
-    let mut result = vals[0] * vals[1];
+    Ok(vec![g_1_and_3_first, g_1_and_3_second, range_check])
+}
 
-    // Add eq_r * g1 * g3 for second k
-    result += vals[2] * vals[3];
+/// The degree of the folding sumcheck polynomial without its eq factors.
+pub(super) fn sumcheck_polynomial_degree<NTT: OverField>(
+    terms: &[VirtualPolynomialInfo<NTT>],
+) -> usize {
+    terms
+        .iter()
+        .map(VirtualPolynomialInfo::degree)
+        .max()
+        .unwrap_or(0)
+}
 
-    // We have k * extension degree mles of b
-    // each one consists of (2 * small_b) -1 extensions
-    // We start at index 5
-    // Multiply each group of (2 * small_b) -1 extensions
-    // Then multiply by the eq_beta evaluation at index 4
-    for (k, mu) in mu_s.iter().enumerate() {
-        let mut inter_result = NTT::zero();
-        for d in (0..extension_degree).rev() {
-            let i = k * extension_degree + d;
+/// The degree of the folding sumcheck over `log_m` variables, the degree of its terms plus one
+/// for the eq factors.
+///
+/// The degree does not depend on the $\mu$ challenges, so the terms are built with $\mu_i = 1$.
+pub(crate) fn sumcheck_degree<NTT: SuitableRing>(
+    params: &DecompositionParamData,
+    log_m: usize,
+) -> Result<usize, FoldingError<NTT>> {
+    let mu_s = vec![NTT::one(); 2 * params.k()];
 
-            let f_i = vals[5 + i];
+    Ok(sumcheck_polynomial_degree(&sumcheck_polynomial_terms(params, log_m, &mu_s)?) + 1)
+}
 
-            if f_i.is_zero() {
-                if !inter_result.is_zero() {
-                    inter_result *= mu;
-                }
-                continue;
-            }
+/// Combines evaluations of MLE into evaluations of the terms of the folding sumcheck polynomial,
+/// without their eq factors
+///
+/// # Arguments
+///
+/// - `terms: &[VirtualPolynomialInfo<NTT>]`:
+///   The terms returned by `sumcheck_polynomial_terms`
+/// - `vals: &[NTT]`:
+///   The evaluations of the multilinear extensions produced by the `create_sumcheck_polynomial` function,
+///   followed by the evaluations of the $\hat{f}$ multilinear extensions
+/// - `out: &mut [NTT]`
+///   Where the terms multiplying the eq factors returned by `create_sumcheck_polynomial` are written
+pub(crate) fn sumcheck_polynomial_comb_fn<NTT: OverField>(
+    terms: &[VirtualPolynomialInfo<NTT>],
+    vals: &[NTT],
+    out: &mut [NTT],
+) {
+    for (term, out) in terms.iter().zip(out) {
+        *out = term.combine(vals);
+    }
+}
 
-            // start with eq_b
-            let mut eval = vals[4];
+/// Computes the message of the first round of the folding sumcheck, the evaluations on
+/// `0, ..., degree + 1`, given the MLEs and the eq points returned by `create_sumcheck_polynomial`.
+///
+/// In the first round, the slots of the $\hat{f}$ extensions on the evaluation points are small
+/// integers, so their range polynomial is looked up in a [`SmallValueTable`] instead of being
+/// multiplied out. Returns `None` when the table would cost more than it saves, or if a slot is
+/// not small, the round is then left to the sumcheck prover.
+pub(super) fn small_value_first_round<NTT: SuitableRing>(
+    params: &DecompositionParamData,
+    g_mles: &[DenseMultilinearExtension<NTT>],
+    f_hat_mles: &[Vec<DenseMultilinearExtension<NTT>>],
+    eq_points: &[Vec<NTT>],
+    mu_s: &[NTT],
+    degree: usize,
+) -> Option<Vec<NTT>> {
+    let nvars = eq_points.first()?.len();
+    if nvars == 0 || g_mles.len() != 2 || eq_points.len() != 3 || mu_s.len() != f_hat_mles.len() {
+        return None;
+    }
+    let num_points = degree + 2;
+    let max_abs = params.b_small().checked_sub(1)? as u64;
+    let bound = (2 * num_points as u64 - 1) * max_abs;
+    let f_hats: Vec<&DenseMultilinearExtension<NTT>> = f_hat_mles.iter().flatten().collect();
+    // mu_i^(j + 1) for the j-th extension of the i-th witness
+    let mu_powers: Vec<NTT> = f_hat_mles
+        .iter()
+        .zip(mu_s)
+        .flat_map(|(f_hat_mles_i, &mu)| {
+            successors(Some(mu), move |&power| Some(power * mu)).take(f_hat_mles_i.len())
+        })
+        .collect();
+    let half = 1usize << (nvars - 1);
+    if (2 * bound + 1) as usize > half * num_points * f_hats.len() {
+        return None;
+    }
 
-            let f_i_squared = f_i * f_i;
+    let table = SmallValueTable::new(bound, |x: NTT::BaseRing| {
+        (1..params.b_small()).fold(x, |p, j| p * (x * x - NTT::BaseRing::from((j * j) as u128)))
+    });
+
+    let splits: Vec<SplitEq<NTT>> = eq_points.iter().map(|p| SplitEq::new(p)).collect();
+    let tables: Vec<(&[NTT], &[NTT])> = splits.iter().map(SplitEq::tables).collect();
+    let (low_len, high_len) = (tables[0].0.len(), tables[0].1.len());
+    let eval = |mle: &DenseMultilinearExtension<NTT>, i: usize| {
+        mle.evaluations.get(i).copied().unwrap_or_else(NTT::zero)
+    };
+
+    let partial_sums: Option<Vec<Vec<Vec<NTT>>>> = cfg_into_iter!(0..high_len)
+        .map(|y_high| {
+            let mut sums = vec![vec![NTT::zero(); num_points]; 3];
+            let mut range = vec![NTT::zero(); num_points];
+            let mut slots = Vec::new();
+            for y_low in 0..low_len {
+                let y = y_low + y_high * low_len;
+                for ((sum, (low, _)), g) in sums.iter_mut().zip(&tables).zip(g_mles) {
+                    let (g_0, g_1) = (eval(g, 2 * y), eval(g, 2 * y + 1));
+                    let (weighted, step) = (low[y_low] * g_0, low[y_low] * (g_1 - g_0));
+                    successors(Some(weighted), |&v| Some(v + step))
+                        .zip(sum.iter_mut())
+                        .for_each(|(v, s)| *s += v);
+                }
 
-            for b in 1..<P>::B_SMALL {
-                let multiplicand = f_i_squared - NTT::from(b as u128 * b as u128);
-                if multiplicand.is_zero() {
-                    eval = NTT::zero();
-                    break;
+                range.iter_mut().for_each(|r| *r = NTT::zero());
+                for (f_hat, &mu_power) in f_hats.iter().zip(&mu_powers) {
+                    slots.clear();
+                    for (&f_0, &f_1) in eval(f_hat, 2 * y)
+                        .coeffs()
+                        .iter()
+                        .zip(eval(f_hat, 2 * y + 1).coeffs())
+                    {
+                        let f_0 = as_small_integer(&f_0, max_abs)?;
+                        slots.push((f_0, as_small_integer(&f_1, max_abs)? - f_0));
+                    }
+                    for (x, r) in range.iter_mut().enumerate() {
+                        let mut p = NTT::zero();
+                        for (p_s, &(f_0, step)) in p.coeffs_mut().iter_mut().zip(&slots) {
+                            *p_s = table.get(f_0 + x as i64 * step)?;
+                        }
+                        *r += mu_power * p;
+                    }
                 }
-                eval *= multiplicand
+                let low = tables[2].0[y_low];
+                sums[2]
+                    .iter_mut()
+                    .zip(&range)
+                    .for_each(|(s, &r)| *s += low * r);
+            }
+            for (sum, (_, high)) in sums.iter_mut().zip(&tables) {
+                sum.iter_mut().for_each(|s| *s *= high[y_high]);
             }
-            eval *= f_i;
-            inter_result += eval;
-            inter_result *= mu
+            Some(sums)
+        })
+        .collect();
+
+    let mut q = vec![vec![NTT::zero(); num_points]; 3];
+    for sums in partial_sums? {
+        for (q_k, sum) in q.iter_mut().zip(sums) {
+            q_k.iter_mut().zip(sum).for_each(|(q_kx, s)| *q_kx += s);
         }
-        result += inter_result;
     }
 
-    result
+    Some(
+        (0..num_points)
+            .map(|x| {
+                let point = NTT::from(x as u128);
+                splits
+                    .iter()
+                    .zip(&q)
+                    .fold(NTT::zero(), |acc, (split, q_k)| {
+                        acc + split.current(point) * q_k[x]
+                    })
+            })
+            .collect(),
+    )
 }
 
 /// Computes the grand sum from point 4 of the Latticefold folding protocol.
 ///
 /// # Arguments
 ///
+/// - `params: &DecompositionParamData`  
+///   The decomposition parameters of the protocol.
+///
 /// - `alpha_s: &[NTT]`  
 ///   A slice containing the $\alpha$ challenges.
 ///
@@ -366,7 +531,8 @@ pub(crate) fn sumcheck_polynomial_comb_fn<NTT: SuitableRing, P: DecompositionPar
 /// - `NTT`  
 ///   Returns the expected value of the sumcheck claim.
 ///
-pub(super) fn compute_sumcheck_claim_expected_value<NTT: Ring, P: DecompositionParams>(
+pub(super) fn compute_sumcheck_claim_expected_value<NTT: Ring>(
+    params: &DecompositionParamData,
     alpha_s: &[NTT],
     mu_s: &[NTT],
     theta_s: &[Vec<NTT>],
@@ -375,7 +541,7 @@ pub(super) fn compute_sumcheck_claim_expected_value<NTT: Ring, P: DecompositionP
     zeta_s: &[NTT],
     eta_s: &[Vec<NTT>],
 ) -> NTT {
-    (0..(2 * P::K))
+    (0..(2 * params.k()))
         .map(|i| {
             // Evaluation claims about f hats.
             let mut s_summand: NTT = successors(Some(alpha_s[i]), |alpha_power| {
@@ -392,7 +558,7 @@ pub(super) fn compute_sumcheck_claim_expected_value<NTT: Ring, P: DecompositionP
                     .map(|(mu_power, &theta)| {
                         mu_power
                             * theta
-                            * (1..P::B_SMALL)
+                            * (1..params.b_small())
                                 .map(|x| NTT::from(x as u128))
                                 .map(|j_hat| (theta - j_hat) * (theta + j_hat))
                                 .product::<NTT>()
@@ -523,7 +689,6 @@ pub(super) fn compute_v0_u0_x0_cm_0<NTT: SuitableRing>(
 /// Get the MLEs needed for $k$ g1 and g3 components of the sumcheck polynomial
 fn prepare_g1_and_3_k_mles_list<NTT: OverField>(
     mles: &mut Vec<DenseMultilinearExtension<NTT>>,
-    r_i_eq: DenseMultilinearExtension<NTT>,
     f_hat_mle_s: &[Vec<DenseMultilinearExtension<NTT>>],
     alpha_s: &[NTT],
     challenged_Ms: &DenseMultilinearExtension<NTT>,
@@ -541,17 +706,5 @@ fn prepare_g1_and_3_k_mles_list<NTT: OverField>(
 
     combined_mle += challenged_Ms;
 
-    mles.push(r_i_eq);
     mles.push(combined_mle);
 }
-/// Get the MLEs needed for one g2 component of the sumcheck polynomial
-fn prepare_g2_i_mle_list<NTT: OverField>(
-    mles: &mut Vec<DenseMultilinearExtension<NTT>>,
-    beta_eq_x: DenseMultilinearExtension<NTT>,
-    f_hat_mles: Vec<Vec<DenseMultilinearExtension<NTT>>>,
-) {
-    mles.push(beta_eq_x);
-    f_hat_mles
-        .into_iter()
-        .for_each(|mut fhms| mles.append(&mut fhms))
-}
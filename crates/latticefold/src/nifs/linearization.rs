@@ -1,18 +1,25 @@
 use cyclotomic_rings::rings::SuitableRing;
+use rand::RngCore;
 use stark_rings::OverField;
 use stark_rings_poly::mle::DenseMultilinearExtension;
 
 pub use self::structs::*;
-use self::utils::{compute_u, prepare_lin_sumcheck_polynomial, sumcheck_polynomial_comb_fn};
+use self::utils::{compute_u, sumcheck_degree, sumcheck_polynomial_info};
 use super::error::LinearizationError;
 use crate::{
     arith::{Instance, Witness, CCCS, CCS, LCCCS},
     ark_base::*,
     nifs::linearization::utils::SqueezeBeta,
-    transcript::Transcript,
+    pcs::PcsRing,
+    transcript::{Transcript, TranscriptWithShortChallenges},
     utils::{
         mle_helpers::{calculate_Mz_mles, evaluate_mles},
-        sumcheck::{utils::eq_eval, MLSumcheck, Proof, SumCheckError::SumCheckFailed},
+        sumcheck::{
+            utils::eq_eval,
+            zk::{MaskClaim, MaskCommitmentScheme, MaskEvalProof, MaskOpening, SumcheckMask},
+            MLSumcheck, Proof,
+            SumCheckError::SumCheckFailed,
+        },
     },
 };
 
@@ -77,48 +84,73 @@ pub trait LinearizationVerifier<NTT: OverField, T: Transcript<NTT>> {
 
 impl<NTT: SuitableRing, T: Transcript<NTT>> LFLinearizationProver<NTT, T> {
     /// Step 2 of Fig 5: Construct polynomial $g$ and generate $\beta$ challenges.
+    ///
+    /// Returns the $\beta$ challenges and the MLEs of $M_j \mathbf{z}$, the eq factor of $g$
+    /// is left to the sumcheck prover.
     fn construct_polynomial_g(
         z_ccs: &[NTT],
         transcript: &mut impl Transcript<NTT>,
         ccs: &CCS<NTT>,
-    ) -> Result<
-        (
-            Vec<DenseMultilinearExtension<NTT>>,
-            usize,
-            Vec<DenseMultilinearExtension<NTT>>,
-        ),
-        LinearizationError<NTT>,
-    > {
+    ) -> Result<(Vec<NTT>, Vec<DenseMultilinearExtension<NTT>>), LinearizationError<NTT>> {
         // Generate beta challenges from Step 1
         let beta_s = transcript.squeeze_beta_challenges(ccs.s);
 
         // Prepare MLEs
         let Mz_mles = calculate_Mz_mles::<NTT, LinearizationError<NTT>>(ccs, z_ccs)?;
 
-        // Construct the sumcheck polynomial g
-        let (g_mles, g_degree) =
-            prepare_lin_sumcheck_polynomial(&ccs.c, &ccs.d, &Mz_mles, &ccs.S, &beta_s)?;
-
-        Ok((g_mles, g_degree, Mz_mles))
+        Ok((beta_s, Mz_mles))
     }
 
     /// Step 2: Run linearization sum-check protocol.
-    fn generate_sumcheck_proof(
+    ///
+    /// The sumcheck is run on $g$ without materializing $eq(\vec{\beta}, \vec{\mathbf{x}})$. With
+    /// `masking`, it is masked, and the mask is returned with the claim about it and its opening.
+    #[allow(clippy::type_complexity)]
+    fn generate_sumcheck_proof<Rng: rand::Rng + ?Sized>(
         transcript: &mut impl Transcript<NTT>,
-        mles: Vec<DenseMultilinearExtension<NTT>>,
-        nvars: usize,
-        degree: usize,
-        comb_fn: impl Fn(&[NTT]) -> NTT + Sync + Send,
-    ) -> Result<(Proof<NTT>, Vec<NTT>), LinearizationError<NTT>> {
-        let (sum_check_proof, prover_state) =
-            MLSumcheck::prove_as_subprotocol(transcript, mles, nvars, degree, comb_fn);
+        Mz_mles: &[DenseMultilinearExtension<NTT>],
+        beta_s: &[NTT],
+        ccs: &CCS<NTT>,
+        masking: Option<(&MaskCommitmentScheme<NTT>, &mut Rng)>,
+    ) -> Result<
+        (
+            Proof<NTT>,
+            Vec<NTT>,
+            Option<(SumcheckMask<NTT>, MaskClaim<NTT>, MaskOpening<NTT>)>,
+        ),
+        LinearizationError<NTT>,
+    > {
+        let mles = Mz_mles.iter().collect::<Vec<_>>();
+        let eq_points = [beta_s.to_vec()];
+        let info = sumcheck_polynomial_info(ccs)?;
+        let degree = info.degree();
+        let comb_fn = |vals: &[NTT], out: &mut [NTT]| out[0] = info.combine(vals);
+        let (sum_check_proof, prover_state, mask) = match masking {
+            Some((scheme, rng)) => {
+                let (proof, prover_state, opening) = MLSumcheck::prove_zk_with_eqs(
+                    transcript, &mles, ccs.s, degree, &eq_points, comb_fn, scheme, rng,
+                )?;
+                let claim = MaskClaim::new(&proof.mask, &prover_state);
+                (
+                    proof.sumcheck,
+                    prover_state,
+                    Some((proof.mask, claim, opening)),
+                )
+            }
+            None => {
+                let (proof, prover_state) = MLSumcheck::prove_with_eqs(
+                    transcript, &mles, ccs.s, degree, &eq_points, comb_fn,
+                );
+                (proof, prover_state, None)
+            }
+        };
         let point_r = prover_state
             .randomness
             .into_iter()
             .map(|x| x.into())
             .collect::<Vec<NTT>>();
 
-        Ok((sum_check_proof, point_r))
+        Ok((sum_check_proof, point_r, mask))
     }
 
     /// Step 3: the mle evaluations that the prover sends to the verifier.
@@ -148,19 +180,73 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> LinearizationProver<NTT, T>
         transcript: &mut impl Transcript<NTT>,
         ccs: &CCS<NTT>,
     ) -> Result<(LCCCS<NTT>, LinearizationProof<NTT>), LinearizationError<NTT>> {
+        let (lcccs, proof, _) =
+            Self::prove_with_masking(cm_i, wit, transcript, ccs, None::<(_, &mut dyn RngCore)>)?;
+
+        Ok((lcccs, proof))
+    }
+}
+
+impl<NTT: PcsRing, T: Transcript<NTT>> LFLinearizationProver<NTT, T> {
+    /// [`LinearizationProver::prove`] with a masked sumcheck, see
+    /// [`MLSumcheck::prove_zk_with_eqs`].
+    ///
+    /// The mask of the sumcheck is committed with `scheme` and its evaluation is proved with
+    /// [`MaskClaim::prove`] after the step. The masking only hides the round messages: the
+    /// evaluations `v` and `u` of the proof are sent in the clear.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_masked<Rng: rand::Rng + ?Sized>(
+        cm_i: &CCCS<NTT>,
+        wit: &Witness<NTT>,
+        transcript: &mut (impl TranscriptWithShortChallenges<NTT> + Clone),
+        ccs: &CCS<NTT>,
+        scheme: &MaskCommitmentScheme<NTT>,
+        rng: &mut Rng,
+    ) -> Result<
+        (
+            LCCCS<NTT>,
+            LinearizationProof<NTT>,
+            SumcheckMask<NTT>,
+            MaskEvalProof<NTT>,
+        ),
+        LinearizationError<NTT>,
+    > {
+        let (lcccs, proof, mask) =
+            Self::prove_with_masking(cm_i, wit, transcript, ccs, Some((scheme, &mut *rng)))?;
+        let (mask, claim, opening) = mask.expect("the sumcheck is masked");
+        let mask_proof = claim.prove(scheme, &opening, transcript, rng)?;
+
+        Ok((lcccs, proof, mask, mask_proof))
+    }
+}
+
+impl<NTT: SuitableRing, T: Transcript<NTT>> LFLinearizationProver<NTT, T> {
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn prove_with_masking<Rng: rand::Rng + ?Sized>(
+        cm_i: &CCCS<NTT>,
+        wit: &Witness<NTT>,
+        transcript: &mut impl Transcript<NTT>,
+        ccs: &CCS<NTT>,
+        masking: Option<(&MaskCommitmentScheme<NTT>, &mut Rng)>,
+    ) -> Result<
+        (
+            LCCCS<NTT>,
+            LinearizationProof<NTT>,
+            Option<(SumcheckMask<NTT>, MaskClaim<NTT>, MaskOpening<NTT>)>,
+        ),
+        LinearizationError<NTT>,
+    > {
         // Step 1: Generate beta challenges (done in construct_polynomial_g because they are not needed
         // elsewhere.
 
         // Step 2: Sum check protocol.
         // z_ccs vector, i.e. concatenation x || 1 || w.
         let z_ccs = cm_i.get_z_vector(&wit.w_ccs);
-        let (g_mles, g_degree, Mz_mles) = Self::construct_polynomial_g(&z_ccs, transcript, ccs)?;
-
-        let comb_fn = |vals: &[NTT]| -> NTT { sumcheck_polynomial_comb_fn(vals, ccs) };
+        let (beta_s, Mz_mles) = Self::construct_polynomial_g(&z_ccs, transcript, ccs)?;
 
         // Run sumcheck protocol.
-        let (sumcheck_proof, point_r) =
-            Self::generate_sumcheck_proof(transcript, g_mles, ccs.s, g_degree, comb_fn)?;
+        let (sumcheck_proof, point_r, mask) =
+            Self::generate_sumcheck_proof(transcript, &Mz_mles, &beta_s, ccs, masking)?;
 
         // Step 3: Compute v, u_vector.
         let (point_r, v, u) = Self::compute_evaluation_vectors(wit, &point_r, &Mz_mles)?;
@@ -185,31 +271,50 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> LinearizationProver<NTT, T>
             h: NTT::one(),
         };
 
-        Ok((lcccs, linearization_proof))
+        Ok((lcccs, linearization_proof, mask))
     }
 }
 
 impl<NTT: SuitableRing, T: Transcript<NTT>> LFLinearizationVerifier<NTT, T> {
     fn verify_sumcheck_proof(
         proof: &LinearizationProof<NTT>,
+        mask: Option<&SumcheckMask<NTT>>,
         transcript: &mut impl Transcript<NTT>,
         ccs: &CCS<NTT>,
-    ) -> Result<(Vec<NTT>, NTT), LinearizationError<NTT>> {
-        // The polynomial has degree <= ccs.d + 1 and log_m (ccs.s) vars.
+    ) -> Result<(Vec<NTT>, NTT, Option<MaskClaim<NTT>>), LinearizationError<NTT>> {
+        // The polynomial is the product of the eq factor with the CCS polynomial, over log_m
+        // (ccs.s) vars.
         let nvars = ccs.s;
-        let degree = ccs.d + 1;
-
-        let subclaim = MLSumcheck::verify_as_subprotocol(
-            transcript,
-            nvars,
-            degree,
-            NTT::zero(),
-            &proof.linearization_sumcheck,
-        )?;
+        let degree = sumcheck_degree(ccs)?;
+
+        let (subclaim, mask_claim) = match mask {
+            Some(mask) => {
+                let (subclaim, mask_claim) = MLSumcheck::verify_masked(
+                    transcript,
+                    nvars,
+                    degree,
+                    NTT::zero(),
+                    mask,
+                    &proof.linearization_sumcheck,
+                )?;
+                (subclaim, Some(mask_claim))
+            }
+            None => (
+                MLSumcheck::verify_as_subprotocol(
+                    transcript,
+                    nvars,
+                    degree,
+                    NTT::zero(),
+                    &proof.linearization_sumcheck,
+                )?,
+                None,
+            ),
+        };
 
         Ok((
             subclaim.point.into_iter().map(|x| x.into()).collect(),
             subclaim.expected_evaluation,
+            mask_claim,
         ))
     }
 
@@ -225,12 +330,8 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> LFLinearizationVerifier<NTT, T> {
         ccs: &CCS<NTT>,
     ) -> Result<(), LinearizationError<NTT>> {
         let e = eq_eval(point_r, beta_s)?;
-        let should_equal_s = e * ccs // e * (\sum c_i * \Pi_{j \in S_i} u_j)
-            .c
-            .iter()
-            .enumerate()
-            .map(|(i, &c)| c * ccs.S[i].iter().map(|&j| proof.u[j]).product::<NTT>()) // c_i * \Pi_{j \in S_i} u_j
-            .sum::<NTT>(); // \sum c_i * \Pi_{j \in S_i} u_j
+        // e * (\sum c_i * \Pi_{j \in S_i} u_j)
+        let should_equal_s = e * sumcheck_polynomial_info(ccs)?.evaluate(&proof.u)?;
 
         if should_equal_s != s {
             return Err(LinearizationError::SumCheckError(SumCheckFailed(
@@ -267,11 +368,47 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> LinearizationVerifier<NTT, T>
         transcript: &mut impl Transcript<NTT>,
         ccs: &CCS<NTT>,
     ) -> Result<LCCCS<NTT>, LinearizationError<NTT>> {
+        let (lcccs, _) = Self::verify_with_masking(cm_i, proof, None, transcript, ccs)?;
+
+        Ok(lcccs)
+    }
+}
+
+impl<NTT: PcsRing, T: Transcript<NTT>> LFLinearizationVerifier<NTT, T> {
+    /// Verifies a proof of [`LFLinearizationProver::prove_masked`] with the sumcheck mask `mask`
+    /// and the proof `mask_proof` of its evaluation, committed with `scheme`.
+    pub fn verify_masked(
+        cm_i: &CCCS<NTT>,
+        proof: &LinearizationProof<NTT>,
+        mask: &SumcheckMask<NTT>,
+        mask_proof: &MaskEvalProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+        ccs: &CCS<NTT>,
+        scheme: &MaskCommitmentScheme<NTT>,
+    ) -> Result<LCCCS<NTT>, LinearizationError<NTT>> {
+        let (lcccs, mask_claim) =
+            Self::verify_with_masking(cm_i, proof, Some(mask), transcript, ccs)?;
+        mask_claim
+            .expect("the sumcheck is masked")
+            .verify(scheme, mask_proof, transcript)?;
+
+        Ok(lcccs)
+    }
+}
+
+impl<NTT: SuitableRing, T: Transcript<NTT>> LFLinearizationVerifier<NTT, T> {
+    pub(crate) fn verify_with_masking(
+        cm_i: &CCCS<NTT>,
+        proof: &LinearizationProof<NTT>,
+        mask: Option<&SumcheckMask<NTT>>,
+        transcript: &mut impl Transcript<NTT>,
+        ccs: &CCS<NTT>,
+    ) -> Result<(LCCCS<NTT>, Option<MaskClaim<NTT>>), LinearizationError<NTT>> {
         // Step 1: Generate the beta challenges.
         let beta_s = transcript.squeeze_beta_challenges(ccs.s);
 
         //Step 2: The sumcheck.
-        let (point_r, s) = Self::verify_sumcheck_proof(proof, transcript, ccs)?;
+        let (point_r, s, mask_claim) = Self::verify_sumcheck_proof(proof, mask, transcript, ccs)?;
 
         Self::verify_evaluation_claim(&beta_s, &point_r, s, proof, ccs)?;
 
@@ -280,6 +417,9 @@ impl<NTT: SuitableRing, T: Transcript<NTT>> LinearizationVerifier<NTT, T>
         transcript.absorb_slice(&proof.u);
 
         // Step 5: Output z_o
-        Ok(Self::prepare_verifier_output(cm_i, point_r, proof))
+        Ok((
+            Self::prepare_verifier_output(cm_i, point_r, proof),
+            mask_claim,
+        ))
     }
 }
@@ -18,7 +18,7 @@ use crate::{
         test_params::{BabyBearDP, FrogDP, GoldilocksDP, StarkDP},
         DecompositionParams,
     },
-    nifs::linearization::utils::{sumcheck_polynomial_comb_fn, SqueezeBeta},
+    nifs::linearization::utils::SqueezeBeta,
     transcript::poseidon::PoseidonTranscript,
 };
 
@@ -76,7 +76,7 @@ fn test_construct_polynomial() {
     let z_ccs = cm_i.get_z_vector(&wit.w_ccs);
 
     let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
-    let (_, g_degree, mz_mles) =
+    let (beta_s, mz_mles) =
         LFLinearizationProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::construct_polynomial_g(
             &z_ccs,
             &mut transcript,
@@ -85,10 +85,8 @@ fn test_construct_polynomial() {
         .unwrap();
 
     // Check dimensions
+    assert_eq!(beta_s.len(), ccs.s);
     assert_eq!(mz_mles.len(), ccs.t);
-
-    // Check degree of g
-    assert!(g_degree <= ccs.q + 1)
 }
 
 #[test]
@@ -102,7 +100,7 @@ fn test_generate_sumcheck() {
     let z_ccs = cm_i.get_z_vector(&wit.w_ccs);
 
     let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
-    let (g_mles, g_degree, _) =
+    let (beta_s, mz_mles) =
         LFLinearizationProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::construct_polynomial_g(
             &z_ccs,
             &mut transcript,
@@ -110,15 +108,13 @@ fn test_generate_sumcheck() {
         )
         .unwrap();
 
-    let comb_fn = |vals: &[RqNTT]| -> RqNTT { sumcheck_polynomial_comb_fn::<RqNTT>(vals, &ccs) };
-
-    let (_, point_r) =
+    let (_, point_r, _) =
         LFLinearizationProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::generate_sumcheck_proof(
             &mut transcript,
-            g_mles,
-            ccs.s,
-            g_degree,
-            comb_fn,
+            &mz_mles,
+            &beta_s,
+            &ccs,
+            None::<(_, &mut dyn RngCore)>,
         )
         .unwrap();
 
@@ -134,7 +130,7 @@ fn prepare_test_vectors<RqNTT: SuitableRing, CS: LatticefoldChallengeSet<RqNTT>>
     let z_ccs = cm_i.get_z_vector(&wit.w_ccs);
 
     let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
-    let (g_mles, g_degree, Mz_mles) =
+    let (beta_s, Mz_mles) =
         LFLinearizationProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::construct_polynomial_g(
             &z_ccs,
             &mut transcript,
@@ -142,15 +138,13 @@ fn prepare_test_vectors<RqNTT: SuitableRing, CS: LatticefoldChallengeSet<RqNTT>>
         )
         .unwrap();
 
-    let comb_fn = |vals: &[RqNTT]| -> RqNTT { sumcheck_polynomial_comb_fn::<RqNTT>(vals, ccs) };
-
-    let (_, point_r) =
+    let (_, point_r, _) =
         LFLinearizationProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::generate_sumcheck_proof(
             &mut transcript,
-            g_mles,
-            ccs.s,
-            g_degree,
-            comb_fn,
+            &Mz_mles,
+            &beta_s,
+            ccs,
+            None::<(_, &mut dyn RngCore)>,
         )
         .unwrap();
 
@@ -271,13 +265,14 @@ fn test_verify_sumcheck_proof() {
     let result =
         LFLinearizationVerifier::<RqNTT, PoseidonTranscript<RqNTT, CS>>::verify_sumcheck_proof(
             &proof,
+            None,
             &mut verify_transcript,
             &ccs,
         );
 
     // Instead of unwrapping, handle the result
     match result {
-        Ok((point_r, _)) => {
+        Ok((point_r, _, _)) => {
             assert_eq!(point_r.len(), ccs.s);
             // We know that point_r from lcccs is valid
             assert_eq!(point_r, lcccs.r);
@@ -308,9 +303,10 @@ fn test_verify_evaluation_claim() {
     let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
     let beta_s = transcript.squeeze_beta_challenges(ccs.s);
 
-    let (point_r, s) =
+    let (point_r, s, _) =
         LFLinearizationVerifier::<RqNTT, PoseidonTranscript<RqNTT, CS>>::verify_sumcheck_proof(
             &proof,
+            None,
             &mut transcript,
             &ccs,
         )
@@ -323,6 +319,23 @@ fn test_verify_evaluation_claim() {
         );
 
     assert!(result.is_ok());
+
+    // The number of evaluations is checked against the CCS
+    let mut short_proof = proof.clone();
+    short_proof.u.pop();
+    let result =
+        LFLinearizationVerifier::<RqNTT, PoseidonTranscript<RqNTT, CS>>::verify_evaluation_claim(
+            &beta_s,
+            &point_r,
+            s,
+            &short_proof,
+            &ccs,
+        );
+
+    assert!(matches!(
+        result,
+        Err(LinearizationError::ArithmeticError(_))
+    ));
 }
 
 #[test]
@@ -394,3 +407,36 @@ fn test_verify_invalid_proof() {
 
     assert!(result.is_err());
 }
+
+#[test]
+fn test_full_prove_permuted_multisets() {
+    type RqNTT = GoldilocksRqNTT;
+    type CS = GoldilocksChallengeSet;
+    type DP = GoldilocksDP;
+    let n = WIT_LEN * DP::L;
+    let (wit, cm_i, mut ccs, _) = setup_test_environment::<RqNTT, DP>(None, n);
+
+    // The same constraints, with the multisets no longer listing the matrices in order
+    ccs.S.reverse();
+    ccs.c.reverse();
+
+    let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
+    let (_, proof) = LFLinearizationProver::<RqNTT, PoseidonTranscript<RqNTT, CS>>::prove(
+        &cm_i,
+        &wit,
+        &mut transcript,
+        &ccs,
+    )
+    .unwrap();
+
+    let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
+    assert!(
+        LFLinearizationVerifier::<RqNTT, PoseidonTranscript<RqNTT, CS>>::verify(
+            &cm_i,
+            &proof,
+            &mut transcript,
+            &ccs,
+        )
+        .is_ok()
+    );
+}
@@ -7,7 +7,10 @@ use crate::{
     ark_base::Vec,
     nifs::{error::LinearizationError, CCS},
     transcript::Transcript,
-    utils::{mle_helpers::evaluate_mles, sumcheck::utils::build_eq_x_r},
+    utils::{
+        mle_helpers::evaluate_mles,
+        sumcheck::{utils::build_eq_x_r, virtual_polynomial::VirtualPolynomialInfo},
+    },
 };
 
 /// Computes the evaluation of the MLEs of $\\{ M_j \mathbf{z} \mid j = 1, 2, \dots, t \\}$ at the sumcheck challenge point.
@@ -44,66 +47,55 @@ pub fn compute_u<NTT: OverField>(
 ///  
 /// # Parameters:
 ///
-/// * `c` (`&[NTT]`): The second multiplicand of the polynomial is a linear combination of products of lists of MLEs, c is the coefficients of the lists
+/// * `d` (`&usize`): The degree of the CCS
 ///
 /// * `M_mles` (`&[DenseMultilinearExtension<NTT>]`): MLEs that the polynomial is constructed from
 ///
-/// * `S` (`&[Vec<usize>]`): ] indices for the MLE lists
-///
 /// * `beta_s` (`&[NTT]`): Randomness
 ///
 /// # Returns:
 ///
-/// * The MLEs which form the polynomial
+/// * The MLEs which form the polynomial, i.e. `M_mles` in their original order,
+///   so that a multiset index `j` refers to the `j`th MLE, followed by $eq(\vec{\beta}, \vec{\mathbf{x}})$
 /// * The max degree of the polynomial
 ///
 /// # Errors:
 /// * Will return an error if any of the MLEs are of the wrong size
 ///
 pub fn prepare_lin_sumcheck_polynomial<NTT: OverField>(
-    c: &[NTT],
     d: &usize,
     M_mles: &[DenseMultilinearExtension<NTT>],
-    S: &[Vec<usize>],
     beta_s: &[NTT],
 ) -> Result<(Vec<DenseMultilinearExtension<NTT>>, usize), LinearizationError<NTT>> {
-    let len = 1 + c
-        .iter()
-        .enumerate()
-        .filter(|(_, c)| !c.is_zero())
-        .map(|(i, _)| S[i].len())
-        .sum::<usize>();
-
-    let mut mles = Vec::with_capacity(len);
-
-    for (i, _) in c.iter().enumerate().filter(|(_, c)| !c.is_zero()) {
-        for &j in &S[i] {
-            mles.push(M_mles[j].clone());
-        }
-    }
+    let mut mles = Vec::with_capacity(M_mles.len() + 1);
 
+    mles.extend_from_slice(M_mles);
     mles.push(build_eq_x_r(beta_s)?);
 
     Ok((mles, d + 1))
 }
 
-pub(crate) fn sumcheck_polynomial_comb_fn<NTT: SuitableRing>(vals: &[NTT], ccs: &CCS<NTT>) -> NTT {
-    let mut result = NTT::zero();
-    'outer: for (i, &c) in ccs.c.iter().enumerate() {
-        if c.is_zero() {
-            continue;
-        }
-        let mut term = c;
-        for &j in &ccs.S[i] {
-            if vals[j].is_zero() {
-                continue 'outer;
-            }
-            term *= vals[j];
-        }
-        result += term;
+/// The linearization polynomial without its eq factor,
+/// $\sum_i c_i \prod_{j \in S_i} \mathrm{mle}[M_j \mathbf{z}]$, over the MLEs of $M_j \mathbf{z}$.
+///
+/// Both the prover and the verifier derive the degree of the sumcheck from it.
+pub(crate) fn sumcheck_polynomial_info<NTT: OverField>(
+    ccs: &CCS<NTT>,
+) -> Result<VirtualPolynomialInfo<NTT>, LinearizationError<NTT>> {
+    let mut info = VirtualPolynomialInfo::new(ccs.s, ccs.t);
+    for (&c, S_i) in ccs.c.iter().zip(&ccs.S) {
+        info.add_product(c, S_i.iter().copied())?;
     }
-    // eq() is the last term added
-    result * vals[vals.len() - 1]
+
+    Ok(info)
+}
+
+/// The degree of the linearization sumcheck, the degree of [`sumcheck_polynomial_info`] plus one
+/// for the eq factor.
+pub(crate) fn sumcheck_degree<NTT: OverField>(
+    ccs: &CCS<NTT>,
+) -> Result<usize, LinearizationError<NTT>> {
+    Ok(sumcheck_polynomial_info(ccs)?.degree() + 1)
 }
 
 pub(crate) trait SqueezeBeta<NTT: SuitableRing> {
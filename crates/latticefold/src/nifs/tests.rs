@@ -1,3 +1,4 @@
+use ark_serialize::CanonicalSerialize;
 use ark_std::{test_rng, vec::Vec};
 use cyclotomic_rings::{challenge_set::LatticefoldChallengeSet, rings::SuitableRing};
 use rand::Rng;
@@ -5,12 +6,17 @@ use rand::Rng;
 use crate::{
     arith::{r1cs::get_test_z_split, tests::get_test_ccs, Witness, CCCS, CCS, LCCCS},
     commitment::AjtaiCommitmentScheme,
-    decomposition_parameters::DecompositionParams,
+    decomposition_parameters::{DecompositionParamData, DecompositionParams},
     nifs::{
-        linearization::{LFLinearizationProver, LinearizationProver},
+        check_decomposed_norms, check_folded_norm,
+        error::{LatticefoldError, ProtocolStep},
+        folding,
+        linearization::{self, LFLinearizationProver, LinearizationProver},
         NIFSProver, NIFSVerifier,
     },
+    pcs::PcsRing,
     transcript::{poseidon::PoseidonTranscript, TranscriptWithShortChallenges},
+    utils::sumcheck::zk::{MaskCommitmentScheme, MaskingPolynomial},
 };
 
 fn setup_test_environment<
@@ -116,6 +122,241 @@ fn test_nifs_verify<
     assert!(result.is_ok());
 }
 
+fn test_nifs_compressed_proof<
+    RqNTT: SuitableRing,
+    CS: LatticefoldChallengeSet<RqNTT>,
+    DP: DecompositionParams,
+    T: TranscriptWithShortChallenges<RqNTT>,
+>(
+    kappa: usize,
+    n: usize,
+    wit_len: usize,
+) {
+    let (acc, w_acc, cm_i, w_i, ccs, scheme) =
+        setup_test_environment::<RqNTT, DP, CS>(kappa, n, wit_len);
+
+    let mut prover_transcript = PoseidonTranscript::<RqNTT, CS>::default();
+    let mut verifier_transcript = PoseidonTranscript::<RqNTT, CS>::default();
+
+    let (_, _, proof) = NIFSProver::<RqNTT, DP, T>::prove(
+        &acc,
+        &w_acc,
+        &cm_i,
+        &w_i,
+        &mut prover_transcript,
+        &ccs,
+        &scheme,
+    )
+    .unwrap();
+    let compressed = proof.compress::<DP>(&ccs).unwrap();
+    assert!(compressed.compressed_size() < proof.compressed_size());
+
+    let result = NIFSVerifier::<RqNTT, DP, T>::verify(
+        &acc,
+        &cm_i,
+        &compressed,
+        &mut verifier_transcript,
+        &ccs,
+    );
+
+    assert!(result.is_ok());
+}
+
+fn test_nifs_runtime_params<
+    RqNTT: SuitableRing,
+    CS: LatticefoldChallengeSet<RqNTT>,
+    DP: DecompositionParams,
+    T: TranscriptWithShortChallenges<RqNTT>,
+>(
+    kappa: usize,
+    n: usize,
+    wit_len: usize,
+) {
+    let (acc, w_acc, cm_i, w_i, ccs, scheme) =
+        setup_test_environment::<RqNTT, DP, CS>(kappa, n, wit_len);
+    let params = DecompositionParamData::new::<RqNTT>(DP::B, DP::L, DP::B_SMALL, DP::K).unwrap();
+    assert_eq!(params, DP::DATA);
+
+    let mut prover_transcript = PoseidonTranscript::<RqNTT, CS>::default();
+    let mut verifier_transcript = PoseidonTranscript::<RqNTT, CS>::default();
+
+    let (_, _, proof) = NIFSProver::<RqNTT, DP, T>::prove_with_params(
+        &params,
+        &acc,
+        &w_acc,
+        &cm_i,
+        &w_i,
+        &mut prover_transcript,
+        &ccs,
+        &scheme,
+    )
+    .unwrap();
+
+    // A proof produced with runtime parameters is accepted by the compile-time verifier
+    let result =
+        NIFSVerifier::<RqNTT, DP, T>::verify(&acc, &cm_i, &proof, &mut verifier_transcript, &ccs);
+
+    assert!(result.is_ok());
+}
+
+fn test_nifs_masked<
+    RqNTT: PcsRing,
+    CS: LatticefoldChallengeSet<RqNTT>,
+    DP: DecompositionParams,
+    T: TranscriptWithShortChallenges<RqNTT>,
+>(
+    kappa: usize,
+    n: usize,
+    wit_len: usize,
+) {
+    let (acc, w_acc, cm_i, w_i, ccs, scheme) =
+        setup_test_environment::<RqNTT, DP, CS>(kappa, n, wit_len);
+    let mut rng = test_rng();
+    let degree = usize::max(
+        linearization::utils::sumcheck_degree(&ccs).unwrap(),
+        folding::sumcheck_degree::<RqNTT>(&DP::DATA, ccs.s).unwrap(),
+    );
+    let mask_scheme = MaskCommitmentScheme::rand(
+        kappa,
+        MaskingPolynomial::<RqNTT>::num_coeffs(ccs.s, degree),
+        DP::B,
+        2,
+        1,
+        &mut rng,
+    )
+    .unwrap();
+
+    let (folded, _, proof) = NIFSProver::<RqNTT, DP, T>::prove_masked(
+        &acc,
+        &w_acc,
+        &cm_i,
+        &w_i,
+        &mut PoseidonTranscript::<RqNTT, CS>::default(),
+        &ccs,
+        &scheme,
+        &mask_scheme,
+        &mut rng,
+    )
+    .unwrap();
+
+    let verified = NIFSVerifier::<RqNTT, DP, T>::verify_masked(
+        &acc,
+        &cm_i,
+        &proof,
+        &mut PoseidonTranscript::<RqNTT, CS>::default(),
+        &ccs,
+        &mask_scheme,
+    )
+    .unwrap();
+    assert_eq!(verified, folded);
+
+    // A wrong mask evaluation is rejected
+    let mut wrong_proof = proof.clone();
+    wrong_proof.folding_mask.evaluation += RqNTT::ONE;
+    assert!(NIFSVerifier::<RqNTT, DP, T>::verify_masked(
+        &acc,
+        &cm_i,
+        &wrong_proof,
+        &mut PoseidonTranscript::<RqNTT, CS>::default(),
+        &ccs,
+        &mask_scheme,
+    )
+    .is_err());
+
+    // The sumchecks are masked
+    let (_, _, plain) = NIFSProver::<RqNTT, DP, T>::prove(
+        &acc,
+        &w_acc,
+        &cm_i,
+        &w_i,
+        &mut PoseidonTranscript::<RqNTT, CS>::default(),
+        &ccs,
+        &scheme,
+    )
+    .unwrap();
+    assert_ne!(
+        proof.proof.linearization_proof.linearization_sumcheck,
+        plain.linearization_proof.linearization_sumcheck
+    );
+    assert_ne!(
+        proof.proof.folding_proof.pointshift_sumcheck_proof,
+        plain.folding_proof.pointshift_sumcheck_proof
+    );
+
+    // The masked proof does not verify as a plain one
+    assert!(NIFSVerifier::<RqNTT, DP, T>::verify(
+        &acc,
+        &cm_i,
+        &proof.proof,
+        &mut PoseidonTranscript::<RqNTT, CS>::default(),
+        &ccs,
+    )
+    .is_err());
+}
+
+fn test_nifs_norm_guard<
+    RqNTT: SuitableRing,
+    CS: LatticefoldChallengeSet<RqNTT>,
+    DP: DecompositionParams,
+    T: TranscriptWithShortChallenges<RqNTT>,
+>(
+    kappa: usize,
+    n: usize,
+    wit_len: usize,
+) {
+    let (acc, mut w_acc, cm_i, w_i, ccs, scheme) =
+        setup_test_environment::<RqNTT, DP, CS>(kappa, n, wit_len);
+    w_acc.f_coeff[0] = RqNTT::CoefficientRepresentation::from(DP::B as u64);
+
+    let mut transcript = PoseidonTranscript::<RqNTT, CS>::default();
+
+    let result = NIFSProver::<RqNTT, DP, T>::prove(
+        &acc,
+        &w_acc,
+        &cm_i,
+        &w_i,
+        &mut transcript,
+        &ccs,
+        &scheme,
+    );
+
+    assert!(matches!(
+        result,
+        Err(LatticefoldError::NormBoundExceeded(
+            ProtocolStep::Linearization,
+            _,
+            bound
+        )) if bound == DP::B
+    ));
+}
+
+fn test_nifs_step_norm_guards<RqNTT: SuitableRing, DP: DecompositionParams>(wit_len: usize) {
+    let zero = Witness::<RqNTT>::from_w_ccs::<DP>(vec![RqNTT::zero(); wit_len]);
+    let params = DP::DATA;
+
+    // A decomposed witness reaching b
+    let mut wit_s: Vec<_> = (0..DP::K).map(|_| zero.clone()).collect();
+    assert!(check_decomposed_norms(&params, &wit_s).is_ok());
+    wit_s[DP::K - 1].f_coeff[0] = RqNTT::CoefficientRepresentation::from(DP::B_SMALL as u64);
+    assert!(matches!(
+        check_decomposed_norms(&params, &wit_s),
+        Err(LatticefoldError::NormBoundExceeded(
+            ProtocolStep::Decomposition,
+            _,
+            bound
+        )) if bound == DP::B_SMALL as u128
+    ));
+
+    // A folded witness reaching B
+    let mut wit = zero;
+    assert!(check_folded_norm(&params, &wit).is_ok());
+    wit.f_coeff[0] = RqNTT::CoefficientRepresentation::from(DP::B as u64);
+    assert!(matches!(
+        check_folded_norm(&params, &wit),
+        Err(LatticefoldError::NormBoundExceeded(ProtocolStep::Folding, _, bound)) if bound == DP::B
+    ));
+}
+
 mod e2e_tests {
     use super::*;
     mod stark {
@@ -173,6 +414,31 @@ mod e2e_tests {
         fn test_verify() {
             test_nifs_verify::<RqNTT, CS, DP, T>(KAPPA, N, WIT_LEN);
         }
+
+        #[test]
+        fn test_runtime_params() {
+            test_nifs_runtime_params::<RqNTT, CS, DP, T>(KAPPA, N, WIT_LEN);
+        }
+
+        #[test]
+        fn test_norm_guard() {
+            test_nifs_norm_guard::<RqNTT, CS, DP, T>(KAPPA, N, WIT_LEN);
+        }
+
+        #[test]
+        fn test_step_norm_guards() {
+            test_nifs_step_norm_guards::<RqNTT, DP>(WIT_LEN);
+        }
+
+        #[test]
+        fn test_compressed_proof() {
+            test_nifs_compressed_proof::<RqNTT, CS, DP, T>(KAPPA, N, WIT_LEN);
+        }
+
+        #[test]
+        fn test_masked() {
+            test_nifs_masked::<RqNTT, CS, DP, T>(KAPPA, N, WIT_LEN);
+        }
     }
 
     mod babybear {
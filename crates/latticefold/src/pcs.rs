@@ -0,0 +1,810 @@
+//! A lattice-based polynomial commitment scheme for multilinear evaluation claims.
+//!
+//! The scheme opens the MLE of a vector $f \in R^N$ committed with an Ajtai key $A$ as
+//! $\mathrm{cm} = A f$, such as the witness of an [`LCCCS`](crate::arith::LCCCS), without
+//! sending $f$. It follows the one-level structure of
+//! [Greyhound](https://eprint.iacr.org/2024/1293), with the Ajtai commitment `cm` in place of
+//! the outer commitment: $f$ is arranged as $m$ columns $f_1, \ldots, f_m$ of length $n$, with
+//! $N = m \cdot n$ and $m, n \approx \sqrt{N}$.
+//!
+//! * The prover gadget-decomposes each column, $s_i = G^{-1}(f_i)$, commits to it with the inner
+//!   Ajtai matrix $D$, $t_i = D s_i$, and sends the decomposed inner commitments
+//!   $\hat{t}_i = G^{-1}(t_i)$.
+//! * Given challenges $\rho$ and $\gamma$, the evaluation claim $\mathrm{mle}\[f\](r) = v$ and the
+//!   consistency claim $\rho^\top A f = \rho^\top \mathrm{cm}$ are batched into the sumcheck
+//!   $$\sum_{x} \mathrm{mle}\[f\](x) \cdot \left(\mathrm{eq}(r, x) +
+//!   \gamma \cdot \mathrm{mle}\[\rho^\top A\](x)\right) = v + \gamma \cdot \rho^\top \mathrm{cm},$$
+//!   which reduces them to the evaluation of $\mathrm{mle}\[f\]$ at a random point $r'$.
+//! * $\mathrm{mle}\[f\](r')$ is the evaluation at the high variables of $r'$ of the column
+//!   evaluations $y_i = \mathrm{mle}\[f_i\](r'_{\mathrm{low}})$. The prover sends them, receives
+//!   short challenges $c_i$ and answers with the short vector $z = \sum_i c_i s_i$. The verifier
+//!   checks $D z = \sum_i c_i G \hat{t}_i$, $\mathrm{mle}\[G z\](r'_{\mathrm{low}}) =
+//!   \sum_i c_i y_i$ and the norms of $z$ and $\hat{t}$.
+//!
+//! The proof consists of $O(\sqrt{N})$ ring elements. The verifier evaluates
+//! $\mathrm{mle}\[\rho^\top A\]$, which takes time linear in the size of the key.
+//!
+//! The argument binds the opened vector to `cm` but does not bound its norm: like any Ajtai
+//! commitment, `cm` is only binding for short vectors. With [`LatticePcs::rand_with_bound`], the
+//! columns are decomposed into the fewest digits that represent the vectors of norm below a bound
+//! $B$, so that the norm check of $z$ also bounds the norm of $f$, up to the slack of the folding
+//! challenges as for any folded opening.
+//!
+//! [`LatticePcs::prove_lcccs`] decides an accumulated [`LCCCS`](crate::arith::LCCCS) with such a
+//! scheme. At the challenge point $r$ of the instance, the `v` claims on the slices of
+//! $\hat{f}$ determine $\mathrm{mle}\[f\](r)$, and each `u` claim
+//! $\mathrm{mle}\[M_j z\](r) = u_j$ is a linear claim
+//! $\langle G^\top M_j^\top \mathrm{eq}(r, \cdot), f \rangle = u_j - p_j$, $p_j$ being the
+//! contribution of the public part of $z$. The linear claims are batched into the same sumcheck.
+
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::slice;
+use cyclotomic_rings::rings::{
+    BabyBearRingNTT, FrogRingNTT, GoldilocksRingNTT, StarkRingNTT, SuitableRing,
+};
+use num_bigint::BigUint;
+use stark_rings::{
+    balanced_decomposition::{GadgetDecompose, GadgetRecompose},
+    cyclotomic_ring::{CRT, ICRT},
+    OverField, PolyRing, Ring,
+};
+use stark_rings_poly::{mle::DenseMultilinearExtension, polynomials::ArithErrors};
+use thiserror::Error;
+
+use crate::{
+    arith::{Witness, CCS, LCCCS},
+    ark_base::*,
+    commitment::{AjtaiCommitmentScheme, Commitment, CommitmentError},
+    decomposition_parameters::{gadget_length, DecompositionParamData},
+    transcript::TranscriptWithShortChallenges,
+    utils::{
+        mle_helpers::{Evaluate, MleEvaluationError},
+        norms::{l1_norm, linf_norm},
+        sumcheck::{
+            utils::{build_eq_x_r_vec, eq_eval},
+            MLSumcheck, Proof, SumCheckError,
+        },
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Errors that can arise in the polynomial commitment scheme.
+#[derive(Debug, Error)]
+pub enum PcsError<R: Ring> {
+    #[error("invalid parameters: {0}")]
+    InvalidParameters(String),
+    #[error("the vector has length {0}, more than {1}")]
+    VectorTooLong(usize, usize),
+    #[error("the point has {0} variables, expected {1}")]
+    WrongNumberOfVariables(usize, usize),
+    #[error("the proof has the wrong shape")]
+    MalformedProof,
+    #[error("the instance has the wrong shape: {0}")]
+    MalformedInstance(String),
+    #[error("the column evaluations do not match the sumcheck claim")]
+    EvaluationMismatch,
+    #[error("the folded column does not open the combined inner commitments")]
+    InnerCommitmentMismatch,
+    #[error("the folded column does not match the column evaluations")]
+    InnerProductMismatch,
+    #[error("the proof vectors exceed their norm bounds")]
+    NormBoundExceeded,
+    #[error("sum check failed in the evaluation reduction: {0}")]
+    SumCheckError(#[from] SumCheckError<R>),
+    #[error("ajtai commitment error: {0}")]
+    CommitmentError(#[from] CommitmentError),
+    #[error("arithmetic error: {0}")]
+    ArithError(#[from] ArithErrors),
+    #[error("mle evaluation failed: {0}")]
+    EvaluationError(#[from] MleEvaluationError),
+}
+
+/// An evaluation proof of [`LatticePcs`].
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EvalProof<NTT: OverField> {
+    /// The decomposed inner commitments.
+    pub t_hat: Vec<NTT>,
+    /// The sumcheck reducing the evaluation and consistency claims to an evaluation at $r'$.
+    pub sumcheck: Proof<NTT>,
+    /// The evaluations $\mathrm{mle}\[f_i\](r'_{\mathrm{low}})$ of the columns.
+    pub y: Vec<NTT>,
+    /// The folded decomposed column $\sum_i c_i s_i$.
+    pub z: Vec<NTT>,
+}
+
+/// A proof of [`LatticePcs::prove_lcccs`].
+pub type LcccsProof<NTT> = EvalProof<NTT>;
+
+/// A ring with a bound on the growth of the coefficients of a product, which the verifier of
+/// [`LatticePcs`] needs to bound the norm of the folded column.
+pub trait PcsRing: SuitableRing {
+    /// The expansion factor of the coefficient form, i.e. the smallest $\gamma$ such that
+    /// $\lVert a b \rVert_\infty \le \gamma \lVert a \rVert_1 \lVert b \rVert_\infty$
+    /// for all $a, b$.
+    const PRODUCT_EXPANSION: u128;
+}
+
+impl PcsRing for GoldilocksRingNTT {
+    /// The reduction modulo $X^{2m} - X^m + 1$ at most doubles the coefficients.
+    const PRODUCT_EXPANSION: u128 = 2;
+}
+
+impl PcsRing for BabyBearRingNTT {
+    /// The reduction modulo $X^{2m} - X^m + 1$ at most doubles the coefficients.
+    const PRODUCT_EXPANSION: u128 = 2;
+}
+
+impl PcsRing for StarkRingNTT {
+    /// The reduction modulo $X^d + 1$ only negates coefficients.
+    const PRODUCT_EXPANSION: u128 = 1;
+}
+
+impl PcsRing for FrogRingNTT {
+    /// The reduction modulo $X^d + 1$ only negates coefficients.
+    const PRODUCT_EXPANSION: u128 = 1;
+}
+
+/// The public parameters of the polynomial commitment scheme for vectors of length up to
+/// $2^{\mathrm{num\\_vars}}$.
+#[derive(Clone, Debug)]
+pub struct LatticePcs<NTT: PcsRing> {
+    num_vars: usize,
+    /// Base of the gadget decompositions.
+    b: u128,
+    /// Number of digits of the gadget decompositions.
+    l: usize,
+    /// Number of digits of the decomposed columns, `l` unless the vectors are bounded.
+    digits: usize,
+    /// Bound on the norm of the committed vectors, if any.
+    bound: Option<u128>,
+    /// Inner Ajtai matrix, committing to a decomposed column.
+    inner: AjtaiCommitmentScheme<NTT>,
+}
+
+impl<NTT: PcsRing> LatticePcs<NTT> {
+    /// Samples parameters for vectors of length up to `2^num_vars`.
+    ///
+    /// `kappa` is the height of the inner Ajtai matrix and `b` is the base of the gadget
+    /// decompositions.
+    pub fn rand<Rng: rand::Rng + ?Sized>(
+        num_vars: usize,
+        kappa: usize,
+        b: u128,
+        rng: &mut Rng,
+    ) -> Result<Self, PcsError<NTT>> {
+        if num_vars == 0 || b < 2 || kappa == 0 {
+            return Err(PcsError::InvalidParameters(format!(
+                "num_vars = {num_vars}, b = {b}, kappa = {kappa}"
+            )));
+        }
+
+        let l = gadget_length::<NTT>(b);
+        let n = 1 << low_vars(num_vars);
+
+        Ok(Self {
+            num_vars,
+            b,
+            l,
+            digits: l,
+            bound: None,
+            inner: AjtaiCommitmentScheme::rand(kappa, n * l, rng),
+        })
+    }
+
+    /// Samples parameters for vectors of length up to `2^num_vars` and norm below `bound`.
+    ///
+    /// The columns are decomposed into the fewest digits in base `b` representing the vectors of
+    /// norm below `bound`, which the verifier checks through the norm of the folded column.
+    pub fn rand_with_bound<Rng: rand::Rng + ?Sized>(
+        num_vars: usize,
+        kappa: usize,
+        b: u128,
+        bound: u128,
+        rng: &mut Rng,
+    ) -> Result<Self, PcsError<NTT>> {
+        if num_vars == 0 || b < 2 || kappa == 0 || bound == 0 {
+            return Err(PcsError::InvalidParameters(format!(
+                "num_vars = {num_vars}, b = {b}, kappa = {kappa}, bound = {bound}"
+            )));
+        }
+
+        // Balanced digits in base b represent the integers of absolute value at most b^d / 2
+        let l = gadget_length::<NTT>(b);
+        let digits = (1..l)
+            .find(|&d| BigUint::from(b).pow(d as u32) >= BigUint::from(bound) * 2u32)
+            .unwrap_or(l);
+        let n = 1 << low_vars(num_vars);
+
+        Ok(Self {
+            num_vars,
+            b,
+            l,
+            digits,
+            bound: Some(bound),
+            inner: AjtaiCommitmentScheme::rand(kappa, n * digits, rng),
+        })
+    }
+
+    /// The number of variables of the committed MLEs.
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// The length of a column.
+    fn n(&self) -> usize {
+        1 << low_vars(self.num_vars)
+    }
+
+    /// The number of columns.
+    fn m(&self) -> usize {
+        1 << (self.num_vars - low_vars(self.num_vars))
+    }
+
+    /// Proves that `mle[f](point) = eval` for the commitment `key.commit_ntt(f)`, where `eval`
+    /// is returned along with the proof.
+    pub fn prove(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        f: &[NTT],
+        point: &[NTT],
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    ) -> Result<(NTT, EvalProof<NTT>), PcsError<NTT>> {
+        self.check_shape(key, point)?;
+        let cm = key.commit_ntt(f)?;
+        let (s, t_hat) = self.decompose(f)?;
+
+        let mut f = f.to_vec();
+        f.resize(1 << self.num_vars, NTT::zero());
+        let eval = (&f).evaluate(point)?;
+
+        let (rho, gamma) = absorb_statement(transcript, &cm, point, &eval, &t_hat);
+        let g = self.batch(key, point, &rho, gamma, &[])?;
+
+        Ok((eval, self.open(f, &s, t_hat, g, transcript)?))
+    }
+
+    /// Verifies that `mle[f](point) = eval` for the vector `f` committed in `cm` with `key`.
+    pub fn verify(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        cm: &Commitment<NTT>,
+        point: &[NTT],
+        eval: &NTT,
+        proof: &EvalProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    ) -> Result<(), PcsError<NTT>> {
+        self.check_shape(key, point)?;
+        check_commitment(key, cm)?;
+        self.check_proof_shape(proof)?;
+
+        let (rho, gamma) = absorb_statement(transcript, cm, point, eval, &proof.t_hat);
+        let claimed_sum = batched_sum(cm, eval, &rho, gamma, &[]);
+
+        self.check_opening(key, point, &rho, gamma, &[], claimed_sum, proof, transcript)
+    }
+
+    /// Proves that `wit` satisfies the accumulated instance `lcccs` of `ccs`: `lcccs.cm` commits
+    /// to `wit.f` with `key`, `wit.f` is below the bound of the scheme and the `v` and `u` claims
+    /// hold at `lcccs.r`.
+    ///
+    /// The scheme must be sampled with [`LatticePcs::rand_with_bound`] for the bound $B$ of
+    /// `params`, and have `ccs.s` variables.
+    pub fn prove_lcccs(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        ccs: &CCS<NTT>,
+        params: &DecompositionParamData,
+        lcccs: &LCCCS<NTT>,
+        wit: &Witness<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    ) -> Result<LcccsProof<NTT>, PcsError<NTT>> {
+        self.check_lcccs_shape(key, ccs, params, lcccs)?;
+        let (s, t_hat) = self.decompose(&wit.f)?;
+
+        let mut f = wit.f.clone();
+        f.resize(1 << self.num_vars, NTT::zero());
+
+        let (rho, gamma) = absorb_lcccs(transcript, lcccs, &t_hat);
+        let claims = linearized_claims(ccs, params, lcccs, 1 << self.num_vars)?;
+        let g = self.batch(key, &lcccs.r, &rho, gamma, &claims)?;
+
+        self.open(f, &s, t_hat, g, transcript)
+    }
+
+    /// Verifies a proof of [`LatticePcs::prove_lcccs`] for the accumulated instance `lcccs` of
+    /// `ccs`.
+    pub fn verify_lcccs(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        ccs: &CCS<NTT>,
+        params: &DecompositionParamData,
+        lcccs: &LCCCS<NTT>,
+        proof: &LcccsProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    ) -> Result<(), PcsError<NTT>> {
+        self.check_lcccs_shape(key, ccs, params, lcccs)?;
+        check_commitment(key, &lcccs.cm)?;
+        self.check_proof_shape(proof)?;
+
+        let (rho, gamma) = absorb_lcccs(transcript, lcccs, &proof.t_hat);
+        let claims = linearized_claims(ccs, params, lcccs, 1 << self.num_vars)?;
+        let eval = evaluation_from_f_hat(&lcccs.v);
+        let claimed_sum = batched_sum(&lcccs.cm, &eval, &rho, gamma, &claims);
+
+        self.check_opening(
+            key,
+            &lcccs.r,
+            &rho,
+            gamma,
+            &claims,
+            claimed_sum,
+            proof,
+            transcript,
+        )
+    }
+
+    /// The vector $g = \mathrm{eq}(r, \cdot) + \gamma \rho^\top A + \sum_j \gamma^{j + 2} g_j$
+    /// batching the evaluation claim at `point`, the consistency claim and the linear claims
+    /// $\langle g_j, f \rangle$ of `claims`.
+    fn batch(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        point: &[NTT],
+        rho: &[NTT],
+        gamma: NTT,
+        claims: &[(Vec<NTT>, NTT)],
+    ) -> Result<Vec<NTT>, PcsError<NTT>> {
+        let mut g = build_eq_x_r_vec(point)?;
+        let rows = combine_rows(key, rho, 1 << self.num_vars);
+        let mut gamma_j = gamma;
+        for h in ark_std::iter::once(&rows).chain(claims.iter().map(|(g_j, _)| g_j)) {
+            g.iter_mut()
+                .zip(h)
+                .for_each(|(g_k, h_k)| *g_k += gamma_j * h_k);
+            gamma_j *= gamma;
+        }
+
+        Ok(g)
+    }
+
+    /// Runs the sumcheck of $\sum_x \mathrm{mle}\[f\](x) \cdot \mathrm{mle}\[g\](x)$ and
+    /// opens $\mathrm{mle}\[f\]$ at its final point, for the columns `s` of `f` decomposed into
+    /// `t_hat`.
+    fn open(
+        &self,
+        f: Vec<NTT>,
+        s: &[Vec<NTT>],
+        t_hat: Vec<NTT>,
+        g: Vec<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    ) -> Result<EvalProof<NTT>, PcsError<NTT>> {
+        let mles = vec![
+            DenseMultilinearExtension::from_evaluations_slice(self.num_vars, &f),
+            DenseMultilinearExtension::from_evaluations_vec(self.num_vars, g),
+        ];
+        let (sumcheck, prover_state) = MLSumcheck::prove_as_subprotocol(
+            transcript,
+            mles,
+            self.num_vars,
+            2,
+            |vals: &[NTT]| vals[0] * vals[1],
+        );
+        let point_r: Vec<NTT> = prover_state
+            .randomness
+            .into_iter()
+            .map(From::from)
+            .collect();
+
+        let n = self.n();
+        let y = f
+            .chunks(n)
+            .map(|f_i| f_i.evaluate(&point_r[..low_vars(self.num_vars)]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        transcript.absorb_slice(&y);
+        let c: Vec<NTT> = CRT::elementwise_crt(transcript.get_small_challenges(self.m()));
+
+        let mut z = vec![NTT::zero(); n * self.digits];
+        for (s_i, c_i) in s.iter().zip(&c) {
+            z.iter_mut()
+                .zip(s_i)
+                .for_each(|(z_j, s_ij)| *z_j += *c_i * s_ij);
+        }
+
+        Ok(EvalProof {
+            t_hat,
+            sumcheck,
+            y,
+            z,
+        })
+    }
+
+    /// Checks an opening of [`LatticePcs::open`] for the claimed sum of the sumcheck and the
+    /// vector $g$ of [`LatticePcs::batch`].
+    #[allow(clippy::too_many_arguments)]
+    fn check_opening(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        point: &[NTT],
+        rho: &[NTT],
+        gamma: NTT,
+        claims: &[(Vec<NTT>, NTT)],
+        claimed_sum: NTT,
+        proof: &EvalProof<NTT>,
+        transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    ) -> Result<(), PcsError<NTT>> {
+        let (m, kappa, l, digits) = (self.m(), self.inner.kappa(), self.l, self.digits);
+        let subclaim = MLSumcheck::verify_as_subprotocol(
+            transcript,
+            self.num_vars,
+            2,
+            claimed_sum,
+            &proof.sumcheck,
+        )?;
+        let point_r: Vec<NTT> = subclaim.point.into_iter().map(From::from).collect();
+        let (point_low, point_high) = point_r.split_at(low_vars(self.num_vars));
+
+        let mut g = eq_eval(point, &point_r)?;
+        let mut gamma_j = gamma;
+        g += gamma_j * combine_rows(key, rho, 1 << self.num_vars).evaluate(&point_r)?;
+        for (g_j, _) in claims {
+            gamma_j *= gamma;
+            g += gamma_j * g_j.evaluate(&point_r)?;
+        }
+        if (&proof.y).evaluate(point_high)? * g != subclaim.expected_evaluation {
+            return Err(PcsError::EvaluationMismatch);
+        }
+
+        transcript.absorb_slice(&proof.y);
+        let c_coeff = transcript.get_small_challenges(m);
+        let c: Vec<NTT> = CRT::elementwise_crt(c_coeff.clone());
+
+        // The digits are at most b / 2, so z = sum_i c_i s_i is at most
+        // PRODUCT_EXPANSION * b / 2 * sum_i |c_i|_1
+        let t_hat_coeff: Vec<NTT::CoefficientRepresentation> =
+            ICRT::elementwise_icrt(proof.t_hat.clone());
+        let z_coeff: Vec<NTT::CoefficientRepresentation> = ICRT::elementwise_icrt(proof.z.clone());
+        let z_bound = BigUint::from(NTT::PRODUCT_EXPANSION * (self.b / 2))
+            * c_coeff
+                .iter()
+                .map(|c_i| l1_norm(slice::from_ref(c_i)))
+                .sum::<BigUint>();
+        if linf_norm(&t_hat_coeff) >= BigUint::from(self.b) || linf_norm(&z_coeff) > z_bound {
+            return Err(PcsError::NormBoundExceeded);
+        }
+
+        let mut combined_t = vec![NTT::zero(); kappa];
+        for (t_hat_i, c_i) in proof.t_hat.chunks(kappa * l).zip(&c) {
+            let t_i = t_hat_i.to_vec().gadget_recompose(self.b, l);
+            combined_t
+                .iter_mut()
+                .zip(t_i)
+                .for_each(|(x, t_ij)| *x += *c_i * t_ij);
+        }
+        if self.inner.commit_ntt(&proof.z)?.as_ref() != combined_t.as_slice() {
+            return Err(PcsError::InnerCommitmentMismatch);
+        }
+
+        let folded_column = proof.z.gadget_recompose(self.b, digits);
+        let combined_y = c
+            .iter()
+            .zip(&proof.y)
+            .fold(NTT::zero(), |acc, (c_i, y_i)| acc + *c_i * y_i);
+        if folded_column.evaluate(point_low)? != combined_y {
+            return Err(PcsError::InnerProductMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the vectors of `proof` have the lengths of the scheme.
+    fn check_proof_shape(&self, proof: &EvalProof<NTT>) -> Result<(), PcsError<NTT>> {
+        let (n, m, kappa) = (self.n(), self.m(), self.inner.kappa());
+        if proof.y.len() != m
+            || proof.t_hat.len() != m * kappa * self.l
+            || proof.z.len() != n * self.digits
+        {
+            return Err(PcsError::MalformedProof);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the scheme bounds the vectors by the bound $B$ of `params` and that `lcccs`
+    /// is an instance of `ccs` at a challenge point.
+    fn check_lcccs_shape(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        ccs: &CCS<NTT>,
+        params: &DecompositionParamData,
+        lcccs: &LCCCS<NTT>,
+    ) -> Result<(), PcsError<NTT>> {
+        if self.bound != Some(params.b()) {
+            return Err(PcsError::InvalidParameters(format!(
+                "the scheme bounds the vectors by {:?}, expected B = {}",
+                self.bound,
+                params.b()
+            )));
+        }
+        self.check_shape(key, &lcccs.r)?;
+
+        let tau = NTT::CoefficientRepresentation::dimension() / NTT::dimension();
+        if lcccs.v.len() != tau {
+            return Err(PcsError::MalformedInstance(format!(
+                "{} v claims, expected {tau}",
+                lcccs.v.len()
+            )));
+        }
+        if lcccs.u.len() != ccs.t || lcccs.x_w.len() != ccs.l {
+            return Err(PcsError::MalformedInstance(format!(
+                "{} u claims and {} public inputs, expected {} and {}",
+                lcccs.u.len(),
+                lcccs.x_w.len(),
+                ccs.t,
+                ccs.l
+            )));
+        }
+        // The v claims determine the evaluation of f only at a point with scalar coordinates
+        if !lcccs
+            .r
+            .iter()
+            .all(|r_k| r_k.coeffs().iter().all(|x| *x == r_k.coeffs()[0]))
+        {
+            return Err(PcsError::MalformedInstance(
+                "r is not a challenge point".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `key` commits to vectors of at most `2^num_vars` entries and that `point`
+    /// has `num_vars` variables.
+    fn check_shape(
+        &self,
+        key: &AjtaiCommitmentScheme<NTT>,
+        point: &[NTT],
+    ) -> Result<(), PcsError<NTT>> {
+        if key.width() > 1 << self.num_vars {
+            return Err(PcsError::VectorTooLong(key.width(), 1 << self.num_vars));
+        }
+        if point.len() != self.num_vars {
+            return Err(PcsError::WrongNumberOfVariables(point.len(), self.num_vars));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the decomposed columns $s_i$ and the decomposed inner commitments $\hat{t}$.
+    fn decompose(&self, f: &[NTT]) -> Result<(Vec<Vec<NTT>>, Vec<NTT>), PcsError<NTT>> {
+        let (n, m) = (self.n(), self.m());
+        if f.len() > n * m {
+            return Err(PcsError::VectorTooLong(f.len(), n * m));
+        }
+        if let Some(bound) = self.bound {
+            let f_coeff: Vec<NTT::CoefficientRepresentation> = ICRT::elementwise_icrt(f.to_vec());
+            if linf_norm(&f_coeff) >= BigUint::from(bound) {
+                return Err(PcsError::NormBoundExceeded);
+            }
+        }
+
+        let mut s = Vec::with_capacity(m);
+        let mut t_hat = Vec::with_capacity(m * self.inner.kappa() * self.l);
+        for i in 0..m {
+            let mut f_i: Vec<NTT> = f.iter().skip(i * n).take(n).copied().collect();
+            f_i.resize(n, NTT::zero());
+
+            let s_i = self.gadget_decompose(f_i, self.digits);
+            let t_i = self.inner.commit_ntt(&s_i)?;
+            t_hat.extend(self.gadget_decompose(t_i.as_ref().to_vec(), self.l));
+            s.push(s_i);
+        }
+
+        Ok((s, t_hat))
+    }
+
+    fn gadget_decompose(&self, v: Vec<NTT>, digits: usize) -> Vec<NTT> {
+        let coeff: Vec<NTT::CoefficientRepresentation> = ICRT::elementwise_icrt(v);
+        CRT::elementwise_crt(coeff.gadget_decompose(self.b, digits))
+    }
+}
+
+/// The number of variables indexing the entries of a column.
+fn low_vars(num_vars: usize) -> usize {
+    num_vars.div_ceil(2)
+}
+
+/// The combination $\rho^\top A$ of the rows of `key`, padded with zeros to `len` entries.
+fn combine_rows<NTT: SuitableRing>(
+    key: &AjtaiCommitmentScheme<NTT>,
+    rho: &[NTT],
+    len: usize,
+) -> Vec<NTT> {
+    let mut h = vec![NTT::zero(); len];
+    for (row, rho_k) in key.matrix().vals.iter().zip(rho) {
+        h.iter_mut()
+            .zip(row)
+            .for_each(|(h_j, a_kj)| *h_j += *rho_k * a_kj);
+    }
+
+    h
+}
+
+/// Checks that `cm` is a commitment of `key`.
+fn check_commitment<NTT: SuitableRing>(
+    key: &AjtaiCommitmentScheme<NTT>,
+    cm: &Commitment<NTT>,
+) -> Result<(), PcsError<NTT>> {
+    if cm.as_ref().len() != key.kappa() {
+        return Err(CommitmentError::WrongCommitmentLength(cm.as_ref().len(), key.kappa()).into());
+    }
+
+    Ok(())
+}
+
+/// The claimed sum $v + \gamma \rho^\top \mathrm{cm} + \sum_j \gamma^{j + 2} c_j$ of the
+/// sumcheck of [`LatticePcs::batch`], `c_j` being the values of the linear claims.
+fn batched_sum<NTT: SuitableRing>(
+    cm: &Commitment<NTT>,
+    eval: &NTT,
+    rho: &[NTT],
+    gamma: NTT,
+    claims: &[(Vec<NTT>, NTT)],
+) -> NTT {
+    let mut sum = *eval;
+    let mut gamma_j = gamma;
+    sum += gamma_j
+        * rho
+            .iter()
+            .zip(cm.as_ref())
+            .fold(NTT::zero(), |acc, (rho_k, cm_k)| acc + *rho_k * cm_k);
+    for (_, c_j) in claims {
+        gamma_j *= gamma;
+        sum += gamma_j * c_j;
+    }
+
+    sum
+}
+
+/// The `u` claims of `lcccs` as linear claims on its witness $f$, with vectors of `len` entries.
+///
+/// With $c_j = M_j^\top \mathrm{eq}(r, \cdot)$, $\mathrm{mle}\[M_j z\](r) = \langle c_j, z
+/// \rangle$. The witness part of $z$ is the gadget recomposition $G f$, so the claim on $f$ has
+/// the vector $G^\top c_j$ and the value $u_j$ minus the contribution of `x_w` and `h`.
+fn linearized_claims<NTT: SuitableRing>(
+    ccs: &CCS<NTT>,
+    params: &DecompositionParamData,
+    lcccs: &LCCCS<NTT>,
+    len: usize,
+) -> Result<Vec<(Vec<NTT>, NTT)>, PcsError<NTT>> {
+    let eq = build_eq_x_r_vec(&lcccs.r)?;
+    let public: Vec<NTT> = lcccs.x_w.iter().copied().chain([lcccs.h]).collect();
+    let mut powers = Vec::with_capacity(params.l());
+    let mut power = NTT::ONE;
+    for _ in 0..params.l() {
+        powers.push(power);
+        power *= NTT::from(params.b());
+    }
+
+    ccs.M
+        .iter()
+        .zip(&lcccs.u)
+        .map(|(M_j, u_j)| {
+            let mut c = vec![NTT::zero(); ccs.n];
+            for (row, eq_row) in M_j.coeffs.iter().zip(&eq) {
+                for &(a, col) in row {
+                    c[col] += *eq_row * a;
+                }
+            }
+
+            let (c_public, c_w) = c.split_at(public.len());
+            let p_j = c_public
+                .iter()
+                .zip(&public)
+                .fold(NTT::zero(), |acc, (c_k, z_k)| acc + *c_k * z_k);
+            let mut g_j: Vec<NTT> = c_w
+                .iter()
+                .flat_map(|c_i| powers.iter().map(move |p| *c_i * p))
+                .collect();
+            if g_j.len() > len {
+                return Err(PcsError::VectorTooLong(g_j.len(), len));
+            }
+            g_j.resize(len, NTT::zero());
+
+            Ok((g_j, *u_j - p_j))
+        })
+        .collect()
+}
+
+/// The evaluation of the MLE of a vector at a point with scalar coordinates, given the
+/// evaluations `v` of the MLEs of its $\hat{f}$ slices, as in [`LCCCS::v`].
+///
+/// At such a point, the slots of the `j`-th slice evaluate to the `j`-th chunk of the
+/// coefficients of the evaluation, which lie in the slot field. Their NTT is taken component by
+/// component over the base prime field.
+fn evaluation_from_f_hat<NTT: SuitableRing>(v: &[NTT]) -> NTT {
+    let coeffs: Vec<NTT::BaseRing> = v
+        .iter()
+        .flat_map(|v_j| v_j.coeffs().iter().copied())
+        .collect();
+    let degree = <NTT::BaseRing as Field>::extension_degree() as usize;
+
+    let components: Vec<NTT::CoefficientRepresentation> = (0..degree)
+        .map(|k| {
+            let mut a = NTT::CoefficientRepresentation::ZERO;
+            a.coeffs_mut()
+                .iter_mut()
+                .zip(&coeffs)
+                .for_each(|(a_t, v_t)| {
+                    *a_t = v_t
+                        .to_base_prime_field_elements()
+                        .nth(k)
+                        .expect("k is below the extension degree")
+                });
+            a
+        })
+        .collect();
+
+    CRT::elementwise_crt(components)
+        .into_iter()
+        .enumerate()
+        .fold(NTT::zero(), |acc, (k, a_k)| {
+            let mut basis = vec![<<NTT::BaseRing as Field>::BasePrimeField as Field>::ZERO; degree];
+            basis[k] = <<NTT::BaseRing as Field>::BasePrimeField as Field>::ONE;
+            let basis = <NTT::BaseRing as Field>::from_base_prime_field_elems(&basis)
+                .expect("the basis has the extension degree");
+            acc + NTT::from(basis) * a_k
+        })
+}
+
+/// Absorbs the accumulated instance and the decomposed inner commitments, and returns the
+/// challenges $\rho$ and $\gamma$ batching the consistency, evaluation and linear claims.
+fn absorb_lcccs<NTT: SuitableRing>(
+    transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    lcccs: &LCCCS<NTT>,
+    t_hat: &[NTT],
+) -> (Vec<NTT>, NTT) {
+    transcript.absorb_slice(lcccs.cm.as_ref());
+    transcript.absorb_slice(&lcccs.r);
+    transcript.absorb_slice(&lcccs.v);
+    transcript.absorb_slice(&lcccs.u);
+    transcript.absorb_slice(&lcccs.x_w);
+    transcript.absorb(&lcccs.h);
+    transcript.absorb_slice(t_hat);
+
+    let rho = transcript
+        .get_challenges(lcccs.cm.as_ref().len())
+        .into_iter()
+        .map(From::from)
+        .collect();
+    let gamma = transcript.get_challenge().into();
+
+    (rho, gamma)
+}
+
+/// Absorbs the statement and the decomposed inner commitments, and returns the challenges
+/// $\rho$ and $\gamma$ batching the consistency and evaluation claims.
+fn absorb_statement<NTT: SuitableRing>(
+    transcript: &mut impl TranscriptWithShortChallenges<NTT>,
+    cm: &Commitment<NTT>,
+    point: &[NTT],
+    eval: &NTT,
+    t_hat: &[NTT],
+) -> (Vec<NTT>, NTT) {
+    transcript.absorb_slice(cm.as_ref());
+    transcript.absorb_slice(point);
+    transcript.absorb(eval);
+    transcript.absorb_slice(t_hat);
+
+    let rho = transcript
+        .get_challenges(cm.as_ref().len())
+        .into_iter()
+        .map(From::from)
+        .collect();
+    let gamma = transcript.get_challenge().into();
+
+    (rho, gamma)
+}
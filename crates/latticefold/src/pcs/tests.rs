@@ -0,0 +1,313 @@
+use ark_std::{test_rng, One, UniformRand, Zero};
+use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+
+use super::*;
+use crate::{
+    arith::{r1cs::get_test_z_split, tests::get_test_ccs, CCCS},
+    decomposition_parameters::{test_params::GoldilocksDP, DecompositionParams},
+    nifs::{
+        linearization::{LFLinearizationProver, LinearizationProver},
+        NIFSProver,
+    },
+    transcript::poseidon::PoseidonTranscript,
+    utils::mle_helpers::evaluate_mles,
+};
+
+type R = GoldilocksRingNTT;
+type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+const KAPPA: usize = 2;
+const KAPPA_KEY: usize = 3;
+const B: u128 = 1 << 16;
+
+struct Setup {
+    pcs: LatticePcs<R>,
+    key: AjtaiCommitmentScheme<R>,
+    f: Vec<R>,
+    cm: Commitment<R>,
+    point: Vec<R>,
+}
+
+fn setup(num_vars: usize, len: usize) -> Setup {
+    let mut rng = test_rng();
+    let pcs = LatticePcs::rand(num_vars, KAPPA, B, &mut rng).unwrap();
+    let key = AjtaiCommitmentScheme::rand(KAPPA_KEY, len, &mut rng);
+    let f: Vec<R> = (0..len).map(|_| R::rand(&mut rng)).collect();
+    let cm = key.commit_ntt(&f).unwrap();
+    let point = (0..num_vars).map(|_| R::rand(&mut rng)).collect();
+
+    Setup {
+        pcs,
+        key,
+        f,
+        cm,
+        point,
+    }
+}
+
+fn mle_eval(f: &[R], point: &[R]) -> R {
+    let mut f = f.to_vec();
+    f.resize(1 << point.len(), R::zero());
+
+    f.evaluate(point).unwrap()
+}
+
+#[test]
+fn test_pcs_completeness() {
+    for (num_vars, len) in [(1, 1), (1, 2), (2, 3), (5, 32), (6, 50)] {
+        let Setup {
+            pcs,
+            key,
+            f,
+            cm,
+            point,
+        } = setup(num_vars, len);
+
+        let (eval, proof) = pcs.prove(&key, &f, &point, &mut T::default()).unwrap();
+        assert_eq!(eval, mle_eval(&f, &point));
+
+        pcs.verify(&key, &cm, &point, &eval, &proof, &mut T::default())
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_pcs_proof_is_sublinear() {
+    let Setup {
+        pcs, key, f, point, ..
+    } = setup(10, 1 << 10);
+    let (_, proof) = pcs.prove(&key, &f, &point, &mut T::default()).unwrap();
+
+    // 32 columns of 32 entries, 4 digits per element
+    assert_eq!(proof.t_hat.len(), 32 * KAPPA * 4);
+    assert_eq!(proof.y.len(), 32);
+    assert_eq!(proof.z.len(), 32 * 4);
+}
+
+#[test]
+fn test_pcs_soundness() {
+    let Setup {
+        pcs,
+        key,
+        f,
+        cm,
+        point,
+    } = setup(5, 32);
+    let (eval, proof) = pcs.prove(&key, &f, &point, &mut T::default()).unwrap();
+
+    assert!(matches!(
+        pcs.verify(
+            &key,
+            &cm,
+            &point,
+            &(eval + R::one()),
+            &proof,
+            &mut T::default()
+        ),
+        Err(PcsError::SumCheckError(_))
+    ));
+
+    let mut bad_proof = proof.clone();
+    bad_proof.y[0] += R::one();
+    bad_proof.y[1] -= R::one();
+    assert!(pcs
+        .verify(&key, &cm, &point, &eval, &bad_proof, &mut T::default())
+        .is_err());
+
+    let mut bad_proof = proof.clone();
+    bad_proof.t_hat[0] += R::one();
+    assert!(pcs
+        .verify(&key, &cm, &point, &eval, &bad_proof, &mut T::default())
+        .is_err());
+
+    let mut bad_proof = proof.clone();
+    bad_proof.z[0] += R::one();
+    assert!(pcs
+        .verify(&key, &cm, &point, &eval, &bad_proof, &mut T::default())
+        .is_err());
+
+    let mut bad_proof = proof.clone();
+    bad_proof.z.pop();
+    assert!(matches!(
+        pcs.verify(&key, &cm, &point, &eval, &bad_proof, &mut T::default()),
+        Err(PcsError::MalformedProof)
+    ));
+
+    assert!(matches!(
+        pcs.verify(&key, &cm, &point[1..], &eval, &proof, &mut T::default()),
+        Err(PcsError::WrongNumberOfVariables(4, 5))
+    ));
+}
+
+#[test]
+fn test_pcs_is_bound_to_the_commitment() {
+    let Setup {
+        pcs,
+        key,
+        mut f,
+        cm,
+        point,
+    } = setup(5, 32);
+
+    // An honest opening of another vector does not open `cm`
+    f[3] += R::one();
+    let (eval, proof) = pcs.prove(&key, &f, &point, &mut T::default()).unwrap();
+    assert!(pcs
+        .verify(&key, &cm, &point, &eval, &proof, &mut T::default())
+        .is_err());
+    pcs.verify(
+        &key,
+        &key.commit_ntt(&f).unwrap(),
+        &point,
+        &eval,
+        &proof,
+        &mut T::default(),
+    )
+    .unwrap();
+
+    let other_key = AjtaiCommitmentScheme::rand(KAPPA_KEY, 32, &mut test_rng());
+    assert!(pcs
+        .verify(&other_key, &cm, &point, &eval, &proof, &mut T::default())
+        .is_err());
+
+    let short_cm: Commitment<R> = cm.as_ref()[1..].into();
+    assert!(matches!(
+        pcs.verify(&key, &short_cm, &point, &eval, &proof, &mut T::default()),
+        Err(PcsError::CommitmentError(
+            CommitmentError::WrongCommitmentLength(2, 3)
+        ))
+    ));
+}
+
+#[test]
+fn test_pcs_invalid_parameters() {
+    let mut rng = test_rng();
+    assert!(matches!(
+        LatticePcs::<R>::rand(0, KAPPA, B, &mut rng),
+        Err(PcsError::InvalidParameters(_))
+    ));
+    assert!(matches!(
+        LatticePcs::<R>::rand(5, KAPPA, 1, &mut rng),
+        Err(PcsError::InvalidParameters(_))
+    ));
+
+    let Setup { pcs, f, point, .. } = setup(5, 32);
+    let wide_key = AjtaiCommitmentScheme::rand(KAPPA_KEY, 33, &mut rng);
+    assert!(matches!(
+        pcs.prove(&wide_key, &f, &point, &mut T::default()),
+        Err(PcsError::VectorTooLong(33, 32))
+    ));
+}
+
+#[test]
+fn test_evaluation_from_f_hat() {
+    let mut rng = test_rng();
+    let wit = Witness::<R>::rand::<_, GoldilocksDP>(&mut rng, 32);
+    let point: Vec<R> = (0..log2_len(wit.f.len()))
+        .map(|_| R::from(<R as PolyRing>::BaseRing::rand(&mut rng)))
+        .collect();
+
+    let v: Vec<R> = evaluate_mles::<R, _, _, MleEvaluationError>(&wit.f_hat, &point).unwrap();
+    assert_eq!(evaluation_from_f_hat(&v), mle_eval(&wit.f, &point));
+}
+
+fn log2_len(len: usize) -> usize {
+    len.next_power_of_two().trailing_zeros() as usize
+}
+
+/// An accumulated instance and its witness, folded from the linearization of a satisfied
+/// instance and the instance itself.
+fn folded_lcccs() -> (CCS<R>, AjtaiCommitmentScheme<R>, LCCCS<R>, Witness<R>) {
+    const WIT_LEN: usize = 4;
+    let n = WIT_LEN * GoldilocksDP::L;
+    let ccs = get_test_ccs::<R>(n, GoldilocksDP::L);
+    let scheme = AjtaiCommitmentScheme::rand(4, n, &mut test_rng());
+
+    let (_, x_ccs, w_ccs) = get_test_z_split::<R>(3);
+    let wit = Witness::from_w_ccs::<GoldilocksDP>(w_ccs);
+    let cccs = CCCS {
+        cm: wit.commit::<GoldilocksDP>(&scheme).unwrap(),
+        x_ccs,
+    };
+
+    let (acc, _) =
+        LFLinearizationProver::<_, T>::prove(&cccs, &wit, &mut T::default(), &ccs).unwrap();
+    let (lcccs, folded_wit, _) = NIFSProver::<R, GoldilocksDP, T>::prove(
+        &acc,
+        &wit,
+        &cccs,
+        &wit,
+        &mut T::default(),
+        &ccs,
+        &scheme,
+    )
+    .unwrap();
+
+    (ccs, scheme, lcccs, folded_wit)
+}
+
+#[test]
+fn test_pcs_decides_folded_lcccs() {
+    let (ccs, key, lcccs, wit) = folded_lcccs();
+    let params = GoldilocksDP::DATA;
+    let pcs = LatticePcs::rand_with_bound(ccs.s, KAPPA, B, params.b(), &mut test_rng()).unwrap();
+
+    let proof = pcs
+        .prove_lcccs(&key, &ccs, &params, &lcccs, &wit, &mut T::default())
+        .unwrap();
+    pcs.verify_lcccs(&key, &ccs, &params, &lcccs, &proof, &mut T::default())
+        .unwrap();
+
+    // Every claim of the instance is checked
+    let mut bad_lcccs = lcccs.clone();
+    bad_lcccs.v[0] += R::one();
+    assert!(pcs
+        .verify_lcccs(&key, &ccs, &params, &bad_lcccs, &proof, &mut T::default())
+        .is_err());
+
+    let mut bad_lcccs = lcccs.clone();
+    bad_lcccs.u[1] += R::one();
+    assert!(pcs
+        .verify_lcccs(&key, &ccs, &params, &bad_lcccs, &proof, &mut T::default())
+        .is_err());
+
+    let mut bad_lcccs = lcccs.clone();
+    bad_lcccs.h += R::one();
+    assert!(pcs
+        .verify_lcccs(&key, &ccs, &params, &bad_lcccs, &proof, &mut T::default())
+        .is_err());
+
+    // A false claim cannot be proven either
+    let proof = pcs
+        .prove_lcccs(&key, &ccs, &params, &bad_lcccs, &wit, &mut T::default())
+        .unwrap();
+    assert!(pcs
+        .verify_lcccs(&key, &ccs, &params, &bad_lcccs, &proof, &mut T::default())
+        .is_err());
+}
+
+#[test]
+fn test_pcs_lcccs_norm_bound() {
+    let (ccs, key, lcccs, wit) = folded_lcccs();
+    let params = GoldilocksDP::DATA;
+    let mut rng = test_rng();
+
+    // The columns are decomposed into a single digit
+    let pcs = LatticePcs::rand_with_bound(ccs.s, KAPPA, B, params.b(), &mut rng).unwrap();
+    assert_eq!(pcs.digits, 1);
+
+    let mut f = wit.f.clone();
+    f[0] = R::from(params.b());
+    let large_wit = Witness::from_f_with_params(&params, f);
+    assert!(matches!(
+        pcs.prove_lcccs(&key, &ccs, &params, &lcccs, &large_wit, &mut T::default()),
+        Err(PcsError::NormBoundExceeded)
+    ));
+
+    // The scheme must bound the vectors by B
+    let unbounded = LatticePcs::rand(ccs.s, KAPPA, B, &mut rng).unwrap();
+    assert!(matches!(
+        unbounded.prove_lcccs(&key, &ccs, &params, &lcccs, &wit, &mut T::default()),
+        Err(PcsError::InvalidParameters(_))
+    ));
+}
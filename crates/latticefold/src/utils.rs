@@ -2,5 +2,10 @@
 
 pub(crate) mod mle_helpers;
 #[cfg(feature = "std")]
+pub mod msis;
+pub mod norms;
+#[cfg(feature = "std")]
+pub mod param_selection;
+#[cfg(feature = "std")]
 pub mod security_check;
 pub mod sumcheck;
@@ -0,0 +1,448 @@
+//! Provides Module-SIS hardness estimates under the core-SVP methodology.
+//!
+//! An MSIS instance over a ring of degree $d$ with a $\kappa \times w$ matrix is an SIS
+//! instance over $\mathbb{Z}_q$ with $n = d \kappa$ rows and $m = d w$ columns. The attack
+//! runs BKZ with block size $\beta$ on the $q$-ary lattice of a sub-matrix with $m' \le m$
+//! columns, whose profile is modelled by the geometric series assumption, and looks for a
+//! short enough vector:
+//!
+//! - for the $\ell_2$ norm, the first non-$q$ vector of the reduced basis has to be short
+//!   enough;
+//! - for the $\ell_\infty$ norm, the vectors found by the last sieve are modelled as
+//!   Gaussians, and the attack is repeated until one of them has all its coordinates
+//!   bounded.
+//!
+//! The cost of BKZ is the cost of a single call to the SVP oracle in dimension $\beta$,
+//! i.e. $2^{0.292 \beta}$ classically and $2^{0.265 \beta}$ quantumly.
+
+use ark_std::f64::consts::{E, PI, SQRT_2};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+/// Cost exponent of classical sieving, $\log_2 \sqrt{3/2}$.
+pub const CLASSICAL_SIEVING_EXPONENT: f64 = 0.292;
+/// Cost exponent of quantum sieving, $\log_2 \sqrt{13/9}$.
+pub const QUANTUM_SIEVING_EXPONENT: f64 = 0.265;
+/// A sieve in dimension $\beta$ outputs $2^{0.2075 \beta}$ short vectors, $\log_2 \sqrt{4/3}$.
+const SIEVE_OUTPUT_EXPONENT: f64 = 0.2075;
+/// Smallest block size for which the root-Hermite factor model is meaningful.
+const MIN_BLOCK_SIZE: usize = 50;
+/// Number of sub-lattice dimensions tried per block size, before refinement.
+const DIMENSION_SAMPLES: usize = 16;
+/// Step between the block sizes tried before refinement.
+const BLOCK_SIZE_STEP: usize = 16;
+
+/// The norm bounding MSIS solutions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Norm {
+    L2,
+    Infinity,
+}
+
+/// A Module-SIS instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MsisParams {
+    /// Degree of the ring.
+    pub degree: usize,
+    /// Modulus of the ring.
+    pub modulus: BigUint,
+    /// Number of rows of the MSIS matrix, in ring elements.
+    pub kappa: usize,
+    /// Number of columns of the MSIS matrix, in ring elements.
+    pub width: usize,
+    /// Bound on the norm of a solution, over the coefficient embedding.
+    pub bound: f64,
+    /// The norm `bound` applies to.
+    pub norm: Norm,
+}
+
+/// The cheapest attack found for a given SVP cost exponent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttackCost {
+    /// Logarithm of the attack cost, in bits.
+    /// Infinite if no block size up to the lattice dimension yields a solution.
+    pub bits: f64,
+    /// BKZ block size.
+    pub block_size: usize,
+    /// Dimension of the attacked sub-lattice.
+    pub dimension: usize,
+}
+
+/// Concrete security of an MSIS instance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MsisEstimate {
+    /// Cheapest attack with classical sieving.
+    pub classical: AttackCost,
+    /// Cheapest attack with quantum sieving.
+    pub quantum: AttackCost,
+}
+
+/// Root-Hermite factor reached by BKZ with block size `block_size`.
+pub fn root_hermite_factor(block_size: usize) -> f64 {
+    let beta = block_size as f64;
+    ((beta / (2.0 * PI * E)) * (PI * beta).powf(1.0 / beta)).powf(1.0 / (2.0 * (beta - 1.0)))
+}
+
+/// Bound on the $\ell_2$ norm of a `dimension`-dimensional vector of $\ell_\infty$ norm at
+/// most `bound`.
+pub fn linf_to_l2(bound: f64, dimension: usize) -> f64 {
+    bound * (dimension as f64).sqrt()
+}
+
+/// Error function, accurate to about $10^{-12}$.
+fn erf(x: f64) -> f64 {
+    if x < 0.0 {
+        return -erf(-x);
+    }
+    if x < 3.0 {
+        // Maclaurin series
+        let (mut term, mut sum) = (x, x);
+        for n in 1..100 {
+            term *= -x * x / n as f64;
+            let summand = term / (2 * n + 1) as f64;
+            sum += summand;
+            if summand.abs() < 1e-17 {
+                break;
+            }
+        }
+        2.0 / PI.sqrt() * sum
+    } else {
+        // Continued fraction of erfc
+        let fraction = (1..=60).rev().fold(x, |acc, k| x + (k as f64 / 2.0) / acc);
+        1.0 - (-x * x).exp() / (PI.sqrt() * fraction)
+    }
+}
+
+/// Reduced basis profile of a $q$-ary lattice, under the geometric series assumption.
+struct Profile {
+    /// Number of leading $q$-vectors.
+    head: usize,
+    /// Number of vectors on the slope.
+    slope: usize,
+    /// Natural logarithm of the norm of the first vector on the slope.
+    log_first: f64,
+}
+
+impl Profile {
+    /// Profile of a BKZ-`beta` reduced basis with `nq` $q$-vectors and `n1` unit vectors.
+    fn new(log_q: f64, nq: usize, n1: usize, beta: usize) -> Option<Self> {
+        let slope = -2.0 * root_hermite_factor(beta).ln();
+        let slope_len = (log_q / -slope) as usize;
+        let dim = nq + n1;
+
+        // Log-volume of the first `n` vectors of the unreduced profile, followed by the slope
+        let prefix = |n: usize| -> f64 {
+            if n <= nq {
+                return n as f64 * log_q;
+            }
+            let s = (n - nq).min(slope_len) as f64;
+            nq as f64 * log_q + s * log_q + slope * s * (s + 1.0) / 2.0
+        };
+        let window = |x: usize| prefix(x + dim) - prefix(x);
+
+        // The window of `dim` vectors is slid until its volume matches the lattice volume
+        let volume = nq as f64 * log_q;
+        let shift = if window(0) <= volume {
+            0
+        } else {
+            let (mut lo, mut hi) = (0, slope_len);
+            while lo + 1 < hi {
+                let mid = (lo + hi) / 2;
+                if window(mid) <= volume {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+            hi
+        };
+
+        let head = nq.saturating_sub(shift);
+        let slope_in_window = slope_len.min(dim - head);
+        if slope_in_window == 0 {
+            return None;
+        }
+
+        let t = shift + head;
+        let log_t = if t < nq {
+            log_q
+        } else if t < nq + slope_len {
+            log_q + (t - nq + 1) as f64 * slope
+        } else {
+            0.0
+        };
+        let correction = (volume - window(shift)) / slope_in_window as f64;
+
+        Some(Self {
+            head,
+            slope: slope_in_window,
+            log_first: log_t + correction,
+        })
+    }
+}
+
+impl MsisParams {
+    /// The same instance with an $\ell_2$ bound implied by the $\ell_\infty$ one.
+    ///
+    /// The $\ell_2$ instance is easier, so its security is a lower bound on the security
+    /// of the original one.
+    pub fn to_l2(&self) -> Self {
+        let bound = match self.norm {
+            Norm::L2 => self.bound,
+            Norm::Infinity => linf_to_l2(self.bound, self.degree * self.width),
+        };
+
+        Self {
+            bound,
+            norm: Norm::L2,
+            ..self.clone()
+        }
+    }
+
+    /// Estimates the cost of the cheapest attack, classically and quantumly.
+    pub fn estimate(&self) -> MsisEstimate {
+        MsisEstimate {
+            classical: self.cost(CLASSICAL_SIEVING_EXPONENT),
+            quantum: self.cost(QUANTUM_SIEVING_EXPONENT),
+        }
+    }
+
+    /// Estimates the cost of the cheapest attack, for an SVP oracle of cost
+    /// $2^{\text{exponent} \cdot \beta}$ in dimension $\beta$.
+    pub fn cost(&self, exponent: f64) -> AttackCost {
+        let q = self.modulus.to_f64().unwrap_or(f64::INFINITY);
+        let nq = self.degree * self.kappa;
+        let m = self.degree * self.width;
+
+        if self.bound >= q {
+            return AttackCost {
+                bits: 0.0,
+                block_size: 0,
+                dimension: 1,
+            };
+        }
+
+        let mut best = AttackCost {
+            bits: f64::INFINITY,
+            block_size: m,
+            dimension: m,
+        };
+        if m < MIN_BLOCK_SIZE {
+            return best;
+        }
+
+        // Coarse pass over the block sizes, then refinement around the best one. Block sizes
+        // whose SVP call alone costs more than the best attack are skipped.
+        let coarse = (MIN_BLOCK_SIZE..=m).step_by(BLOCK_SIZE_STEP).chain([m]);
+        for beta in coarse {
+            if exponent * beta as f64 >= best.bits {
+                break;
+            }
+            if let Some(attack) = self.attack(exponent, q, nq, m, beta) {
+                if attack.bits < best.bits {
+                    best = attack;
+                }
+            }
+        }
+
+        if best.bits.is_finite() {
+            let center = best.block_size;
+            let fine = center
+                .saturating_sub(BLOCK_SIZE_STEP - 1)
+                .max(MIN_BLOCK_SIZE)..=(center + BLOCK_SIZE_STEP - 1).min(m);
+            for beta in fine {
+                if exponent * beta as f64 >= best.bits {
+                    break;
+                }
+                if let Some(attack) = self.attack(exponent, q, nq, m, beta) {
+                    if attack.bits < best.bits {
+                        best = attack;
+                    }
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The cheapest attack with block size `beta` on a sub-lattice of the $q$-ary lattice with
+    /// `nq` $q$-vectors and at most `m` dimensions, if any.
+    fn attack(
+        &self,
+        exponent: f64,
+        q: f64,
+        nq: usize,
+        m: usize,
+        beta: usize,
+    ) -> Option<AttackCost> {
+        let log_q = q.ln();
+        let slope_len = (log_q / (2.0 * root_hermite_factor(beta).ln())) as usize;
+        let (lo, hi) = (usize::max(nq + 1, beta), usize::min(m, nq + slope_len));
+        if lo > hi {
+            return None;
+        }
+
+        let attack = |dim: usize| {
+            Profile::new(log_q, nq, dim - nq, beta).map_or(f64::INFINITY, |profile| {
+                exponent * beta as f64 + self.repetitions(q, beta, &profile)
+            })
+        };
+
+        // Coarse sampling of the sub-lattice dimension, then refinement around the best
+        let step = usize::max(1, (hi - lo) / DIMENSION_SAMPLES);
+        let coarse = (lo..=hi).step_by(step).chain([hi]);
+        let (mut bits, mut dim) = coarse
+            .map(|d| (attack(d), d))
+            .fold((f64::INFINITY, lo), |a, b| if b.0 < a.0 { b } else { a });
+        if step > 1 {
+            let fine = (dim.saturating_sub(step).max(lo)..=(dim + step).min(hi))
+                .step_by(usize::max(1, step / DIMENSION_SAMPLES));
+            for d in fine {
+                let b = attack(d);
+                if b < bits {
+                    (bits, dim) = (b, d);
+                }
+            }
+        }
+
+        Some(AttackCost {
+            bits,
+            block_size: beta,
+            dimension: dim,
+        })
+    }
+
+    /// Logarithm of the number of BKZ runs needed to find a solution, infinite if none can
+    /// be found.
+    fn repetitions(&self, q: f64, beta: usize, profile: &Profile) -> f64 {
+        // Any bounded vector in l2 norm is also bounded in infinity norm
+        if profile.log_first <= self.bound.ln() {
+            return 0.0;
+        }
+        if self.norm == Norm::L2 {
+            return f64::INFINITY;
+        }
+
+        let sigma = profile.log_first.exp() / (profile.slope as f64).sqrt();
+        let p_slope = erf(self.bound / (sigma * SQRT_2));
+        if p_slope <= 0.0 {
+            return f64::INFINITY;
+        }
+        let p_head = f64::min(1.0, 2.0 * self.bound / q);
+
+        let log_success =
+            profile.slope as f64 * p_slope.log2() + profile.head as f64 * p_head.log2();
+        f64::max(0.0, -log_success - SIEVE_OUTPUT_EXPONENT * beta as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dilithium(kappa: usize, width: usize, bound: f64) -> MsisParams {
+        MsisParams {
+            degree: 256,
+            modulus: BigUint::from(8380417u32),
+            kappa,
+            width,
+            bound,
+            norm: Norm::Infinity,
+        }
+    }
+
+    #[test]
+    fn test_erf() {
+        assert_eq!(erf(0.0), 0.0);
+        assert!((erf(0.5) - 0.520_499_877_813_046_5).abs() < 1e-12);
+        assert!((erf(2.0) - 0.995_322_265_018_952_7).abs() < 1e-12);
+        assert!((erf(-1.0) + 0.842_700_792_949_714_9).abs() < 1e-12);
+        assert!((erf(4.0) - 0.999_999_984_582_742_1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_root_hermite_factor() {
+        assert!((root_hermite_factor(100) - 1.009_259).abs() < 1e-6);
+        assert!((root_hermite_factor(400) - 1.003_982).abs() < 1e-6);
+        assert!(root_hermite_factor(500) < root_hermite_factor(400));
+    }
+
+    #[test]
+    fn test_dilithium2() {
+        // Dilithium2 (round 3 specification): the MSIS instance behind strong unforgeability
+        // has 4 x 8 blocks and zeta' = 4 * gamma_2 + 2. The published core-SVP estimate is a
+        // block size of 423, for 123 classical and 112 quantum bits.
+        let gamma_2 = (8380417 - 1) / 88;
+        let estimate = dilithium(4, 8, (4 * gamma_2 + 2) as f64).estimate();
+
+        assert!(estimate.classical.block_size.abs_diff(423) <= 10);
+        assert!((estimate.classical.bits - 123.0).abs() <= 3.0);
+        assert!((estimate.quantum.bits - 112.0).abs() <= 3.0);
+    }
+
+    #[test]
+    fn test_dilithium3_and_5() {
+        // Dilithium3 and Dilithium5 (round 3 specification) have 6 x 11 and 8 x 15 blocks,
+        // gamma_1 = 2^19 and gamma_2 = (q - 1) / 32, so zeta' = max(2 * gamma_1, 4 * gamma_2 + 2)
+        // = 2^20. The published core-SVP estimates are block sizes of 638 and 909, for 186 and
+        // 265 classical bits, and 169 and 241 quantum bits.
+        let zeta = (1u32 << 20) as f64;
+
+        let estimate = dilithium(6, 11, zeta).estimate();
+        assert!(estimate.classical.block_size.abs_diff(638) <= 15);
+        assert!((estimate.classical.bits - 186.0).abs() <= 4.0);
+        assert!((estimate.quantum.bits - 169.0).abs() <= 4.0);
+
+        let estimate = dilithium(8, 15, zeta).estimate();
+        assert!(estimate.classical.block_size.abs_diff(909) <= 15);
+        assert!((estimate.classical.bits - 265.0).abs() <= 4.0);
+        assert!((estimate.quantum.bits - 241.0).abs() <= 4.0);
+    }
+
+    #[test]
+    fn test_block_size_search() {
+        // The coarse-to-fine search finds an attack about as cheap as trying every block size
+        let params = dilithium(4, 8, 380930.0);
+        let exhaustive = (MIN_BLOCK_SIZE..=params.degree * params.width)
+            .filter_map(|beta| {
+                params.attack(
+                    CLASSICAL_SIEVING_EXPONENT,
+                    8380417.0,
+                    params.degree * params.kappa,
+                    params.degree * params.width,
+                    beta,
+                )
+            })
+            .fold(f64::INFINITY, |best, attack| best.min(attack.bits));
+
+        let bits = params.cost(CLASSICAL_SIEVING_EXPONENT).bits;
+        assert!(bits >= exhaustive && bits - exhaustive <= 1.0);
+    }
+
+    #[test]
+    fn test_norm_conversion() {
+        let params = dilithium(4, 8, 1e5);
+        let l2 = params.to_l2();
+
+        assert_eq!(l2.norm, Norm::L2);
+        assert_eq!(l2.bound, linf_to_l2(1e5, 256 * 8));
+        assert!(
+            l2.cost(CLASSICAL_SIEVING_EXPONENT).bits
+                <= params.cost(CLASSICAL_SIEVING_EXPONENT).bits
+        );
+    }
+
+    #[test]
+    fn test_monotonicity() {
+        let bits = |kappa: usize, bound: f64| {
+            dilithium(kappa, 8, bound)
+                .cost(CLASSICAL_SIEVING_EXPONENT)
+                .bits
+        };
+
+        assert!(bits(3, 380930.0) < bits(4, 380930.0));
+        assert!(bits(4, 380930.0) < bits(5, 380930.0));
+        assert!(bits(4, 1e6) < bits(4, 380930.0));
+        assert_eq!(bits(4, 1e7), 0.0);
+    }
+}
@@ -0,0 +1,78 @@
+//! Provides norms of vectors of ring elements in the coefficient form.
+//!
+//! Coefficients are taken in the balanced representation, i.e. as integers in
+//! $(-q/2, q/2\]$.
+
+use ark_ff::{PrimeField, Zero};
+use ark_std::cmp;
+use num_bigint::BigUint;
+use stark_rings::PolyRing;
+
+/// Returns the infinity norm of `v`.
+pub fn linf_norm<R: PolyRing>(v: &[R]) -> BigUint
+where
+    R::BaseRing: PrimeField,
+{
+    let mut norm = R::BaseRing::zero();
+    for ele in v {
+        for x in ele.coeffs().iter() {
+            norm = cmp::max(norm, cmp::min(*x, -*x));
+        }
+    }
+
+    norm.into_bigint().into()
+}
+
+/// Returns the l1 norm of `v`.
+pub fn l1_norm<R: PolyRing>(v: &[R]) -> BigUint
+where
+    R::BaseRing: PrimeField,
+{
+    let mut norm = BigUint::zero();
+    for ele in v {
+        for x in ele.coeffs().iter() {
+            norm += BigUint::from(cmp::min(*x, -*x).into_bigint());
+        }
+    }
+
+    norm
+}
+
+/// Returns the squared l2 norm of `v`.
+pub fn l2_norm_squared<R: PolyRing>(v: &[R]) -> BigUint
+where
+    R::BaseRing: PrimeField,
+{
+    let mut norm = BigUint::zero();
+    for ele in v {
+        for x in ele.coeffs().iter() {
+            let abs: BigUint = cmp::min(*x, -*x).into_bigint().into();
+            norm += &abs * &abs;
+        }
+    }
+
+    norm
+}
+
+#[cfg(test)]
+mod tests {
+    use cyclotomic_rings::rings::GoldilocksRingPoly;
+    use stark_rings::cyclotomic_ring::models::goldilocks::Fq;
+
+    use super::*;
+
+    #[test]
+    fn test_norms() {
+        let mut coeffs = vec![Fq::from(3u64), -Fq::from(4u64)];
+        coeffs.resize(GoldilocksRingPoly::dimension(), Fq::zero());
+        let v = vec![
+            GoldilocksRingPoly::from(coeffs),
+            -GoldilocksRingPoly::from(2u64),
+        ];
+
+        assert_eq!(linf_norm(&v), BigUint::from(4u32));
+        assert_eq!(l1_norm(&v), BigUint::from(9u32));
+        assert_eq!(l2_norm_squared(&v), BigUint::from(29u32));
+        assert_eq!(linf_norm::<GoldilocksRingPoly>(&[]), BigUint::zero());
+    }
+}
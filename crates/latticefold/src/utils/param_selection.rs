@@ -0,0 +1,379 @@
+//! Provides a search over LatticeFold parameters.
+//!
+//! Given a ring, a witness length and a target security level, [`select_params`] enumerates
+//! decomposition bases `b` and exponents `k`, picks the smallest gadget length `L` and
+//! commitment height `kappa` that work for them, and ranks the resulting parameter sets by
+//! a [`CostObjective`].
+//!
+//! For every candidate, the bound $B = b^k$ has to be large enough to absorb the norm growth
+//! of a folding step, i.e. $2k \cdot c \cdot (b - 1) \le B$ where $c$ is the expansion factor
+//! of the challenge set, and the Ajtai commitment has to reach the target security
+//! according to [`estimate_msis_security_bits`], i.e. the core-SVP estimates of
+//! [`crate::utils::msis`] for the MSIS bound $2B$ of a binding break.
+
+use ark_ff::{Field, PrimeField};
+use cyclotomic_rings::{challenge_set::LatticefoldChallengeSet, rings::SuitableRing};
+use num_bigint::BigUint;
+use stark_rings::PolyRing;
+use thiserror::Error;
+
+use crate::{
+    ark_base::*,
+    decomposition_parameters::{gadget_length, DecompositionParamData},
+    utils::{norms::l1_norm, security_check::estimate_msis_security_bits},
+};
+
+/// The quantity minimized by [`select_params`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostObjective {
+    /// Estimated number of ring elements in a folding proof.
+    ProofSize,
+    /// Estimated number of ring multiplications of the decomposition and folding provers.
+    ProverTime,
+    /// Number of ring elements in a commitment, i.e. `kappa`.
+    CommitmentWidth,
+}
+
+/// Errors that can arise during parameter selection.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ParamSelectionError {
+    #[error("invalid parameter query: {0}")]
+    InvalidQuery(String),
+    #[error("no parameter set reaches {0} bits of security")]
+    NoCandidates(f64),
+}
+
+/// The requirements of a parameter search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamQuery {
+    /// Length of the CCS witness, in ring elements.
+    pub wit_len: usize,
+    /// Target bit-security of the Ajtai commitment.
+    pub security_bits: f64,
+    /// Bound on $\lVert \rho \cdot f \rVert_\infty / \lVert f \rVert_\infty$ for the
+    /// challenges $\rho$ of the challenge set.
+    pub challenge_expansion: u128,
+    /// The quantity to minimize.
+    pub objective: CostObjective,
+    /// Largest commitment height to consider.
+    pub max_kappa: usize,
+    /// Largest decomposition base `b` to consider.
+    pub max_b_small: usize,
+    /// Maximal number of returned parameter sets.
+    pub max_results: usize,
+}
+
+impl ParamQuery {
+    /// Creates a query minimizing proof size, with a default search space.
+    pub fn new(wit_len: usize, security_bits: f64, challenge_expansion: u128) -> Self {
+        Self {
+            wit_len,
+            security_bits,
+            challenge_expansion,
+            objective: CostObjective::ProofSize,
+            max_kappa: 64,
+            max_b_small: 64,
+            max_results: 10,
+        }
+    }
+
+    fn check(&self) -> Result<(), ParamSelectionError> {
+        let invalid = |msg: &str| Err(ParamSelectionError::InvalidQuery(msg.to_string()));
+
+        if self.wit_len == 0 {
+            return invalid("witness length must be positive");
+        }
+        if !(self.security_bits.is_finite() && self.security_bits > 0.0) {
+            return invalid("target security must be positive");
+        }
+        if self.challenge_expansion == 0 {
+            return invalid("challenge expansion must be positive");
+        }
+        if self.max_kappa == 0 || self.max_b_small < 2 || self.max_results == 0 {
+            return invalid("empty search space");
+        }
+
+        Ok(())
+    }
+}
+
+/// A validated parameter set returned by [`select_params`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamCandidate {
+    /// The decomposition parameters.
+    pub params: DecompositionParamData,
+    /// The height of the Ajtai commitment matrix.
+    pub kappa: usize,
+    /// The estimated bit-security of the Ajtai commitment.
+    pub security_bits: f64,
+    /// The ratio $B / (2k \cdot c \cdot (b - 1))$, at least 1.
+    pub norm_growth_margin: f64,
+    /// The value of the cost objective.
+    pub cost: u128,
+}
+
+/// Searches parameter sets for the ring `R` meeting `query`.
+///
+/// Returns at most `query.max_results` validated parameter sets, cheapest first. Ties are
+/// broken in favour of higher security.
+///
+/// # Errors
+///
+/// Returns an error if the query is malformed or if no parameter set in the search space
+/// reaches the target security.
+pub fn select_params<R: SuitableRing>(
+    query: &ParamQuery,
+) -> Result<Vec<ParamCandidate>, ParamSelectionError> {
+    query.check()?;
+
+    let modulus: BigUint =
+        <<<R as PolyRing>::BaseRing as Field>::BasePrimeField as PrimeField>::MODULUS.into();
+    let modulus_half = &modulus / 2u32;
+    let degree = R::CoefficientRepresentation::dimension();
+
+    let mut candidates = Vec::new();
+    for b_small in 2..=query.max_b_small {
+        let mut b = 1u128;
+        for k in 1.. {
+            b = match b.checked_mul(b_small as u128) {
+                Some(b) if BigUint::from(b) < modulus_half => b,
+                _ => break,
+            };
+
+            let growth = (2 * k as u128)
+                .saturating_mul(query.challenge_expansion)
+                .saturating_mul(b_small as u128 - 1);
+            if growth > b {
+                continue;
+            }
+
+            let l = gadget_length::<R>(b);
+            let num_cols = query.wit_len * l;
+            let security =
+                |kappa: usize| estimate_msis_security_bits(&modulus, kappa, degree, num_cols, b);
+            if security(query.max_kappa) < query.security_bits {
+                continue;
+            }
+
+            // Security grows with kappa, look for the smallest sufficient one
+            let (mut lo, mut hi) = (0, query.max_kappa);
+            while lo + 1 < hi {
+                let mid = (lo + hi) / 2;
+                if security(mid) >= query.security_bits {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+            let (kappa, security_bits) = (hi, security(hi));
+
+            let Ok(params) = DecompositionParamData::new::<R>(b, l, b_small, k) else {
+                continue;
+            };
+
+            candidates.push(ParamCandidate {
+                params,
+                kappa,
+                security_bits,
+                norm_growth_margin: b as f64 / growth as f64,
+                cost: cost(query, &params, kappa),
+            });
+        }
+    }
+
+    if candidates.is_empty() {
+        return Err(ParamSelectionError::NoCandidates(query.security_bits));
+    }
+
+    candidates.sort_by(|x, y| {
+        x.cost
+            .cmp(&y.cost)
+            .then(y.security_bits.total_cmp(&x.security_bits))
+    });
+    candidates.truncate(query.max_results);
+
+    Ok(candidates)
+}
+
+/// Returns the expansion factor of the challenge set `CS`, i.e. the largest l1 norm of one of
+/// its challenges, which bounds $\lVert \rho \cdot f \rVert_\infty / \lVert f \rVert_\infty$.
+///
+/// Every coefficient of a challenge is read from its own bits, as an offset from an integer
+/// that grows with them, so the largest coefficients are obtained from bytes that are all
+/// zeros or all ones.
+pub fn challenge_expansion<R: SuitableRing, CS: LatticefoldChallengeSet<R>>() -> u128 {
+    [0x00, 0xff]
+        .into_iter()
+        .filter_map(|byte| {
+            CS::short_challenge_from_random_bytes(&vec![byte; CS::BYTES_NEEDED]).ok()
+        })
+        .map(|challenge| l1_norm(&[challenge]).try_into().unwrap_or(u128::MAX))
+        .max()
+        .unwrap_or(0)
+}
+
+fn cost(query: &ParamQuery, params: &DecompositionParamData, kappa: usize) -> u128 {
+    let (b_small, k, l) = (
+        params.b_small() as u128,
+        params.k() as u128,
+        params.l() as u128,
+    );
+    let kappa = kappa as u128;
+    let m = (query.wit_len * params.l()).next_power_of_two() as u128;
+    let log_m = m.trailing_zeros() as u128;
+
+    match query.objective {
+        // Commitments of the 2k decomposed witnesses and the degree 2b folding sumcheck
+        CostObjective::ProofSize => 2 * k * kappa + log_m * (2 * b_small + 1),
+        // Committing the decomposed witnesses and running the folding sumcheck over them
+        CostObjective::ProverTime => {
+            k * kappa * query.wit_len as u128 * l + 2 * k * m * (2 * b_small)
+        }
+        CostObjective::CommitmentWidth => kappa,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cyclotomic_rings::rings::{
+        BabyBearChallengeSet, BabyBearRingNTT, FrogChallengeSet, FrogRingNTT,
+        GoldilocksChallengeSet, GoldilocksRingNTT, StarkChallengeSet, StarkRingNTT,
+    };
+
+    use super::*;
+    use crate::utils::msis::{MsisParams, Norm, CLASSICAL_SIEVING_EXPONENT};
+
+    // Worst-case expansion of the Goldilocks challenge set: degree 24, coefficients below 32
+    const GOLDILOCKS_EXPANSION: u128 = 24 * 32;
+
+    #[test]
+    fn test_challenge_expansion() {
+        assert_eq!(
+            challenge_expansion::<GoldilocksRingNTT, GoldilocksChallengeSet>(),
+            GOLDILOCKS_EXPANSION
+        );
+        // 24 coefficients in [-32, 32[
+        assert_eq!(
+            challenge_expansion::<BabyBearRingNTT, BabyBearChallengeSet>(),
+            24 * 32
+        );
+        // 16 coefficients in [0, 256[
+        assert_eq!(
+            challenge_expansion::<StarkRingNTT, StarkChallengeSet>(),
+            16 * 255
+        );
+        // 16 coefficients in [-128, 128[
+        assert_eq!(
+            challenge_expansion::<FrogRingNTT, FrogChallengeSet>(),
+            16 * 128
+        );
+    }
+
+    #[test]
+    fn test_select_params_goldilocks() {
+        type R = GoldilocksRingNTT;
+
+        let query = ParamQuery::new(1 << 10, 128.0, GOLDILOCKS_EXPANSION);
+        let candidates = select_params::<R>(&query).unwrap();
+
+        assert!(!candidates.is_empty() && candidates.len() <= query.max_results);
+        assert!(candidates.windows(2).all(|w| w[0].cost <= w[1].cost));
+
+        let modulus: BigUint =
+            <<<R as PolyRing>::BaseRing as Field>::BasePrimeField as PrimeField>::MODULUS.into();
+        for c in &candidates {
+            let p = c.params;
+            assert_eq!(
+                DecompositionParamData::new::<R>(p.b(), p.l(), p.b_small(), p.k()),
+                Ok(p)
+            );
+            assert!(c.security_bits >= query.security_bits);
+            // Binding is estimated on the difference of two openings, of norm at most 2B
+            let msis = MsisParams {
+                degree: 24,
+                modulus: modulus.clone(),
+                kappa: c.kappa,
+                width: query.wit_len * p.l(),
+                bound: 2.0 * p.b() as f64,
+                norm: Norm::Infinity,
+            };
+            assert_eq!(c.security_bits, msis.cost(CLASSICAL_SIEVING_EXPONENT).bits);
+            assert!(c.norm_growth_margin >= 1.0);
+            assert!(BigUint::from(p.b()).pow(p.l() as u32) > &modulus / 2u32);
+            // The commitment height is minimal
+            assert!(
+                c.kappa == 1
+                    || estimate_msis_security_bits(
+                        &modulus,
+                        c.kappa - 1,
+                        24,
+                        query.wit_len * p.l(),
+                        p.b()
+                    ) < query.security_bits
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_params_objectives() {
+        type R = StarkRingNTT;
+
+        let mut query = ParamQuery::new(1 << 12, 100.0, 16 * 256);
+        query.max_results = usize::MAX;
+        let by_size = select_params::<R>(&query).unwrap();
+
+        query.objective = CostObjective::CommitmentWidth;
+        let by_width = select_params::<R>(&query).unwrap();
+
+        assert_eq!(by_size.len(), by_width.len());
+        let min_kappa = by_size.iter().map(|c| c.kappa).min().unwrap();
+        assert_eq!(by_width[0].kappa, min_kappa);
+        assert_eq!(by_width[0].cost, min_kappa as u128);
+    }
+
+    #[test]
+    fn test_select_params_errors() {
+        type R = GoldilocksRingNTT;
+
+        let query = ParamQuery::new(0, 128.0, GOLDILOCKS_EXPANSION);
+        assert!(matches!(
+            select_params::<R>(&query),
+            Err(ParamSelectionError::InvalidQuery(_))
+        ));
+
+        let mut query = ParamQuery::new(1 << 10, 128.0, GOLDILOCKS_EXPANSION);
+        query.max_kappa = 1;
+        assert_eq!(
+            select_params::<R>(&query),
+            Err(ParamSelectionError::NoCandidates(128.0))
+        );
+    }
+
+    #[test]
+    fn test_security_grows_with_kappa() {
+        let modulus = BigUint::from(u64::MAX);
+        let bits: Vec<f64> = (1..16)
+            .map(|kappa| estimate_msis_security_bits(&modulus, kappa, 24, 1 << 12, 1 << 15))
+            .collect();
+
+        assert!(bits.windows(2).all(|w| w[0] <= w[1]));
+        assert!(bits[bits.len() - 1] > 100.0);
+    }
+
+    #[test]
+    fn test_security_uses_doubled_bound() {
+        let modulus = BigUint::from(u64::MAX);
+        let bits = |b: u128| estimate_msis_security_bits(&modulus, 8, 24, 1 << 12, b);
+        let msis = MsisParams {
+            degree: 24,
+            modulus: modulus.clone(),
+            kappa: 8,
+            width: 1 << 12,
+            bound: (1u64 << 16) as f64,
+            norm: Norm::Infinity,
+        };
+
+        assert_eq!(bits(1 << 15), msis.cost(CLASSICAL_SIEVING_EXPONENT).bits);
+        assert!(bits(1 << 15) <= bits(1 << 14));
+    }
+}
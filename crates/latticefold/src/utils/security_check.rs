@@ -2,6 +2,8 @@ use ark_std::f64;
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
 
+use super::msis::{MsisParams, Norm, CLASSICAL_SIEVING_EXPONENT};
+
 fn calculate_bound_l2(degree: usize, kappa: usize, ring_modulus_log2: f64) -> BigUint {
     // The current security parameter use log2(delta)
     let delta = 1.0045_f64;
@@ -43,3 +45,28 @@ pub fn check_ring_modulus_128_bits_security(
     // Return the result of the condition
     bound_l2_check && b_check && b_pow_l_check
 }
+
+/// Estimates the bit-security of the MSIS instance behind an Ajtai commitment.
+///
+/// The commitment matrix has `kappa` rows and `num_cols` columns over a ring of degree
+/// `degree`, and committed vectors have infinity norm at most `b`. Binding reduces to MSIS on
+/// the difference of two openings, so the MSIS bound is `2 * b`. Returns the classical
+/// core-SVP cost of the cheapest attack, see [`MsisParams::cost`].
+pub fn estimate_msis_security_bits(
+    ring_modulus: &BigUint,
+    kappa: usize,
+    degree: usize,
+    num_cols: usize,
+    b: u128,
+) -> f64 {
+    MsisParams {
+        degree,
+        modulus: ring_modulus.clone(),
+        kappa,
+        width: num_cols,
+        bound: 2.0 * b.to_f64().unwrap(),
+        norm: Norm::Infinity,
+    }
+    .cost(CLASSICAL_SIEVING_EXPONENT)
+    .bits
+}
@@ -5,12 +5,21 @@ use stark_rings::{OverField, Ring};
 use stark_rings_poly::polynomials::{ArithErrors, DenseMultilinearExtension};
 use thiserror::Error;
 
-use self::verifier::SubClaim;
+use self::verifier::{interpolate_uni_poly, SubClaim};
 use crate::{ark_base::*, transcript::Transcript};
 
+pub mod batched;
+pub mod gkr;
+pub mod lean;
 pub mod prover;
+pub mod small_value;
+pub mod split_eq;
+pub mod standalone;
+pub mod univariate_skip;
 pub mod utils;
 pub mod verifier;
+pub mod virtual_polynomial;
+pub mod zk;
 
 /// Interactive Proof for Multilinear Sumcheck
 pub struct IPForMLSumcheck<R, T> {
@@ -26,6 +35,16 @@ pub enum SumCheckError<R: Ring + Display> {
     SumCheckFailed(R, R),
     #[error("max degree exceeded")]
     MaxDegreeExceeded,
+    #[error("the batched proof has {0} evaluations, expected {1}")]
+    WrongNumberOfEvaluations(usize, usize),
+    #[error("no claims to batch")]
+    NoClaimsToBatch,
+    #[error("the batched claim {0} has no variables")]
+    ClaimWithoutVariables(usize),
+    #[error("cannot skip {0} of {1} variables")]
+    InvalidSkip(usize, usize),
+    #[error("the first round message has {0} evaluations, expected {1}")]
+    WrongSkipMessageLength(usize, usize),
 }
 
 impl<R: Ring> From<ArithErrors> for SumCheckError<R> {
@@ -38,11 +57,28 @@ impl<R: Ring> From<ArithErrors> for SumCheckError<R> {
 pub struct MLSumcheck<R, T>(#[doc(hidden)] PhantomData<(R, T)>);
 
 /// proof generated by prover
+///
+/// A proof is either in the full format, each round message holding `degree + 1` evaluations, or
+/// in the compressed format, without the evaluation on 1 (see [`Proof::compress`]). The verifier
+/// accepts both.
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Proof<R1: Ring>(Vec<ProverMsg<R1>>);
 
+impl<R1: Ring> Proof<R1> {
+    /// Returns the proof in the compressed format, for a sumcheck of degree `degree`.
+    ///
+    /// Every round message omits its evaluation on 1, saving one ring element per round. The
+    /// verifier recovers it from the claim of the round, so a compressed proof verifies exactly
+    /// when the original does.
+    pub fn compress(&self, degree: usize) -> Self {
+        Self(self.0.iter().map(|msg| msg.compress(degree)).collect())
+    }
+}
+
 impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
     /// extract sum from the proof
+    ///
+    /// The proof must be in the full format, a compressed proof does not determine its sum.
     pub fn extract_sum(proof: &Proof<R>) -> R {
         proof.0[0].evaluations[0] + proof.0[0].evaluations[1]
     }
@@ -56,15 +92,29 @@ impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
         nvars: usize,
         degree: usize,
         comb_fn: impl Fn(&[R]) -> R + Sync + Send,
+    ) -> (Proof<R>, ProverState<R>) {
+        Self::prove_as_subprotocol_with_hook(transcript, mles, nvars, degree, comb_fn, |_, _, _| {})
+    }
+
+    /// [`MLSumcheck::prove_as_subprotocol`], calling `on_round` with the round number, the
+    /// randomness of the previous rounds and the message of the round before it is absorbed.
+    fn prove_as_subprotocol_with_hook(
+        transcript: &mut T,
+        mles: Vec<DenseMultilinearExtension<R>>,
+        nvars: usize,
+        degree: usize,
+        comb_fn: impl Fn(&[R]) -> R + Sync + Send,
+        mut on_round: impl FnMut(usize, &[R::BaseRing], &mut [R]),
     ) -> (Proof<R>, ProverState<R>) {
         transcript.absorb(&R::from(nvars as u128));
         transcript.absorb(&R::from(degree as u128));
         let mut prover_state = IPForMLSumcheck::<R, T>::prover_init(mles, nvars, degree);
         let mut verifier_msg = None;
         let mut prover_msgs = Vec::with_capacity(nvars);
-        for _ in 0..nvars {
-            let prover_msg =
+        for round in 0..nvars {
+            let mut prover_msg =
                 IPForMLSumcheck::<R, T>::prove_round(&mut prover_state, &verifier_msg, &comb_fn);
+            on_round(round, &prover_state.randomness, &mut prover_msg.evaluations);
             transcript.absorb_slice(&prover_msg.evaluations);
             prover_msgs.push(prover_msg);
             let next_verifier_msg = IPForMLSumcheck::<R, T>::sample_round(transcript);
@@ -92,12 +142,19 @@ impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
         transcript.absorb(&R::from(degree as u128));
 
         let mut verifier_state = IPForMLSumcheck::<R, T>::verifier_init(nvars, degree);
+        // The claim of the current round, needed to decompress the round messages
+        let mut claim = claimed_sum;
         for i in 0..nvars {
-            let prover_msg = proof.0.get(i).expect("proof is incomplete");
+            let prover_msg = proof
+                .0
+                .get(i)
+                .expect("proof is incomplete")
+                .decompress(degree, claim);
             transcript.absorb_slice(&prover_msg.evaluations);
             let verifier_msg =
                 IPForMLSumcheck::verify_round(prover_msg.clone(), &mut verifier_state, transcript);
             transcript.absorb(&verifier_msg.randomness.into());
+            claim = interpolate_uni_poly(&prover_msg.evaluations, verifier_msg.randomness);
         }
 
         IPForMLSumcheck::<R, T>::check_and_generate_subclaim(verifier_state, claimed_sum)
@@ -186,6 +243,55 @@ mod tests {
         );
     }
 
+    fn test_compressed_sumcheck<R, CS>()
+    where
+        R: SuitableRing,
+        CS: LatticefoldChallengeSet<R>,
+    {
+        let mut rng = ark_std::test_rng();
+        let nvars = 5;
+
+        for _ in 0..20 {
+            let (poly_degree, sum, proof) = generate_sumcheck_proof::<R, CS>(nvars, &mut rng);
+            let compressed = proof.compress(poly_degree);
+            assert_eq!(
+                compressed.compressed_size() + nvars * R::zero().compressed_size(),
+                proof.compressed_size()
+            );
+
+            let mut transcript: PoseidonTranscript<R, CS> = PoseidonTranscript::default();
+            let subclaim =
+                MLSumcheck::verify_as_subprotocol(&mut transcript, nvars, poly_degree, sum, &proof)
+                    .unwrap();
+
+            let mut transcript: PoseidonTranscript<R, CS> = PoseidonTranscript::default();
+            let compressed_subclaim = MLSumcheck::verify_as_subprotocol(
+                &mut transcript,
+                nvars,
+                poly_degree,
+                sum,
+                &compressed,
+            )
+            .unwrap();
+            assert_eq!(subclaim.point, compressed_subclaim.point);
+            assert_eq!(
+                subclaim.expected_evaluation,
+                compressed_subclaim.expected_evaluation
+            );
+
+            let mut transcript: PoseidonTranscript<R, CS> = PoseidonTranscript::default();
+            let res = MLSumcheck::verify_as_subprotocol(
+                &mut transcript,
+                nvars,
+                poly_degree,
+                sum + R::one(),
+                &compressed,
+            );
+            assert!(res.map_or(true, |subclaim| subclaim.expected_evaluation
+                != compressed_subclaim.expected_evaluation));
+        }
+    }
+
     fn test_failing_sumcheck<R, CS>()
     where
         R: SuitableRing,
@@ -239,6 +345,11 @@ mod tests {
             super::test_sumcheck_proof_serialization::<RqNTT, CS>();
         }
 
+        #[test]
+        fn test_compressed_sumcheck() {
+            super::test_compressed_sumcheck::<RqNTT, CS>();
+        }
+
         #[test]
         fn test_failing_sumcheck() {
             super::test_failing_sumcheck::<RqNTT, CS>();
@@ -261,6 +372,11 @@ mod tests {
             super::test_sumcheck_proof_serialization::<RqNTT, CS>();
         }
 
+        #[test]
+        fn test_compressed_sumcheck() {
+            super::test_compressed_sumcheck::<RqNTT, CS>();
+        }
+
         #[test]
         fn test_failing_sumcheck() {
             super::test_failing_sumcheck::<RqNTT, CS>();
@@ -283,6 +399,11 @@ mod tests {
             super::test_sumcheck_proof_serialization::<RqNTT, CS>();
         }
 
+        #[test]
+        fn test_compressed_sumcheck() {
+            super::test_compressed_sumcheck::<RqNTT, CS>();
+        }
+
         #[test]
         fn test_failing_sumcheck() {
             super::test_failing_sumcheck::<RqNTT, CS>();
@@ -305,6 +426,11 @@ mod tests {
             super::test_sumcheck_proof_serialization::<RqNTT, CS>();
         }
 
+        #[test]
+        fn test_compressed_sumcheck() {
+            super::test_compressed_sumcheck::<RqNTT, CS>();
+        }
+
         #[test]
         fn test_failing_sumcheck() {
             super::test_failing_sumcheck::<RqNTT, CS>();
@@ -0,0 +1,405 @@
+//! Batched sumcheck
+//!
+//! Proves several sumcheck claims, possibly over different numbers of variables, with a single
+//! sumcheck. A claim $\sum_{x \in \\{0,1\\}^{n_i}} g_i(x) = s_i$ is seen as a claim over the
+//! largest number of variables $n$, about the polynomial $h_i(x) = g_i(x_1, \ldots, x_{n_i})$,
+//! whose sum is $2^{n - n_i} s_i$. The claims are combined with random coefficients $\rho_i$ into
+//! $\sum_i \rho_i h_i$, of degree the largest degree of the claims.
+//!
+//! Variables are bound in the same order as [`MLSumcheck::prove_as_subprotocol`], so the point of
+//! the subclaim of $g_i$ is the first $n_i$ challenges of the batched sumcheck.
+
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::boxed::Box;
+use stark_rings::{OverField, Ring};
+use stark_rings_poly::polynomials::DenseMultilinearExtension;
+
+use super::{
+    prover::{ProverMsg, ProverState},
+    verifier::{interpolate_uni_poly, SubClaim, VerifierMsg},
+    IPForMLSumcheck, MLSumcheck, Proof, SumCheckError,
+};
+use crate::{ark_base::*, transcript::Transcript};
+
+/// A sumcheck claim to be proven in a batch.
+pub struct BatchedClaim<'a, R: OverField> {
+    /// The multilinear extensions the polynomial is built from.
+    pub mles: Vec<DenseMultilinearExtension<R>>,
+    /// The number of variables of the polynomial.
+    pub nvars: usize,
+    /// The degree of the polynomial.
+    pub degree: usize,
+    /// Combines the evaluations of the multilinear extensions into an evaluation of the
+    /// polynomial.
+    pub comb_fn: Box<dyn Fn(&[R]) -> R + Sync + Send + 'a>,
+    /// The sum of the polynomial over the boolean hypercube.
+    pub claimed_sum: R,
+}
+
+/// A proof of a batch of sumcheck claims.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchedProof<R1: Ring> {
+    /// The sumcheck proof of the combined claim.
+    pub proof: Proof<R1>,
+    /// The evaluations of the polynomials of the claims at the points of their subclaims.
+    pub evaluations: Vec<R1>,
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Proves a batch of sumcheck claims with a single sumcheck.
+    ///
+    /// Returns the proof and the prover state of every claim, whose randomness is the point of
+    /// the subclaim of the claim.
+    ///
+    /// # Errors
+    ///
+    /// Fails if there are no claims or if a claim has no variables.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_batched(
+        transcript: &mut T,
+        claims: Vec<BatchedClaim<R>>,
+    ) -> Result<(BatchedProof<R>, Vec<ProverState<R>>), SumCheckError<R>> {
+        let shapes: Vec<(usize, usize, R)> = claims
+            .iter()
+            .map(|claim| (claim.nvars, claim.degree, claim.claimed_sum))
+            .collect();
+        let (nvars, degree, coeffs) = Self::batch_setup(transcript, &shapes)?;
+
+        let (mut states, comb_fns): (Vec<_>, Vec<_>) = claims
+            .into_iter()
+            .map(|claim| {
+                (
+                    IPForMLSumcheck::<R, T>::prover_init(claim.mles, claim.nvars, claim.degree),
+                    claim.comb_fn,
+                )
+            })
+            .unzip();
+        // The last round message of each claim, and its evaluation once all its variables are bound
+        let mut last_msgs: Vec<Vec<R>> = vec![Vec::new(); states.len()];
+        let mut evaluations = vec![R::zero(); states.len()];
+
+        transcript.absorb(&R::from(nvars as u128));
+        transcript.absorb(&R::from(degree as u128));
+        let mut verifier_msg: Option<VerifierMsg<R>> = None;
+        let mut prover_msgs = Vec::with_capacity(nvars);
+        for round in 1..=nvars {
+            let mut round_evals = vec![R::zero(); degree + 1];
+            for (i, (state, comb_fn)) in states.iter_mut().zip(&comb_fns).enumerate() {
+                let claim_evals = if round <= state.num_vars {
+                    let msg = IPForMLSumcheck::<R, T>::prove_round(state, &verifier_msg, comb_fn);
+                    let scale = pow2::<R>(nvars - state.num_vars);
+                    let evals = extend_evaluations(&msg.evaluations, degree)
+                        .into_iter()
+                        .map(|e| e * scale)
+                        .collect();
+                    last_msgs[i] = msg.evaluations;
+                    evals
+                } else {
+                    if round == state.num_vars + 1 {
+                        // Claims have variables, so this is not the first round
+                        let r = verifier_msg
+                            .as_ref()
+                            .expect("not the first round")
+                            .randomness;
+                        state.randomness.push(r);
+                        evaluations[i] = interpolate_uni_poly(&last_msgs[i], r);
+                    }
+                    vec![evaluations[i] * pow2::<R>(nvars - round); degree + 1]
+                };
+
+                let rho: R = coeffs[i].into();
+                round_evals
+                    .iter_mut()
+                    .zip(claim_evals)
+                    .for_each(|(e, c)| *e += rho * c);
+            }
+
+            transcript.absorb_slice(&round_evals);
+            prover_msgs.push(ProverMsg {
+                evaluations: round_evals,
+            });
+            let next_verifier_msg = IPForMLSumcheck::<R, T>::sample_round(transcript);
+            transcript.absorb(&next_verifier_msg.randomness.into());
+
+            verifier_msg = Some(next_verifier_msg);
+        }
+
+        let r = verifier_msg.expect("claims have variables").randomness;
+        for (i, state) in states.iter_mut().enumerate() {
+            if state.num_vars == nvars {
+                state.randomness.push(r);
+                evaluations[i] = interpolate_uni_poly(&last_msgs[i], r);
+            }
+        }
+        transcript.absorb_slice(&evaluations);
+
+        Ok((
+            BatchedProof {
+                proof: Proof(prover_msgs),
+                evaluations,
+            },
+            states,
+        ))
+    }
+
+    /// Verifies a batch of sumcheck claims, given as `(nvars, degree, claimed_sum)`.
+    ///
+    /// Returns a subclaim for every claim.
+    pub fn verify_batched(
+        transcript: &mut T,
+        claims: &[(usize, usize, R)],
+        proof: &BatchedProof<R>,
+    ) -> Result<Vec<SubClaim<R>>, SumCheckError<R>> {
+        if proof.evaluations.len() != claims.len() {
+            return Err(SumCheckError::WrongNumberOfEvaluations(
+                proof.evaluations.len(),
+                claims.len(),
+            ));
+        }
+        let (nvars, degree, coeffs) = Self::batch_setup(transcript, claims)?;
+
+        let claimed_sum = claims
+            .iter()
+            .zip(&coeffs)
+            .fold(R::zero(), |acc, (&(n, _, sum), &rho)| {
+                acc + sum * pow2::<R>(nvars - n) * R::from(rho)
+            });
+        let subclaim =
+            Self::verify_as_subprotocol(transcript, nvars, degree, claimed_sum, &proof.proof)?;
+
+        let combined = proof
+            .evaluations
+            .iter()
+            .zip(&coeffs)
+            .fold(R::zero(), |acc, (&v, &rho)| acc + v * R::from(rho));
+        if combined != subclaim.expected_evaluation {
+            return Err(SumCheckError::SumCheckFailed(
+                combined,
+                subclaim.expected_evaluation,
+            ));
+        }
+        transcript.absorb_slice(&proof.evaluations);
+
+        Ok(claims
+            .iter()
+            .zip(&proof.evaluations)
+            .map(|(&(n, _, _), &v)| SubClaim {
+                point: subclaim.point[..n].to_vec(),
+                expected_evaluation: v,
+            })
+            .collect())
+    }
+
+    /// Absorbs the claims and samples their coefficients.
+    ///
+    /// Returns the number of variables and the degree of the batched sumcheck, and the
+    /// coefficients.
+    #[allow(clippy::type_complexity)]
+    fn batch_setup(
+        transcript: &mut T,
+        claims: &[(usize, usize, R)],
+    ) -> Result<(usize, usize, Vec<R::BaseRing>), SumCheckError<R>> {
+        if claims.is_empty() {
+            return Err(SumCheckError::NoClaimsToBatch);
+        }
+        if let Some(i) = claims.iter().position(|&(nvars, _, _)| nvars == 0) {
+            return Err(SumCheckError::ClaimWithoutVariables(i));
+        }
+
+        transcript.absorb(&R::from(claims.len() as u128));
+        for &(nvars, degree, claimed_sum) in claims {
+            transcript.absorb(&R::from(nvars as u128));
+            transcript.absorb(&R::from(degree as u128));
+            transcript.absorb(&claimed_sum);
+        }
+        let coeffs = (0..claims.len())
+            .map(|_| transcript.get_challenge())
+            .collect();
+
+        let nvars = claims.iter().map(|&(n, _, _)| n).max().unwrap();
+        let degree = claims.iter().map(|&(_, d, _)| d).max().unwrap();
+
+        Ok((nvars, degree, coeffs))
+    }
+}
+
+/// Evaluations at `0..=degree` of the polynomial given by its evaluations at
+/// `0..evals.len()`.
+fn extend_evaluations<R: OverField>(evals: &[R], degree: usize) -> Vec<R> {
+    (0..=degree)
+        .map(|x| match evals.get(x) {
+            Some(e) => *e,
+            None => interpolate_uni_poly(evals, R::BaseRing::from(x as u64)),
+        })
+        .collect()
+}
+
+pub(crate) fn pow2<R: OverField>(exp: usize) -> R {
+    R::BaseRing::from(2u64).pow([exp as u64]).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::One;
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+    use stark_rings_poly::mle::MultilinearExtension;
+
+    use super::*;
+    use crate::{
+        transcript::poseidon::PoseidonTranscript,
+        utils::sumcheck::utils::{rand_poly, rand_poly_comb_fn},
+    };
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    struct TestClaim {
+        mles: Vec<DenseMultilinearExtension<R>>,
+        products: Vec<(R, Vec<usize>)>,
+        nvars: usize,
+        degree: usize,
+        sum: R,
+    }
+
+    fn claims(nvars: &[usize]) -> Vec<TestClaim> {
+        let mut rng = ark_std::test_rng();
+        nvars
+            .iter()
+            .map(|&n| {
+                let ((mles, degree), products, sum) = rand_poly(n, (2, 5), 3, &mut rng).unwrap();
+                TestClaim {
+                    mles,
+                    products,
+                    nvars: n,
+                    degree,
+                    sum,
+                }
+            })
+            .collect()
+    }
+
+    fn batched_claims(claims: &[TestClaim]) -> Vec<BatchedClaim<R>> {
+        claims
+            .iter()
+            .map(|c| BatchedClaim {
+                mles: c.mles.clone(),
+                nvars: c.nvars,
+                degree: c.degree,
+                comb_fn: Box::new(move |vals: &[R]| rand_poly_comb_fn(vals, &c.products)),
+                claimed_sum: c.sum,
+            })
+            .collect()
+    }
+
+    fn prove(claims: &[TestClaim]) -> BatchedProof<R> {
+        MLSumcheck::prove_batched(&mut T::default(), batched_claims(claims))
+            .unwrap()
+            .0
+    }
+
+    fn shapes(claims: &[TestClaim]) -> Vec<(usize, usize, R)> {
+        claims.iter().map(|c| (c.nvars, c.degree, c.sum)).collect()
+    }
+
+    #[test]
+    fn test_batched_sumcheck() {
+        let claims = claims(&[3, 5, 1, 5]);
+        let proof = prove(&claims);
+
+        let subclaims =
+            MLSumcheck::verify_batched(&mut T::default(), &shapes(&claims), &proof).unwrap();
+
+        for (claim, subclaim) in claims.iter().zip(subclaims) {
+            assert_eq!(subclaim.point.len(), claim.nvars);
+            let point: Vec<R> = subclaim.point.iter().map(|&x| x.into()).collect();
+            let vals: Vec<R> = claim
+                .mles
+                .iter()
+                .map(|mle| mle.evaluate(&point).unwrap())
+                .collect();
+            assert_eq!(
+                rand_poly_comb_fn(&vals, &claim.products),
+                subclaim.expected_evaluation
+            );
+        }
+    }
+
+    #[test]
+    fn test_batched_sumcheck_compressed() {
+        let claims = claims(&[2, 4]);
+        let proof = prove(&claims);
+        let degree = claims.iter().map(|c| c.degree).max().unwrap();
+        let compressed = BatchedProof {
+            proof: proof.proof.compress(degree),
+            evaluations: proof.evaluations.clone(),
+        };
+
+        MLSumcheck::verify_batched(&mut T::default(), &shapes(&claims), &compressed).unwrap();
+    }
+
+    #[test]
+    fn test_failing_batched_sumcheck() {
+        let claims = claims(&[3, 4]);
+        let proof = prove(&claims);
+
+        let mut wrong_shapes = shapes(&claims);
+        wrong_shapes[0].2 += R::one();
+        assert!(MLSumcheck::verify_batched(&mut T::default(), &wrong_shapes, &proof).is_err());
+
+        let mut wrong_proof = proof.clone();
+        wrong_proof.evaluations[1] += R::one();
+        assert!(
+            MLSumcheck::verify_batched(&mut T::default(), &shapes(&claims), &wrong_proof).is_err()
+        );
+    }
+
+    #[test]
+    fn test_malformed_batch() {
+        let mut claims = claims(&[2]);
+        let proof = prove(&claims);
+
+        assert!(matches!(
+            MLSumcheck::prove_batched(&mut T::default(), Vec::new()),
+            Err(SumCheckError::NoClaimsToBatch)
+        ));
+        assert!(matches!(
+            MLSumcheck::verify_batched(
+                &mut T::default(),
+                &[],
+                &BatchedProof {
+                    proof: proof.proof.clone(),
+                    evaluations: Vec::new(),
+                }
+            ),
+            Err(SumCheckError::NoClaimsToBatch)
+        ));
+
+        // A constant polynomial, with no variables
+        claims.push(TestClaim {
+            mles: vec![DenseMultilinearExtension::from_evaluations_vec(
+                0,
+                vec![R::one()],
+            )],
+            products: vec![(R::one(), vec![0])],
+            nvars: 0,
+            degree: 1,
+            sum: R::one(),
+        });
+        assert!(matches!(
+            MLSumcheck::prove_batched(&mut T::default(), batched_claims(&claims)),
+            Err(SumCheckError::ClaimWithoutVariables(1))
+        ));
+        assert!(matches!(
+            MLSumcheck::verify_batched(
+                &mut T::default(),
+                &shapes(&claims),
+                &BatchedProof {
+                    proof: proof.proof,
+                    evaluations: vec![R::one(); 2],
+                }
+            ),
+            Err(SumCheckError::ClaimWithoutVariables(1))
+        ));
+    }
+}
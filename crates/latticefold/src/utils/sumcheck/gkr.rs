@@ -0,0 +1,733 @@
+//! GKR protocol for layered arithmetic circuits
+//!
+//! A [`LayeredCircuit`] is a sequence of layers of addition and multiplication gates, every gate
+//! reading two values of the layer below it. Writing $V_i$ for the multilinear extension of the
+//! values of layer $i$, layer $0$ being the output layer,
+//! $$
+//! V_i(z) = \sum_{x, y} \mathrm{add}_i(z, x, y) (V_{i+1}(x) + V_{i+1}(y))
+//!     + \mathrm{mul}_i(z, x, y) V_{i+1}(x) V_{i+1}(y),
+//! $$
+//! where the wiring predicates $\mathrm{add}_i$ and $\mathrm{mul}_i$ are sparse multilinear
+//! extensions, with one non-zero entry per gate.
+//!
+//! [`Gkr::prove`] reduces a claim about the outputs to claims about the values of every layer, down
+//! to the inputs. At every layer, a random combination of the two claims about $V_i$ is reduced to
+//! claims about $V_{i+1}(r_x)$ and $V_{i+1}(r_y)$ by two sumchecks of degree 2, over $x$ and then
+//! over $y$, in time linear in the size of the layer. The two claims about the inputs are finally
+//! combined into a single [`EvaluationClaim`], which the caller checks against the inputs or a
+//! commitment to them. The circuit and the outputs are absorbed before the first challenge.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use stark_rings::{OverField, Ring};
+use stark_rings_poly::polynomials::{ArithErrors, DenseMultilinearExtension};
+use thiserror::Error;
+
+use super::{
+    utils::{build_eq_x_r_vec, eq_eval},
+    MLSumcheck, Proof, SumCheckError,
+};
+use crate::{ark_base::*, transcript::Transcript};
+
+#[derive(Debug, Error)]
+pub enum CircuitError {
+    #[error("invalid circuit: {0}")]
+    InvalidCircuit(String),
+    #[error("got {0} inputs, the circuit has {1}")]
+    WrongNumberOfInputs(usize, usize),
+}
+
+#[derive(Debug, Error)]
+pub enum GkrError<R: Ring> {
+    #[error("circuit error: {0}")]
+    CircuitError(#[from] CircuitError),
+    #[error("got {0} outputs, the circuit has {1}")]
+    WrongNumberOfOutputs(usize, usize),
+    #[error("the proof has {0} layers, the circuit has {1}")]
+    WrongNumberOfLayers(usize, usize),
+    #[error("sum check failed: {0}")]
+    SumCheckError(#[from] SumCheckError<R>),
+    #[error("arithmetic error: {0}")]
+    ArithmeticError(#[from] ArithErrors),
+    #[error("the final check of layer {0} failed")]
+    LayerCheckFailed(usize),
+    #[error("the final check of the input layer failed")]
+    InputCheckFailed,
+}
+
+/// The operation of a gate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GateKind {
+    Add,
+    Mul,
+}
+
+/// A gate, reading the values `left` and `right` of the layer below.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gate {
+    pub kind: GateKind,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Gate {
+    /// The gate computing `left + right`.
+    pub fn add(left: usize, right: usize) -> Self {
+        Self {
+            kind: GateKind::Add,
+            left,
+            right,
+        }
+    }
+
+    /// The gate computing `left * right`.
+    pub fn mul(left: usize, right: usize) -> Self {
+        Self {
+            kind: GateKind::Mul,
+            left,
+            right,
+        }
+    }
+}
+
+/// A layer of gates.
+///
+/// The gates are the non-zero entries of the wiring predicates of the layer: gate `z` of kind
+/// `Add` reading `x` and `y` is the entry `add(z, x, y) = 1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layer {
+    gates: Vec<Gate>,
+    nvars: usize,
+}
+
+impl Layer {
+    /// The gates.
+    pub fn gates(&self) -> &[Gate] {
+        &self.gates
+    }
+
+    /// The number of variables of the extension of the values of the layer.
+    pub fn nvars(&self) -> usize {
+        self.nvars
+    }
+
+    /// Evaluates the wiring predicates at `(z, x, y)`, given the table `eq_z` of the weights of
+    /// `z` over the hypercube.
+    ///
+    /// Returns `(add(z, x, y), mul(z, x, y))`, in time linear in the number of gates.
+    pub fn evaluate_wiring<R: Ring>(
+        &self,
+        eq_z: &[R],
+        x: &[R],
+        y: &[R],
+    ) -> Result<(R, R), ArithErrors> {
+        let eq_x = build_eq_x_r_vec(x)?;
+        let eq_y = build_eq_x_r_vec(y)?;
+
+        Ok(self
+            .gates
+            .iter()
+            .zip(eq_z)
+            .fold((R::zero(), R::zero()), |(add, mul), (gate, &w)| {
+                let term = w * eq_x[gate.left] * eq_y[gate.right];
+                match gate.kind {
+                    GateKind::Add => (add + term, mul),
+                    GateKind::Mul => (add, mul + term),
+                }
+            }))
+    }
+}
+
+/// A layered arithmetic circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayeredCircuit {
+    num_inputs: usize,
+    input_nvars: usize,
+    /// The layers, from the outputs to the layer reading the inputs
+    layers: Vec<Layer>,
+}
+
+/// The number of variables of the extension of `len` values, at least one so that every sumcheck
+/// has a round.
+fn nvars_of(len: usize) -> usize {
+    (len.next_power_of_two().ilog2() as usize).max(1)
+}
+
+impl LayeredCircuit {
+    /// The circuit over `num_inputs` inputs with the gates `layers`, from the outputs to the
+    /// layer reading the inputs.
+    pub fn new(num_inputs: usize, layers: Vec<Vec<Gate>>) -> Result<Self, CircuitError> {
+        if num_inputs == 0 || layers.is_empty() {
+            return Err(CircuitError::InvalidCircuit(
+                "the circuit has no inputs or no layers".to_string(),
+            ));
+        }
+
+        let mut below = num_inputs;
+        for (i, gates) in layers.iter().enumerate().rev() {
+            if gates.is_empty() {
+                return Err(CircuitError::InvalidCircuit(format!(
+                    "layer {i} has no gates"
+                )));
+            }
+            if let Some(gate) = gates
+                .iter()
+                .find(|gate| gate.left >= below || gate.right >= below)
+            {
+                return Err(CircuitError::InvalidCircuit(format!(
+                    "a gate of layer {i} reads {} and {}, the layer below has {below} values",
+                    gate.left, gate.right
+                )));
+            }
+            below = gates.len();
+        }
+
+        Ok(Self {
+            num_inputs,
+            input_nvars: nvars_of(num_inputs),
+            layers: layers
+                .into_iter()
+                .map(|gates| Layer {
+                    nvars: nvars_of(gates.len()),
+                    gates,
+                })
+                .collect(),
+        })
+    }
+
+    /// The number of inputs.
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    /// The number of outputs.
+    pub fn num_outputs(&self) -> usize {
+        self.layers[0].gates.len()
+    }
+
+    /// The number of variables of the extension of the inputs.
+    pub fn input_nvars(&self) -> usize {
+        self.input_nvars
+    }
+
+    /// The layers, from the outputs to the layer reading the inputs.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// The number of variables of the extension of the values read by layer `i`.
+    fn nvars_below(&self, i: usize) -> usize {
+        self.layers
+            .get(i + 1)
+            .map_or(self.input_nvars, |layer| layer.nvars)
+    }
+
+    /// Evaluates the circuit, returning the outputs.
+    pub fn evaluate<R: Ring>(&self, inputs: &[R]) -> Result<Vec<R>, CircuitError> {
+        let mut outputs = self.layer_values(inputs)?.swap_remove(0);
+        outputs.truncate(self.num_outputs());
+        Ok(outputs)
+    }
+
+    /// The values of every layer, from the outputs to the inputs, padded with zeros to a power of
+    /// two.
+    fn layer_values<R: Ring>(&self, inputs: &[R]) -> Result<Vec<Vec<R>>, CircuitError> {
+        if inputs.len() != self.num_inputs {
+            return Err(CircuitError::WrongNumberOfInputs(
+                inputs.len(),
+                self.num_inputs,
+            ));
+        }
+
+        let mut below = inputs.to_vec();
+        below.resize(1 << self.input_nvars, R::zero());
+        let mut values = vec![below];
+        for layer in self.layers.iter().rev() {
+            let below = values.last().expect("values are not empty");
+            let mut layer_values: Vec<R> = layer
+                .gates
+                .iter()
+                .map(|gate| match gate.kind {
+                    GateKind::Add => below[gate.left] + below[gate.right],
+                    GateKind::Mul => below[gate.left] * below[gate.right],
+                })
+                .collect();
+            layer_values.resize(1 << layer.nvars, R::zero());
+            values.push(layer_values);
+        }
+        values.reverse();
+
+        Ok(values)
+    }
+}
+
+/// A claim `V(point) = evaluation` about the extension `V` of some values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvaluationClaim<R> {
+    pub point: Vec<R>,
+    pub evaluation: R,
+}
+
+/// The reduction of the claims about a layer to claims about the layer below.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LayerProof<R: Ring> {
+    /// The sumcheck over the left inputs `x` of the gates
+    pub left_sumcheck: Proof<R>,
+    /// The value `V(r_x)` of the layer below
+    pub left_evaluation: R,
+    /// The sumcheck over the right inputs `y` of the gates
+    pub right_sumcheck: Proof<R>,
+    /// The value `V(r_y)` of the layer below
+    pub right_evaluation: R,
+}
+
+/// A GKR proof.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GkrProof<R: Ring> {
+    /// The reductions of the layers, from the outputs to the layer reading the inputs
+    pub layers: Vec<LayerProof<R>>,
+    /// The sumcheck combining the two claims about the inputs
+    pub input_sumcheck: Proof<R>,
+    /// The evaluation of the extension of the inputs at the point of the input sumcheck
+    pub input_evaluation: R,
+}
+
+/// GKR protocol for layered circuits
+pub struct Gkr<R, T>(#[doc(hidden)] PhantomData<(R, T)>);
+
+impl<R: OverField, T: Transcript<R>> Gkr<R, T> {
+    /// Proves the evaluation of `circuit` on `inputs`.
+    ///
+    /// Returns the outputs, the proof and the final claim about the extension of the inputs.
+    pub fn prove(
+        transcript: &mut T,
+        circuit: &LayeredCircuit,
+        inputs: &[R],
+    ) -> Result<(Vec<R>, GkrProof<R>, EvaluationClaim<R>), GkrError<R>> {
+        let values = circuit.layer_values(inputs)?;
+        let outputs = values[0][..circuit.num_outputs()].to_vec();
+
+        let mut claims = vec![output_claim(transcript, circuit, &outputs)?];
+        let mut layers = Vec::with_capacity(circuit.layers.len());
+        for (layer, below) in circuit.layers.iter().zip(&values[1..]) {
+            let (layer_proof, below_claims) = prove_layer(transcript, layer, below, &claims)?;
+            layers.push(layer_proof);
+            claims = below_claims;
+        }
+
+        let inputs = values.last().expect("values are not empty");
+        let nvars = circuit.input_nvars;
+        let (coeffs, _) = sample_combination(transcript, &claims);
+        let weights = DenseMultilinearExtension::from_evaluations_vec(
+            nvars,
+            combined_eq(&claims, &coeffs, nvars)?,
+        );
+        let inputs_mle = DenseMultilinearExtension::from_evaluations_slice(nvars, inputs);
+        let (input_sumcheck, state) = MLSumcheck::prove_borrowed(
+            transcript,
+            &[&inputs_mle, &weights],
+            nvars,
+            2,
+            |vals: &[R]| vals[0] * vals[1],
+        );
+        let point: Vec<R> = state.randomness.into_iter().map(Into::into).collect();
+        let input_evaluation = evaluate_values(inputs, &point)?;
+        transcript.absorb(&input_evaluation);
+
+        let proof = GkrProof {
+            layers,
+            input_sumcheck,
+            input_evaluation,
+        };
+        let claim = EvaluationClaim {
+            point,
+            evaluation: input_evaluation,
+        };
+
+        Ok((outputs, proof, claim))
+    }
+
+    /// Verifies that `circuit` evaluates to `outputs`.
+    ///
+    /// Returns the final claim about the extension of the inputs, which the caller still has to
+    /// check.
+    pub fn verify(
+        transcript: &mut T,
+        circuit: &LayeredCircuit,
+        outputs: &[R],
+        proof: &GkrProof<R>,
+    ) -> Result<EvaluationClaim<R>, GkrError<R>> {
+        if outputs.len() != circuit.num_outputs() {
+            return Err(GkrError::WrongNumberOfOutputs(
+                outputs.len(),
+                circuit.num_outputs(),
+            ));
+        }
+        if proof.layers.len() != circuit.layers.len() {
+            return Err(GkrError::WrongNumberOfLayers(
+                proof.layers.len(),
+                circuit.layers.len(),
+            ));
+        }
+
+        let mut claims = vec![output_claim(transcript, circuit, outputs)?];
+        for (i, (layer, layer_proof)) in circuit.layers.iter().zip(&proof.layers).enumerate() {
+            let nvars = circuit.nvars_below(i);
+            let (coeffs, sum) = sample_combination(transcript, &claims);
+
+            let left = MLSumcheck::verify_as_subprotocol(
+                transcript,
+                nvars,
+                2,
+                sum,
+                &layer_proof.left_sumcheck,
+            )?;
+            transcript.absorb(&layer_proof.left_evaluation);
+            let right = MLSumcheck::verify_as_subprotocol(
+                transcript,
+                nvars,
+                2,
+                left.expected_evaluation,
+                &layer_proof.right_sumcheck,
+            )?;
+            transcript.absorb(&layer_proof.right_evaluation);
+
+            let x: Vec<R> = left.point.into_iter().map(Into::into).collect();
+            let y: Vec<R> = right.point.into_iter().map(Into::into).collect();
+            let eq_z = combined_eq(&claims, &coeffs, layer.nvars)?;
+            let (add, mul) = layer.evaluate_wiring(&eq_z, &x, &y)?;
+            let (vx, vy) = (layer_proof.left_evaluation, layer_proof.right_evaluation);
+            if right.expected_evaluation != vy * (add + vx * mul) + vx * add {
+                return Err(GkrError::LayerCheckFailed(i));
+            }
+
+            claims = vec![
+                EvaluationClaim {
+                    point: x,
+                    evaluation: vx,
+                },
+                EvaluationClaim {
+                    point: y,
+                    evaluation: vy,
+                },
+            ];
+        }
+
+        let (coeffs, sum) = sample_combination(transcript, &claims);
+        let subclaim = MLSumcheck::verify_as_subprotocol(
+            transcript,
+            circuit.input_nvars,
+            2,
+            sum,
+            &proof.input_sumcheck,
+        )?;
+        transcript.absorb(&proof.input_evaluation);
+
+        let point: Vec<R> = subclaim.point.into_iter().map(Into::into).collect();
+        let weight = claims
+            .iter()
+            .zip(&coeffs)
+            .try_fold(R::zero(), |acc, (claim, &coeff)| {
+                Ok::<R, ArithErrors>(acc + coeff * eq_eval(&claim.point, &point)?)
+            })?;
+        if subclaim.expected_evaluation != proof.input_evaluation * weight {
+            return Err(GkrError::InputCheckFailed);
+        }
+
+        Ok(EvaluationClaim {
+            point,
+            evaluation: proof.input_evaluation,
+        })
+    }
+}
+
+/// Absorbs the circuit, its layer sizes and the wiring of every gate, so that the challenges are
+/// bound to the statement and not only to the outputs.
+fn absorb_circuit<R: OverField, T: Transcript<R>>(transcript: &mut T, circuit: &LayeredCircuit) {
+    transcript.absorb(&R::from(circuit.num_inputs as u128));
+    transcript.absorb(&R::from(circuit.layers.len() as u128));
+    for layer in &circuit.layers {
+        transcript.absorb(&R::from(layer.gates.len() as u128));
+        let wiring: Vec<R> = layer
+            .gates
+            .iter()
+            .flat_map(|gate| {
+                let kind = match gate.kind {
+                    GateKind::Add => 0u128,
+                    GateKind::Mul => 1,
+                };
+                [kind, gate.left as u128, gate.right as u128]
+            })
+            .map(R::from)
+            .collect();
+        transcript.absorb_slice(&wiring);
+    }
+}
+
+/// Absorbs the circuit and the outputs, and returns the claim about the extension of the outputs
+/// at a random point.
+fn output_claim<R: OverField, T: Transcript<R>>(
+    transcript: &mut T,
+    circuit: &LayeredCircuit,
+    outputs: &[R],
+) -> Result<EvaluationClaim<R>, ArithErrors> {
+    absorb_circuit(transcript, circuit);
+    transcript.absorb_slice(outputs);
+    let point: Vec<R> = transcript
+        .get_challenges(circuit.layers[0].nvars)
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let evaluation = evaluate_values(outputs, &point)?;
+
+    Ok(EvaluationClaim { point, evaluation })
+}
+
+/// Samples the coefficients of a random combination of `claims`, returning them with the
+/// combined evaluation.
+fn sample_combination<R: OverField, T: Transcript<R>>(
+    transcript: &mut T,
+    claims: &[EvaluationClaim<R>],
+) -> (Vec<R>, R) {
+    let coeffs: Vec<R> = claims
+        .iter()
+        .map(|_| transcript.get_challenge().into())
+        .collect();
+    let sum = claims
+        .iter()
+        .zip(&coeffs)
+        .fold(R::zero(), |acc, (claim, &coeff)| {
+            acc + coeff * claim.evaluation
+        });
+
+    (coeffs, sum)
+}
+
+/// The table of $\sum_j c_j \mathrm{eq}(p_j, \cdot)$ over `nvars` variables, $p_j$ being the
+/// points of `claims` and $c_j$ the coefficients `coeffs`.
+fn combined_eq<R: Ring>(
+    claims: &[EvaluationClaim<R>],
+    coeffs: &[R],
+    nvars: usize,
+) -> Result<Vec<R>, ArithErrors> {
+    let mut weights = vec![R::zero(); 1 << nvars];
+    for (claim, &coeff) in claims.iter().zip(coeffs) {
+        for (w, e) in weights.iter_mut().zip(build_eq_x_r_vec(&claim.point)?) {
+            *w += coeff * e;
+        }
+    }
+
+    Ok(weights)
+}
+
+/// Evaluates the extension of `values`, padded with zeros, at `point`.
+fn evaluate_values<R: Ring>(values: &[R], point: &[R]) -> Result<R, ArithErrors> {
+    Ok(build_eq_x_r_vec(point)?
+        .into_iter()
+        .zip(values)
+        .fold(R::zero(), |acc, (e, &v)| acc + e * v))
+}
+
+/// Reduces the claims about the values of `layer` to two claims about the values `below` of the
+/// layer below.
+fn prove_layer<R: OverField, T: Transcript<R>>(
+    transcript: &mut T,
+    layer: &Layer,
+    below: &[R],
+    claims: &[EvaluationClaim<R>],
+) -> Result<(LayerProof<R>, Vec<EvaluationClaim<R>>), GkrError<R>> {
+    let nvars = below.len().ilog2() as usize;
+    let (coeffs, _) = sample_combination(transcript, claims);
+    let eq_z = combined_eq(claims, &coeffs, layer.nvars)?;
+    let below_mle = DenseMultilinearExtension::from_evaluations_slice(nvars, below);
+
+    // Over x: V(x) * sum_y (add(z, x, y) + mul(z, x, y) V(y)) + sum_y add(z, x, y) V(y)
+    let mut factor = vec![R::zero(); below.len()];
+    let mut addend = vec![R::zero(); below.len()];
+    for (gate, &w) in layer.gates.iter().zip(&eq_z) {
+        match gate.kind {
+            GateKind::Add => {
+                factor[gate.left] += w;
+                addend[gate.left] += w * below[gate.right];
+            }
+            GateKind::Mul => factor[gate.left] += w * below[gate.right],
+        }
+    }
+    let (left_sumcheck, x, vx) = prove_phase(transcript, &below_mle, factor, addend)?;
+
+    // Over y: V(y) * (add(z, r_x, y) + mul(z, r_x, y) V(r_x)) + add(z, r_x, y) V(r_x)
+    let eq_x = build_eq_x_r_vec(&x)?;
+    let mut factor = vec![R::zero(); below.len()];
+    let mut addend = vec![R::zero(); below.len()];
+    for (gate, &w) in layer.gates.iter().zip(&eq_z) {
+        let w = w * eq_x[gate.left];
+        match gate.kind {
+            GateKind::Add => {
+                factor[gate.right] += w;
+                addend[gate.right] += w * vx;
+            }
+            GateKind::Mul => factor[gate.right] += w * vx,
+        }
+    }
+    let (right_sumcheck, y, vy) = prove_phase(transcript, &below_mle, factor, addend)?;
+
+    let layer_proof = LayerProof {
+        left_sumcheck,
+        left_evaluation: vx,
+        right_sumcheck,
+        right_evaluation: vy,
+    };
+    let claims = vec![
+        EvaluationClaim {
+            point: x,
+            evaluation: vx,
+        },
+        EvaluationClaim {
+            point: y,
+            evaluation: vy,
+        },
+    ];
+
+    Ok((layer_proof, claims))
+}
+
+/// Runs the sumcheck of `V * factor + addend`, and absorbs the evaluation of `V` at its point.
+///
+/// Returns the proof, the point and the evaluation of `V`.
+fn prove_phase<R: OverField, T: Transcript<R>>(
+    transcript: &mut T,
+    below: &DenseMultilinearExtension<R>,
+    factor: Vec<R>,
+    addend: Vec<R>,
+) -> Result<(Proof<R>, Vec<R>, R), ArithErrors> {
+    let nvars = below.evaluations.len().ilog2() as usize;
+    let factor = DenseMultilinearExtension::from_evaluations_vec(nvars, factor);
+    let addend = DenseMultilinearExtension::from_evaluations_vec(nvars, addend);
+    let (proof, state) = MLSumcheck::prove_borrowed(
+        transcript,
+        &[below, &factor, &addend],
+        nvars,
+        2,
+        |vals: &[R]| vals[0] * vals[1] + vals[2],
+    );
+
+    let point: Vec<R> = state.randomness.into_iter().map(Into::into).collect();
+    let evaluation = evaluate_values(&below.evaluations, &point)?;
+    transcript.absorb(&evaluation);
+
+    Ok((proof, point, evaluation))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::{One, UniformRand};
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::transcript::poseidon::PoseidonTranscript;
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    /// `(x0 * x1 + x2 + x3) * (x2 + x3)^2`, `x0 * x1 * x4^6` and `16 * x4^4`
+    fn circuit() -> LayeredCircuit {
+        LayeredCircuit::new(
+            5,
+            vec![
+                vec![Gate::mul(0, 1), Gate::mul(2, 3), Gate::mul(4, 4)],
+                vec![
+                    Gate::add(0, 1),
+                    Gate::mul(1, 1),
+                    Gate::mul(0, 2),
+                    Gate::mul(2, 2),
+                    Gate::mul(3, 3),
+                ],
+                vec![
+                    Gate::mul(0, 1),
+                    Gate::add(2, 3),
+                    Gate::mul(4, 4),
+                    Gate::add(4, 4),
+                ],
+            ],
+        )
+        .unwrap()
+    }
+
+    fn rand_inputs() -> Vec<R> {
+        let mut rng = ark_std::test_rng();
+        (0..5).map(|_| R::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_gkr() {
+        let circuit = circuit();
+        let x = rand_inputs();
+        let (x01, x23, x4) = (x[0] * x[1], x[2] + x[3], x[4]);
+        let expected = vec![
+            (x01 + x23) * x23 * x23,
+            x01 * x4 * x4 * x4 * x4 * x4 * x4,
+            R::from(16u64) * x4 * x4 * x4 * x4,
+        ];
+        assert_eq!(circuit.evaluate(&x).unwrap(), expected);
+
+        let (outputs, proof, claim) = Gkr::prove(&mut T::default(), &circuit, &x).unwrap();
+        assert_eq!(outputs, expected);
+        assert_eq!(claim.evaluation, evaluate_values(&x, &claim.point).unwrap());
+
+        let verifier_claim = Gkr::verify(&mut T::default(), &circuit, &outputs, &proof).unwrap();
+        assert_eq!(verifier_claim, claim);
+    }
+
+    #[test]
+    fn test_gkr_wrong_outputs() {
+        let circuit = circuit();
+        let (mut outputs, proof, _) =
+            Gkr::prove(&mut T::default(), &circuit, &rand_inputs()).unwrap();
+
+        outputs[1] += R::one();
+        assert!(Gkr::verify(&mut T::default(), &circuit, &outputs, &proof).is_err());
+        assert!(Gkr::verify(&mut T::default(), &circuit, &outputs[1..], &proof).is_err());
+    }
+
+    #[test]
+    fn test_gkr_tampered_proof() {
+        let circuit = circuit();
+        let (outputs, proof, _) = Gkr::prove(&mut T::default(), &circuit, &rand_inputs()).unwrap();
+
+        let mut wrong_proof = proof.clone();
+        wrong_proof.layers[1].left_evaluation += R::one();
+        assert!(Gkr::verify(&mut T::default(), &circuit, &outputs, &wrong_proof).is_err());
+
+        let mut wrong_proof = proof;
+        wrong_proof.input_evaluation += R::one();
+        assert!(Gkr::verify(&mut T::default(), &circuit, &outputs, &wrong_proof).is_err());
+    }
+
+    #[test]
+    fn test_gkr_is_bound_to_the_circuit() {
+        let circuit = circuit();
+        let x = rand_inputs();
+        let (outputs, proof, _) = Gkr::prove(&mut T::default(), &circuit, &x).unwrap();
+
+        // The same outputs, with the operands of a gate swapped
+        let mut layers: Vec<Vec<Gate>> = circuit
+            .layers()
+            .iter()
+            .map(|layer| layer.gates().to_vec())
+            .collect();
+        layers[0][0] = Gate::mul(1, 0);
+        let swapped = LayeredCircuit::new(circuit.num_inputs(), layers).unwrap();
+        assert_eq!(swapped.evaluate(&x).unwrap(), outputs);
+        assert!(Gkr::verify(&mut T::default(), &swapped, &outputs, &proof).is_err());
+    }
+
+    #[test]
+    fn test_invalid_circuit() {
+        assert!(LayeredCircuit::new(2, vec![vec![Gate::add(0, 2)]]).is_err());
+        assert!(LayeredCircuit::new(2, vec![vec![Gate::add(0, 1)], vec![]]).is_err());
+        assert!(LayeredCircuit::new(2, vec![]).is_err());
+
+        let circuit = LayeredCircuit::new(2, vec![vec![Gate::add(0, 1)]]).unwrap();
+        assert!(circuit.evaluate(&[R::one()]).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! Memory-lean sumcheck prover
+//!
+//! [`MLSumcheck::prove_as_subprotocol`] takes its multilinear extensions by value, so callers that
+//! still need them afterwards clone them, and the first round of `fix_variables` copies them
+//! once more. The prover of [`MLSumcheck::prove_borrowed`] borrows the extensions instead: the
+//! first round is computed from the originals, binding the first variable writes fresh half-size
+//! buffers, and operands referring to the same extension share a single table.
+
+use ark_std::{borrow::Cow, cfg_into_iter, ptr};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use stark_rings::{OverField, Ring};
+use stark_rings_poly::polynomials::DenseMultilinearExtension;
+
+use super::{
+    prover::{ProverMsg, ProverState},
+    IPForMLSumcheck, MLSumcheck, Proof,
+};
+use crate::{ark_base::*, transcript::Transcript};
+
+/// The evaluation tables of the operands of a sumcheck.
+///
+/// The tables are borrowed until the first variable is bound, and operands referring to the same
+/// multilinear extension share one table.
+pub(crate) struct SharedMles<'a, R: Clone> {
+    tables: Vec<Cow<'a, [R]>>,
+    /// The table of every operand
+    operands: Vec<usize>,
+}
+
+impl<'a, R: Ring> SharedMles<'a, R> {
+    pub(crate) fn new(mles: &[&'a DenseMultilinearExtension<R>]) -> Self {
+        let mut unique: Vec<&'a DenseMultilinearExtension<R>> = Vec::new();
+        let operands = mles
+            .iter()
+            .map(|&mle| {
+                unique
+                    .iter()
+                    .position(|&u| ptr::eq(u, mle))
+                    .unwrap_or_else(|| {
+                        unique.push(mle);
+                        unique.len() - 1
+                    })
+            })
+            .collect();
+
+        Self {
+            tables: unique
+                .into_iter()
+                .map(|mle| Cow::Borrowed(mle.evaluations.as_slice()))
+                .collect(),
+            operands,
+        }
+    }
+
+    /// The number of distinct tables.
+    pub(crate) fn num_tables(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// The number of operands.
+    pub(crate) fn num_operands(&self) -> usize {
+        self.operands.len()
+    }
+
+    /// Loads the evaluations of the tables at `2 * b` in `cur`, and their differences with the
+    /// evaluations at `2 * b + 1` in `steps`.
+    pub(crate) fn load(&self, b: usize, cur: &mut [R], steps: &mut [R]) {
+        for ((c, s), table) in cur.iter_mut().zip(steps.iter_mut()).zip(&self.tables) {
+            *c = table[b << 1];
+            *s = table[(b << 1) + 1] - table[b << 1];
+        }
+    }
+
+    /// Writes the values of the operands, given the values `cur` of the tables.
+    pub(crate) fn operand_values(&self, cur: &[R], vals: &mut [R]) {
+        for (v, &i) in vals.iter_mut().zip(&self.operands) {
+            *v = cur[i];
+        }
+    }
+
+    /// Binds the first variable of every table to `r`, into half-size buffers.
+    pub(crate) fn bind(&mut self, r: R) {
+        for table in self.tables.iter_mut() {
+            let evals: &[R] = table;
+            let bound: Vec<R> = cfg_into_iter!(0..evals.len() >> 1)
+                .map(|b| evals[b << 1] + r * (evals[(b << 1) + 1] - evals[b << 1]))
+                .collect();
+            *table = Cow::Owned(bound);
+        }
+    }
+
+    /// The operands as multilinear extensions over `nvars` variables.
+    pub(crate) fn into_mles(self, nvars: usize) -> Vec<DenseMultilinearExtension<R>> {
+        self.operands
+            .iter()
+            .map(|&i| DenseMultilinearExtension::from_evaluations_slice(nvars, &self.tables[i]))
+            .collect()
+    }
+}
+
+/// Steps the values `cur` of the tables to the next evaluation point.
+pub(crate) fn step<R: Ring>(cur: &mut [R], steps: &[R]) {
+    cur.iter_mut().zip(steps).for_each(|(c, s)| *c += *s);
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Runs [`MLSumcheck::prove_as_subprotocol`] on borrowed multilinear extensions.
+    ///
+    /// The extensions are not cloned: the first round reads them directly, and the tables of the
+    /// following rounds are at most half their size. The same extension can be passed several
+    /// times, its operands then share one table. The proof and the transcript are the same as
+    /// with [`MLSumcheck::prove_as_subprotocol`].
+    pub fn prove_borrowed(
+        transcript: &mut T,
+        mles: &[&DenseMultilinearExtension<R>],
+        nvars: usize,
+        degree: usize,
+        comb_fn: impl Fn(&[R]) -> R + Sync + Send,
+    ) -> (Proof<R>, ProverState<R>) {
+        assert!(nvars > 0, "Attempt to prove a constant.");
+
+        transcript.absorb(&R::from(nvars as u128));
+        transcript.absorb(&R::from(degree as u128));
+        let mut shared = SharedMles::new(mles);
+        let mut randomness: Vec<R::BaseRing> = Vec::with_capacity(nvars);
+        let mut prover_msgs = Vec::with_capacity(nvars);
+        for round in 0..nvars {
+            if let Some(&r) = randomness.last() {
+                shared.bind(R::from(r));
+            }
+
+            let prover_msg = lean_round(&shared, 1 << (nvars - round - 1), degree, &comb_fn);
+            transcript.absorb_slice(&prover_msg.evaluations);
+            prover_msgs.push(prover_msg);
+            let verifier_msg = IPForMLSumcheck::<R, T>::sample_round(transcript);
+            transcript.absorb(&verifier_msg.randomness.into());
+            randomness.push(verifier_msg.randomness);
+        }
+
+        let prover_state = ProverState {
+            randomness,
+            mles: shared.into_mles(1),
+            num_vars: nvars,
+            max_degree: degree,
+            round: nvars,
+        };
+
+        (Proof(prover_msgs), prover_state)
+    }
+}
+
+/// Computes the message of the current round, the evaluations on `0, ..., degree`, `half` being
+/// the number of points of the remaining hypercube after the current variable.
+fn lean_round<R: OverField>(
+    shared: &SharedMles<R>,
+    half: usize,
+    degree: usize,
+    comb_fn: &(impl Fn(&[R]) -> R + Sync + Send),
+) -> ProverMsg<R> {
+    struct Scratch<R> {
+        evals: Vec<R>,
+        cur: Vec<R>,
+        steps: Vec<R>,
+        vals: Vec<R>,
+    }
+    let scratch = || Scratch {
+        evals: vec![R::zero(); degree + 1],
+        cur: vec![R::zero(); shared.num_tables()],
+        steps: vec![R::zero(); shared.num_tables()],
+        vals: vec![R::zero(); shared.num_operands()],
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let zeros = scratch();
+    #[cfg(feature = "parallel")]
+    let zeros = scratch;
+
+    let summer = cfg_into_iter!(0..half).fold(zeros, |mut s, b| {
+        shared.load(b, &mut s.cur, &mut s.steps);
+        for (x, eval) in s.evals.iter_mut().enumerate() {
+            if x > 0 {
+                step(&mut s.cur, &s.steps);
+            }
+            shared.operand_values(&s.cur, &mut s.vals);
+            *eval += comb_fn(&s.vals);
+        }
+        s
+    });
+
+    // Rayon's fold outputs an iter which still needs to be summed over
+    #[cfg(feature = "parallel")]
+    let evaluations = summer.map(|s| s.evals).reduce(
+        || vec![R::zero(); degree + 1],
+        |mut evaluations, levals| {
+            evaluations
+                .iter_mut()
+                .zip(levals)
+                .for_each(|(e, l)| *e += l);
+            evaluations
+        },
+    );
+
+    #[cfg(not(feature = "parallel"))]
+    let evaluations = summer.evals;
+
+    ProverMsg { evaluations }
+}
+
+#[cfg(test)]
+mod tests {
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::{
+        transcript::poseidon::PoseidonTranscript,
+        utils::sumcheck::utils::{rand_poly, rand_poly_comb_fn},
+    };
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    #[test]
+    fn test_borrowed_sumcheck_matches_owned() {
+        for nvars in [1, 2, 5] {
+            let ((mles, degree), products, _) =
+                rand_poly::<R>(nvars, (2, 4), 3, &mut ark_std::test_rng()).unwrap();
+            let comb_fn = |vals: &[R]| rand_poly_comb_fn(vals, &products);
+
+            let (expected, expected_state) = MLSumcheck::prove_as_subprotocol(
+                &mut T::default(),
+                mles.clone(),
+                nvars,
+                degree,
+                comb_fn,
+            );
+
+            let refs: Vec<&DenseMultilinearExtension<R>> = mles.iter().collect();
+            let (proof, state) =
+                MLSumcheck::prove_borrowed(&mut T::default(), &refs, nvars, degree, comb_fn);
+            assert_eq!(proof, expected);
+            assert_eq!(state.randomness, expected_state.randomness);
+            assert_eq!(state.mles, expected_state.mles);
+        }
+    }
+
+    #[test]
+    fn test_repeated_operands_share_tables() {
+        const NVARS: usize = 4;
+        let ((mles, _), _, _) = rand_poly::<R>(NVARS, (2, 3), 1, &mut ark_std::test_rng()).unwrap();
+        let (f, g) = (&mles[0], &mles[1]);
+        // f^2 * g + f
+        let comb_fn = |vals: &[R]| vals[0] * vals[1] * vals[2] + vals[3];
+
+        let refs = [f, f, g, f];
+        let shared = SharedMles::new(&refs);
+        assert_eq!(shared.num_tables(), 2);
+        assert_eq!(shared.num_operands(), 4);
+
+        let owned = vec![f.clone(), f.clone(), g.clone(), f.clone()];
+        let (expected, _) =
+            MLSumcheck::prove_as_subprotocol(&mut T::default(), owned, NVARS, 3, comb_fn);
+        let (proof, _) = MLSumcheck::prove_borrowed(&mut T::default(), &refs, NVARS, 3, comb_fn);
+        assert_eq!(proof, expected);
+    }
+}
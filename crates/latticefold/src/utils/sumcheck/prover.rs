@@ -13,9 +13,38 @@ use super::{verifier::VerifierMsg, IPForMLSumcheck};
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ProverMsg<R1: Ring> {
     /// evaluations on P(0), P(1), P(2), ...
+    ///
+    /// A compressed message omits P(1), which the verifier recovers from P(0) + P(1) being the
+    /// claim of the round.
     pub(crate) evaluations: Vec<R1>,
 }
 
+impl<R1: Ring> ProverMsg<R1> {
+    /// Returns the message without the evaluation on P(1).
+    pub(crate) fn compress(&self, degree: usize) -> Self {
+        if self.evaluations.len() != degree + 1 {
+            return self.clone();
+        }
+
+        let mut evaluations = self.evaluations.clone();
+        evaluations.remove(1);
+        Self { evaluations }
+    }
+
+    /// Recovers the evaluation on P(1) of a compressed message, given the claim of the round.
+    ///
+    /// Uncompressed messages are returned as is.
+    pub(crate) fn decompress(&self, degree: usize, claim: R1) -> Self {
+        if self.evaluations.len() != degree {
+            return self.clone();
+        }
+
+        let mut evaluations = self.evaluations.clone();
+        evaluations.insert(1, claim - evaluations[0]);
+        Self { evaluations }
+    }
+}
+
 /// Prover State
 pub struct ProverState<R: OverField> {
     /// sampled randomness given by the verifier
@@ -0,0 +1,549 @@
+//! Small-value sumcheck prover
+//!
+//! When the multilinear extensions of a [virtual polynomial](super::virtual_polynomial) hold
+//! small integers, e.g. decomposed witnesses, the first rounds of the sumcheck can be computed
+//! over the integers. For a product $\prod_{k=1}^d f_{j_k}$ in round $i$,
+//! $$
+//! s_i(X) = \sum_{b_1, \ldots, b_d \in \\{0,1\\}^{i-1}} \prod_k \mathrm{eq}(r, b_k) \cdot
+//!     \sum_{y} \prod_k f_{j_k}(b_k, X, y),
+//! $$
+//! where the inner sums only involve the small evaluations and are accumulated in `i128`. Ring
+//! arithmetic is then only needed to combine $2^{(i-1)d}$ accumulators per evaluation point,
+//! instead of for every point of the hypercube. As the number of accumulators grows
+//! exponentially with the round, the prover switches to [`IPForMLSumcheck::prove_round`] after a
+//! few rounds.
+
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_std::cfg_iter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use stark_rings::OverField;
+use stark_rings_poly::{
+    mle::MultilinearExtension,
+    polynomials::{ArithErrors, DenseMultilinearExtension},
+};
+
+use super::{
+    prover::{ProverMsg, ProverState},
+    utils::{build_eq_x_r_vec, from_i128},
+    verifier::VerifierMsg,
+    virtual_polynomial::VirtualPolynomialInfo,
+    IPForMLSumcheck, MLSumcheck, Proof,
+};
+use crate::{ark_base::*, transcript::Transcript};
+
+/// A multilinear extension with small integer evaluations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmallMle {
+    nvars: usize,
+    evals: Vec<i64>,
+}
+
+impl SmallMle {
+    /// Creates the extension over `nvars` variables with evaluations `evals`.
+    pub fn new(nvars: usize, evals: Vec<i64>) -> Result<Self, ArithErrors> {
+        if evals.len() != 1 << nvars {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "{} evaluations for {nvars} variables",
+                evals.len()
+            )));
+        }
+
+        Ok(Self { nvars, evals })
+    }
+
+    /// The number of variables.
+    pub fn nvars(&self) -> usize {
+        self.nvars
+    }
+
+    /// The evaluations over the boolean hypercube.
+    pub fn evals(&self) -> &[i64] {
+        &self.evals
+    }
+
+    /// The extension with its evaluations in the ring `R`.
+    pub fn to_mle<R: OverField>(&self) -> DenseMultilinearExtension<R> {
+        DenseMultilinearExtension::from_evaluations_vec(
+            self.nvars,
+            self.evals
+                .iter()
+                .map(|&x| R::from(from_i128::<R::BaseRing>(x as i128)))
+                .collect(),
+        )
+    }
+
+    /// The extension in the ring `R` with its first `point.len()` variables bound to `point`.
+    ///
+    /// Only the `2^(nvars - point.len())` evaluations of the bound extension are computed in the
+    /// ring, as eq-weighted sums of the small evaluations.
+    pub fn fix_variables<R: OverField>(
+        &self,
+        point: &[R::BaseRing],
+    ) -> Result<DenseMultilinearExtension<R>, ArithErrors> {
+        if point.len() > self.nvars {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "cannot bind {} variables of {}",
+                point.len(),
+                self.nvars
+            )));
+        }
+        if point.is_empty() {
+            return Ok(self.to_mle());
+        }
+
+        let eq = build_eq_x_r_vec(point)?;
+        let evals = self
+            .evals
+            .chunks(eq.len())
+            .map(|chunk| {
+                let sum = chunk
+                    .iter()
+                    .zip(&eq)
+                    .filter(|&(&x, _)| x != 0)
+                    .fold(R::BaseRing::zero(), |sum, (&x, &w)| {
+                        sum + w * from_i128::<R::BaseRing>(x as i128)
+                    });
+                R::from(sum)
+            })
+            .collect();
+
+        Ok(DenseMultilinearExtension::from_evaluations_vec(
+            self.nvars - point.len(),
+            evals,
+        ))
+    }
+
+    fn max_abs(&self) -> u64 {
+        self.evals
+            .iter()
+            .map(|x| x.unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The values of a function on the integers of $\[-B, B\]$, looked up instead of computed on
+/// small arguments.
+///
+/// The folding sumcheck tabulates its range polynomial $x \prod_{j=1}^{b-1} (x^2 - j^2)$ on the
+/// evaluations of the decomposed witness in its first round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmallValueTable<F> {
+    bound: u64,
+    values: Vec<F>,
+}
+
+impl<F: Field> SmallValueTable<F> {
+    /// Tabulates `f` on the integers of `[-bound, bound]`.
+    pub fn new(bound: u64, f: impl Fn(F) -> F) -> Self {
+        let values = (0..=2 * bound)
+            .map(|i| f(from_i128(i as i128 - bound as i128)))
+            .collect();
+
+        Self { bound, values }
+    }
+
+    /// The bound on the absolute value of the arguments.
+    pub fn bound(&self) -> u64 {
+        self.bound
+    }
+
+    /// The value at `x`, if `|x|` is at most the bound.
+    pub fn get(&self, x: i64) -> Option<F> {
+        let i = x.checked_add_unsigned(self.bound)?;
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| self.values.get(i))
+            .copied()
+    }
+}
+
+/// Reads `x` as an integer of absolute value at most `bound`, if it is one.
+pub(crate) fn as_small_integer<F: Field>(x: &F, bound: u64) -> Option<i64> {
+    let mut elements = x.to_base_prime_field_elements();
+    let first = elements.next()?;
+    if elements.any(|e| !e.is_zero()) {
+        return None;
+    }
+
+    let abs = first.min(-first);
+    let limbs = abs.into_bigint();
+    let (&low, high) = limbs.as_ref().split_first()?;
+    if high.iter().any(|&l| l != 0) || low > bound || low > i64::MAX as u64 {
+        return None;
+    }
+
+    Some(if abs == first {
+        low as i64
+    } else {
+        -(low as i64)
+    })
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Runs the sumcheck of the virtual polynomial with structure `info` and small-valued
+    /// extensions `mles`, computing the first `small_rounds` rounds over the integers.
+    ///
+    /// The proof and the transcript are the same as with [`MLSumcheck::prove_virtual`], so the
+    /// proof is checked with [`MLSumcheck::verify_virtual`]. If the integer accumulators could
+    /// overflow, all rounds are computed in the ring.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the extensions do not match `info`, or if the polynomial has no
+    /// variables.
+    pub fn prove_small_values(
+        transcript: &mut T,
+        info: &VirtualPolynomialInfo<R>,
+        mles: Vec<SmallMle>,
+        small_rounds: usize,
+    ) -> Result<(Proof<R>, ProverState<R>), ArithErrors> {
+        let (nvars, degree) = (info.nvars(), info.degree());
+        if nvars == 0 {
+            return Err(ArithErrors::InvalidParameters(
+                "the polynomial has no variables".to_string(),
+            ));
+        }
+        if mles.len() != info.num_mles() {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "got {} MLEs, expected {}",
+                mles.len(),
+                info.num_mles()
+            )));
+        }
+        if let Some(mle) = mles.iter().find(|mle| mle.nvars != nvars) {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "an MLE has {} variables, expected {nvars}",
+                mle.nvars
+            )));
+        }
+        let small_rounds = if fits_in_i128(&mles, degree, nvars) {
+            small_rounds.min(nvars)
+        } else {
+            0
+        };
+
+        transcript.absorb(&R::from(nvars as u128));
+        transcript.absorb(&R::from(degree as u128));
+        let mut randomness: Vec<R::BaseRing> = Vec::with_capacity(nvars);
+        let mut prover_msgs = Vec::with_capacity(nvars);
+        for _ in 0..small_rounds {
+            let evaluations = small_round(info, &mles, &randomness, degree)?;
+            transcript.absorb_slice(&evaluations);
+            prover_msgs.push(ProverMsg { evaluations });
+            let verifier_msg = IPForMLSumcheck::<R, T>::sample_round(transcript);
+            transcript.absorb(&verifier_msg.randomness.into());
+            randomness.push(verifier_msg.randomness);
+        }
+
+        // Switch to the ring: bind the variables of all but the last small round, as the ring
+        // prover binds a variable when it receives the challenge of the previous round. The
+        // variables are bound from the small evaluations, so only the tables of the remaining
+        // variables are allocated in the ring.
+        let bound = randomness.len().saturating_sub(1);
+        let ring_mles = cfg_iter!(mles)
+            .map(|mle| mle.fix_variables(&randomness[..bound]))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut prover_state = IPForMLSumcheck::<R, T>::prover_init(ring_mles, nvars, degree);
+        let mut verifier_msg = None;
+        if let Some((&last, bound)) = randomness.split_last() {
+            prover_state.randomness = bound.to_vec();
+            prover_state.round = small_rounds;
+            verifier_msg = Some(VerifierMsg { randomness: last });
+        }
+
+        for _ in small_rounds..nvars {
+            let prover_msg =
+                IPForMLSumcheck::<R, T>::prove_round(&mut prover_state, &verifier_msg, |vals| {
+                    info.combine(vals)
+                });
+            transcript.absorb_slice(&prover_msg.evaluations);
+            prover_msgs.push(prover_msg);
+            let next_verifier_msg = IPForMLSumcheck::<R, T>::sample_round(transcript);
+            transcript.absorb(&next_verifier_msg.randomness.into());
+
+            verifier_msg = Some(next_verifier_msg);
+        }
+        prover_state
+            .randomness
+            .push(verifier_msg.expect("nvars > 0").randomness);
+
+        Ok((Proof(prover_msgs), prover_state))
+    }
+}
+
+/// Computes the message of the round following the challenges `r` over the integers.
+///
+/// For every `y` and evaluation point `x`, the evaluations of the extensions at `(b, x, y)` are
+/// tabulated once for all `b`. The products over the tuples `(b_1, ..., b_d)` are then built
+/// factor by factor, each table extending the one of the previous factors, so that a product of
+/// `d` extensions costs about one multiplication per tuple instead of `d`. The eq weights of the
+/// tuples are tabulated in the same way.
+fn small_round<R: OverField>(
+    info: &VirtualPolynomialInfo<R>,
+    mles: &[SmallMle],
+    r: &[R::BaseRing],
+    degree: usize,
+) -> Result<Vec<R>, ArithErrors> {
+    let bound_vars = r.len();
+    let half = 1 << bound_vars;
+    let num_y = 1 << (info.nvars() - bound_vars - 1);
+    let eq = if r.is_empty() {
+        vec![R::BaseRing::one()]
+    } else {
+        build_eq_x_r_vec(r)?
+    };
+
+    // ext[j][x * half + b] is the evaluation of the j-th extension at (b, x, y)
+    let mut ext = vec![vec![0i128; (degree + 1) * half]; mles.len()];
+    let mut table = Vec::new();
+    let mut next = Vec::new();
+
+    let mut evaluations = vec![R::zero(); degree + 1];
+    for (coeff, indices) in info.products() {
+        // The accumulators of the tuples (b_1, ..., b_d), b_k being the k-th group of
+        // `bound_vars` bits of the tuple index
+        let num_tuples = 1 << (bound_vars * indices.len());
+        let mut acc = vec![vec![0i128; num_tuples]; degree + 1];
+        for y in 0..num_y {
+            for &j in indices {
+                tabulate_extension(&mles[j], y, bound_vars, &mut ext[j]);
+            }
+            for (x, acc_x) in acc.iter_mut().enumerate() {
+                product_table(
+                    indices.iter().map(|&j| &ext[j][x * half..(x + 1) * half]),
+                    &mut table,
+                    &mut next,
+                );
+                acc_x.iter_mut().zip(&table).for_each(|(a, &p)| *a += p);
+            }
+        }
+
+        let mut weights = Vec::new();
+        product_table(
+            (0..indices.len()).map(|_| eq.as_slice()),
+            &mut weights,
+            &mut Vec::new(),
+        );
+        for (eval, acc_x) in evaluations.iter_mut().zip(&acc) {
+            let sum = acc_x
+                .iter()
+                .zip(&weights)
+                .fold(R::BaseRing::zero(), |sum, (&a, &w)| {
+                    sum + w * from_i128::<R::BaseRing>(a)
+                });
+            *eval += *coeff * R::from(sum);
+        }
+    }
+
+    Ok(evaluations)
+}
+
+/// Writes the evaluations of `mle` at `(b, x, y)` in `ext[x * half + b]`, for the `half` values
+/// of the `bound_vars` bound variables `b` and the points `x` of the round.
+fn tabulate_extension(mle: &SmallMle, y: usize, bound_vars: usize, ext: &mut [i128]) {
+    let half = 1 << bound_vars;
+    let offset = y << (bound_vars + 1);
+    let (evals_0, evals_1) = (
+        &mle.evals[offset..offset + half],
+        &mle.evals[offset + half..offset + 2 * half],
+    );
+    for (b, (&f0, &f1)) in evals_0.iter().zip(evals_1).enumerate() {
+        let (f0, step) = (f0 as i128, f1 as i128 - f0 as i128);
+        let mut value = f0;
+        for x_b in ext.iter_mut().skip(b).step_by(half) {
+            *x_b = value;
+            value += step;
+        }
+    }
+}
+
+/// Writes in `table` the products of the factors over all tuples, the factor of the k-th table
+/// being selected by the k-th group of bits of the tuple index.
+///
+/// The table of the first `k` factors is extended by the next one, `next` being scratch space.
+fn product_table<'a, V>(
+    factors: impl Iterator<Item = &'a [V]>,
+    table: &mut Vec<V>,
+    next: &mut Vec<V>,
+) where
+    V: Copy + One + core::ops::Mul<Output = V> + 'a,
+{
+    table.clear();
+    table.push(V::one());
+    for factor in factors {
+        next.clear();
+        next.reserve(table.len() * factor.len());
+        for &f in factor {
+            next.extend(table.iter().map(|&t| t * f));
+        }
+        core::mem::swap(table, next);
+    }
+}
+
+/// Whether the accumulators of [`small_round`] fit in an `i128`.
+fn fits_in_i128(mles: &[SmallMle], degree: usize, nvars: usize) -> bool {
+    // An evaluation at x <= degree is at most (2 * degree + 1) * max_abs
+    let max_abs = mles.iter().map(SmallMle::max_abs).max().unwrap_or(0) as u128;
+    (2 * degree as u128 + 1)
+        .checked_mul(max_abs)
+        .and_then(|v| v.checked_pow(degree as u32))
+        .and_then(|p| p.checked_mul(1 << nvars.min(127)))
+        .is_some_and(|bound| bound < 1 << 126)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::UniformRand;
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+    use rand::Rng;
+    use stark_rings::PolyRing;
+
+    use super::*;
+    use crate::{
+        transcript::poseidon::PoseidonTranscript,
+        utils::sumcheck::virtual_polynomial::VirtualPolynomial,
+    };
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    const NVARS: usize = 6;
+
+    fn rand_small_poly() -> (VirtualPolynomialInfo<R>, Vec<SmallMle>) {
+        let mut rng = ark_std::test_rng();
+        let mles: Vec<SmallMle> = (0..4)
+            .map(|_| {
+                let evals = (0..1 << NVARS).map(|_| rng.gen_range(-3..=3)).collect();
+                SmallMle::new(NVARS, evals).unwrap()
+            })
+            .collect();
+
+        let mut info = VirtualPolynomialInfo::new(NVARS, mles.len());
+        info.add_product(R::rand(&mut rng), [0, 1, 2]).unwrap();
+        info.add_product(R::rand(&mut rng), [3]).unwrap();
+        info.add_product(R::rand(&mut rng), [1, 3]).unwrap();
+        info.add_product(R::rand(&mut rng), []).unwrap();
+
+        (info, mles)
+    }
+
+    #[test]
+    fn test_small_value_sumcheck() {
+        let (info, mles) = rand_small_poly();
+
+        let mut poly = VirtualPolynomial::new(NVARS);
+        for mle in &mles {
+            poly.add_mle(mle.to_mle()).unwrap();
+        }
+        for (coeff, indices) in info.products() {
+            poly.add_product(*coeff, indices.iter().copied()).unwrap();
+        }
+        let (expected, expected_state) = MLSumcheck::prove_virtual(&mut T::default(), poly);
+
+        for small_rounds in [0, 1, 2, 3, NVARS] {
+            let (proof, state) = MLSumcheck::prove_small_values(
+                &mut T::default(),
+                &info,
+                mles.clone(),
+                small_rounds,
+            )
+            .unwrap();
+            assert_eq!(proof, expected);
+            assert_eq!(state.randomness, expected_state.randomness);
+            assert_eq!(state.mles, expected_state.mles);
+        }
+    }
+
+    #[test]
+    fn test_small_mle_fix_variables() {
+        let (_, mles) = rand_small_poly();
+        let mut rng = ark_std::test_rng();
+        let point: Vec<<R as PolyRing>::BaseRing> =
+            (0..2).map(|_| UniformRand::rand(&mut rng)).collect();
+
+        let mut expected: DenseMultilinearExtension<R> = mles[0].to_mle();
+        expected.fix_variables(&point.iter().map(|&r| r.into()).collect::<Vec<R>>());
+        assert_eq!(mles[0].fix_variables::<R>(&point).unwrap(), expected);
+        assert_eq!(mles[0].fix_variables::<R>(&[]).unwrap(), mles[0].to_mle());
+        assert!(mles[0]
+            .fix_variables::<R>(&vec![point[0]; NVARS + 1])
+            .is_err());
+    }
+
+    #[test]
+    fn test_small_value_sumcheck_verifies() {
+        let (info, mles) = rand_small_poly();
+        let sum = {
+            let mles: Vec<DenseMultilinearExtension<R>> = mles.iter().map(|m| m.to_mle()).collect();
+            (0..1 << NVARS).fold(R::zero(), |sum, x| {
+                let vals: Vec<R> = mles.iter().map(|mle| mle.evaluations[x]).collect();
+                sum + info.evaluate(&vals).unwrap()
+            })
+        };
+
+        let (proof, _) = MLSumcheck::prove_small_values(&mut T::default(), &info, mles, 2).unwrap();
+        MLSumcheck::verify_virtual(&mut T::default(), &info, sum, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_small_value_sumcheck_invalid_input() {
+        let (info, mles) = rand_small_poly();
+        assert!(
+            MLSumcheck::prove_small_values(&mut T::default(), &info, mles[1..].to_vec(), 2)
+                .is_err()
+        );
+
+        let mut short_mles = mles.clone();
+        short_mles[0] = SmallMle::new(NVARS - 1, vec![0; 1 << (NVARS - 1)]).unwrap();
+        assert!(MLSumcheck::prove_small_values(&mut T::default(), &info, short_mles, 2).is_err());
+
+        let info = VirtualPolynomialInfo::new(0, 0);
+        assert!(MLSumcheck::prove_small_values(&mut T::default(), &info, vec![], 2).is_err());
+    }
+
+    #[test]
+    fn test_product_table() {
+        let mut table = Vec::new();
+        product_table(
+            [[2i128, 3].as_slice(), &[5, 7]].into_iter(),
+            &mut table,
+            &mut Vec::new(),
+        );
+        // The first factor is selected by the lowest bit
+        assert_eq!(table, vec![10, 15, 14, 21]);
+
+        product_table(core::iter::empty(), &mut table, &mut Vec::new());
+        assert_eq!(table, vec![1]);
+    }
+
+    #[test]
+    fn test_small_value_table() {
+        type F = <R as PolyRing>::BaseRing;
+
+        let table = SmallValueTable::new(5, |x: F| x * (x * x - F::one()));
+        for x in -5i64..=5 {
+            assert_eq!(
+                table.get(x),
+                Some(from_i128::<F>((x * (x * x - 1)) as i128))
+            );
+        }
+        assert_eq!(table.get(6), None);
+        assert_eq!(table.get(-6), None);
+        assert_eq!(table.get(i64::MIN), None);
+
+        for x in [-5i64, -1, 0, 1, 5] {
+            assert_eq!(as_small_integer(&from_i128::<F>(x as i128), 5), Some(x));
+        }
+        assert_eq!(as_small_integer(&from_i128::<F>(6), 5), None);
+        assert_eq!(as_small_integer(&from_i128::<F>(-6), 5), None);
+    }
+
+    #[test]
+    fn test_overflow_falls_back_to_ring() {
+        let mles = vec![SmallMle::new(2, vec![i64::MAX, 1, 2, i64::MIN]).unwrap(); 2];
+        assert!(!fits_in_i128(&mles, 2, 2));
+        assert!(fits_in_i128(&mles[..1], 1, 2));
+    }
+}
@@ -0,0 +1,519 @@
+//! Sumcheck of eq-weighted polynomials
+//!
+//! Most sumchecks of the protocols prove sums $\sum_x \mathrm{eq}(\beta, x) \cdot P(x)$. Following
+//! Gruen's split-eq technique, the prover of [`MLSumcheck::prove_with_eqs`] never materializes
+//! $\mathrm{eq}(\beta, \cdot)$. In round $i$,
+//! $$
+//! s_i(X) = \mathrm{eq}(\beta_{<i}, r_{<i}) \cdot \mathrm{eq}(\beta_i, X) \cdot q_i(X), \quad
+//! q_i(X) = \sum_y \mathrm{eq}(\beta_{>i}, y) \cdot P(r_{<i}, X, y),
+//! $$
+//! so the prover only computes $q_i$, whose degree is one less than the degree of $s_i$. The
+//! weights $\mathrm{eq}(\beta_{>i}, y)$ are the products of two tables over the low and the high
+//! half of the variables, each of size about $2^{n/2}$.
+//!
+//! The proof and the transcript are the same as with materialized eq extensions, so the proof is
+//! checked with [`MLSumcheck::verify_as_subprotocol`] for the degree of $P$ plus one.
+
+use ark_std::cfg_into_iter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use stark_rings::{OverField, Ring};
+use stark_rings_poly::polynomials::DenseMultilinearExtension;
+
+use super::{
+    lean::{step, SharedMles},
+    prover::{ProverMsg, ProverState},
+    verifier::interpolate_uni_poly,
+    virtual_polynomial::VirtualPolynomial,
+    IPForMLSumcheck, MLSumcheck, Proof,
+};
+use crate::{ark_base::*, transcript::Transcript};
+
+/// The table of $\mathrm{eq}(\beta, \cdot)$ over the variables of a sumcheck, split in two halves.
+///
+/// Binding a variable only updates a scalar, the tables of the remaining variables are suffixes
+/// of the two halves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitEq<R> {
+    point: Vec<R>,
+    /// `low[k]` is the table of `eq(point[k..split], ·)`
+    low: Vec<Vec<R>>,
+    /// `high[k]` is the table of `eq(point[split + k..], ·)`
+    high: Vec<Vec<R>>,
+    /// `eq(point[..round], r)` over the bound variables
+    scalar: R,
+    round: usize,
+}
+
+impl<R: Ring> SplitEq<R> {
+    /// Splits the table of `eq(point, ·)`.
+    pub fn new(point: &[R]) -> Self {
+        let split = point.len() / 2;
+        Self {
+            point: point.to_vec(),
+            low: suffix_tables(&point[..split]),
+            high: suffix_tables(&point[split..]),
+            scalar: R::one(),
+            round: 0,
+        }
+    }
+
+    /// The number of variables.
+    pub fn nvars(&self) -> usize {
+        self.point.len()
+    }
+
+    /// The number of bound variables.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// The value of eq over the bound variables.
+    pub fn scalar(&self) -> R {
+        self.scalar
+    }
+
+    /// The two tables of eq over the variables after the current one.
+    ///
+    /// The weight of `y = y_low + y_high * low.len()` is `low[y_low] * high[y_high]`.
+    pub fn tables(&self) -> (&[R], &[R]) {
+        let split = self.low.len() - 1;
+        let next = self.round + 1;
+        if next <= split {
+            (&self.low[next], &self.high[0])
+        } else {
+            (&self.low[split], &self.high[next - split])
+        }
+    }
+
+    /// The value of eq for the current variable at `x`, i.e. `eq(point[round], x)`.
+    pub fn current(&self, x: R) -> R {
+        let b = self.point[self.round];
+        b * x + (R::one() - b) * (R::one() - x)
+    }
+
+    /// Binds the current variable to `r`.
+    pub fn bind(&mut self, r: R) {
+        self.scalar *= self.current(r);
+        self.round += 1;
+    }
+}
+
+/// The tables of `eq(point[k..], ·)` for `k = 0, ..., point.len()`, `point[k]` being the lowest
+/// bit of the table index.
+fn suffix_tables<R: Ring>(point: &[R]) -> Vec<Vec<R>> {
+    let mut tables = vec![vec![R::one()]];
+    for &p in point.iter().rev() {
+        let last = tables.last().expect("tables are not empty");
+        let mut table = Vec::with_capacity(2 * last.len());
+        for &t in last {
+            let high = p * t;
+            table.push(t - high);
+            table.push(high);
+        }
+        tables.push(table);
+    }
+    tables.reverse();
+    tables
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Runs the sumcheck of $\sum_k \mathrm{eq}(\beta_k, x) \cdot P_k(x)$, $\beta_k$ being
+    /// `eq_points[k]`, without materializing the eq extensions.
+    ///
+    /// Given the evaluations of `mles`, `comb_fn` writes the evaluation of every $P_k$ in
+    /// `out[k]`. `degree` is the highest degree of the $P_k$. The proof and the transcript are the
+    /// same as with [`MLSumcheck::prove_as_subprotocol`] on `mles` followed by the eq extensions,
+    /// for degree `degree + 1`. As with [`MLSumcheck::prove_borrowed`], the extensions are
+    /// borrowed.
+    pub fn prove_with_eqs(
+        transcript: &mut T,
+        mles: &[&DenseMultilinearExtension<R>],
+        nvars: usize,
+        degree: usize,
+        eq_points: &[Vec<R>],
+        comb_fn: impl Fn(&[R], &mut [R]) + Sync + Send,
+    ) -> (Proof<R>, ProverState<R>) {
+        Self::prove_with_eqs_and_first_round(
+            transcript, mles, nvars, degree, eq_points, None, comb_fn,
+        )
+    }
+
+    /// [`MLSumcheck::prove_with_eqs`] for $\sum_k \mathrm{eq}(\beta_k, x) \cdot P_k(x) + Q(x)$,
+    /// with a term $Q$ without eq factor.
+    ///
+    /// `comb_fn` writes the evaluation of $Q$ in `out[eq_points.len()]`. $Q$ has degree at most
+    /// `degree + 1`, so the proof is still checked for degree `degree + 1`.
+    pub fn prove_with_eqs_and_eq_free(
+        transcript: &mut T,
+        mles: &[&DenseMultilinearExtension<R>],
+        nvars: usize,
+        degree: usize,
+        eq_points: &[Vec<R>],
+        comb_fn: impl Fn(&[R], &mut [R]) + Sync + Send,
+    ) -> (Proof<R>, ProverState<R>) {
+        Self::prove_with_eqs_and_hook(
+            transcript,
+            mles,
+            nvars,
+            degree,
+            eq_points,
+            true,
+            None,
+            comb_fn,
+            |_, _, _| {},
+        )
+    }
+
+    /// [`MLSumcheck::prove_with_eqs`], with the message of the first round given by `first_round`
+    /// if it is computed by the caller, e.g. with [small values](super::small_value).
+    ///
+    /// `first_round` holds the evaluations on `0, ..., degree + 1`, as computed by the prover.
+    pub(crate) fn prove_with_eqs_and_first_round(
+        transcript: &mut T,
+        mles: &[&DenseMultilinearExtension<R>],
+        nvars: usize,
+        degree: usize,
+        eq_points: &[Vec<R>],
+        first_round: Option<Vec<R>>,
+        comb_fn: impl Fn(&[R], &mut [R]) + Sync + Send,
+    ) -> (Proof<R>, ProverState<R>) {
+        Self::prove_with_eqs_and_hook(
+            transcript,
+            mles,
+            nvars,
+            degree,
+            eq_points,
+            false,
+            first_round,
+            comb_fn,
+            |_, _, _| {},
+        )
+    }
+
+    /// [`MLSumcheck::prove_with_eqs_and_first_round`], calling `on_round` with the round number,
+    /// the randomness of the previous rounds and the message of the round before it is absorbed.
+    ///
+    /// If `eq_free`, `comb_fn` also writes a term without eq factor, as in
+    /// [`MLSumcheck::prove_with_eqs_and_eq_free`].
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn prove_with_eqs_and_hook(
+        transcript: &mut T,
+        mles: &[&DenseMultilinearExtension<R>],
+        nvars: usize,
+        degree: usize,
+        eq_points: &[Vec<R>],
+        eq_free: bool,
+        mut first_round: Option<Vec<R>>,
+        comb_fn: impl Fn(&[R], &mut [R]) + Sync + Send,
+        mut on_round: impl FnMut(usize, &[R::BaseRing], &mut [R]),
+    ) -> (Proof<R>, ProverState<R>) {
+        assert!(nvars > 0, "Attempt to prove a constant.");
+        assert!(!eq_points.is_empty(), "no eq points");
+        assert!(
+            eq_points.iter().all(|point| point.len() == nvars),
+            "eq points have the wrong number of variables"
+        );
+        assert!(
+            first_round
+                .as_ref()
+                .map_or(true, |msg| msg.len() == degree + 2),
+            "the first round has the wrong number of evaluations"
+        );
+        let mut splits: Vec<SplitEq<R>> = eq_points.iter().map(|p| SplitEq::new(p)).collect();
+
+        transcript.absorb(&R::from(nvars as u128));
+        transcript.absorb(&R::from((degree + 1) as u128));
+        let mut shared = SharedMles::new(mles);
+        let mut randomness: Vec<R::BaseRing> = Vec::with_capacity(nvars);
+        let mut prover_msgs = Vec::with_capacity(nvars);
+        for round in 0..nvars {
+            if let Some(&r) = randomness.last() {
+                let r = R::from(r);
+                shared.bind(r);
+                splits.iter_mut().for_each(|split| split.bind(r));
+            }
+
+            let mut evaluations = match first_round.take() {
+                Some(evaluations) => evaluations,
+                None => eq_round(&shared, &splits, degree, eq_free, &comb_fn),
+            };
+            on_round(round, &randomness, &mut evaluations);
+            transcript.absorb_slice(&evaluations);
+            prover_msgs.push(ProverMsg { evaluations });
+            let verifier_msg = IPForMLSumcheck::<R, T>::sample_round(transcript);
+            transcript.absorb(&verifier_msg.randomness.into());
+            randomness.push(verifier_msg.randomness);
+        }
+
+        let prover_state = ProverState {
+            randomness,
+            mles: shared.into_mles(1),
+            num_vars: nvars,
+            max_degree: degree + 1,
+            round: nvars,
+        };
+
+        (Proof(prover_msgs), prover_state)
+    }
+
+    /// Runs the sumcheck of $\mathrm{eq}(\beta, x) \cdot P(x)$ for the virtual polynomial $P$,
+    /// without materializing the eq extension.
+    ///
+    /// The proof is checked with [`MLSumcheck::verify_as_subprotocol`] for the degree of $P$ plus
+    /// one.
+    pub fn prove_with_eq(
+        transcript: &mut T,
+        beta: &[R],
+        poly: VirtualPolynomial<R>,
+    ) -> (Proof<R>, ProverState<R>) {
+        let (info, mles) = poly.into_parts();
+
+        Self::prove_with_eqs(
+            transcript,
+            &mles.iter().collect::<Vec<_>>(),
+            info.nvars(),
+            info.degree(),
+            &[beta.to_vec()],
+            |vals, out| out[0] = info.combine(vals),
+        )
+    }
+}
+
+/// Computes the message of the current round, the evaluations on `0, ..., degree + 1` of
+/// $\sum_k \mathrm{eq}(\beta_{k, <i}, r_{<i}) \cdot \mathrm{eq}(\beta_{k, i}, X) \cdot q_{k, i}(X)$,
+/// plus $\sum_y Q(r_{<i}, X, y)$ if `eq_free`.
+fn eq_round<R: OverField>(
+    shared: &SharedMles<R>,
+    splits: &[SplitEq<R>],
+    degree: usize,
+    eq_free: bool,
+    comb_fn: &(impl Fn(&[R], &mut [R]) + Sync + Send),
+) -> Vec<R> {
+    // The q_k are evaluated on at least two points, so that constant ones need no interpolation.
+    // The eq-free term has no eq factor to lower its degree, so it is evaluated on all points.
+    let num_points = if eq_free {
+        degree + 2
+    } else {
+        degree.max(1) + 1
+    };
+    let num_terms = splits.len() + usize::from(eq_free);
+    let tables: Vec<(&[R], &[R])> = splits.iter().map(SplitEq::tables).collect();
+    let (low_len, high_len) = (tables[0].0.len(), tables[0].1.len());
+
+    let partial_sums: Vec<Vec<Vec<R>>> = cfg_into_iter!(0..high_len)
+        .map(|y_high| {
+            let mut sums = vec![vec![R::zero(); num_points]; num_terms];
+            let mut cur = vec![R::zero(); shared.num_tables()];
+            let mut steps = vec![R::zero(); shared.num_tables()];
+            let mut vals = vec![R::zero(); shared.num_operands()];
+            let mut out = vec![R::zero(); num_terms];
+            for y_low in 0..low_len {
+                shared.load(y_low + y_high * low_len, &mut cur, &mut steps);
+                for x in 0..num_points {
+                    if x > 0 {
+                        step(&mut cur, &steps);
+                    }
+                    shared.operand_values(&cur, &mut vals);
+                    comb_fn(&vals, &mut out);
+                    for ((sum, (low, _)), o) in sums.iter_mut().zip(&tables).zip(&out) {
+                        sum[x] += low[y_low] * *o;
+                    }
+                    if eq_free {
+                        sums[splits.len()][x] += out[splits.len()];
+                    }
+                }
+            }
+            for (sum, (_, high)) in sums.iter_mut().zip(&tables) {
+                sum.iter_mut().for_each(|s| *s *= high[y_high]);
+            }
+            sums
+        })
+        .collect();
+
+    let mut q = vec![vec![R::zero(); num_points]; num_terms];
+    for sums in partial_sums {
+        for (q_k, sum) in q.iter_mut().zip(sums) {
+            q_k.iter_mut().zip(sum).for_each(|(q_kx, s)| *q_kx += s);
+        }
+    }
+
+    (0..degree + 2)
+        .map(|x| {
+            let x_base = R::BaseRing::from(x as u64);
+            let eq_terms = splits.iter().zip(&q).fold(R::zero(), |acc, (split, q_k)| {
+                let q_kx = if x < num_points {
+                    q_k[x]
+                } else {
+                    interpolate_uni_poly(q_k, x_base)
+                };
+                acc + split.scalar() * split.current(R::from(x_base)) * q_kx
+            });
+            if eq_free {
+                eq_terms + q[splits.len()][x]
+            } else {
+                eq_terms
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::UniformRand;
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+    use stark_rings_poly::mle::MultilinearExtension;
+
+    use super::*;
+    use crate::{
+        transcript::poseidon::PoseidonTranscript,
+        utils::sumcheck::utils::{build_eq_x_r, build_eq_x_r_vec, rand_poly},
+    };
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    fn rand_point(nvars: usize, rng: &mut impl ark_std::rand::Rng) -> Vec<R> {
+        (0..nvars).map(|_| R::rand(rng)).collect()
+    }
+
+    #[test]
+    fn test_split_eq_tables() {
+        let mut rng = ark_std::test_rng();
+        for nvars in 1..=6 {
+            let point = rand_point(nvars, &mut rng);
+            let mut split = SplitEq::new(&point);
+            let r = rand_point(nvars, &mut rng);
+            for round in 0..nvars {
+                let (low, high) = split.tables();
+                let expected = if round + 1 < nvars {
+                    build_eq_x_r_vec(&point[round + 1..]).unwrap()
+                } else {
+                    vec![R::one()]
+                };
+                assert_eq!(low.len() * high.len(), expected.len());
+                for (y, e) in expected.iter().enumerate() {
+                    assert_eq!(low[y % low.len()] * high[y / low.len()], *e);
+                }
+                split.bind(r[round]);
+            }
+            let bound = build_eq_x_r(&point).unwrap().evaluate(&r).unwrap();
+            assert_eq!(split.scalar(), bound);
+        }
+    }
+
+    #[test]
+    fn test_eq_sumcheck_matches_materialized() {
+        const NVARS: usize = 5;
+        let mut rng = ark_std::test_rng();
+        let ((mles, degree), products, _) = rand_poly::<R>(NVARS, (2, 4), 3, &mut rng).unwrap();
+        let beta = rand_point(NVARS, &mut rng);
+
+        let mut poly = VirtualPolynomial::new(NVARS);
+        for mle in mles.iter().cloned() {
+            poly.add_mle(mle).unwrap();
+        }
+        for (coeff, indices) in products {
+            poly.add_product(coeff, indices).unwrap();
+        }
+        let info = poly.info().clone();
+        assert_eq!(info.degree(), degree);
+
+        let mut materialized = mles;
+        materialized.push(build_eq_x_r(&beta).unwrap());
+        let (expected, expected_state) = MLSumcheck::prove_as_subprotocol(
+            &mut T::default(),
+            materialized,
+            NVARS,
+            degree + 1,
+            |vals: &[R]| info.combine(&vals[..vals.len() - 1]) * vals[vals.len() - 1],
+        );
+
+        let (proof, state) = MLSumcheck::prove_with_eq(&mut T::default(), &beta, poly);
+        assert_eq!(proof, expected);
+        assert_eq!(state.randomness, expected_state.randomness);
+
+        let sum = MLSumcheck::<R, T>::extract_sum(&proof);
+        MLSumcheck::verify_as_subprotocol(&mut T::default(), NVARS, degree + 1, sum, &proof)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_eq_sumcheck_several_points() {
+        const NVARS: usize = 4;
+        let mut rng = ark_std::test_rng();
+        let mles: Vec<DenseMultilinearExtension<R>> = (0..3)
+            .map(|_| {
+                DenseMultilinearExtension::from_evaluations_vec(
+                    NVARS,
+                    rand_point(1 << NVARS, &mut rng),
+                )
+            })
+            .collect();
+        let points: Vec<Vec<R>> = (0..3).map(|_| rand_point(NVARS, &mut rng)).collect();
+        let c = R::rand(&mut rng);
+        // eq_0 * f_0 + eq_1 * f_1 * f_2 + eq_2 * c
+        let comb_fn = |vals: &[R], out: &mut [R]| {
+            out[0] = vals[0];
+            out[1] = vals[1] * vals[2];
+            out[2] = c;
+        };
+
+        let mut materialized = mles.clone();
+        materialized.extend(points.iter().map(|p| build_eq_x_r(p).unwrap()));
+        let (expected, _) = MLSumcheck::prove_as_subprotocol(
+            &mut T::default(),
+            materialized,
+            NVARS,
+            3,
+            |vals: &[R]| vals[3] * vals[0] + vals[4] * vals[1] * vals[2] + vals[5] * c,
+        );
+
+        let refs: Vec<&DenseMultilinearExtension<R>> = mles.iter().collect();
+        let (proof, _) =
+            MLSumcheck::prove_with_eqs(&mut T::default(), &refs, NVARS, 2, &points, comb_fn);
+        assert_eq!(proof, expected);
+    }
+
+    #[test]
+    fn test_eq_sumcheck_eq_free_term() {
+        const NVARS: usize = 4;
+        let mut rng = ark_std::test_rng();
+        let mles: Vec<DenseMultilinearExtension<R>> = (0..3)
+            .map(|_| {
+                DenseMultilinearExtension::from_evaluations_vec(
+                    NVARS,
+                    rand_point(1 << NVARS, &mut rng),
+                )
+            })
+            .collect();
+        let points: Vec<Vec<R>> = (0..2).map(|_| rand_point(NVARS, &mut rng)).collect();
+        // eq_0 * f_0 + eq_1 * f_1 + f_0 * f_2
+        let comb_fn = |vals: &[R], out: &mut [R]| {
+            out[0] = vals[0];
+            out[1] = vals[1];
+            out[2] = vals[0] * vals[2];
+        };
+
+        let mut materialized = mles.clone();
+        materialized.extend(points.iter().map(|p| build_eq_x_r(p).unwrap()));
+        let (expected, _) = MLSumcheck::prove_as_subprotocol(
+            &mut T::default(),
+            materialized,
+            NVARS,
+            2,
+            |vals: &[R]| vals[3] * vals[0] + vals[4] * vals[1] + vals[0] * vals[2],
+        );
+
+        let refs: Vec<&DenseMultilinearExtension<R>> = mles.iter().collect();
+        let (proof, _) = MLSumcheck::prove_with_eqs_and_eq_free(
+            &mut T::default(),
+            &refs,
+            NVARS,
+            1,
+            &points,
+            comb_fn,
+        );
+        assert_eq!(proof, expected);
+    }
+}
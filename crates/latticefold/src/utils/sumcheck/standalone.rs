@@ -0,0 +1,188 @@
+//! Standalone sumcheck
+//!
+//! [`MLSumcheck::prove_as_subprotocol`] and [`MLSumcheck::verify_as_subprotocol`] are meant to run
+//! inside a larger protocol: the caller binds the claimed sum into the transcript and checks the
+//! final subclaim. [`MLSumcheck::prove`] and [`MLSumcheck::verify`] do both, the verifier querying
+//! an [`MleOracle`] for the evaluations of the multilinear extensions at the final point.
+
+use stark_rings::{OverField, Ring};
+use stark_rings_poly::{
+    mle::MultilinearExtension,
+    polynomials::{ArithErrors, DenseMultilinearExtension},
+};
+
+use super::{
+    prover::ProverState,
+    virtual_polynomial::{VirtualPolynomial, VirtualPolynomialInfo},
+    MLSumcheck, Proof, SumCheckError,
+};
+use crate::{ark_base::*, transcript::Transcript};
+
+/// Access of the verifier to the multilinear extensions of a virtual polynomial.
+///
+/// This is typically an opening of a commitment to the extensions, or the extensions themselves.
+pub trait MleOracle<R> {
+    /// The evaluation of the `i`-th multilinear extension at `point`.
+    fn evaluate(&self, i: usize, point: &[R]) -> Result<R, ArithErrors>;
+}
+
+impl<R: Ring> MleOracle<R> for [DenseMultilinearExtension<R>] {
+    fn evaluate(&self, i: usize, point: &[R]) -> Result<R, ArithErrors> {
+        let mle = self.get(i).ok_or_else(|| {
+            ArithErrors::InvalidParameters(format!(
+                "MLE index {i} is out of bounds, there are {} MLEs",
+                self.len()
+            ))
+        })?;
+
+        MultilinearExtension::evaluate(mle, point).ok_or_else(|| {
+            ArithErrors::InvalidParameters(format!(
+                "the MLE has {} variables, the point has {}",
+                mle.num_vars(),
+                point.len()
+            ))
+        })
+    }
+}
+
+impl<R, F> MleOracle<R> for F
+where
+    F: Fn(usize, &[R]) -> Result<R, ArithErrors>,
+{
+    fn evaluate(&self, i: usize, point: &[R]) -> Result<R, ArithErrors> {
+        self(i, point)
+    }
+}
+
+/// Absorbs the statement of a standalone sumcheck: the structure of the polynomial and its sum.
+fn absorb_statement<R: OverField, T: Transcript<R>>(
+    transcript: &mut T,
+    info: &VirtualPolynomialInfo<R>,
+    claimed_sum: R,
+) {
+    transcript.absorb(&R::from(info.nvars() as u128));
+    transcript.absorb(&R::from(info.num_mles() as u128));
+    transcript.absorb(&R::from(info.products().len() as u128));
+    for (coeff, indices) in info.products() {
+        transcript.absorb(coeff);
+        transcript.absorb(&R::from(indices.len() as u128));
+        for &j in indices {
+            transcript.absorb(&R::from(j as u128));
+        }
+    }
+    transcript.absorb(&claimed_sum);
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Proves the sum of `poly` over the boolean hypercube.
+    ///
+    /// The structure of the polynomial and its sum are absorbed before the rounds, so the proof
+    /// does not rely on the caller binding them. Returns the sum, the proof and the final state
+    /// of the prover.
+    pub fn prove(transcript: &mut T, poly: VirtualPolynomial<R>) -> (R, Proof<R>, ProverState<R>) {
+        let claimed_sum = poly.sum();
+        absorb_statement(transcript, poly.info(), claimed_sum);
+        let (proof, prover_state) = Self::prove_virtual(transcript, poly);
+
+        (claimed_sum, proof, prover_state)
+    }
+
+    /// Verifies a proof of [`MLSumcheck::prove`] that the polynomial with structure `info` sums to
+    /// `claimed_sum`, querying `oracle` for the evaluations of its multilinear extensions at the
+    /// final point.
+    ///
+    /// Returns the final point.
+    pub fn verify<O: MleOracle<R> + ?Sized>(
+        transcript: &mut T,
+        info: &VirtualPolynomialInfo<R>,
+        claimed_sum: R,
+        proof: &Proof<R>,
+        oracle: &O,
+    ) -> Result<Vec<R::BaseRing>, SumCheckError<R>> {
+        absorb_statement(transcript, info, claimed_sum);
+        let subclaim = Self::verify_virtual(transcript, info, claimed_sum, proof)?;
+
+        let point: Vec<R> = subclaim.point.iter().map(|&x| x.into()).collect();
+        let mle_evals = (0..info.num_mles())
+            .map(|i| oracle.evaluate(i, &point))
+            .collect::<Result<Vec<R>, _>>()?;
+        let evaluation = info.evaluate(&mle_evals)?;
+        if evaluation != subclaim.expected_evaluation {
+            return Err(SumCheckError::SumCheckFailed(
+                subclaim.expected_evaluation,
+                evaluation,
+            ));
+        }
+
+        Ok(subclaim.point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::One;
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::{transcript::poseidon::PoseidonTranscript, utils::sumcheck::utils::rand_poly};
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    const NVARS: usize = 5;
+
+    fn rand_virtual_poly() -> VirtualPolynomial<R> {
+        let ((mles, _), products, _) =
+            rand_poly(NVARS, (2, 5), 3, &mut ark_std::test_rng()).unwrap();
+
+        let mut poly = VirtualPolynomial::new(NVARS);
+        for mle in mles {
+            poly.add_mle(mle).unwrap();
+        }
+        for (coeff, indices) in products {
+            poly.add_product(coeff, indices).unwrap();
+        }
+
+        poly
+    }
+
+    #[test]
+    fn test_standalone_sumcheck() {
+        let poly = rand_virtual_poly();
+        let (info, mles) = poly.clone().into_parts();
+
+        let (sum, proof, prover_state) = MLSumcheck::prove(&mut T::default(), poly);
+        let point =
+            MLSumcheck::verify(&mut T::default(), &info, sum, &proof, mles.as_slice()).unwrap();
+        assert_eq!(point, prover_state.randomness);
+
+        let oracle = |i: usize, point: &[R]| mles.as_slice().evaluate(i, point);
+        assert!(MLSumcheck::verify(&mut T::default(), &info, sum, &proof, &oracle).is_ok());
+    }
+
+    #[test]
+    fn test_standalone_sumcheck_binds_the_sum() {
+        let poly = rand_virtual_poly();
+        let (info, mles) = poly.clone().into_parts();
+        let (sum, proof, _) = MLSumcheck::prove(&mut T::default(), poly);
+
+        let wrong_sum = sum + R::one();
+        assert!(
+            MLSumcheck::verify(&mut T::default(), &info, wrong_sum, &proof, mles.as_slice())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_standalone_sumcheck_wrong_oracle() {
+        let poly = rand_virtual_poly();
+        let (info, mles) = poly.clone().into_parts();
+        let (sum, proof, _) = MLSumcheck::prove(&mut T::default(), poly);
+
+        let wrong_oracle = |i: usize, point: &[R]| -> Result<R, ArithErrors> {
+            Ok(mles.as_slice().evaluate(i, point)? + R::one())
+        };
+        assert!(MLSumcheck::verify(&mut T::default(), &info, sum, &proof, &wrong_oracle).is_err());
+        assert!(MLSumcheck::verify(&mut T::default(), &info, sum, &proof, &mles[1..]).is_err());
+    }
+}
@@ -0,0 +1,384 @@
+//! Univariate skip
+//!
+//! The first rounds of a sumcheck are the most expensive ones, and with a high degree the
+//! prover evaluates the combination function on `degree + 1` points of every pair of the
+//! hypercube. Following Gruen, [`MLSumcheck::prove_with_skip`] handles the first $k$ variables
+//! at once: their $2^k$ assignments are identified with the domain $D = \\{0, \ldots, 2^k - 1\\}$,
+//! and every multilinear extension $f_j$ with the polynomial $\hat f_j(Y, x')$ of degree less
+//! than $2^k$ in $Y$ interpolating $f_j(b, x')$ on $D$. The first message is
+//! $$
+//! s_0(Y) = \sum_{x'} P(\hat f_1(Y, x'), \ldots),
+//! $$
+//! of degree $d (2^k - 1)$, whose sum over $D$ is the claimed sum. After the challenge $r_0$,
+//! the remaining $n - k$ variables are proven with [`MLSumcheck::prove_as_subprotocol`].
+//!
+//! The final point is $(r_0, r')$, and the extensions are evaluated there as
+//! $\hat f_j(r_0, r') = \sum_b L_b(r_0) f_j(b, r')$, see [`UniSkipSubClaim::evaluate_mle`].
+//! For $k = 1$, this is the usual evaluation of $f_j$ at $(r_0, r')$.
+
+use ark_ff::{Field, One};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::cfg_into_iter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use stark_rings::{OverField, Ring};
+use stark_rings_poly::{
+    mle::MultilinearExtension,
+    polynomials::{ArithErrors, DenseMultilinearExtension},
+};
+
+use super::{
+    prover::{ProverMsg, ProverState},
+    verifier::interpolate_uni_poly,
+    IPForMLSumcheck, MLSumcheck, Proof, SumCheckError,
+};
+use crate::{ark_base::*, transcript::Transcript};
+
+/// A sumcheck proof whose first round skips several variables.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UniSkipProof<R: Ring> {
+    /// The evaluations of $s_0$ on $0, \ldots, d (2^k - 1)$
+    first: ProverMsg<R>,
+    /// The sumcheck over the remaining variables
+    rest: Proof<R>,
+}
+
+/// The subclaim of a sumcheck with a skipped first round.
+pub struct UniSkipSubClaim<R: OverField> {
+    /// The challenge of the skipped variables
+    pub skip_challenge: R::BaseRing,
+    /// The point of the remaining variables
+    pub point: Vec<R::BaseRing>,
+    /// The expected evaluation of the polynomial at `(skip_challenge, point)`
+    pub expected_evaluation: R,
+}
+
+impl<R: OverField> UniSkipSubClaim<R> {
+    /// Evaluates the extension of `mle` with skipped variables at the point of the subclaim.
+    pub fn evaluate_mle(&self, mle: &DenseMultilinearExtension<R>) -> Result<R, ArithErrors> {
+        let skip = mle
+            .num_vars()
+            .checked_sub(self.point.len())
+            .ok_or_else(|| {
+                ArithErrors::InvalidParameters(format!(
+                    "the MLE has {} variables, fewer than the point",
+                    mle.num_vars()
+                ))
+            })?;
+        let weights = lagrange_weights::<R>(1 << skip, self.skip_challenge);
+        let folded = DenseMultilinearExtension::from_evaluations_vec(
+            self.point.len(),
+            fold_skipped(&mle.evaluations, &weights),
+        );
+        let point: Vec<R> = self.point.iter().map(|&r| r.into()).collect();
+
+        Ok(folded
+            .evaluate(&point)
+            .expect("the folded MLE has one variable per coordinate"))
+    }
+}
+
+/// The Lagrange basis of the domain `0, ..., size - 1` at `y`.
+fn lagrange_weights<R: OverField>(size: usize, y: R::BaseRing) -> Vec<R> {
+    (0..size)
+        .map(|b| {
+            let b_f = R::BaseRing::from(b as u64);
+            let (num, den) = (0..size).filter(|&j| j != b).fold(
+                (R::BaseRing::one(), R::BaseRing::one()),
+                |(num, den), j| {
+                    let j_f = R::BaseRing::from(j as u64);
+                    (num * (y - j_f), den * (b_f - j_f))
+                },
+            );
+            (num * den.inverse().expect("the domain points are distinct")).into()
+        })
+        .collect()
+}
+
+/// Combines every chunk of `weights.len()` consecutive evaluations with `weights`.
+fn fold_skipped<R: Ring>(evals: &[R], weights: &[R]) -> Vec<R> {
+    cfg_into_iter!(0..evals.len() / weights.len())
+        .map(|x| {
+            evals[x * weights.len()..(x + 1) * weights.len()]
+                .iter()
+                .zip(weights)
+                .fold(R::zero(), |acc, (&e, &w)| acc + e * w)
+        })
+        .collect()
+}
+
+/// The number of evaluations of the first message, for `size` skipped assignments.
+fn num_skip_evaluations(size: usize, degree: usize) -> usize {
+    degree.max(1) * (size - 1) + 1
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Runs [`MLSumcheck::prove_as_subprotocol`] with its first `skip` variables handled in a
+    /// single round, over the domain `0, ..., 2^skip - 1`.
+    ///
+    /// The randomness of the prover state starts with the challenge of the skipped variables.
+    pub fn prove_with_skip(
+        transcript: &mut T,
+        mles: Vec<DenseMultilinearExtension<R>>,
+        nvars: usize,
+        degree: usize,
+        skip: usize,
+        comb_fn: impl Fn(&[R]) -> R + Sync + Send,
+    ) -> (UniSkipProof<R>, ProverState<R>) {
+        assert!(
+            0 < skip && skip < nvars,
+            "cannot skip {skip} of {nvars} variables"
+        );
+
+        transcript.absorb(&R::from(nvars as u128));
+        transcript.absorb(&R::from(degree as u128));
+        transcript.absorb(&R::from(skip as u128));
+        let first = skip_round(&mles, skip, degree, &comb_fn);
+        transcript.absorb_slice(&first.evaluations);
+        let skip_challenge = IPForMLSumcheck::<R, T>::sample_round(transcript).randomness;
+        transcript.absorb(&skip_challenge.into());
+
+        let weights = lagrange_weights::<R>(1 << skip, skip_challenge);
+        let mles = cfg_into_iter!(mles)
+            .map(|mle| {
+                DenseMultilinearExtension::from_evaluations_vec(
+                    nvars - skip,
+                    fold_skipped(&mle.evaluations, &weights),
+                )
+            })
+            .collect();
+        let (rest, mut prover_state) =
+            Self::prove_as_subprotocol(transcript, mles, nvars - skip, degree, comb_fn);
+        prover_state.randomness.insert(0, skip_challenge);
+
+        (UniSkipProof { first, rest }, prover_state)
+    }
+
+    /// Verifies a proof of [`MLSumcheck::prove_with_skip`] that the polynomial sums to
+    /// `claimed_sum`.
+    pub fn verify_with_skip(
+        transcript: &mut T,
+        nvars: usize,
+        degree: usize,
+        skip: usize,
+        claimed_sum: R,
+        proof: &UniSkipProof<R>,
+    ) -> Result<UniSkipSubClaim<R>, SumCheckError<R>> {
+        if skip == 0 || skip >= nvars {
+            return Err(SumCheckError::InvalidSkip(skip, nvars));
+        }
+        let size = 1 << skip;
+        let evaluations = &proof.first.evaluations;
+        let expected_len = num_skip_evaluations(size, degree);
+        if evaluations.len() != expected_len {
+            return Err(SumCheckError::WrongSkipMessageLength(
+                evaluations.len(),
+                expected_len,
+            ));
+        }
+
+        transcript.absorb(&R::from(nvars as u128));
+        transcript.absorb(&R::from(degree as u128));
+        transcript.absorb(&R::from(skip as u128));
+        let sum = evaluations[..size]
+            .iter()
+            .fold(R::zero(), |acc, &e| acc + e);
+        if sum != claimed_sum {
+            return Err(SumCheckError::SumCheckFailed(sum, claimed_sum));
+        }
+        transcript.absorb_slice(evaluations);
+        let skip_challenge = IPForMLSumcheck::<R, T>::sample_round(transcript).randomness;
+        transcript.absorb(&skip_challenge.into());
+
+        let claim = interpolate_uni_poly(evaluations, skip_challenge);
+        let subclaim =
+            Self::verify_as_subprotocol(transcript, nvars - skip, degree, claim, &proof.rest)?;
+
+        Ok(UniSkipSubClaim {
+            skip_challenge,
+            point: subclaim.point,
+            expected_evaluation: subclaim.expected_evaluation,
+        })
+    }
+}
+
+/// The backward differences $\nabla^i v$ of `values` at their last point, for
+/// `i = 0, ..., values.len() - 1`.
+fn last_differences<R: Ring>(values: &[R]) -> Vec<R> {
+    let mut table = values.to_vec();
+    let mut differences = Vec::with_capacity(values.len());
+    while let Some(&last) = table.last() {
+        differences.push(last);
+        for j in 0..table.len() - 1 {
+            table[j] = table[j + 1] - table[j];
+        }
+        table.pop();
+    }
+
+    differences
+}
+
+/// Steps the differences of [`last_differences`] to the next point of a polynomial of degree
+/// less than their number, and returns its value there.
+fn next_value<R: Ring>(differences: &mut [R]) -> R {
+    for i in (0..differences.len().saturating_sub(1)).rev() {
+        let higher = differences[i + 1];
+        differences[i] += higher;
+    }
+
+    differences[0]
+}
+
+/// Computes the first message, the evaluations of $s_0$ on `0, ..., degree * (2^skip - 1)`.
+///
+/// The values of $\hat f_j(\cdot, x')$ outside of the domain are extrapolated with their
+/// differences, with additions only.
+fn skip_round<R: OverField>(
+    mles: &[DenseMultilinearExtension<R>],
+    skip: usize,
+    degree: usize,
+    comb_fn: &(impl Fn(&[R]) -> R + Sync + Send),
+) -> ProverMsg<R> {
+    let size = 1 << skip;
+    let num_evals = num_skip_evaluations(size, degree);
+    let rest = mles.first().map_or(0, |mle| mle.evaluations.len() >> skip);
+
+    let summer = cfg_into_iter!(0..rest).map(|x| {
+        let chunks: Vec<&[R]> = mles
+            .iter()
+            .map(|mle| &mle.evaluations[x * size..(x + 1) * size])
+            .collect();
+        let mut differences: Vec<Vec<R>> =
+            chunks.iter().map(|chunk| last_differences(chunk)).collect();
+        let mut vals = vec![R::zero(); mles.len()];
+        (0..num_evals)
+            .map(|y| {
+                for ((v, chunk), differences) in vals.iter_mut().zip(&chunks).zip(&mut differences)
+                {
+                    *v = if y < size {
+                        chunk[y]
+                    } else {
+                        next_value(differences)
+                    };
+                }
+                comb_fn(&vals)
+            })
+            .collect::<Vec<R>>()
+    });
+    let add = |mut evaluations: Vec<R>, levals: Vec<R>| {
+        evaluations
+            .iter_mut()
+            .zip(levals)
+            .for_each(|(e, l)| *e += l);
+        evaluations
+    };
+
+    #[cfg(feature = "parallel")]
+    let evaluations = summer.reduce(|| vec![R::zero(); num_evals], add);
+
+    #[cfg(not(feature = "parallel"))]
+    let evaluations = summer.fold(vec![R::zero(); num_evals], add);
+
+    ProverMsg { evaluations }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::One;
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::{
+        transcript::poseidon::PoseidonTranscript,
+        utils::sumcheck::utils::{rand_poly, rand_poly_comb_fn},
+    };
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    const NVARS: usize = 5;
+
+    #[test]
+    fn test_univariate_skip() {
+        for skip in 1..NVARS {
+            let ((mles, degree), products, sum) =
+                rand_poly::<R>(NVARS, (2, 4), 3, &mut ark_std::test_rng()).unwrap();
+            let comb_fn = |vals: &[R]| rand_poly_comb_fn(vals, &products);
+
+            let (proof, prover_state) = MLSumcheck::prove_with_skip(
+                &mut T::default(),
+                mles.clone(),
+                NVARS,
+                degree,
+                skip,
+                comb_fn,
+            );
+            let subclaim =
+                MLSumcheck::verify_with_skip(&mut T::default(), NVARS, degree, skip, sum, &proof)
+                    .unwrap();
+            assert_eq!(subclaim.skip_challenge, prover_state.randomness[0]);
+            assert_eq!(subclaim.point, prover_state.randomness[1..]);
+
+            let vals: Vec<R> = mles
+                .iter()
+                .map(|mle| subclaim.evaluate_mle(mle).unwrap())
+                .collect();
+            assert_eq!(comb_fn(&vals), subclaim.expected_evaluation);
+        }
+    }
+
+    #[test]
+    fn test_skip_one_variable_is_multilinear() {
+        let ((mles, degree), products, sum) =
+            rand_poly::<R>(NVARS, (2, 4), 3, &mut ark_std::test_rng()).unwrap();
+        let comb_fn = |vals: &[R]| rand_poly_comb_fn(vals, &products);
+
+        let (proof, _) =
+            MLSumcheck::prove_with_skip(&mut T::default(), mles.clone(), NVARS, degree, 1, comb_fn);
+        let subclaim =
+            MLSumcheck::verify_with_skip(&mut T::default(), NVARS, degree, 1, sum, &proof).unwrap();
+
+        let point: Vec<R> = [subclaim.skip_challenge]
+            .iter()
+            .chain(&subclaim.point)
+            .map(|&r| r.into())
+            .collect();
+        for mle in &mles {
+            assert_eq!(
+                subclaim.evaluate_mle(mle).unwrap(),
+                mle.evaluate(&point).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_extrapolate_with_differences() {
+        // 2y^3 - y + 5 on 0, ..., 3 and beyond
+        let p = |y: u64| R::from(2 * y * y * y + 5) - R::from(y);
+        let mut differences = last_differences(&(0..4).map(p).collect::<Vec<_>>());
+        for y in 4..10 {
+            assert_eq!(next_value(&mut differences), p(y));
+        }
+
+        let mut constant = last_differences(&[R::from(7u64)]);
+        assert_eq!(next_value(&mut constant), R::from(7u64));
+    }
+
+    #[test]
+    fn test_failing_univariate_skip() {
+        let ((mles, degree), products, sum) =
+            rand_poly::<R>(NVARS, (2, 4), 3, &mut ark_std::test_rng()).unwrap();
+        let comb_fn = |vals: &[R]| rand_poly_comb_fn(vals, &products);
+        let (proof, _) =
+            MLSumcheck::prove_with_skip(&mut T::default(), mles, NVARS, degree, 2, comb_fn);
+
+        let wrong_sum = sum + R::one();
+        let verify = |skip, sum| {
+            MLSumcheck::verify_with_skip(&mut T::default(), NVARS, degree, skip, sum, &proof)
+        };
+        assert!(verify(2, wrong_sum).is_err());
+        assert!(verify(3, sum).is_err());
+        assert!(verify(0, sum).is_err());
+        assert!(verify(NVARS, sum).is_err());
+    }
+}
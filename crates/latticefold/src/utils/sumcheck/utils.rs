@@ -6,6 +6,7 @@
 //! This module defines our main mathematical object `DensePolynomial`; and
 //! various functions associated with it.
 
+use ark_ff::Field;
 use ark_std::{
     cfg_iter_mut, end_timer,
     rand::{Rng, RngCore},
@@ -91,6 +92,16 @@ pub fn eq_eval<R: Ring>(x: &[R], y: &[R]) -> Result<R, ArithErrors> {
     Ok(res)
 }
 
+/// Maps the integer `x` into the field.
+pub(crate) fn from_i128<F: Field>(x: i128) -> F {
+    let abs = F::from(x.unsigned_abs());
+    if x < 0 {
+        -abs
+    } else {
+        abs
+    }
+}
+
 /// This function build the eq(x, r) polynomial for any given r.
 ///
 /// Evaluate
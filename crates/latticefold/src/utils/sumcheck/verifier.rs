@@ -3,7 +3,7 @@ use ark_ff::{Field, One, Zero};
 use ark_std::vec::Vec;
 use stark_rings::OverField;
 
-use super::{prover::ProverMsg, IPForMLSumcheck, SumCheckError};
+use super::{prover::ProverMsg, utils::from_i128, IPForMLSumcheck, SumCheckError};
 use crate::transcript::Transcript;
 
 pub const SQUEEZE_NATIVE_ELEMENTS_NUM: usize = 1;
@@ -218,11 +218,7 @@ pub(crate) fn interpolate_uni_poly<R: OverField>(p_i: &[R], eval_at: R::BaseRing
         let mut ratio_enumerator = 1u128;
 
         for i in (0..len).rev() {
-            let ratio_numerator_f = if ratio_numerator < 0 {
-                -R::BaseRing::from((-ratio_numerator) as u128)
-            } else {
-                R::BaseRing::from(ratio_numerator as u128)
-            };
+            let ratio_numerator_f = from_i128::<R::BaseRing>(ratio_numerator);
 
             let x: R = (prod * R::BaseRing::from(ratio_enumerator)
                 / (last_denom * ratio_numerator_f * evals[i]))
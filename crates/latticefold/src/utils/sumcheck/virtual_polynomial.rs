@@ -0,0 +1,383 @@
+//! Virtual polynomials
+//!
+//! A virtual polynomial is a sum of products of multilinear extensions,
+//! $\sum_i c_i \prod_{j \in S_i} f_j$. Its degree is derived from the products, so the prover and
+//! the verifier of [`MLSumcheck::prove_virtual`] and [`MLSumcheck::verify_virtual`] cannot
+//! disagree on it.
+
+use ark_std::cfg_into_iter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use stark_rings::{OverField, Ring};
+use stark_rings_poly::{
+    mle::MultilinearExtension,
+    polynomials::{ArithErrors, DenseMultilinearExtension},
+};
+
+use super::{prover::ProverState, verifier::SubClaim, MLSumcheck, Proof, SumCheckError};
+use crate::{ark_base::*, transcript::Transcript};
+
+/// The structure of a virtual polynomial: its number of variables and its products, without the
+/// multilinear extensions.
+///
+/// This is what the verifier knows about the polynomial.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VirtualPolynomialInfo<R> {
+    nvars: usize,
+    num_mles: usize,
+    products: Vec<(R, Vec<usize>)>,
+    /// For every product, the length of the previous product if it is a prefix of this one, else
+    /// zero.
+    shared_prefixes: Vec<usize>,
+}
+
+impl<R: Ring> VirtualPolynomialInfo<R> {
+    /// The structure of a polynomial over `nvars` variables built from `num_mles` multilinear
+    /// extensions, with no products.
+    pub fn new(nvars: usize, num_mles: usize) -> Self {
+        Self {
+            nvars,
+            num_mles,
+            products: Vec::new(),
+            shared_prefixes: Vec::new(),
+        }
+    }
+
+    /// Adds the product `coeff * prod_{j in indices} f_j`.
+    pub fn add_product(
+        &mut self,
+        coeff: R,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> Result<(), ArithErrors> {
+        let indices: Vec<usize> = indices.into_iter().collect();
+        if let Some(&j) = indices.iter().find(|&&j| j >= self.num_mles) {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "MLE index {j} is out of bounds, there are {} MLEs",
+                self.num_mles
+            )));
+        }
+
+        let shared_prefix = match self.products.last() {
+            Some((_, previous)) if indices.starts_with(previous) => previous.len(),
+            _ => 0,
+        };
+        self.products.push((coeff, indices));
+        self.shared_prefixes.push(shared_prefix);
+        Ok(())
+    }
+
+    /// The number of variables.
+    pub fn nvars(&self) -> usize {
+        self.nvars
+    }
+
+    /// The number of multilinear extensions.
+    pub fn num_mles(&self) -> usize {
+        self.num_mles
+    }
+
+    /// The products, as a coefficient and the indices of the multiplied extensions.
+    pub fn products(&self) -> &[(R, Vec<usize>)] {
+        &self.products
+    }
+
+    /// The degree of the polynomial, i.e. the length of its longest product.
+    pub fn degree(&self) -> usize {
+        self.products
+            .iter()
+            .map(|(_, indices)| indices.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Evaluates the polynomial given the evaluations of its multilinear extensions at the same
+    /// point.
+    pub fn evaluate(&self, mle_evals: &[R]) -> Result<R, ArithErrors> {
+        if mle_evals.len() != self.num_mles {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "got {} MLE evaluations, expected {}",
+                mle_evals.len(),
+                self.num_mles
+            )));
+        }
+
+        Ok(self.combine(mle_evals))
+    }
+
+    /// The combination function of the polynomial.
+    ///
+    /// A product extending the previous one, e.g. the powers of an extension added in increasing
+    /// order, only multiplies its remaining factors into the value of the previous one. Other
+    /// products of up to three extensions are unrolled through slice patterns.
+    pub(crate) fn combine(&self, vals: &[R]) -> R {
+        let mut sum = R::zero();
+        let mut product = R::one();
+        for ((coeff, indices), &shared) in self.products.iter().zip(&self.shared_prefixes) {
+            product = match (shared, indices.as_slice()) {
+                (0, []) => R::one(),
+                (0, [a]) => vals[*a],
+                (0, [a, b]) => vals[*a] * vals[*b],
+                (0, [a, b, c]) => vals[*a] * vals[*b] * vals[*c],
+                (0, indices) => indices.iter().fold(R::one(), |p, &j| p * vals[j]),
+                (shared, indices) => indices[shared..].iter().fold(product, |p, &j| p * vals[j]),
+            };
+            sum += *coeff * product;
+        }
+
+        sum
+    }
+}
+
+/// A sum of products of multilinear extensions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VirtualPolynomial<R> {
+    info: VirtualPolynomialInfo<R>,
+    mles: Vec<DenseMultilinearExtension<R>>,
+}
+
+impl<R: Ring> VirtualPolynomial<R> {
+    /// The zero polynomial over `nvars` variables.
+    pub fn new(nvars: usize) -> Self {
+        Self {
+            info: VirtualPolynomialInfo::new(nvars, 0),
+            mles: Vec::new(),
+        }
+    }
+
+    /// Adds a multilinear extension, returning its index.
+    pub fn add_mle(&mut self, mle: DenseMultilinearExtension<R>) -> Result<usize, ArithErrors> {
+        if mle.num_vars() != self.info.nvars {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "the MLE has {} variables, expected {}",
+                mle.num_vars(),
+                self.info.nvars
+            )));
+        }
+
+        self.mles.push(mle);
+        self.info.num_mles += 1;
+        Ok(self.mles.len() - 1)
+    }
+
+    /// Adds the product `coeff * prod_{j in indices} f_j` of already added extensions.
+    pub fn add_product(
+        &mut self,
+        coeff: R,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> Result<(), ArithErrors> {
+        self.info.add_product(coeff, indices)
+    }
+
+    /// The structure of the polynomial.
+    pub fn info(&self) -> &VirtualPolynomialInfo<R> {
+        &self.info
+    }
+
+    /// The multilinear extensions.
+    pub fn mles(&self) -> &[DenseMultilinearExtension<R>] {
+        &self.mles
+    }
+
+    /// The degree of the polynomial.
+    pub fn degree(&self) -> usize {
+        self.info.degree()
+    }
+
+    /// The sum of the polynomial over the boolean hypercube.
+    pub fn sum(&self) -> R {
+        let n = 1 << self.info.nvars;
+        self.info
+            .products
+            .iter()
+            .map(|(coeff, indices)| {
+                let sum: R = cfg_into_iter!(0..n)
+                    .map(|x| {
+                        indices
+                            .iter()
+                            .fold(R::one(), |p, &j| p * self.mles[j].evaluations[x])
+                    })
+                    .sum();
+                *coeff * sum
+            })
+            .sum()
+    }
+
+    /// Splits the polynomial into its structure and its multilinear extensions.
+    pub fn into_parts(self) -> (VirtualPolynomialInfo<R>, Vec<DenseMultilinearExtension<R>>) {
+        (self.info, self.mles)
+    }
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Runs [`MLSumcheck::prove_as_subprotocol`] on a virtual polynomial, with the degree of the
+    /// polynomial.
+    pub fn prove_virtual(
+        transcript: &mut T,
+        poly: VirtualPolynomial<R>,
+    ) -> (Proof<R>, ProverState<R>) {
+        let (info, mles) = poly.into_parts();
+        let (nvars, degree) = (info.nvars(), info.degree());
+
+        Self::prove_as_subprotocol(transcript, mles, nvars, degree, |vals| info.combine(vals))
+    }
+
+    /// Runs [`MLSumcheck::verify_as_subprotocol`] for a virtual polynomial with structure
+    /// `info`.
+    pub fn verify_virtual(
+        transcript: &mut T,
+        info: &VirtualPolynomialInfo<R>,
+        claimed_sum: R,
+        proof: &Proof<R>,
+    ) -> Result<SubClaim<R>, SumCheckError<R>> {
+        Self::verify_as_subprotocol(transcript, info.nvars(), info.degree(), claimed_sum, proof)
+    }
+
+    /// Verifies the sumcheck of a virtual polynomial, and checks its final claim against the
+    /// evaluations `mle_evals` of its multilinear extensions at the point of the subclaim.
+    ///
+    /// Returns the point, at which the caller still has to check `mle_evals`.
+    pub fn verify_virtual_with_evaluations(
+        transcript: &mut T,
+        info: &VirtualPolynomialInfo<R>,
+        claimed_sum: R,
+        proof: &Proof<R>,
+        mle_evals: &[R],
+    ) -> Result<Vec<R::BaseRing>, SumCheckError<R>> {
+        let subclaim = Self::verify_virtual(transcript, info, claimed_sum, proof)?;
+
+        let evaluation = info.evaluate(mle_evals)?;
+        if evaluation != subclaim.expected_evaluation {
+            return Err(SumCheckError::SumCheckFailed(
+                subclaim.expected_evaluation,
+                evaluation,
+            ));
+        }
+
+        Ok(subclaim.point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::One;
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+
+    use super::*;
+    use crate::{transcript::poseidon::PoseidonTranscript, utils::sumcheck::utils::rand_poly};
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    const NVARS: usize = 5;
+
+    fn rand_virtual_poly() -> (VirtualPolynomial<R>, usize, R) {
+        let ((mles, degree), products, sum) =
+            rand_poly(NVARS, (2, 5), 3, &mut ark_std::test_rng()).unwrap();
+
+        let mut poly = VirtualPolynomial::new(NVARS);
+        for mle in mles {
+            poly.add_mle(mle).unwrap();
+        }
+        for (coeff, indices) in products {
+            poly.add_product(coeff, indices).unwrap();
+        }
+
+        (poly, degree, sum)
+    }
+
+    #[test]
+    fn test_virtual_sumcheck() {
+        let (poly, degree, sum) = rand_virtual_poly();
+        assert_eq!(poly.degree(), degree);
+        let info = poly.info().clone();
+        let mles = poly.mles().to_vec();
+
+        let (proof, _) = MLSumcheck::prove_virtual(&mut T::default(), poly);
+
+        let subclaim = MLSumcheck::verify_virtual(&mut T::default(), &info, sum, &proof).unwrap();
+        let point: Vec<R> = subclaim.point.iter().map(|&x| x.into()).collect();
+        let mle_evals: Vec<R> = mles
+            .iter()
+            .map(|mle| mle.evaluate(&point).unwrap())
+            .collect();
+        assert_eq!(
+            info.evaluate(&mle_evals).unwrap(),
+            subclaim.expected_evaluation
+        );
+
+        assert_eq!(
+            MLSumcheck::verify_virtual_with_evaluations(
+                &mut T::default(),
+                &info,
+                sum,
+                &proof,
+                &mle_evals
+            )
+            .unwrap(),
+            subclaim.point
+        );
+
+        let mut wrong_evals = mle_evals;
+        wrong_evals[0] += R::one();
+        assert!(MLSumcheck::verify_virtual_with_evaluations(
+            &mut T::default(),
+            &info,
+            sum,
+            &proof,
+            &wrong_evals
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_virtual_polynomial_sum() {
+        let (poly, _, sum) = rand_virtual_poly();
+        assert_eq!(poly.sum(), sum);
+    }
+
+    #[test]
+    fn test_combine_unrolled_products() {
+        let mut info = VirtualPolynomialInfo::<R>::new(1, 5);
+        info.add_product(R::from(3u64), []).unwrap();
+        info.add_product(R::from(5u64), [0]).unwrap();
+        info.add_product(R::one(), [1, 2]).unwrap();
+        info.add_product(R::one(), [0, 1, 2]).unwrap();
+        info.add_product(R::from(2u64), [1, 2, 3, 4]).unwrap();
+        assert_eq!(info.degree(), 4);
+
+        let vals: Vec<R> = (1..=5u64).map(R::from).collect();
+        // 3 + 5 * 1 + 2 * 3 + 1 * 2 * 3 + 2 * 2 * 3 * 4 * 5
+        assert_eq!(info.evaluate(&vals).unwrap(), R::from(260u64));
+
+        // Powers of the third extension, each extending the previous product
+        let mut info = VirtualPolynomialInfo::<R>::new(1, 5);
+        info.add_product(R::from(2u64), [2]).unwrap();
+        info.add_product(R::one(), [2, 2]).unwrap();
+        info.add_product(R::from(4u64), [2, 2, 2, 2]).unwrap();
+        info.add_product(R::one(), [1]).unwrap();
+        info.add_product(R::one(), [1, 4]).unwrap();
+        assert_eq!(info.shared_prefixes, vec![0, 1, 2, 0, 1]);
+        // 2 * 3 + 3^2 + 4 * 3^4 + 2 + 2 * 5
+        assert_eq!(info.evaluate(&vals).unwrap(), R::from(351u64));
+    }
+
+    #[test]
+    fn test_invalid_virtual_polynomial() {
+        let mut poly = VirtualPolynomial::<R>::new(NVARS);
+        assert!(poly
+            .add_mle(DenseMultilinearExtension::from_evaluations_vec(
+                NVARS - 1,
+                vec![R::one(); 1 << (NVARS - 1)]
+            ))
+            .is_err());
+
+        let j = poly
+            .add_mle(DenseMultilinearExtension::from_evaluations_vec(
+                NVARS,
+                vec![R::one(); 1 << NVARS],
+            ))
+            .unwrap();
+        assert!(poly.add_product(R::one(), [j, j + 1]).is_err());
+        assert!(poly.info().evaluate(&[]).is_err());
+    }
+}
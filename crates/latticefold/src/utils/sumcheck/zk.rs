@@ -0,0 +1,1040 @@
+//! Zero-knowledge sumcheck
+//!
+//! The round messages of [`MLSumcheck::prove_as_subprotocol`] are restrictions of the summed
+//! polynomial $f$, and leak information about the witness. Following Libra, the prover of
+//! [`MLSumcheck::prove_zk`] samples a masking polynomial
+//! $$
+//! g(x) = g_0 + \sum_i g_i(x_i),
+//! $$
+//! the $g_i$ being random univariates of the degree of the sumcheck without constant term, and
+//! commits to the gadget decomposition of its coefficients with a [`MaskCommitmentScheme`]. It
+//! sends the sum $G$ of $g$, the verifier samples $\rho$, and the sumcheck proves that
+//! $f + \rho g$ sums to $H + \rho G$. [`MLSumcheck::prove_zk_with_eqs`] does the same for the
+//! eq-weighted sumchecks of [`MLSumcheck::prove_with_eqs`].
+//!
+//! The final claim about $f + \rho g$ is split into the subclaim about $f$, as returned by
+//! [`MLSumcheck::verify_as_subprotocol`], and a [`MaskClaim`] about $g$. The prover discharges
+//! the [`MaskClaim`] with [`MaskClaim::prove`], a zero-knowledge proof of the evaluation of the
+//! committed coefficients, see [`MaskEvalProof`]. Without it the verifier would accept any
+//! evaluation of $g$.
+//!
+//! The masking is honest-verifier zero-knowledge: it hides the round messages from a verifier
+//! whose challenges are honestly sampled, which the Fiat-Shamir transcript enforces. The final
+//! evaluation of $f$ itself is not masked, so the subclaim about $f$ has to be discharged in zero
+//! knowledge as well, or $f$ be a polynomial whose evaluation at a random point reveals nothing.
+
+use ark_ff::{Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{slice, Zero};
+use cyclotomic_rings::{challenge_set::LatticefoldChallengeSet, rings::SuitableRing};
+use stark_rings::{
+    balanced_decomposition::{GadgetDecompose, GadgetRecompose},
+    cyclotomic_ring::{CRT, ICRT},
+    OverField, PolyRing, Ring,
+};
+use stark_rings_poly::polynomials::{ArithErrors, DenseMultilinearExtension};
+
+use super::{
+    batched::pow2, prover::ProverState, verifier::SubClaim, MLSumcheck, Proof, SumCheckError,
+};
+use crate::{
+    ark_base::*,
+    commitment::{
+        sample_short, Commitment, CommitmentError, HidingAjtaiCommitmentScheme, NormBound, Opening,
+    },
+    decomposition_parameters::gadget_length,
+    pcs::PcsRing,
+    transcript::{Transcript, TranscriptWithShortChallenges},
+    utils::norms::l1_norm,
+};
+
+/// A masking polynomial $g(x) = g_0 + \sum_i g_i(x_i)$.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaskingPolynomial<R> {
+    constant: R,
+    /// The coefficients of $X, \ldots, X^d$ of every $g_i$
+    univariates: Vec<Vec<R>>,
+}
+
+impl<R: OverField> MaskingPolynomial<R> {
+    /// Samples a masking polynomial over `nvars` variables, of degree `degree` in each.
+    pub fn rand<Rng: rand::Rng + ?Sized>(nvars: usize, degree: usize, rng: &mut Rng) -> Self {
+        Self {
+            constant: R::rand(rng),
+            univariates: (0..nvars)
+                .map(|_| (0..degree).map(|_| R::rand(rng)).collect())
+                .collect(),
+        }
+    }
+
+    /// The number of coefficients of a masking polynomial, i.e. the width of the commitment
+    /// scheme for it.
+    pub fn num_coeffs(nvars: usize, degree: usize) -> usize {
+        1 + nvars * degree
+    }
+
+    /// The polynomial over `nvars` variables of degree `degree` with the coefficients `coeffs`,
+    /// in the order of [`MaskingPolynomial::coeffs`].
+    fn from_coeffs(nvars: usize, degree: usize, coeffs: &[R]) -> Self {
+        Self {
+            constant: coeffs[0],
+            univariates: (0..nvars)
+                .map(|i| coeffs[1 + i * degree..1 + (i + 1) * degree].to_vec())
+                .collect(),
+        }
+    }
+
+    /// The number of variables.
+    pub fn nvars(&self) -> usize {
+        self.univariates.len()
+    }
+
+    /// The coefficients, the constant first and then the coefficients of every $g_i$ by
+    /// increasing degree.
+    pub fn coeffs(&self) -> Vec<R> {
+        let mut coeffs =
+            Vec::with_capacity(1 + self.univariates.iter().map(Vec::len).sum::<usize>());
+        coeffs.push(self.constant);
+        for univariate in &self.univariates {
+            coeffs.extend_from_slice(univariate);
+        }
+        coeffs
+    }
+
+    /// Evaluates $g_i$ at `x`.
+    fn univariate(&self, i: usize, x: R) -> R {
+        self.univariates[i]
+            .iter()
+            .rev()
+            .fold(R::zero(), |acc, &c| (acc + c) * x)
+    }
+
+    /// The sum of the polynomial over the boolean hypercube.
+    pub fn sum(&self) -> R {
+        let nvars = self.nvars();
+        let sum = pow2::<R>(nvars) * self.constant;
+        if nvars == 0 {
+            return sum;
+        }
+
+        // g_i(0) = 0, and g_i(1) is the sum of its coefficients
+        let ones = self
+            .univariates
+            .iter()
+            .flatten()
+            .fold(R::zero(), |acc, &c| acc + c);
+        sum + pow2::<R>(nvars - 1) * ones
+    }
+
+    /// Evaluates the polynomial at `point`.
+    pub fn evaluate(&self, point: &[R]) -> Result<R, ArithErrors> {
+        if point.len() != self.nvars() {
+            return Err(ArithErrors::InvalidParameters(format!(
+                "the point has {} variables, expected {}",
+                point.len(),
+                self.nvars()
+            )));
+        }
+
+        Ok(point
+            .iter()
+            .enumerate()
+            .fold(self.constant, |acc, (i, &x)| acc + self.univariate(i, x)))
+    }
+
+    /// The message of round `round` of the sumcheck of the polynomial, i.e. its evaluations on
+    /// `0, ..., degree` with the previous variables bound to `randomness`.
+    fn round_evaluations(&self, round: usize, randomness: &[R], degree: usize) -> Vec<R> {
+        let rest = self.nvars() - round - 1;
+        let bound = randomness
+            .iter()
+            .enumerate()
+            .fold(self.constant, |acc, (i, &r)| acc + self.univariate(i, r));
+        // The variables after the current one each contribute g_j(1) on half the hypercube
+        let tail = match rest {
+            0 => R::zero(),
+            _ => {
+                let ones = self.univariates[round + 1..]
+                    .iter()
+                    .flatten()
+                    .fold(R::zero(), |acc, &c| acc + c);
+                pow2::<R>(rest - 1) * ones
+            }
+        };
+
+        (0..=degree)
+            .map(|x| pow2::<R>(rest) * (bound + self.univariate(round, R::from(x as u128))) + tail)
+            .collect()
+    }
+}
+
+/// A hiding commitment scheme for masking polynomials.
+///
+/// The coefficients of a masking polynomial are uniform in the ring. They are committed through
+/// their balanced gadget decomposition in base `b`, so that the committed vector is short and an
+/// opening is checked against a norm bound. The digits are padded with zeros to the width of the
+/// hiding scheme, so one scheme commits to masking polynomials with up to
+/// [`MaskCommitmentScheme::max_coeffs`] coefficients.
+#[derive(Clone, Debug)]
+pub struct MaskCommitmentScheme<R> {
+    scheme: HidingAjtaiCommitmentScheme<R>,
+    b: u128,
+    l: usize,
+}
+
+impl<R: SuitableRing> MaskCommitmentScheme<R> {
+    /// Create a scheme committing to the digits in base `b` with `scheme`.
+    pub fn new(scheme: HidingAjtaiCommitmentScheme<R>, b: u128) -> Result<Self, CommitmentError> {
+        if b < 2 {
+            return Err(CommitmentError::InvalidGadgetBase(b));
+        }
+
+        Ok(Self {
+            scheme,
+            b,
+            l: gadget_length::<R>(b),
+        })
+    }
+
+    /// Returns a random scheme for masking polynomials with up to `max_coeffs` coefficients, see
+    /// [`MaskingPolynomial::num_coeffs`].
+    pub fn rand<Rng: rand::Rng + ?Sized>(
+        kappa: usize,
+        max_coeffs: usize,
+        b: u128,
+        rand_cols: usize,
+        mask_bound: u128,
+        rng: &mut Rng,
+    ) -> Result<Self, CommitmentError> {
+        if b < 2 {
+            return Err(CommitmentError::InvalidGadgetBase(b));
+        }
+        let width = max_coeffs * gadget_length::<R>(b);
+
+        Self::new(
+            HidingAjtaiCommitmentScheme::rand(kappa, width, rand_cols, mask_bound, rng)?,
+            b,
+        )
+    }
+
+    /// The largest number of coefficients of a committed masking polynomial.
+    pub fn max_coeffs(&self) -> usize {
+        self.scheme.width() / self.l
+    }
+
+    /// The base of the gadget decomposition.
+    pub fn b(&self) -> u128 {
+        self.b
+    }
+
+    /// The number of digits of a coefficient.
+    pub fn l(&self) -> usize {
+        self.l
+    }
+
+    /// The weights $\ell$ of the committed digits in the evaluation at `point` of a masking
+    /// polynomial of degree `degree`, i.e. $g(r) = \langle \ell, d \rangle$.
+    fn evaluation_weights(&self, point: &[R], degree: usize) -> Result<Vec<R>, CommitmentError> {
+        let width = self.scheme.width();
+        let len = MaskingPolynomial::<R>::num_coeffs(point.len(), degree) * self.l;
+        if len > width {
+            return Err(CommitmentError::WrongWitnessLength(len, width));
+        }
+
+        let mut coeff_weights = Vec::with_capacity(len / self.l);
+        coeff_weights.push(R::ONE);
+        for &x in point {
+            let mut power = R::ONE;
+            for _ in 0..degree {
+                power *= x;
+                coeff_weights.push(power);
+            }
+        }
+        let mut powers = Vec::with_capacity(self.l);
+        let mut power = R::ONE;
+        for _ in 0..self.l {
+            powers.push(power);
+            power *= R::from(self.b);
+        }
+
+        let mut weights: Vec<R> = coeff_weights
+            .iter()
+            .flat_map(|&w| powers.iter().map(move |&p| w * p))
+            .collect();
+        weights.resize(width, R::zero());
+
+        Ok(weights)
+    }
+
+    /// Commits to `polynomial` under a freshly sampled mask.
+    pub fn commit<Rng: rand::Rng + ?Sized>(
+        &self,
+        polynomial: &MaskingPolynomial<R>,
+        rng: &mut Rng,
+    ) -> Result<(Commitment<R>, MaskOpening<R>), CommitmentError> {
+        let mut digits =
+            ICRT::elementwise_icrt(polynomial.coeffs()).gadget_decompose(self.b, self.l);
+        if digits.len() > self.scheme.width() {
+            return Err(CommitmentError::WrongWitnessLength(
+                digits.len(),
+                self.scheme.width(),
+            ));
+        }
+        digits.resize(self.scheme.width(), R::CoefficientRepresentation::zero());
+        let (commitment, randomness) = self
+            .scheme
+            .commit_ntt(&CRT::elementwise_crt(digits.clone()), rng)?;
+
+        Ok((
+            commitment,
+            MaskOpening {
+                digits: Opening::new(digits),
+                randomness,
+            },
+        ))
+    }
+
+    /// Checks that `opening` opens `commitment`, with digits below `b` and a mask within the
+    /// mask bound, and returns the committed polynomial over `nvars` variables of degree
+    /// `degree`.
+    pub fn open(
+        &self,
+        commitment: &Commitment<R>,
+        opening: &MaskOpening<R>,
+        nvars: usize,
+        degree: usize,
+    ) -> Result<MaskingPolynomial<R>, CommitmentError> {
+        self.scheme.verify_opening(
+            commitment,
+            &opening.digits,
+            &opening.randomness,
+            NormBound::Infinity(self.b),
+        )?;
+
+        let len = MaskingPolynomial::<R>::num_coeffs(nvars, degree) * self.l;
+        if len > opening.digits.f.len() || opening.digits.f[len..].iter().any(|d| !d.is_zero()) {
+            return Err(CommitmentError::InvalidOpening);
+        }
+        let coeffs =
+            CRT::elementwise_crt(opening.digits.f[..len].to_vec()).gadget_recompose(self.b, self.l);
+
+        Ok(MaskingPolynomial::from_coeffs(nvars, degree, &coeffs))
+    }
+}
+
+impl<R: PcsRing> MaskCommitmentScheme<R> {
+    /// The bounds $(\beta, \beta_r)$ on the coefficients of the masks of the digits and of the
+    /// commitment randomness in a [`MaskEvalProof`], for challenges of l1 norm at most
+    /// `challenge_l1`.
+    ///
+    /// They exceed the largest norms of $c d$ and $c r_d$ by a factor of the number of masked
+    /// coefficients, so that an attempt is accepted with probability about $1 / e$. Fails if they
+    /// reach half the modulus, where the answers would wrap around.
+    fn masking_bounds(&self, challenge_l1: u128) -> Result<(u128, u128), CommitmentError> {
+        let n = ((self.scheme.width() + self.scheme.rand_cols())
+            * R::CoefficientRepresentation::dimension()) as u128;
+        // The answers must stay below half the modulus, which is at least 2^(bits - 2)
+        let bits = <<R as PolyRing>::BaseRing as Field>::BasePrimeField::MODULUS_BIT_SIZE;
+        let max = 1u128 << (bits - 2).min(127);
+        let bound = |x: u128| {
+            R::PRODUCT_EXPANSION
+                .checked_mul(challenge_l1)
+                .and_then(|shift| shift.checked_mul(n))
+                .and_then(|shift| shift.checked_mul(x))
+                .filter(|&beta| beta < max)
+                .ok_or(CommitmentError::MaskingBoundTooLarge)
+        };
+
+        Ok((bound(self.b / 2)?, bound(self.scheme.mask_bound())?))
+    }
+
+    /// The norm bounds on the answers of a [`MaskEvalProof`] for the masking bounds
+    /// `beta, beta_r` and the challenge `c`, i.e. the masking bounds minus the largest norms of
+    /// $c d$ and $c r_d$.
+    fn answer_bounds(
+        &self,
+        beta: u128,
+        beta_r: u128,
+        c: &R::CoefficientRepresentation,
+    ) -> (NormBound, NormBound) {
+        let c_l1 = u128::try_from(l1_norm(slice::from_ref(c))).unwrap_or(u128::MAX);
+        let shift = R::PRODUCT_EXPANSION.saturating_mul(c_l1);
+        let z_bound = beta.saturating_sub(shift.saturating_mul(self.b / 2));
+        let z_r_bound = beta_r.saturating_sub(shift.saturating_mul(self.scheme.mask_bound()));
+
+        (
+            NormBound::Infinity(z_bound + 1),
+            NormBound::Infinity(z_r_bound + 1),
+        )
+    }
+}
+
+/// The mask of a zero-knowledge sumcheck, as sent by the prover.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SumcheckMask<R: Ring> {
+    /// The commitment to the masking polynomial
+    pub commitment: Commitment<R>,
+    /// The sum of the masking polynomial
+    pub sum: R,
+    /// The evaluation of the masking polynomial at the point of the sumcheck
+    pub evaluation: R,
+}
+
+/// A zero-knowledge sumcheck proof.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ZkProof<R: Ring> {
+    /// The mask of the sumcheck
+    pub mask: SumcheckMask<R>,
+    /// The sumcheck of the masked polynomial
+    pub sumcheck: Proof<R>,
+}
+
+/// The opening of the commitment to a masking polynomial.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaskOpening<R: SuitableRing> {
+    /// The digits of the coefficients of the polynomial, padded with zeros
+    pub digits: Opening<R::CoefficientRepresentation>,
+    /// The mask of the hiding commitment
+    pub randomness: Vec<R::CoefficientRepresentation>,
+}
+
+/// The claim that the committed masking polynomial of degree `degree` evaluates to `evaluation`
+/// at `point`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaskClaim<R: Ring> {
+    pub commitment: Commitment<R>,
+    pub point: Vec<R>,
+    pub degree: usize,
+    pub evaluation: R,
+}
+
+impl<R: OverField> MaskClaim<R> {
+    /// The claim about `mask` at the point of a masked sumcheck whose prover ended in
+    /// `prover_state`, i.e. the claim the verifier gets from [`MLSumcheck::verify_zk`].
+    pub fn new(mask: &SumcheckMask<R>, prover_state: &ProverState<R>) -> Self {
+        Self {
+            commitment: mask.commitment.clone(),
+            point: prover_state.randomness.iter().map(|&r| r.into()).collect(),
+            degree: prover_state.max_degree,
+            evaluation: mask.evaluation,
+        }
+    }
+}
+
+/// A proof of [`MaskClaim::prove`] that the committed masking polynomial evaluates to the
+/// claimed value.
+///
+/// The evaluation is linear in the committed digits $d$, $g(r) = \langle \ell, d \rangle$, so
+/// the claim is proved with a Fiat-Shamir-with-aborts proof of a linear relation on the opening
+/// $(d, r_d)$ of the commitment $C$. The prover samples short $y, y_r$, sends
+/// $w = A y + A_r y_r$ and $v = \langle \ell, y \rangle$, receives a short challenge $c$ and
+/// answers with $z = y + c d$ and $z_r = y_r + c r_d$. The verifier checks
+/// $A z + A_r z_r = w + c C$, $\langle \ell, z \rangle = v + c \cdot g(r)$ and the norms of
+/// $z, z_r$.
+///
+/// The prover rejects and restarts until the norms of $z, z_r$ fall below the masking bounds minus
+/// the largest norm of $c d$ and $c r_d$. The accepted answers are then uniform, independent of
+/// $(d, r_d)$, and the proof reveals nothing beyond $g(r)$. Two accepting proofs for different
+/// challenges give an opening of $(c - c') C$ to a relaxed witness, as for any folded opening.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MaskEvalProof<R: Ring> {
+    /// The commitment $w$ to the masking digits
+    pub commitment: Commitment<R>,
+    /// The inner product $v$ of the weights with the masking digits
+    pub inner_product: R,
+    /// The answer $z$ for the digits
+    pub digits: Vec<R>,
+    /// The answer $z_r$ for the mask of the commitment
+    pub randomness: Vec<R>,
+}
+
+impl<R: PcsRing> MaskClaim<R> {
+    /// Proves the claim in zero knowledge from the opening of the commitment, see
+    /// [`MaskEvalProof`].
+    pub fn prove<T, Rng>(
+        &self,
+        scheme: &MaskCommitmentScheme<R>,
+        opening: &MaskOpening<R>,
+        transcript: &mut T,
+        rng: &mut Rng,
+    ) -> Result<MaskEvalProof<R>, CommitmentError>
+    where
+        T: TranscriptWithShortChallenges<R> + Clone,
+        Rng: rand::Rng + ?Sized,
+    {
+        let weights = scheme.evaluation_weights(&self.point, self.degree)?;
+        let (width, rand_cols) = (scheme.scheme.width(), scheme.scheme.rand_cols());
+        if opening.digits.f.len() != width {
+            return Err(CommitmentError::WrongWitnessLength(
+                opening.digits.f.len(),
+                width,
+            ));
+        }
+        if opening.randomness.len() != rand_cols {
+            return Err(CommitmentError::WrongMaskLength(
+                opening.randomness.len(),
+                rand_cols,
+            ));
+        }
+        let digits: Vec<R> = CRT::elementwise_crt(opening.digits.f.clone());
+        let randomness: Vec<R> = CRT::elementwise_crt(opening.randomness.clone());
+        let (beta, beta_r) = scheme.masking_bounds(challenge_l1_bound::<R, T>())?;
+
+        loop {
+            let y = sample_short::<R, _>(width, beta, rng);
+            let y_r = sample_short::<R, _>(rand_cols, beta_r, rng);
+            let y: Vec<R> = CRT::elementwise_crt(y);
+            let commitment = scheme.scheme.commit_ntt_with_mask(&y, &y_r)?;
+            let v = inner_product(&weights, &y);
+
+            // A rejected attempt must not reach the transcript
+            let mut attempt = transcript.clone();
+            attempt.absorb_slice(commitment.as_ref());
+            attempt.absorb(&v);
+            let c_coeff = attempt.get_short_challenge();
+            let c: R = c_coeff.crt();
+
+            let z: Vec<R> = y
+                .iter()
+                .zip(&digits)
+                .map(|(&y_i, &d_i)| y_i + c * d_i)
+                .collect();
+            let z_r: Vec<R> = CRT::elementwise_crt(y_r)
+                .into_iter()
+                .zip(&randomness)
+                .map(|(y_i, &r_i)| y_i + c * r_i)
+                .collect();
+            let (z_bound, z_r_bound) = scheme.answer_bounds(beta, beta_r, &c_coeff);
+            if z_bound.check(&ICRT::elementwise_icrt(z.clone()))
+                && z_r_bound.check(&ICRT::elementwise_icrt(z_r.clone()))
+            {
+                *transcript = attempt;
+                return Ok(MaskEvalProof {
+                    commitment,
+                    inner_product: v,
+                    digits: z,
+                    randomness: z_r,
+                });
+            }
+        }
+    }
+
+    /// Verifies a proof of [`MaskClaim::prove`].
+    pub fn verify<T: TranscriptWithShortChallenges<R>>(
+        &self,
+        scheme: &MaskCommitmentScheme<R>,
+        proof: &MaskEvalProof<R>,
+        transcript: &mut T,
+    ) -> Result<(), CommitmentError> {
+        let weights = scheme.evaluation_weights(&self.point, self.degree)?;
+        if proof.commitment.len() != scheme.scheme.kappa() {
+            return Err(CommitmentError::WrongCommitmentLength(
+                proof.commitment.len(),
+                scheme.scheme.kappa(),
+            ));
+        }
+
+        transcript.absorb_slice(proof.commitment.as_ref());
+        transcript.absorb(&proof.inner_product);
+        let c_coeff = transcript.get_short_challenge();
+        let c: R = c_coeff.crt();
+
+        let (beta, beta_r) = scheme.masking_bounds(challenge_l1_bound::<R, T>())?;
+        let (z_bound, z_r_bound) = scheme.answer_bounds(beta, beta_r, &c_coeff);
+        let z_r: Vec<R::CoefficientRepresentation> =
+            ICRT::elementwise_icrt(proof.randomness.clone());
+        if !z_bound.check(&ICRT::elementwise_icrt(proof.digits.clone())) {
+            return Err(CommitmentError::NormBoundExceeded(z_bound));
+        }
+        if !z_r_bound.check(&z_r) {
+            return Err(CommitmentError::NormBoundExceeded(z_r_bound));
+        }
+
+        let mut expected = self.commitment.clone();
+        expected *= &c;
+        expected += &proof.commitment;
+        if scheme.scheme.commit_ntt_with_mask(&proof.digits, &z_r)? != expected
+            || inner_product(&weights, &proof.digits) != proof.inner_product + c * self.evaluation
+        {
+            return Err(CommitmentError::InvalidOpening);
+        }
+
+        Ok(())
+    }
+}
+
+/// A bound on the l1 norm of the short challenges of `T`, whose coefficients are each encoded in
+/// `8 * BYTES_NEEDED / d` bits.
+fn challenge_l1_bound<R: SuitableRing, T: TranscriptWithShortChallenges<R>>() -> u128 {
+    let d = R::CoefficientRepresentation::dimension();
+    let bits = (8 * <T::ChallengeSet as LatticefoldChallengeSet<R>>::BYTES_NEEDED).div_ceil(d);
+
+    (d as u128) << bits
+}
+
+fn inner_product<R: Ring>(a: &[R], b: &[R]) -> R {
+    a.iter()
+        .zip(b)
+        .fold(R::zero(), |acc, (a_i, b_i)| acc + *a_i * b_i)
+}
+
+impl<R: SuitableRing, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Runs [`MLSumcheck::prove_as_subprotocol`] in zero knowledge, masking the round messages
+    /// with a random polynomial committed with `scheme`.
+    ///
+    /// The prover state is the one of [`MLSumcheck::prove_as_subprotocol`]. The opening of the
+    /// mask is returned to prove the [`MaskClaim`] of the verifier with [`MaskClaim::prove`].
+    pub fn prove_zk<Rng: rand::Rng + ?Sized>(
+        transcript: &mut T,
+        mles: Vec<DenseMultilinearExtension<R>>,
+        nvars: usize,
+        degree: usize,
+        comb_fn: impl Fn(&[R]) -> R + Sync + Send,
+        scheme: &MaskCommitmentScheme<R>,
+        rng: &mut Rng,
+    ) -> Result<(ZkProof<R>, ProverState<R>, MaskOpening<R>), CommitmentError> {
+        let (mask, commitment, opening, rho) =
+            Self::commit_mask(transcript, nvars, degree, scheme, rng)?;
+
+        let (sumcheck, prover_state) = Self::prove_as_subprotocol_with_hook(
+            transcript,
+            mles,
+            nvars,
+            degree,
+            comb_fn,
+            |round, randomness, evaluations| {
+                let randomness: Vec<R> = randomness.iter().map(|&r| r.into()).collect();
+                evaluations
+                    .iter_mut()
+                    .zip(mask.round_evaluations(round, &randomness, degree))
+                    .for_each(|(e, m)| *e += rho * m);
+            },
+        );
+
+        let mask = Self::evaluate_mask(transcript, &mask, commitment, &prover_state);
+
+        Ok((ZkProof { mask, sumcheck }, prover_state, opening))
+    }
+
+    /// Runs [`MLSumcheck::prove_with_eqs`] in zero knowledge, as [`MLSumcheck::prove_zk`].
+    ///
+    /// The proof is checked with [`MLSumcheck::verify_zk`] for `degree + 1`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_zk_with_eqs<Rng: rand::Rng + ?Sized>(
+        transcript: &mut T,
+        mles: &[&DenseMultilinearExtension<R>],
+        nvars: usize,
+        degree: usize,
+        eq_points: &[Vec<R>],
+        comb_fn: impl Fn(&[R], &mut [R]) + Sync + Send,
+        scheme: &MaskCommitmentScheme<R>,
+        rng: &mut Rng,
+    ) -> Result<(ZkProof<R>, ProverState<R>, MaskOpening<R>), CommitmentError> {
+        Self::prove_zk_with_eqs_and_first_round(
+            transcript, mles, nvars, degree, eq_points, None, comb_fn, scheme, rng,
+        )
+    }
+
+    /// [`MLSumcheck::prove_zk_with_eqs`], with the unmasked message of the first round given by
+    /// `first_round`, see [`MLSumcheck::prove_with_eqs_and_first_round`].
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn prove_zk_with_eqs_and_first_round<Rng: rand::Rng + ?Sized>(
+        transcript: &mut T,
+        mles: &[&DenseMultilinearExtension<R>],
+        nvars: usize,
+        degree: usize,
+        eq_points: &[Vec<R>],
+        first_round: Option<Vec<R>>,
+        comb_fn: impl Fn(&[R], &mut [R]) + Sync + Send,
+        scheme: &MaskCommitmentScheme<R>,
+        rng: &mut Rng,
+    ) -> Result<(ZkProof<R>, ProverState<R>, MaskOpening<R>), CommitmentError> {
+        let (mask, commitment, opening, rho) =
+            Self::commit_mask(transcript, nvars, degree + 1, scheme, rng)?;
+
+        let (sumcheck, prover_state) = Self::prove_with_eqs_and_hook(
+            transcript,
+            mles,
+            nvars,
+            degree,
+            eq_points,
+            false,
+            first_round,
+            comb_fn,
+            |round, randomness, evaluations| {
+                let randomness: Vec<R> = randomness.iter().map(|&r| r.into()).collect();
+                evaluations
+                    .iter_mut()
+                    .zip(mask.round_evaluations(round, &randomness, degree + 1))
+                    .for_each(|(e, m)| *e += rho * m);
+            },
+        );
+
+        let mask = Self::evaluate_mask(transcript, &mask, commitment, &prover_state);
+
+        Ok((ZkProof { mask, sumcheck }, prover_state, opening))
+    }
+
+    /// Samples a masking polynomial and commits to it, then squeezes the challenge $\rho$ that
+    /// batches it with the sumcheck.
+    fn commit_mask<Rng: rand::Rng + ?Sized>(
+        transcript: &mut T,
+        nvars: usize,
+        degree: usize,
+        scheme: &MaskCommitmentScheme<R>,
+        rng: &mut Rng,
+    ) -> Result<(MaskingPolynomial<R>, Commitment<R>, MaskOpening<R>, R), CommitmentError> {
+        let mask = MaskingPolynomial::rand(nvars, degree, rng);
+        let (commitment, opening) = scheme.commit(&mask, rng)?;
+        transcript.absorb_slice(commitment.as_ref());
+        transcript.absorb(&mask.sum());
+        let rho: R = transcript.get_challenge().into();
+
+        Ok((mask, commitment, opening, rho))
+    }
+
+    /// Evaluates the masking polynomial at the point of the sumcheck and absorbs the evaluation.
+    fn evaluate_mask(
+        transcript: &mut T,
+        mask: &MaskingPolynomial<R>,
+        commitment: Commitment<R>,
+        prover_state: &ProverState<R>,
+    ) -> SumcheckMask<R> {
+        let point: Vec<R> = prover_state.randomness.iter().map(|&r| r.into()).collect();
+        let evaluation = mask
+            .evaluate(&point)
+            .expect("the point has one coordinate per variable");
+        transcript.absorb(&evaluation);
+
+        SumcheckMask {
+            commitment,
+            sum: mask.sum(),
+            evaluation,
+        }
+    }
+}
+
+impl<R: OverField, T: Transcript<R>> MLSumcheck<R, T> {
+    /// Verifies a proof of [`MLSumcheck::prove_zk`] that the polynomial sums to `claimed_sum`.
+    ///
+    /// Returns the subclaim about the polynomial, as [`MLSumcheck::verify_as_subprotocol`], and
+    /// the claim about the masking polynomial, which the caller still has to check with
+    /// [`MaskClaim::verify`].
+    pub fn verify_zk(
+        transcript: &mut T,
+        nvars: usize,
+        degree: usize,
+        claimed_sum: R,
+        proof: &ZkProof<R>,
+    ) -> Result<(SubClaim<R>, MaskClaim<R>), SumCheckError<R>> {
+        Self::verify_masked(
+            transcript,
+            nvars,
+            degree,
+            claimed_sum,
+            &proof.mask,
+            &proof.sumcheck,
+        )
+    }
+
+    /// [`MLSumcheck::verify_zk`] for a proof whose mask and sumcheck are stored apart.
+    pub fn verify_masked(
+        transcript: &mut T,
+        nvars: usize,
+        degree: usize,
+        claimed_sum: R,
+        mask: &SumcheckMask<R>,
+        sumcheck: &Proof<R>,
+    ) -> Result<(SubClaim<R>, MaskClaim<R>), SumCheckError<R>> {
+        transcript.absorb_slice(mask.commitment.as_ref());
+        transcript.absorb(&mask.sum);
+        let rho: R = transcript.get_challenge().into();
+
+        let subclaim = Self::verify_as_subprotocol(
+            transcript,
+            nvars,
+            degree,
+            claimed_sum + rho * mask.sum,
+            sumcheck,
+        )?;
+        transcript.absorb(&mask.evaluation);
+
+        let mask_claim = MaskClaim {
+            commitment: mask.commitment.clone(),
+            point: subclaim.point.iter().map(|&r| r.into()).collect(),
+            degree,
+            evaluation: mask.evaluation,
+        };
+        let subclaim = SubClaim {
+            expected_evaluation: subclaim.expected_evaluation - rho * mask.evaluation,
+            point: subclaim.point,
+        };
+
+        Ok((subclaim, mask_claim))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::{One, UniformRand};
+    use cyclotomic_rings::rings::{GoldilocksChallengeSet, GoldilocksRingNTT};
+    use stark_rings::PolyRing;
+    use stark_rings_poly::mle::MultilinearExtension;
+
+    use super::*;
+    use crate::{
+        transcript::poseidon::PoseidonTranscript,
+        utils::sumcheck::utils::{build_eq_x_r, rand_poly, rand_poly_comb_fn},
+    };
+
+    type R = GoldilocksRingNTT;
+    type T = PoseidonTranscript<R, GoldilocksChallengeSet>;
+
+    const NVARS: usize = 4;
+    const B: u128 = 1 << 16;
+
+    fn scheme(degree: usize) -> MaskCommitmentScheme<R> {
+        MaskCommitmentScheme::rand(
+            4,
+            MaskingPolynomial::<R>::num_coeffs(NVARS, degree),
+            B,
+            2,
+            1,
+            &mut ark_std::test_rng(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_masking_polynomial() {
+        let mut rng = ark_std::test_rng();
+        let mask = MaskingPolynomial::<R>::rand(NVARS, 3, &mut rng);
+        assert_eq!(
+            mask.coeffs().len(),
+            MaskingPolynomial::<R>::num_coeffs(NVARS, 3)
+        );
+        assert_eq!(
+            MaskingPolynomial::from_coeffs(NVARS, 3, &mask.coeffs()),
+            mask
+        );
+
+        let sum = (0..1 << NVARS).fold(R::zero(), |acc, x: usize| {
+            let point: Vec<R> = (0..NVARS)
+                .map(|i| R::from(((x >> i) & 1) as u128))
+                .collect();
+            acc + mask.evaluate(&point).unwrap()
+        });
+        assert_eq!(mask.sum(), sum);
+
+        let evals = mask.round_evaluations(0, &[], 3);
+        assert_eq!(evals[0] + evals[1], sum);
+    }
+
+    #[test]
+    fn test_mask_commitment() {
+        let mut rng = ark_std::test_rng();
+        let degree = 3;
+        let scheme = scheme(degree);
+        let mask = MaskingPolynomial::<R>::rand(NVARS, degree, &mut rng);
+        let (commitment, opening) = scheme.commit(&mask, &mut rng).unwrap();
+
+        // The committed digits are short, and open to the polynomial
+        assert!(NormBound::Infinity(B).check(&opening.digits.f));
+        assert_eq!(
+            scheme.open(&commitment, &opening, NVARS, degree).unwrap(),
+            mask
+        );
+
+        // A smaller polynomial is padded with zeros
+        let small = MaskingPolynomial::<R>::rand(NVARS - 1, degree, &mut rng);
+        let (small_commitment, small_opening) = scheme.commit(&small, &mut rng).unwrap();
+        assert_eq!(
+            scheme
+                .open(&small_commitment, &small_opening, NVARS - 1, degree)
+                .unwrap(),
+            small
+        );
+        assert!(matches!(
+            scheme.open(&commitment, &opening, NVARS - 1, degree),
+            Err(CommitmentError::InvalidOpening)
+        ));
+
+        // Digits out of the bound are rejected even if they open the commitment
+        let mut long_opening = opening.clone();
+        long_opening.digits.f[0].coeffs_mut()[0] = (B as u64).into();
+        assert!(matches!(
+            scheme.open(&commitment, &long_opening, NVARS, degree),
+            Err(CommitmentError::NormBoundExceeded(NormBound::Infinity(B)))
+        ));
+        let mut long_mask = opening.clone();
+        long_mask.randomness[0].coeffs_mut()[0] = 2u64.into();
+        assert!(matches!(
+            scheme.open(&commitment, &long_mask, NVARS, degree),
+            Err(CommitmentError::NormBoundExceeded(NormBound::Infinity(2)))
+        ));
+
+        let too_large = MaskingPolynomial::<R>::rand(NVARS + 1, degree, &mut rng);
+        assert!(matches!(
+            scheme.commit(&too_large, &mut rng),
+            Err(CommitmentError::WrongWitnessLength(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_zk_sumcheck() {
+        let mut rng = ark_std::test_rng();
+        let ((mles, degree), products, sum) = rand_poly(NVARS, (2, 4), 3, &mut rng).unwrap();
+        let comb_fn = |vals: &[R]| rand_poly_comb_fn(vals, &products);
+        let scheme = scheme(degree);
+
+        let mut prover_transcript = T::default();
+        let (proof, prover_state, opening) = MLSumcheck::prove_zk(
+            &mut prover_transcript,
+            mles.clone(),
+            NVARS,
+            degree,
+            comb_fn,
+            &scheme,
+            &mut rng,
+        )
+        .unwrap();
+        let eval_proof = MaskClaim::new(&proof.mask, &prover_state)
+            .prove(&scheme, &opening, &mut prover_transcript, &mut rng)
+            .unwrap();
+
+        let mut verifier_transcript = T::default();
+        let (subclaim, mask_claim) =
+            MLSumcheck::verify_zk(&mut verifier_transcript, NVARS, degree, sum, &proof).unwrap();
+        assert_eq!(subclaim.point, prover_state.randomness);
+        assert_eq!(mask_claim, MaskClaim::new(&proof.mask, &prover_state));
+        mask_claim
+            .verify(&scheme, &eval_proof, &mut verifier_transcript)
+            .unwrap();
+
+        let point: Vec<R> = subclaim.point.iter().map(|&r| r.into()).collect();
+        let vals: Vec<R> = mles
+            .iter()
+            .map(|mle| mle.evaluate(&point).unwrap())
+            .collect();
+        assert_eq!(comb_fn(&vals), subclaim.expected_evaluation);
+
+        // The round messages are those of the masked polynomial
+        let (unmasked, _) =
+            MLSumcheck::prove_as_subprotocol(&mut T::default(), mles, NVARS, degree, comb_fn);
+        assert_ne!(proof.sumcheck, unmasked);
+    }
+
+    #[test]
+    fn test_zk_sumcheck_with_eqs() {
+        let mut rng = ark_std::test_rng();
+        let ((mles, degree), products, _) = rand_poly(NVARS, (2, 4), 3, &mut rng).unwrap();
+        let beta: Vec<R> = (0..NVARS).map(|_| R::rand(&mut rng)).collect();
+        let eq = build_eq_x_r(&beta).unwrap();
+        let sum = (0..1 << NVARS)
+            .map(|i| {
+                let vals: Vec<R> = mles.iter().map(|mle| mle.evaluations[i]).collect();
+                eq.evaluations[i] * rand_poly_comb_fn(&vals, &products)
+            })
+            .sum::<R>();
+        let scheme = scheme(degree + 1);
+
+        let mut prover_transcript = T::default();
+        let (proof, prover_state, opening) = MLSumcheck::prove_zk_with_eqs(
+            &mut prover_transcript,
+            &mles.iter().collect::<Vec<_>>(),
+            NVARS,
+            degree,
+            &[beta.clone()],
+            |vals, out| out[0] = rand_poly_comb_fn(vals, &products),
+            &scheme,
+            &mut rng,
+        )
+        .unwrap();
+
+        let eval_proof = MaskClaim::new(&proof.mask, &prover_state)
+            .prove(&scheme, &opening, &mut prover_transcript, &mut rng)
+            .unwrap();
+
+        let mut verifier_transcript = T::default();
+        let (subclaim, mask_claim) =
+            MLSumcheck::verify_zk(&mut verifier_transcript, NVARS, degree + 1, sum, &proof)
+                .unwrap();
+        assert_eq!(subclaim.point, prover_state.randomness);
+        mask_claim
+            .verify(&scheme, &eval_proof, &mut verifier_transcript)
+            .unwrap();
+
+        let point: Vec<R> = subclaim.point.iter().map(|&r| r.into()).collect();
+        let vals: Vec<R> = mles
+            .iter()
+            .map(|mle| mle.evaluate(&point).unwrap())
+            .collect();
+        assert_eq!(
+            eq.evaluate(&point).unwrap() * rand_poly_comb_fn(&vals, &products),
+            subclaim.expected_evaluation
+        );
+    }
+
+    #[test]
+    fn test_failing_zk_sumcheck() {
+        let mut rng = ark_std::test_rng();
+        let ((mles, degree), products, sum) = rand_poly(NVARS, (2, 4), 3, &mut rng).unwrap();
+        let comb_fn = |vals: &[R]| rand_poly_comb_fn(vals, &products);
+        let scheme = scheme(degree);
+        let mut prover_transcript = T::default();
+        let (proof, prover_state, opening) = MLSumcheck::prove_zk(
+            &mut prover_transcript,
+            mles,
+            NVARS,
+            degree,
+            comb_fn,
+            &scheme,
+            &mut rng,
+        )
+        .unwrap();
+
+        let wrong_sum = sum + R::one();
+        let result = MLSumcheck::verify_zk(&mut T::default(), NVARS, degree, wrong_sum, &proof);
+        assert!(result.is_err());
+
+        // A wrong mask evaluation passes the sumcheck but not the evaluation proof
+        let mut wrong_proof = proof.clone();
+        wrong_proof.mask.evaluation += R::one();
+        let mut wrong_prover_transcript = prover_transcript.clone();
+        let wrong_eval_proof = MaskClaim::new(&wrong_proof.mask, &prover_state)
+            .prove(&scheme, &opening, &mut wrong_prover_transcript, &mut rng)
+            .unwrap();
+        let mut verifier_transcript = T::default();
+        let (_, mask_claim) =
+            MLSumcheck::verify_zk(&mut verifier_transcript, NVARS, degree, sum, &wrong_proof)
+                .unwrap();
+        assert!(matches!(
+            mask_claim.verify(&scheme, &wrong_eval_proof, &mut verifier_transcript),
+            Err(CommitmentError::InvalidOpening)
+        ));
+
+        let eval_proof = MaskClaim::new(&proof.mask, &prover_state)
+            .prove(&scheme, &opening, &mut prover_transcript, &mut rng)
+            .unwrap();
+        let verify = |eval_proof: &MaskEvalProof<R>| {
+            let mut verifier_transcript = T::default();
+            let (_, mask_claim) =
+                MLSumcheck::verify_zk(&mut verifier_transcript, NVARS, degree, sum, &proof)
+                    .unwrap();
+            mask_claim.verify(&scheme, eval_proof, &mut verifier_transcript)
+        };
+        verify(&eval_proof).unwrap();
+
+        let mut tampered = eval_proof.clone();
+        tampered.inner_product += R::one();
+        assert!(verify(&tampered).is_err());
+
+        // An answer out of the bound is rejected even if it satisfies the relations
+        let mut long = eval_proof;
+        long.digits[0] = long.digits[0] + R::from(1u128 << 62);
+        assert!(matches!(
+            verify(&long),
+            Err(CommitmentError::NormBoundExceeded(_))
+        ));
+    }
+}